@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// Somewhere an alert can be sent. Any service taking a JSON POST with a
+// "text" field works: Slack/Teams/Mattermost incoming webhooks, ntfy, ...
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Target {
+    pub name: String,
+    pub webhook_url: String,
+}
+
+// Alert goes to the channel first, then to each on-call contact in turn
+// while nobody acknowledges it within `ack_timeout_mins`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EscalationPolicy {
+    pub channel: Target,
+    pub on_call: Vec<Target>,
+    pub ack_timeout_mins: u64,
+}
+
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        Self {
+            channel: Target { name: "Alerts channel".to_string(), webhook_url: String::new() },
+            on_call: Vec::new(),
+            ack_timeout_mins: 10,
+        }
+    }
+}
+
+impl EscalationPolicy {
+    // Target for an escalation level: 0 is the channel, then on-call contacts
+    pub fn target(&self, level: usize) -> Option<&Target> {
+        match level {
+            0 => Some(&self.channel),
+            n => self.on_call.get(n - 1),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Acknowledgment {
+    pub by: String,
+    pub at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Incident {
+    pub id: u64,
+    pub source: String,
+    pub message: String,
+    pub raised_at: u64,  // unix seconds
+    // Highest level notified so far, see EscalationPolicy::target
+    pub level: usize,
+    pub last_notified_at: Option<u64>,
+    pub acknowledged: Option<Acknowledgment>,
+}
+
+impl Incident {
+    pub fn text(&self) -> String {
+        format!("⚠ Avalanche alert #{} from {}: {}", self.id, self.source, self.message)
+    }
+}
+
+// A notification that should go out now
+pub struct Notification {
+    pub incident_id: u64,
+    pub level: usize,
+    pub target: Target,
+    pub text: String,
+}
+
+pub struct Escalator {
+    path: PathBuf,
+    pub policy: EscalationPolicy,
+    pub incidents: Vec<Incident>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Saved {
+    policy: EscalationPolicy,
+    incidents: Vec<Incident>,
+}
+
+impl Escalator {
+    pub fn load_default() -> Self {
+        let path = crate::storage::data_dir().join("alerting.json");
+        let saved: Saved = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self { path, policy: saved.policy, incidents: saved.incidents }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let saved = Saved { policy: self.policy.clone(), incidents: self.incidents.clone() };
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&saved)?)?;
+        Ok(())
+    }
+
+    pub fn raise(&mut self, source: &str, message: &str, now: u64) -> anyhow::Result<u64> {
+        let id = self.incidents.iter().map(|i| i.id).max().unwrap_or(0) + 1;
+        self.incidents.push(Incident {
+            id,
+            source: source.to_string(),
+            message: message.to_string(),
+            raised_at: now,
+            level: 0,
+            last_notified_at: None,
+            acknowledged: None,
+        });
+        crate::audit::record("alert_raised", format!("#{} {}: {}", id, source, message))?;
+        self.save()?;
+        Ok(id)
+    }
+
+    pub fn open(&self) -> impl Iterator<Item = &Incident> {
+        self.incidents.iter().filter(|i| i.acknowledged.is_none())
+    }
+
+    // Notifications due now: the first one for new incidents, then the next
+    // level once the acknowledgment timeout passes. Marks them as sent;
+    // delivery failures are for the caller to log.
+    pub fn due(&mut self, now: u64) -> Vec<Notification> {
+        let timeout = self.policy.ack_timeout_mins * 60;
+        let mut due = Vec::new();
+        for incident in self.incidents.iter_mut().filter(|i| i.acknowledged.is_none()) {
+            let level = match incident.last_notified_at {
+                None => 0,
+                Some(at) if now.saturating_sub(at) >= timeout => incident.level + 1,
+                Some(_) => continue,
+            };
+            let Some(target) = self.policy.target(level) else {
+                continue;  // chain exhausted
+            };
+            incident.level = level;
+            incident.last_notified_at = Some(now);
+            due.push(Notification {
+                incident_id: incident.id,
+                level,
+                target: target.clone(),
+                text: incident.text(),
+            });
+        }
+        if !due.is_empty() {
+            let _ = self.save();
+        }
+        due
+    }
+
+    pub fn acknowledge(&mut self, id: u64, by: &str, now: u64) -> anyhow::Result<()> {
+        let incident = self
+            .incidents
+            .iter_mut()
+            .find(|i| i.id == id)
+            .ok_or_else(|| anyhow::anyhow!("No incident #{}", id))?;
+        incident.acknowledged = Some(Acknowledgment { by: by.to_string(), at: now });
+        crate::audit::record(
+            "alert_acknowledged",
+            format!("#{} by {} after {}s", id, by, now.saturating_sub(incident.raised_at)),
+        )?;
+        self.save()
+    }
+}
+
+pub async fn send(client: &reqwest::Client, target: &Target, text: &str) -> anyhow::Result<()> {
+    if target.webhook_url.is_empty() {
+        return Err(anyhow::anyhow!("{} has no webhook URL", target.name));
+    }
+    client
+        .post(&target.webhook_url)
+        .json(&serde_json::json!({ "text": text }))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
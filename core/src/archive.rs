@@ -0,0 +1,199 @@
+use crate::history::{History, HistoryEntry};
+use crate::snapshot::{hash_bytes, BlobStore};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+
+// An observation archive is a zstd-compressed tar holding index.json first,
+// then blobs/<sha256> for every image, prompt and raw response the entries
+// refer to. Blobs are stored raw inside and the whole stream is compressed
+// once, which packs runs of similar responses far better than per-file
+// compression. Keeping the index first lets it be listed without unpacking.
+// The zstd stream carries its own checksum and the archive gets a .sha256
+// sidecar, so damage in transit is caught before anything is imported.
+
+pub const EXTENSION: &str = "avarchive";
+const FORMAT_VERSION: u32 = 1;
+// Archives are written once and shared, so the slow high ratio pays off
+const ZSTD_LEVEL: i32 = 19;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveIndex {
+    pub version: u32,
+    pub created_at: u64,  // unix seconds
+    pub entries: Vec<HistoryEntry>,
+    pub blobs: Vec<ArchivedBlob>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedBlob {
+    pub hash: String,
+    pub size: u64,
+}
+
+// Writes the entries and their blobs. Blobs missing from the store are left
+// out of the index, so the archive still opens. Returns the archive size.
+pub fn export(path: &Path, entries: &[&HistoryEntry], store: &BlobStore) -> anyhow::Result<u64> {
+    let mut hashes: Vec<&str> = entries
+        .iter()
+        .flat_map(|e| e.snapshot.blob_hashes())
+        .map(String::as_str)
+        .collect();
+    hashes.sort_unstable();
+    hashes.dedup();
+    let blobs: Vec<(&str, Vec<u8>)> = hashes
+        .into_iter()
+        .filter_map(|hash| Some((hash, store.get(hash).ok()?)))
+        .collect();
+    let index = ArchiveIndex {
+        version: FORMAT_VERSION,
+        created_at: crate::storage::unix_now(),
+        entries: entries.iter().map(|&e| e.clone()).collect(),
+        blobs: blobs.iter().map(|(hash, bytes)| ArchivedBlob { hash: hash.to_string(), size: bytes.len() as u64 }).collect(),
+    };
+
+    // Written beside the target and renamed so a failed export never
+    // leaves a truncated archive under the chosen name
+    let partial = path.with_extension("partial");
+    let mut encoder = zstd::Encoder::new(std::fs::File::create(&partial)?, ZSTD_LEVEL)?;
+    encoder.include_checksum(true)?;
+    let mut tar = tar::Builder::new(encoder);
+    append(&mut tar, "index.json", &serde_json::to_vec(&index)?)?;
+    for (hash, bytes) in &blobs {
+        append(&mut tar, &format!("blobs/{}", hash), bytes)?;
+    }
+    tar.into_inner()?.finish()?.flush()?;
+    std::fs::rename(&partial, path)?;
+    crate::checksum::write_sidecar(path)?;
+    Ok(std::fs::metadata(path)?.len())
+}
+
+fn append<W: Write>(tar: &mut tar::Builder<W>, name: &str, bytes: &[u8]) -> anyhow::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, name, bytes)?;
+    Ok(())
+}
+
+fn open(path: &Path) -> anyhow::Result<tar::Archive<zstd::Decoder<'static, std::io::BufReader<std::fs::File>>>> {
+    Ok(tar::Archive::new(zstd::Decoder::new(std::fs::File::open(path)?)?))
+}
+
+// Leading digits of a hash for messages; index hashes are untrusted
+fn short(hash: &str) -> &str {
+    hash.get(..12).unwrap_or(hash)
+}
+
+fn parse_index(bytes: &[u8]) -> anyhow::Result<ArchiveIndex> {
+    let index: ArchiveIndex = serde_json::from_slice(bytes)?;
+    if index.version > FORMAT_VERSION {
+        return Err(anyhow::anyhow!("Archive was written by a newer version (format {})", index.version));
+    }
+    Ok(index)
+}
+
+// Reads only the index, for showing what an archive holds
+pub fn read_index(path: &Path) -> anyhow::Result<ArchiveIndex> {
+    let mut archive = open(path)?;
+    let mut first = archive.entries()?.next().ok_or_else(|| anyhow::anyhow!("Archive is empty"))??;
+    if first.path()?.as_ref() != Path::new("index.json") {
+        return Err(anyhow::anyhow!("Not an observation archive: index.json is missing"));
+    }
+    let mut bytes = Vec::new();
+    first.read_to_end(&mut bytes)?;
+    parse_index(&bytes)
+}
+
+// An archive read into memory: its index and the blobs by hash
+pub struct ArchiveContents {
+    pub index: ArchiveIndex,
+    pub blobs: std::collections::HashMap<String, Vec<u8>>,
+}
+
+// Reads the whole archive without touching the store, checking it against
+// its sidecar and every blob against its hash and the index
+pub fn read(path: &Path) -> anyhow::Result<ArchiveContents> {
+    crate::checksum::verify(path)?;
+    let name = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+    // Read errors past the header mean the compressed stream is damaged
+    let damaged = |read: usize, err: std::io::Error| {
+        anyhow::anyhow!("{} is damaged or truncated after {} readable files: {}", name, read, err)
+    };
+
+    let mut archive = open(path)?;
+    let mut index = None;
+    let mut blobs = std::collections::HashMap::new();
+    for file in archive.entries().map_err(|e| damaged(0, e))? {
+        let read = blobs.len() + usize::from(index.is_some());
+        let mut file = file.map_err(|e| damaged(read, e))?;
+        let member = file.path().map_err(|e| damaged(read, e))?.to_string_lossy().into_owned();
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).map_err(|e| damaged(read, e))?;
+        if member == "index.json" {
+            index = Some(parse_index(&bytes).map_err(|e| anyhow::anyhow!("{} has an unreadable index: {}", name, e))?);
+        } else if let Some(hash) = member.strip_prefix("blobs/") {
+            if hash_bytes(&bytes) != hash {
+                return Err(anyhow::anyhow!(
+                    "{} is corrupt: {} does not match its checksum ({} bytes)",
+                    name,
+                    member,
+                    bytes.len()
+                ));
+            }
+            blobs.insert(hash.to_string(), bytes);
+        }
+    }
+    let index = index.ok_or_else(|| anyhow::anyhow!("Not an observation archive: index.json is missing"))?;
+    for listed in &index.blobs {
+        match blobs.get(&listed.hash) {
+            Some(bytes) if bytes.len() as u64 == listed.size => {}
+            Some(bytes) => {
+                return Err(anyhow::anyhow!(
+                    "{} is corrupt: blob {} is {} bytes, the index expects {}",
+                    name,
+                    short(&listed.hash),
+                    bytes.len(),
+                    listed.size
+                ))
+            }
+            None => {
+                let affected = index
+                    .entries
+                    .iter()
+                    .filter(|e| e.snapshot.blob_hashes().any(|hash| *hash == listed.hash))
+                    .count();
+                return Err(anyhow::anyhow!(
+                    "{} is incomplete: blob {} used by {} entries is listed in the index but missing",
+                    name,
+                    short(&listed.hash),
+                    affected
+                ));
+            }
+        }
+    }
+    Ok(ArchiveContents { index, blobs })
+}
+
+// Adds the archive's blobs to the store and its entries to the history,
+// skipping entries already present. Nothing is written unless the whole
+// archive reads back intact. Returns how many entries were added.
+pub fn import(path: &Path, store: &BlobStore, history: &mut History) -> anyhow::Result<usize> {
+    let ArchiveContents { index, blobs } = read(path)?;
+    for bytes in blobs.values() {
+        store.put(bytes)?;
+    }
+    let known: std::collections::HashSet<String> = history.entries.iter().map(|e| e.id().to_string()).collect();
+    let mut added = 0;
+    for entry in index.entries {
+        if known.contains(entry.id()) {
+            continue;
+        }
+        store.save_snapshot(&entry.snapshot)?;
+        history.entries.push(entry);
+        added += 1;
+    }
+    history.save()?;
+    Ok(added)
+}
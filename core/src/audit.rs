@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+// One line of the append-only audit log (audit.jsonl in the data directory)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditEvent {
+    pub at: u64,  // unix seconds
+    pub kind: String,
+    pub detail: String,
+}
+
+fn path() -> std::path::PathBuf {
+    crate::storage::data_dir().join("audit.jsonl")
+}
+
+pub fn record(kind: &str, detail: impl Into<String>) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let event = AuditEvent {
+        at: crate::storage::unix_now(),
+        kind: kind.to_string(),
+        detail: detail.into(),
+    };
+    let path = path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&event)?)?;
+    Ok(())
+}
+
+// Every event so far, oldest first; unreadable lines are skipped
+pub fn load() -> Vec<AuditEvent> {
+    std::fs::read_to_string(path())
+        .map(|text| text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,293 @@
+// The published regional avalanche bulletin for a photo's location, to hold
+// the photo's evidence up against. avalanche.org's map layer covers the US
+// forecast zones and is matched by coordinates; EAWS services publish
+// CAAMLv6 bulletins by region, so those need the bulletin URL and the
+// micro-region configured.
+
+use crate::camera::Location;
+use crate::model::AvalancheAnalysis;
+use serde::{Deserialize, Serialize};
+
+pub const AVALANCHE_ORG_URL: &str = "https://api.avalanche.org/v2/public/products/map-layer";
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum BulletinSource {
+    Off,
+    #[default]
+    AvalancheOrg,
+    // A CAAMLv6 JSON bulletin file and the EAWS region ID the photos are
+    // in, e.g. "AT-07-14"
+    Caaml { url: String, region_id: String },
+}
+
+impl BulletinSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BulletinSource::Off => "Off",
+            BulletinSource::AvalancheOrg => "avalanche.org (US)",
+            BulletinSource::Caaml { .. } => "EAWS CAAMLv6",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bulletin {
+    // Forecast zone or region, e.g. "Central Sierra Nevada"
+    pub region: String,
+    // Who published it, e.g. "Sierra Avalanche Center"
+    pub publisher: String,
+    // Highest rating of the day on the 1-5 scale; None when the bulletin
+    // gives no rating, e.g. out of season
+    pub danger_level: Option<u8>,
+    // Travel advice or highlights, as published
+    pub summary: Option<String>,
+    pub link: Option<String>,
+}
+
+// Fetches the bulletin covering `location` from the configured source
+pub async fn fetch(client: &reqwest::Client, source: &BulletinSource, location: Location) -> anyhow::Result<Bulletin> {
+    match source {
+        BulletinSource::Off => anyhow::bail!("No bulletin source is configured"),
+        BulletinSource::AvalancheOrg => {
+            let layer: MapLayer = client.get(AVALANCHE_ORG_URL).send().await?.error_for_status()?.json().await?;
+            layer
+                .features
+                .into_iter()
+                .find(|zone| zone.contains(location))
+                .map(|zone| zone.properties.into())
+                .ok_or_else(|| anyhow::anyhow!("No avalanche.org forecast zone covers this location"))
+        }
+        BulletinSource::Caaml { url, region_id } => {
+            let caaml: Caaml = client.get(url).send().await?.error_for_status()?.json().await?;
+            caaml
+                .bulletins
+                .into_iter()
+                .find_map(|bulletin| bulletin.for_region(region_id))
+                .ok_or_else(|| anyhow::anyhow!("The bulletin does not cover region {}", region_id))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct MapLayer {
+    features: Vec<Zone>,
+}
+
+#[derive(Deserialize)]
+struct Zone {
+    properties: ZoneProperties,
+    // Kept loose so a zone with an odd geometry does not fail the whole layer
+    geometry: serde_json::Value,
+}
+
+impl Zone {
+    // Polygons and multipolygons, with rings of [longitude, latitude] pairs
+    fn contains(&self, point: Location) -> bool {
+        let coordinates = self.geometry["coordinates"].clone();
+        match self.geometry["type"].as_str() {
+            Some("Polygon") => serde_json::from_value::<Vec<Vec<[f64; 2]>>>(coordinates)
+                .is_ok_and(|rings| polygon_contains(&rings, point)),
+            Some("MultiPolygon") => serde_json::from_value::<Vec<Vec<Vec<[f64; 2]>>>>(coordinates)
+                .is_ok_and(|polygons| polygons.iter().any(|rings| polygon_contains(rings, point))),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ZoneProperties {
+    name: String,
+    center: String,
+    // -1 when the zone has no rating
+    danger_level: i32,
+    #[serde(default)]
+    travel_advice: Option<String>,
+    #[serde(default)]
+    link: Option<String>,
+}
+
+impl From<ZoneProperties> for Bulletin {
+    fn from(zone: ZoneProperties) -> Self {
+        Self {
+            region: zone.name,
+            publisher: zone.center,
+            danger_level: u8::try_from(zone.danger_level).ok().filter(|level| (1..=5).contains(level)),
+            summary: zone.travel_advice.filter(|advice| !advice.trim().is_empty()),
+            link: zone.link.filter(|link| !link.is_empty()),
+        }
+    }
+}
+
+// Inside the outer ring and outside any holes, by even-odd ray casting
+fn polygon_contains(rings: &[Vec<[f64; 2]>], point: Location) -> bool {
+    let ring_contains = |ring: &Vec<[f64; 2]>| {
+        let mut inside = false;
+        let mut j = ring.len().saturating_sub(1);
+        for i in 0..ring.len() {
+            let ([ax, ay], [bx, by]) = (ring[i], ring[j]);
+            if (ay > point.latitude) != (by > point.latitude) {
+                let crossing = ax + (point.latitude - ay) / (by - ay) * (bx - ax);
+                if point.longitude < crossing {
+                    inside = !inside;
+                }
+            }
+            j = i;
+        }
+        inside
+    };
+    match rings.split_first() {
+        Some((outer, holes)) => ring_contains(outer) && !holes.iter().any(ring_contains),
+        None => false,
+    }
+}
+
+#[derive(Deserialize)]
+struct Caaml {
+    bulletins: Vec<CaamlBulletin>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CaamlBulletin {
+    regions: Vec<CaamlRegion>,
+    #[serde(default)]
+    danger_ratings: Vec<CaamlRating>,
+    #[serde(default)]
+    highlights: Option<String>,
+    #[serde(default)]
+    source: Option<CaamlSource>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CaamlRegion {
+    #[serde(rename = "regionID")]
+    region_id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CaamlRating {
+    // "low" … "very_high", or "no_snow" / "no_rating"
+    main_value: String,
+}
+
+#[derive(Deserialize)]
+struct CaamlSource {
+    #[serde(default)]
+    provider: Option<CaamlProvider>,
+}
+
+#[derive(Deserialize)]
+struct CaamlProvider {
+    name: String,
+    #[serde(default)]
+    website: Option<String>,
+}
+
+impl CaamlBulletin {
+    fn for_region(self, region_id: &str) -> Option<Bulletin> {
+        let region = self.regions.into_iter().find(|r| r.region_id.eq_ignore_ascii_case(region_id.trim()))?;
+        let provider = self.source.and_then(|source| source.provider);
+        Some(Bulletin {
+            region: region.name,
+            publisher: provider.as_ref().map_or_else(|| "EAWS".to_string(), |p| p.name.clone()),
+            // Ratings differ by elevation and time of day; the worst counts
+            danger_level: self.danger_ratings.iter().filter_map(|r| caaml_level(&r.main_value)).max(),
+            summary: self.highlights.filter(|h| !h.trim().is_empty()),
+            link: provider.and_then(|p| p.website),
+        })
+    }
+}
+
+fn caaml_level(value: &str) -> Option<u8> {
+    match value {
+        "low" => Some(1),
+        "moderate" => Some(2),
+        "considerable" => Some(3),
+        "high" => Some(4),
+        "very_high" => Some(5),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Agreement {
+    Confirms,
+    Contradicts,
+    Inconclusive,
+}
+
+impl Agreement {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Agreement::Confirms => "Photo supports the bulletin",
+            Agreement::Contradicts => "Photo contradicts the bulletin",
+            Agreement::Inconclusive => "Photo neither supports nor contradicts the bulletin",
+        }
+    }
+}
+
+// How a classification squares with a published danger rating, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossCheck {
+    pub agreement: Agreement,
+    pub reasons: Vec<String>,
+}
+
+// Holds the photo's evidence up against the bulletin. Fresh slab releases
+// are the clearest sign of instability: under a Low rating they contradict
+// it, from Considerable on they bear it out. The model's own danger level
+// agrees within one step of the published one and contradicts it from two.
+pub fn cross_check(analysis: &AvalancheAnalysis, bulletin: &Bulletin) -> CrossCheck {
+    let Some(published) = bulletin.danger_level else {
+        return CrossCheck {
+            agreement: Agreement::Inconclusive,
+            reasons: vec!["The bulletin gives no danger rating".to_string()],
+        };
+    };
+    let published_name = crate::model::danger_level_name(published);
+    let mut confirms = Vec::new();
+    let mut contradicts = Vec::new();
+
+    let chars = &analysis.visual_characteristics;
+    let slab = matches!(analysis.avalanche_type.as_str(), "slab" | "wet-slab")
+        || analysis.avalanche_problem.as_deref().is_some_and(|p| p.ends_with("-slab"));
+    if analysis.avalanche_present && slab && chars.fracture_line {
+        let what = if chars.recent_snowfall { "Fresh slab release in new snow" } else { "Slab release" };
+        match published {
+            1 => contradicts.push(format!("{} under a {} rating", what, published_name)),
+            3.. => confirms.push(format!("{} fits a {} rating", what, published_name)),
+            _ => {}
+        }
+    } else if analysis.avalanche_present && published >= 2 {
+        confirms.push(format!("Avalanche activity is expected at {}", published_name));
+    } else if !analysis.avalanche_present && published >= 4 {
+        contradicts.push(format!("No avalanche activity visible despite a {} rating", published_name));
+    }
+
+    if let Some(level) = analysis.danger_level {
+        let estimate = format!(
+            "Photo suggests {} ({}), bulletin says {} ({})",
+            crate::model::danger_level_name(level),
+            level,
+            published_name,
+            published
+        );
+        match level.abs_diff(published) {
+            0 => confirms.push(estimate),
+            1 => {}
+            _ => contradicts.push(estimate),
+        }
+    }
+
+    let agreement = if !contradicts.is_empty() {
+        Agreement::Contradicts
+    } else if !confirms.is_empty() {
+        Agreement::Confirms
+    } else {
+        Agreement::Inconclusive
+    };
+    CrossCheck { agreement, reasons: contradicts.into_iter().chain(confirms).collect() }
+}
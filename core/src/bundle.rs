@@ -0,0 +1,159 @@
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// A model bundle is a tar archive holding manifest.json, manifest.sig (the
+// base64 ed25519 signature of manifest.json) and the files the manifest lists.
+// Trusted public keys live one per line, base64 encoded, in trusted_keys.txt
+// inside the data directory so operators can provision them without a network.
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BundleManifest {
+    pub name: String,
+    pub version: String,
+    pub created_at: u64,  // unix seconds
+    #[serde(default)]
+    pub description: String,
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BundleFile {
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+pub fn models_dir() -> PathBuf {
+    crate::storage::data_dir().join("models")
+}
+
+pub fn trusted_keys() -> anyhow::Result<Vec<VerifyingKey>> {
+    use base64::Engine;
+    let path = crate::storage::data_dir().join("trusted_keys.txt");
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("No trusted keys at {}: {}", path.display(), e))?;
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let bytes = base64::engine::general_purpose::STANDARD.decode(line)?;
+            let bytes: [u8; 32] = bytes
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Trusted key must be 32 bytes"))?;
+            Ok(VerifyingKey::from_bytes(&bytes)?)
+        })
+        .collect()
+}
+
+// Verifies the signature and every file hash before anything is written, then
+// installs the bundle under models/<name>/<version>/
+pub fn import_bundle(bundle_path: &Path, keys: &[VerifyingKey]) -> anyhow::Result<BundleManifest> {
+    use base64::Engine;
+    if keys.is_empty() {
+        return Err(anyhow::anyhow!("No trusted keys configured"));
+    }
+
+    let mut manifest_bytes = None;
+    let mut signature_text = None;
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+
+    crate::checksum::verify(bundle_path)?;
+    let mut archive = tar::Archive::new(std::fs::File::open(bundle_path)?);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        match path.as_str() {
+            "manifest.json" => manifest_bytes = Some(bytes),
+            "manifest.sig" => signature_text = Some(String::from_utf8(bytes)?),
+            _ => files.push((path, bytes)),
+        }
+    }
+
+    let manifest_bytes = manifest_bytes.ok_or_else(|| anyhow::anyhow!("Bundle has no manifest.json"))?;
+    let signature_text = signature_text.ok_or_else(|| anyhow::anyhow!("Bundle is not signed"))?;
+    let signature = Signature::from_slice(
+        &base64::engine::general_purpose::STANDARD.decode(signature_text.trim())?,
+    )?;
+    if !keys.iter().any(|key| key.verify(&manifest_bytes, &signature).is_ok()) {
+        return Err(anyhow::anyhow!("Bundle signature does not match any trusted key"));
+    }
+
+    let manifest: BundleManifest = serde_json::from_slice(&manifest_bytes)?;
+    if !is_safe_component(&manifest.name) || !is_safe_component(&manifest.version) {
+        return Err(anyhow::anyhow!("Invalid bundle name or version"));
+    }
+    for listed in &manifest.files {
+        if !Path::new(&listed.path)
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+        {
+            return Err(anyhow::anyhow!("Unsafe path in bundle: {}", listed.path));
+        }
+        let (_, bytes) = files
+            .iter()
+            .find(|(path, _)| *path == listed.path)
+            .ok_or_else(|| anyhow::anyhow!("Bundle is missing {}", listed.path))?;
+        let hash: String = Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect();
+        if bytes.len() as u64 != listed.size || hash != listed.sha256 {
+            return Err(anyhow::anyhow!("Integrity check failed for {}", listed.path));
+        }
+    }
+
+    // Stage into a temp directory so a failed copy never leaves a half-installed model
+    let target = models_dir().join(&manifest.name).join(&manifest.version);
+    let staging = target.with_extension("partial");
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    std::fs::create_dir_all(&staging)?;
+    for listed in &manifest.files {
+        let (_, bytes) = files.iter().find(|(path, _)| *path == listed.path).unwrap();
+        let path = staging.join(&listed.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, bytes)?;
+    }
+    std::fs::write(staging.join("manifest.json"), &manifest_bytes)?;
+    if target.exists() {
+        std::fs::remove_dir_all(&target)?;
+    }
+    std::fs::rename(&staging, &target)?;
+
+    Ok(manifest)
+}
+
+// Manifests of every installed model, sorted by name then version
+pub fn installed_models() -> Vec<BundleManifest> {
+    let mut manifests = Vec::new();
+    let Ok(names) = std::fs::read_dir(models_dir()) else {
+        return manifests;
+    };
+    for name in names.flatten() {
+        let Ok(versions) = std::fs::read_dir(name.path()) else {
+            continue;
+        };
+        for version in versions.flatten() {
+            if let Some(manifest) = std::fs::read(version.path().join("manifest.json"))
+                .ok()
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            {
+                manifests.push(manifest);
+            }
+        }
+    }
+    manifests.sort_by(|a: &BundleManifest, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    manifests
+}
+
+fn is_safe_component(s: &str) -> bool {
+    !s.is_empty() && s != "." && s != ".." && !s.contains(['/', '\\'])
+}
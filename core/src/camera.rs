@@ -0,0 +1,355 @@
+use crate::model::AvalancheAnalysis;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// Crop rectangle as fractions of the frame, so it survives resolution changes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, schemars::JsonSchema)]
+pub struct Roi {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for Roi {
+    fn default() -> Self {
+        Self { x: 0.0, y: 0.0, width: 1.0, height: 1.0 }
+    }
+}
+
+impl Roi {
+    // Pixel rectangle (x, y, width, height) within a frame of the given size
+    pub fn rect(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let x = ((self.x.clamp(0.0, 1.0) * width as f32) as u32).min(width - 1);
+        let y = ((self.y.clamp(0.0, 1.0) * height as f32) as u32).min(height - 1);
+        let w = ((self.width.clamp(0.0, 1.0) * width as f32) as u32).clamp(1, width - x);
+        let h = ((self.height.clamp(0.0, 1.0) * height as f32) as u32).clamp(1, height - y);
+        (x, y, w, h)
+    }
+
+    // Cuts this region out of a photo as a high-quality JPEG. The photo is
+    // turned upright first, since the region was marked on the upright preview.
+    pub fn crop_photo(&self, image_bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let image = crate::photo_meta::load_upright(image_bytes)?;
+        let (x, y, w, h) = self.rect(image.width(), image.height());
+        let mut bytes = Vec::new();
+        image
+            .crop_imm(x, y, w, h)
+            .to_rgb8()
+            .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Jpeg(95))?;
+        Ok(bytes)
+    }
+}
+
+// Painted grid of cells to leave out of analysis (sky, roads, buildings...),
+// laid over the full frame
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Mask {
+    pub cols: u32,
+    pub rows: u32,
+    // Row-major, true = excluded
+    pub excluded: Vec<bool>,
+}
+
+impl Mask {
+    pub const COLS: u32 = 32;
+    pub const ROWS: u32 = 24;
+
+    pub fn new() -> Self {
+        Self {
+            cols: Self::COLS,
+            rows: Self::ROWS,
+            excluded: vec![false; (Self::COLS * Self::ROWS) as usize],
+        }
+    }
+
+    pub fn is_excluded(&self, col: u32, row: u32) -> bool {
+        self.excluded.get((row * self.cols + col) as usize).copied().unwrap_or(false)
+    }
+
+    pub fn set(&mut self, col: u32, row: u32, excluded: bool) {
+        if let Some(cell) = self.excluded.get_mut((row * self.cols + col) as usize) {
+            *cell = excluded;
+        }
+    }
+
+    // Cell containing the point, given as fractions of the frame
+    pub fn cell_at(&self, x: f32, y: f32) -> Option<(u32, u32)> {
+        if !(0.0..1.0).contains(&x) || !(0.0..1.0).contains(&y) {
+            return None;
+        }
+        Some(((x * self.cols as f32) as u32, (y * self.rows as f32) as u32))
+    }
+
+    // Greys out excluded cells
+    pub fn apply(&self, image: &image::DynamicImage) -> image::DynamicImage {
+        let (w, h) = (image.width(), image.height());
+        let mut rgb = image.to_rgb8();
+        for (x, y, pixel) in rgb.enumerate_pixels_mut() {
+            if self.is_excluded(x * self.cols / w, y * self.rows / h) {
+                *pixel = image::Rgb([128, 128, 128]);
+            }
+        }
+        image::DynamicImage::ImageRgb8(rgb)
+    }
+
+    // Pixel bounding box (x, y, width, height) of the cells left in, so
+    // masked margins can be trimmed instead of uploaded
+    pub fn kept_rect(&self, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+        let kept: Vec<(u32, u32)> = (0..self.rows)
+            .flat_map(|row| (0..self.cols).map(move |col| (col, row)))
+            .filter(|&(col, row)| !self.is_excluded(col, row))
+            .collect();
+        let min_col = kept.iter().map(|c| c.0).min()?;
+        let max_col = kept.iter().map(|c| c.0).max()?;
+        let min_row = kept.iter().map(|c| c.1).min()?;
+        let max_row = kept.iter().map(|c| c.1).max()?;
+        let x0 = min_col * width / self.cols;
+        let y0 = min_row * height / self.rows;
+        let x1 = ((max_col + 1) * width / self.cols).min(width);
+        let y1 = ((max_row + 1) * height / self.rows).min(height);
+        Some((x0, y0, (x1 - x0).max(1), (y1 - y0).max(1)))
+    }
+}
+
+impl Default for Mask {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub latitude: f64,
+    // East-positive
+    pub longitude: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AlertRule {
+    // None matches any avalanche
+    pub avalanche_type: Option<String>,
+    pub min_confidence: f32,
+}
+
+impl AlertRule {
+    pub fn matches(&self, analysis: &AvalancheAnalysis) -> bool {
+        analysis.avalanche_present
+            && self.avalanche_type.as_ref().is_none_or(|t| *t == analysis.avalanche_type)
+            && analysis.confidence_level >= self.min_confidence
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{} at ≥{:.0}%",
+            self.avalanche_type.as_deref().unwrap_or("any avalanche"),
+            self.min_confidence
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CameraProfile {
+    pub name: String,
+    // Endpoint returning a still frame (JPEG/PNG snapshot URL), or an
+    // RTSP stream to grab frames from
+    pub url: String,
+    pub interval_secs: u64,
+    pub roi: Option<Roi>,
+    #[serde(default)]
+    pub mask: Option<Mask>,
+    // Free-text description of the start zone, passed to the model as context
+    #[serde(default)]
+    pub region: String,
+    #[serde(default)]
+    pub alert_rules: Vec<AlertRule>,
+    // Frames differing from the last analyzed one by less than this
+    // percentage are skipped; 0 analyzes every frame
+    #[serde(default = "default_change_threshold")]
+    pub change_threshold: f32,
+    #[serde(default)]
+    pub location: Option<Location>,
+    // Degrees clockwise from true north the camera faces
+    #[serde(default)]
+    pub bearing: Option<f32>,
+    // Pause between sunset and sunrise at the camera's location
+    #[serde(default)]
+    pub daylight_only: bool,
+    // Skip polls while precipitation exceeds this many mm/h
+    #[serde(default)]
+    pub max_precipitation: Option<f32>,
+    // Highway profile: frames are assessed for runout reach toward this
+    // line instead of being classified
+    #[serde(default)]
+    pub corridor: Option<crate::corridor::Corridor>,
+    // Checked against detected activity to flag what it reached
+    #[serde(default)]
+    pub assets: Vec<crate::exposure::Asset>,
+    pub enabled: bool,
+    #[serde(skip)]
+    pub last_polled: Option<u64>,  // unix seconds
+}
+
+impl Default for CameraProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            url: String::new(),
+            interval_secs: 600,
+            roi: None,
+            mask: None,
+            region: String::new(),
+            alert_rules: vec![AlertRule { avalanche_type: None, min_confidence: 70.0 }],
+            change_threshold: default_change_threshold(),
+            location: None,
+            bearing: None,
+            daylight_only: true,
+            max_precipitation: None,
+            corridor: None,
+            assets: Vec::new(),
+            enabled: true,
+            last_polled: None,
+        }
+    }
+}
+
+fn default_change_threshold() -> f32 {
+    2.0
+}
+
+impl CameraProfile {
+    pub fn is_due(&self, now: u64) -> bool {
+        self.enabled
+            && self.last_polled.is_none_or(|last| now.saturating_sub(last) >= self.interval_secs)
+    }
+
+    // Night at the camera; never true without a location
+    pub fn is_dark(&self, now: u64) -> bool {
+        self.daylight_only
+            && self.location.is_some_and(|l| {
+                !crate::conditions::is_daylight(l.latitude, l.longitude, now as i64)
+            })
+    }
+
+    // Whether precipitation is over the configured limit. Weather lookups
+    // that fail don't block polling.
+    pub async fn too_wet(&self, client: &reqwest::Client) -> bool {
+        let (Some(location), Some(limit)) = (self.location, self.max_precipitation) else {
+            return false;
+        };
+        crate::conditions::current_precipitation(client, location.latitude, location.longitude)
+            .await
+            .is_ok_and(|mm| mm > limit)
+    }
+
+    pub fn triggered_rules(&self, analysis: &AvalancheAnalysis) -> Vec<&AlertRule> {
+        self.alert_rules.iter().filter(|rule| rule.matches(analysis)).collect()
+    }
+
+    // Downloads the current frame as served by the camera, or grabs one
+    // from its stream
+    pub async fn fetch_raw(&self, client: &reqwest::Client) -> anyhow::Result<Vec<u8>> {
+        if crate::ffmpeg::is_stream(&self.url) {
+            return crate::ffmpeg::grab_frame(&self.url).await;
+        }
+        let response = client.get(&self.url).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    // Downloads the current frame with the mask and ROI applied
+    pub async fn fetch_frame(&self, client: &reqwest::Client) -> anyhow::Result<Vec<u8>> {
+        let bytes = self.fetch_raw(client).await?;
+        self.prepare_frame(&bytes)
+    }
+
+    // Masks and crops the full frame and draws the corridor. The mask's
+    // cells, the ROI and the corridor are all in full-frame coordinates; the
+    // crop is the intersection of the ROI and the unmasked area.
+    pub fn prepare_frame(&self, image_bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if self.mask.is_none() && self.roi.is_none() && self.corridor.is_none() {
+            return Ok(image_bytes.to_vec());
+        }
+        let mut image = image::load_from_memory(image_bytes)?;
+        let (w, h) = (image.width(), image.height());
+        let mut rect = self.roi.as_ref().map_or((0, 0, w, h), |roi| roi.rect(w, h));
+        if let Some(mask) = &self.mask {
+            let kept = mask
+                .kept_rect(w, h)
+                .ok_or_else(|| anyhow::anyhow!("Mask excludes the whole frame"))?;
+            rect = intersect(rect, kept)
+                .ok_or_else(|| anyhow::anyhow!("Region of interest is entirely masked"))?;
+            image = mask.apply(&image);
+        }
+        if let Some(corridor) = &self.corridor {
+            corridor.draw(&mut image);
+        }
+        let mut bytes = Vec::new();
+        image
+            .crop_imm(rect.0, rect.1, rect.2, rect.3)
+            .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Png)?;
+        Ok(bytes)
+    }
+}
+
+// Small greyscale thumbnail of a frame, cheap to keep and compare
+#[derive(Clone)]
+pub struct FrameSignature(image::GrayImage);
+
+impl FrameSignature {
+    const WIDTH: u32 = 64;
+    const HEIGHT: u32 = 48;
+
+    pub fn from_bytes(image_bytes: &[u8]) -> anyhow::Result<Self> {
+        let image = image::load_from_memory(image_bytes)?;
+        Ok(Self(
+            image
+                .resize_exact(Self::WIDTH, Self::HEIGHT, image::imageops::FilterType::Triangle)
+                .to_luma8(),
+        ))
+    }
+
+    // Mean absolute pixel difference as a percentage. Downscaling first
+    // keeps sensor noise and compression artefacts from counting as change.
+    pub fn difference(&self, other: &Self) -> f32 {
+        let total: u64 = self
+            .0
+            .as_raw()
+            .iter()
+            .zip(other.0.as_raw())
+            .map(|(a, b)| a.abs_diff(*b) as u64)
+            .sum();
+        total as f32 / (self.0.as_raw().len() as f32 * 255.0) * 100.0
+    }
+}
+
+fn intersect(a: (u32, u32, u32, u32), b: (u32, u32, u32, u32)) -> Option<(u32, u32, u32, u32)> {
+    let x0 = a.0.max(b.0);
+    let y0 = a.1.max(b.1);
+    let x1 = (a.0 + a.2).min(b.0 + b.2);
+    let y1 = (a.1 + a.3).min(b.1 + b.3);
+    (x1 > x0 && y1 > y0).then(|| (x0, y0, x1 - x0, y1 - y0))
+}
+
+pub struct Cameras {
+    path: PathBuf,
+    pub cameras: Vec<CameraProfile>,
+}
+
+impl Cameras {
+    pub fn load_default() -> Self {
+        let path = crate::storage::data_dir().join("cameras.json");
+        let cameras = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self { path, cameras }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&self.cameras)?)?;
+        Ok(())
+    }
+}
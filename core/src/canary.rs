@@ -0,0 +1,95 @@
+use crate::provider::{VisionProvider, ANALYSIS_PROMPT};
+
+// A small reference set shipped with the app, for checking a custom prompt
+// before analyses start using it. Each photo is classified with the built-in
+// prompt and the custom one; a photo the built-in prompt gets right and the
+// custom one does not is a regression. Results are not recorded in history.
+
+pub struct Reference {
+    pub name: &'static str,
+    pub image: &'static [u8],
+    pub avalanche_present: bool,
+    pub avalanche_type: &'static str,
+}
+
+pub const REFERENCE_SET: &[Reference] = &[
+    Reference {
+        name: "Powder avalanche",
+        image: include_bytes!("../../samples/powder avalanche.jpg"),
+        avalanche_present: true,
+        avalanche_type: "powder",
+    },
+    Reference {
+        name: "No avalanche",
+        image: include_bytes!("../../samples/no avalanche.jpeg"),
+        avalanche_present: false,
+        avalanche_type: "none",
+    },
+];
+
+impl Reference {
+    pub fn expected(&self) -> &'static str {
+        if self.avalanche_present {
+            self.avalanche_type
+        } else {
+            "none"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Match,
+    // What the model said instead
+    Mismatch(String),
+    // The request failed or the reply could not be parsed
+    Failed(String),
+}
+
+impl Outcome {
+    pub fn is_match(&self) -> bool {
+        *self == Outcome::Match
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CanaryCase {
+    pub name: &'static str,
+    pub expected: &'static str,
+    pub builtin: Outcome,
+    pub custom: Outcome,
+}
+
+impl CanaryCase {
+    pub fn is_regression(&self) -> bool {
+        self.builtin.is_match() && !self.custom.is_match()
+    }
+}
+
+// Classifies every reference photo with both prompts, one request at a time
+pub async fn run(provider: &dyn VisionProvider, custom_prompt: &str) -> Vec<CanaryCase> {
+    let mut cases = Vec::new();
+    for reference in REFERENCE_SET {
+        cases.push(CanaryCase {
+            name: reference.name,
+            expected: reference.expected(),
+            builtin: outcome(provider, reference, ANALYSIS_PROMPT).await,
+            custom: outcome(provider, reference, custom_prompt).await,
+        });
+    }
+    cases
+}
+
+async fn outcome(provider: &dyn VisionProvider, reference: &Reference, prompt: &str) -> Outcome {
+    match provider.classify(reference.image, prompt).await {
+        Ok(analysis) => {
+            let said = if analysis.avalanche_present { analysis.avalanche_type } else { "none".to_string() };
+            if said == reference.expected() {
+                Outcome::Match
+            } else {
+                Outcome::Mismatch(said)
+            }
+        }
+        Err(err) => Outcome::Failed(err.to_string()),
+    }
+}
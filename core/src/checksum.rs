@@ -0,0 +1,72 @@
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// Exports get a <file>.sha256 sidecar in `sha256sum` format, so a copy that
+// went over a flaky link or USB stick can be checked on import here or with
+// `sha256sum -c` anywhere else.
+
+// Sidecar checksum file for an export
+pub fn sidecar_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+pub fn file_sha256(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// Writes the sidecar for a finished export
+pub fn write_sidecar(path: &Path) -> anyhow::Result<()> {
+    let name = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+    std::fs::write(sidecar_path(path), format!("{}  {}\n", file_sha256(path)?, name))?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verification {
+    Verified,
+    // No sidecar travelled with the file, so it could not be checked
+    Unchecked,
+}
+
+// Checks a file against its sidecar. A mismatch is an error that names the
+// file, both checksums and the size, so a damaged copy is easy to tell from
+// a wrong file.
+pub fn verify(path: &Path) -> anyhow::Result<Verification> {
+    let name = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+    let Ok(sidecar) = std::fs::read_to_string(sidecar_path(path)) else {
+        return Ok(Verification::Unchecked);
+    };
+    let expected = sidecar
+        .split_whitespace()
+        .next()
+        .filter(|hash| hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| anyhow::anyhow!("{}.sha256 is not a SHA-256 checksum file", name))?
+        .to_ascii_lowercase();
+    let actual = file_sha256(path).map_err(|e| anyhow::anyhow!("Could not read {}: {}", name, e))?;
+    if actual != expected {
+        let size = std::fs::metadata(path).map_or(0, |m| m.len());
+        return Err(anyhow::anyhow!(
+            "{} is corrupt: its SHA-256 is {} but {}.sha256 expects {} ({} bytes on disk). \
+             It was probably damaged or cut short while copying; copy it again from the source.",
+            name,
+            &actual[..12],
+            name,
+            &expected[..12],
+            size
+        ));
+    }
+    Ok(Verification::Verified)
+}
@@ -0,0 +1,74 @@
+use crate::model::AvalancheAnalysis;
+
+pub struct DiffRow {
+    pub field: String,
+    pub values: Vec<String>,
+    pub changed: bool,
+}
+
+// Lines up every field across versions, one row per dotted field path
+pub fn diff_rows(versions: &[&AvalancheAnalysis]) -> Vec<DiffRow> {
+    let flattened: Vec<Vec<(String, String)>> = versions
+        .iter()
+        .map(|analysis| {
+            let mut fields = Vec::new();
+            if let Ok(value) = serde_json::to_value(analysis) {
+                flatten("", &value, &mut fields);
+            }
+            fields
+        })
+        .collect();
+
+    // Union of fields across versions so ones added by schema changes still get a row
+    let mut field_names: Vec<String> = Vec::new();
+    for fields in &flattened {
+        for (name, _) in fields {
+            if !field_names.contains(name) {
+                field_names.push(name.clone());
+            }
+        }
+    }
+
+    field_names
+        .into_iter()
+        .map(|field| {
+            let values: Vec<String> = flattened
+                .iter()
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .find(|(name, _)| *name == field)
+                        .map_or_else(|| "—".to_string(), |(_, value)| value.clone())
+                })
+                .collect();
+            let changed = values.windows(2).any(|pair| pair[0] != pair[1]);
+            DiffRow { field, values, changed }
+        })
+        .collect()
+}
+
+fn flatten(prefix: &str, value: &serde_json::Value, out: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&path, child, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            let joined = items
+                .iter()
+                .map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string))
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push((prefix.to_string(), joined));
+        }
+        serde_json::Value::String(s) => out.push((prefix.to_string(), s.clone())),
+        serde_json::Value::Null => out.push((prefix.to_string(), "null".to_string())),
+        other => out.push((prefix.to_string(), other.to_string())),
+    }
+}
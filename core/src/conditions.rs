@@ -0,0 +1,86 @@
+// Site conditions under which a camera frame isn't worth analyzing: night,
+// and heavy precipitation hiding the slope
+
+use serde::Deserialize;
+
+const J2000: f64 = 2451545.0;
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SunTimes {
+    // Unix seconds
+    Times { sunrise: i64, sunset: i64 },
+    PolarDay,
+    PolarNight,
+}
+
+// Sunrise and sunset for the local solar day containing `unix`, using the
+// standard sunrise equation (accurate to a minute or two). Longitude is
+// east-positive.
+pub fn sun_times(latitude: f64, longitude: f64, unix: i64) -> SunTimes {
+    let julian_date = unix as f64 / 86400.0 + UNIX_EPOCH_JD;
+    // Day number of the nearest local solar noon
+    let n = (julian_date - J2000 - 0.0008 + longitude / 360.0).round();
+    let mean_solar_noon = n - longitude / 360.0;
+
+    let anomaly = (357.5291 + 0.98560028 * mean_solar_noon).rem_euclid(360.0).to_radians();
+    let center = 1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+    let ecliptic_longitude = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = J2000 + mean_solar_noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+
+    let declination = (ecliptic_longitude.sin() * 23.4397_f64.to_radians().sin()).asin();
+    let latitude = latitude.to_radians();
+    let cos_hour_angle = ((-0.833_f64).to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+    if cos_hour_angle < -1.0 {
+        return SunTimes::PolarDay;
+    }
+    if cos_hour_angle > 1.0 {
+        return SunTimes::PolarNight;
+    }
+    let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+    let to_unix = |jd: f64| ((jd - UNIX_EPOCH_JD) * 86400.0) as i64;
+    SunTimes::Times {
+        sunrise: to_unix(transit - half_day),
+        sunset: to_unix(transit + half_day),
+    }
+}
+
+pub fn is_daylight(latitude: f64, longitude: f64, unix: i64) -> bool {
+    match sun_times(latitude, longitude, unix) {
+        SunTimes::Times { sunrise, sunset } => (sunrise..=sunset).contains(&unix),
+        SunTimes::PolarDay => true,
+        SunTimes::PolarNight => false,
+    }
+}
+
+const WEATHER_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+#[derive(Deserialize)]
+struct Forecast {
+    current: Current,
+}
+
+#[derive(Deserialize)]
+struct Current {
+    precipitation: f32,
+}
+
+// Current precipitation in mm/h from Open-Meteo (free, no API key)
+pub async fn current_precipitation(client: &reqwest::Client, latitude: f64, longitude: f64) -> anyhow::Result<f32> {
+    let forecast: Forecast = client
+        .get(WEATHER_URL)
+        .query(&[
+            ("latitude", latitude.to_string()),
+            ("longitude", longitude.to_string()),
+            ("current", "precipitation".to_string()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(forecast.current.precipitation)
+}
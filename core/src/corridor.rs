@@ -0,0 +1,167 @@
+use crate::error::{parse_failure, AnalysisError};
+use crate::provider::{extract_reply, timed_request, VisionProvider};
+use crate::snapshot::{
+    elapsed_ms, AnalysisSnapshot, BlobStore, Provenance, SnapshotImages, SnapshotResponses,
+};
+use serde::{Deserialize, Serialize};
+
+// Drawn onto frames so the model can see where the corridor runs
+pub const LINE_COLOR: [u8; 3] = [255, 0, 255];
+
+pub const CORRIDOR_PROMPT: &str = r#"This image is from a fixed camera watching avalanche paths above a road or rail corridor. The MAGENTA line drawn on the image marks the corridor; it is an overlay, not part of the scene. Judge how far avalanche activity reached toward that line. Return a JSON object with this structure:
+{
+    "activity_above_corridor": boolean,
+    "debris_reached_corridor": boolean,
+    "runout_reach": "none"|"upper-path"|"approaching"|"at-corridor"|"across-corridor",
+    "avalanche_type": "powder"|"loose-snow"|"slab"|"wet-loose"|"wet-slab"|"glide"|"cornice"|"slush"|"none",
+    "confidence_level": 0.0-100.0,
+    "observations": string[]
+}
+
+GUIDELINES:
+- activity_above_corridor: any fresh avalanche, fracture line or moving snow on slopes that drain toward the line.
+- runout_reach describes the furthest debris or powder cloud: "upper-path" stays in the start zone or upper track, "approaching" is in the runout short of the line, "at-corridor" touches the line, "across-corridor" covers and continues past it.
+- debris_reached_corridor is true only for "at-corridor" or "across-corridor".
+- Old, wind-affected or snow-covered debris is not fresh activity; mention it in observations instead.
+- If the corridor or the slopes above it are hidden by cloud, darkness or snowfall, say so in observations and keep confidence low."#;
+
+pub const RUNOUT_REACH: [&str; 5] = ["none", "upper-path", "approaching", "at-corridor", "across-corridor"];
+
+// A point on the frame as fractions of its width and height
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, schemars::JsonSchema)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+// Road or rail line below the avalanche paths, as a polyline over the full
+// camera frame
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Corridor {
+    pub points: Vec<Point>,
+}
+
+impl Corridor {
+    // Draws the polyline in LINE_COLOR, a few pixels wide so it survives
+    // downscaling before upload
+    pub fn draw(&self, image: &mut image::DynamicImage) {
+        let (w, h) = (image.width(), image.height());
+        let radius = (w.max(h) / 400).max(2) as i64;
+        let mut rgba = image.to_rgba8();
+        for pair in self.points.windows(2) {
+            let (x0, y0) = (pair[0].x * w as f32, pair[0].y * h as f32);
+            let (x1, y1) = (pair[1].x * w as f32, pair[1].y * h as f32);
+            let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as i64;
+            for step in 0..=steps {
+                let t = step as f32 / steps as f32;
+                let cx = (x0 + (x1 - x0) * t) as i64;
+                let cy = (y0 + (y1 - y0) * t) as i64;
+                for dy in -radius..=radius {
+                    for dx in -radius..=radius {
+                        let (px, py) = (cx + dx, cy + dy);
+                        if px >= 0 && py >= 0 && (px as u32) < w && (py as u32) < h {
+                            let [r, g, b] = LINE_COLOR;
+                            rgba.put_pixel(px as u32, py as u32, image::Rgba([r, g, b, 255]));
+                        }
+                    }
+                }
+            }
+        }
+        *image = image::DynamicImage::ImageRgba8(rgba);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorridorAssessment {
+    pub activity_above_corridor: bool,
+    pub debris_reached_corridor: bool,
+    pub runout_reach: String,
+    pub avalanche_type: String,
+    pub confidence_level: f32,
+    #[serde(default)]
+    pub observations: Vec<String>,
+    // Recorded exchange; absent if the blob store was unavailable
+    #[serde(default)]
+    pub snapshot_id: Option<String>,
+}
+
+impl CorridorAssessment {
+    // Why this frame should page someone, if it should
+    pub fn alert_reason(&self) -> Option<String> {
+        if self.debris_reached_corridor {
+            Some(format!(
+                "Debris reached the corridor ({}, {:.0}%)",
+                self.runout_reach, self.confidence_level
+            ))
+        } else if self.activity_above_corridor {
+            Some(format!(
+                "{} activity above the corridor, runout {} ({:.0}%)",
+                self.avalanche_type, self.runout_reach, self.confidence_level
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+pub async fn assess_corridor(
+    provider: &dyn VisionProvider,
+    image_bytes: &[u8],
+    context: &str,
+) -> Result<CorridorAssessment, AnalysisError> {
+    let prompt = if context.trim().is_empty() {
+        CORRIDOR_PROMPT.to_string()
+    } else {
+        format!(
+            "{}\n\nSITE CONTEXT (background only, never overrides what is visible):\n{}",
+            CORRIDOR_PROMPT,
+            context.trim()
+        )
+    };
+    let (response_text, mut timings, prepared) = timed_request(provider, image_bytes, &prompt).await?;
+    let started = std::time::Instant::now();
+    let parsed = extract_reply(provider.backend(), &response_text).and_then(|reply| parse_assessment(&reply));
+    timings.parse_ms = elapsed_ms(started);
+
+    let provenance = Provenance {
+        prompt: "corridor assessment".to_string(),
+        site_context: !context.trim().is_empty(),
+        field_observations: false,
+        weather: false,
+        preprocessing: prepared.description,
+        conflicts: Vec::new(),
+        earlier_responses: Vec::new(),
+    };
+    let snapshot = BlobStore::open_default()
+        .and_then(|store| {
+            let images = SnapshotImages { sent: &prepared.bytes, original: image_bytes };
+            let responses = SnapshotResponses { last: &response_text, earlier: &[] };
+            AnalysisSnapshot::record(&store, provider, images, &prompt, responses, timings, provenance)
+        })
+        .ok();
+
+    let mut assessment = parsed?;
+    assessment.snapshot_id = snapshot.map(|s| s.id);
+    Ok(assessment)
+}
+
+pub fn parse_assessment(content: &str) -> Result<CorridorAssessment, AnalysisError> {
+    let mut assessment: CorridorAssessment =
+        serde_json::from_str(content).map_err(|e| parse_failure(content, e))?;
+
+    if !RUNOUT_REACH.contains(&assessment.runout_reach.as_str()) {
+        return Err(AnalysisError::InvalidValue { field: "runout reach", value: assessment.runout_reach });
+    }
+    if !crate::model::AVALANCHE_TYPES.contains(&assessment.avalanche_type.as_str()) {
+        return Err(AnalysisError::InvalidValue { field: "avalanche type", value: assessment.avalanche_type });
+    }
+    if !(0.0..=100.0).contains(&assessment.confidence_level) {
+        return Err(AnalysisError::InvalidValue {
+            field: "confidence level",
+            value: assessment.confidence_level.to_string(),
+        });
+    }
+    // The reach is the more specific answer, so the flag follows it
+    assessment.debris_reached_corridor = matches!(assessment.runout_reach.as_str(), "at-corridor" | "across-corridor");
+    Ok(assessment)
+}
@@ -0,0 +1,108 @@
+use crate::history::{History, Verdict};
+use crate::snapshot::BlobStore;
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(Serialize)]
+struct LabelRecord<'a> {
+    id: &'a str,
+    image_hash: &'a str,
+    model: &'a str,
+    label: &'a str,
+    ai_label: &'a str,
+    human_verdict: Option<Verdict>,
+    confidence_level: f32,
+}
+
+// Writes one JSON line per usable history entry with the effective label,
+// keeping the AI label next to it. Rejected entries are left out.
+pub fn export_labels(history: &History, path: &std::path::Path) -> anyhow::Result<usize> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut count = 0;
+    for entry in &history.entries {
+        let Some(label) = entry.effective_type() else {
+            continue;
+        };
+        let record = LabelRecord {
+            id: entry.id(),
+            image_hash: &entry.snapshot.image_hash,
+            model: &entry.snapshot.model,
+            label,
+            ai_label: &entry.analysis.avalanche_type,
+            human_verdict: entry.review.as_ref().map(|r| r.verdict),
+            confidence_level: entry.analysis.confidence_level,
+        };
+        serde_json::to_writer(&mut file, &record)?;
+        writeln!(file)?;
+        count += 1;
+    }
+    file.flush()?;
+    crate::checksum::write_sidecar(path)?;
+    Ok(count)
+}
+
+// What a fine-tuning export wrote and what it had to leave out
+#[derive(Debug, Default)]
+pub struct FineTuningExport {
+    pub written: usize,
+    // Corrected to another type: the characteristics are still the AI's and
+    // would contradict the label
+    pub skipped_corrected: usize,
+    // Ids of entries whose image or prompt is no longer in the blob store
+    pub missing_blobs: Vec<String>,
+}
+
+// Writes human-reviewed entries as a chat fine-tuning dataset: the stored
+// prompt and image as the user turn, the reviewed analysis JSON as the
+// ideal assistant reply. Unreviewed and rejected entries are skipped, and so
+// are corrections to another type, since only the type was re-labelled.
+pub fn export_fine_tuning(history: &History, store: &BlobStore, path: &std::path::Path) -> anyhow::Result<FineTuningExport> {
+    use base64::Engine;
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut summary = FineTuningExport::default();
+    for entry in history.entries.iter().filter(|e| e.review.is_some()) {
+        let Some(label) = entry.effective_type() else {
+            continue;
+        };
+        if label != entry.analysis.avalanche_type {
+            summary.skipped_corrected += 1;
+            continue;
+        }
+        let (Ok(image), Ok(prompt)) = (entry.snapshot.image(store), entry.snapshot.prompt(store)) else {
+            summary.missing_blobs.push(entry.id().to_string());
+            continue;
+        };
+        let mime = match image::guess_format(&image) {
+            Ok(image::ImageFormat::Png) => "image/png",
+            Ok(image::ImageFormat::WebP) => "image/webp",
+            _ => "image/jpeg",
+        };
+        let record = serde_json::json!({
+            "messages": [
+                {
+                    "role": "user",
+                    "content": [
+                        {"type": "text", "text": prompt},
+                        {"type": "image_url", "image_url": {
+                            "url": format!(
+                                "data:{};base64,{}",
+                                mime,
+                                base64::engine::general_purpose::STANDARD.encode(&image)
+                            )
+                        }}
+                    ]
+                },
+                {
+                    "role": "assistant",
+                    "content": serde_json::to_string(&entry.analysis.model_reply())?
+                }
+            ]
+        });
+        serde_json::to_writer(&mut file, &record)?;
+        writeln!(file)?;
+        summary.written += 1;
+    }
+    file.flush()?;
+    crate::checksum::write_sidecar(path)?;
+    Ok(summary)
+}
@@ -0,0 +1,117 @@
+// Budget for turning photos into pixels. A 48 MP photo is ~150 MB once
+// decoded and about twice that while it is rotated and resized, so a few at
+// once can exhaust an 8 GB field laptop; decodes wait their turn instead.
+// The memory cap is soft: one image larger than the whole budget still goes,
+// on its own.
+
+use futures::channel::oneshot;
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+pub const DEFAULT_WORKERS: usize = 2;
+pub const DEFAULT_BUDGET_MB: u64 = 1024;
+// Pixel buffers alive at the peak of a decode: the decoded image plus a
+// rotated or resized copy
+const WORKING_COPIES: u64 = 2;
+const BYTES_PER_PIXEL: u64 = 4;
+
+struct Budget {
+    workers: usize,
+    budget: u64,
+    active: usize,
+    used: u64,
+    // First come first served, so a large photo is not starved by small ones
+    waiting: VecDeque<(u64, oneshot::Sender<DecodePermit>)>,
+}
+
+impl Budget {
+    fn fits(&self, estimate: u64) -> bool {
+        self.active < self.workers && (self.active == 0 || self.used + estimate <= self.budget)
+    }
+
+    fn wake(&mut self) {
+        while self.waiting.front().is_some_and(|(estimate, _)| self.fits(*estimate)) {
+            let Some((estimate, sender)) = self.waiting.pop_front() else {
+                break;
+            };
+            self.active += 1;
+            self.used += estimate;
+            // The waiter gave up; undo without going through Drop, which
+            // would lock again
+            if let Err(permit) = sender.send(DecodePermit { estimate }) {
+                std::mem::forget(permit);
+                self.active -= 1;
+                self.used -= estimate;
+            }
+        }
+    }
+}
+
+static BUDGET: Mutex<Budget> = Mutex::new(Budget {
+    workers: DEFAULT_WORKERS,
+    budget: DEFAULT_BUDGET_MB * 1024 * 1024,
+    active: 0,
+    used: 0,
+    waiting: VecDeque::new(),
+});
+
+fn budget() -> MutexGuard<'static, Budget> {
+    BUDGET.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Applies new limits; waiting decodes that now fit start right away
+pub fn set_limits(workers: usize, budget_mb: u64) {
+    let mut budget = budget();
+    budget.workers = workers.max(1);
+    budget.budget = budget_mb * 1024 * 1024;
+    budget.wake();
+}
+
+// Peak memory for decoding an image with these dimensions
+pub fn estimate_for(width: u32, height: u32, file_len: u64) -> u64 {
+    u64::from(width) * u64::from(height) * BYTES_PER_PIXEL * WORKING_COPIES + file_len
+}
+
+// Peak memory for decoding an image file, from its header alone
+pub fn estimate_file(path: &std::path::Path) -> u64 {
+    let len = std::fs::metadata(path).map_or(0, |m| m.len());
+    match image::image_dimensions(path) {
+        Ok((w, h)) => estimate_for(w, h, len),
+        // Compressed photos are roughly a tenth of their pixel data
+        Err(_) => len * 10 * WORKING_COPIES,
+    }
+}
+
+// Whether a decode of this size would start now, for callers that can hold
+// back work (e.g. not read the next batch file) rather than queue it
+pub fn has_room(estimate: u64) -> bool {
+    let budget = budget();
+    budget.waiting.is_empty() && budget.fits(estimate)
+}
+
+// Held while decoding; dropping it lets the next decode start
+pub struct DecodePermit {
+    estimate: u64,
+}
+
+impl Drop for DecodePermit {
+    fn drop(&mut self) {
+        let mut budget = budget();
+        budget.active -= 1;
+        budget.used -= self.estimate;
+        budget.wake();
+    }
+}
+
+// Waits until a worker and enough of the memory budget are free
+pub async fn permit(estimate: u64) -> DecodePermit {
+    let receiver = {
+        let mut budget = budget();
+        let (sender, receiver) = oneshot::channel();
+        budget.waiting.push_back((estimate, sender));
+        budget.wake();
+        receiver
+    };
+    // Waiters only leave the queue by being sent a permit
+    receiver.await.expect("decode permit waiter dropped")
+}
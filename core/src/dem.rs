@@ -0,0 +1,155 @@
+// Elevation grid around an analysis location, for relating a photo to the
+// terrain it shows. Heights come from Open-Meteo's elevation API (Copernicus
+// 90 m DEM, free, no API key).
+
+use crate::camera::Location;
+use crate::orientation::{DEFAULT_FOV_DEG, DEFAULT_RANGE_M};
+use serde::Deserialize;
+
+const ELEVATION_URL: &str = "https://api.open-meteo.com/v1/elevation";
+// Coordinates the API accepts per request
+const MAX_POINTS_PER_REQUEST: usize = 100;
+const METRES_PER_DEGREE: f64 = 111_320.0;
+
+// Slope angles where slab avalanches typically release
+pub const START_ZONE_SLOPE: std::ops::RangeInclusive<f32> = 30.0..=45.0;
+
+// Grid used for the terrain view: a little over twice the view-cone range
+// across, at roughly the DEM's own resolution
+pub const GRID_SIZE: usize = 40;
+pub const GRID_SPACING_M: f32 = 80.0;
+
+#[derive(Deserialize)]
+struct Elevations {
+    elevation: Vec<f32>,
+}
+
+// Square grid of heights centred on `center`; row 0 is the northern edge
+#[derive(Debug, Clone)]
+pub struct Dem {
+    pub center: Location,
+    pub size: usize,
+    pub spacing_m: f32,
+    pub heights: Vec<f32>,  // metres above sea level, row-major
+}
+
+impl Dem {
+    pub async fn fetch(client: &reqwest::Client, center: Location, size: usize, spacing_m: f32) -> anyhow::Result<Self> {
+        let mut grid = Self { center, size, spacing_m, heights: Vec::with_capacity(size * size) };
+        let points: Vec<Location> =
+            (0..size).flat_map(|row| (0..size).map(move |col| (col, row))).map(|(c, r)| grid.location(c, r)).collect();
+        for chunk in points.chunks(MAX_POINTS_PER_REQUEST) {
+            let join = |f: fn(&Location) -> f64| chunk.iter().map(|p| format!("{:.5}", f(p))).collect::<Vec<_>>().join(",");
+            let reply: Elevations = client
+                .get(ELEVATION_URL)
+                .query(&[("latitude", join(|p| p.latitude)), ("longitude", join(|p| p.longitude))])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            if reply.elevation.len() != chunk.len() {
+                anyhow::bail!("Elevation service returned {} heights for {} points", reply.elevation.len(), chunk.len());
+            }
+            grid.heights.extend(reply.elevation);
+        }
+        Ok(grid)
+    }
+
+    pub(crate) fn index(&self, col: usize, row: usize) -> usize {
+        row.min(self.size - 1) * self.size + col.min(self.size - 1)
+    }
+
+    pub fn height(&self, col: usize, row: usize) -> f32 {
+        self.heights[self.index(col, row)]
+    }
+
+    // Metres east and north of the centre
+    pub fn offset(&self, col: usize, row: usize) -> (f32, f32) {
+        let half = (self.size as f32 - 1.0) / 2.0;
+        ((col as f32 - half) * self.spacing_m, (half - row as f32) * self.spacing_m)
+    }
+
+    pub fn location(&self, col: usize, row: usize) -> Location {
+        let (east, north) = self.offset(col, row);
+        self.location_at(east, north)
+    }
+
+    // Location of a point given in metres east and north of the centre
+    pub fn location_at(&self, east: f32, north: f32) -> Location {
+        Location {
+            latitude: self.center.latitude + north as f64 / METRES_PER_DEGREE,
+            longitude: self.center.longitude
+                + east as f64 / (METRES_PER_DEGREE * self.center.latitude.to_radians().cos()),
+        }
+    }
+
+    // Inverse of location_at
+    pub fn offset_of(&self, location: Location) -> (f32, f32) {
+        let north = (location.latitude - self.center.latitude) * METRES_PER_DEGREE;
+        let east = (location.longitude - self.center.longitude)
+            * METRES_PER_DEGREE
+            * self.center.latitude.to_radians().cos();
+        (east as f32, north as f32)
+    }
+
+    // Fractional grid coordinates (column, row) of a point in metres east
+    // and north of the centre
+    pub fn grid_position(&self, east: f32, north: f32) -> (f32, f32) {
+        let half = (self.size as f32 - 1.0) / 2.0;
+        (half + east / self.spacing_m, half - north / self.spacing_m)
+    }
+
+    // Height at fractional grid coordinates, bilinearly interpolated
+    pub fn height_at(&self, col: f32, row: f32) -> f32 {
+        let max = (self.size - 1) as f32;
+        let (col, row) = (col.clamp(0.0, max), row.clamp(0.0, max));
+        let (c0, r0) = (col.floor() as usize, row.floor() as usize);
+        let (fc, fr) = (col - c0 as f32, row - r0 as f32);
+        let top = self.height(c0, r0) * (1.0 - fc) + self.height(c0 + 1, r0) * fc;
+        let bottom = self.height(c0, r0 + 1) * (1.0 - fc) + self.height(c0 + 1, r0 + 1) * fc;
+        top * (1.0 - fr) + bottom * fr
+    }
+
+    // Height change per metre towards the east and the north
+    fn gradient(&self, col: usize, row: usize) -> (f32, f32) {
+        let (c0, c1) = (col.saturating_sub(1), (col + 1).min(self.size - 1));
+        let (r0, r1) = (row.saturating_sub(1), (row + 1).min(self.size - 1));
+        let east = (self.height(c1, row) - self.height(c0, row)) / ((c1 - c0).max(1) as f32 * self.spacing_m);
+        // Rows run southwards
+        let north = (self.height(col, r0) - self.height(col, r1)) / ((r1 - r0).max(1) as f32 * self.spacing_m);
+        (east, north)
+    }
+
+    // Steepest slope at a grid point in degrees, from central differences
+    pub fn slope_deg(&self, col: usize, row: usize) -> f32 {
+        let (east, north) = self.gradient(col, row);
+        east.hypot(north).atan().to_degrees()
+    }
+
+    // Downslope direction in degrees clockwise from north
+    pub fn aspect_deg(&self, col: usize, row: usize) -> f32 {
+        let (east, north) = self.gradient(col, row);
+        crate::orientation::normalize((-east).atan2(-north).to_degrees())
+    }
+
+    // Start-zone-angle grid points the photo could show: within the view
+    // cone when the bearing is known, within its range all around otherwise
+    pub fn highlight(&self, bearing: Option<f32>) -> Vec<bool> {
+        let mut marked = vec![false; self.size * self.size];
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let (east, north) = self.offset(col, row);
+                let in_range = east.hypot(north) as f64 <= DEFAULT_RANGE_M;
+                let in_view = bearing.is_none_or(|b| {
+                    let direction = east.atan2(north).to_degrees();
+                    let diff = (direction - b + 540.0).rem_euclid(360.0) - 180.0;
+                    diff.abs() as f64 <= DEFAULT_FOV_DEG / 2.0
+                });
+                marked[self.index(col, row)] =
+                    in_range && in_view && START_ZONE_SLOPE.contains(&self.slope_deg(col, row));
+            }
+        }
+        marked
+    }
+}
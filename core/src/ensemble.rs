@@ -0,0 +1,101 @@
+use crate::error::AnalysisError;
+use crate::model::AvalancheAnalysis;
+use crate::provider::{classify, Classification, ClassifyOptions, VisionProvider};
+
+// One provider's contribution to an ensemble run
+pub struct Member {
+    pub backend: &'static str,
+    pub model: String,
+    pub outcome: Result<Classification, AnalysisError>,
+}
+
+pub struct Ensemble {
+    pub members: Vec<Member>,
+    // Highest-confidence analysis of the winning type, with the combined confidence
+    pub analysis: AvalancheAnalysis,
+    // (avalanche type, votes), most votes first
+    pub votes: Vec<(String, usize)>,
+}
+
+impl Ensemble {
+    pub fn unanimous(&self) -> bool {
+        self.votes.len() == 1 && self.members.iter().all(|m| m.outcome.is_ok())
+    }
+
+    // Members that failed or voted against the merged type
+    pub fn dissenters(&self) -> impl Iterator<Item = &Member> {
+        self.members.iter().filter(|m| match &m.outcome {
+            Ok(c) => c.analysis.avalanche_type != self.analysis.avalanche_type,
+            Err(_) => true,
+        })
+    }
+}
+
+// Sends the image to every provider concurrently. Each result goes through
+// the usual characteristic scoring before it is allowed to vote.
+pub async fn classify_ensemble(
+    providers: &[Box<dyn VisionProvider>],
+    image_bytes: &[u8],
+    options: &ClassifyOptions,
+) -> anyhow::Result<Ensemble> {
+    let outcomes = futures::future::join_all(
+        providers.iter().map(|p| classify(p.as_ref(), image_bytes, options)),
+    )
+    .await;
+
+    let members = providers
+        .iter()
+        .zip(outcomes)
+        .map(|(provider, outcome)| Member {
+            backend: provider.backend(),
+            model: provider.model().to_string(),
+            outcome,
+        })
+        .collect();
+    merge(members)
+}
+
+// Majority vote over the successful members. Ties go to the type with the
+// higher summed confidence; the combined confidence is the winners' mean
+// confidence scaled by their share of the vote.
+pub fn merge(members: Vec<Member>) -> anyhow::Result<Ensemble> {
+    let voters: Vec<&AvalancheAnalysis> = members
+        .iter()
+        .filter_map(|m| m.outcome.as_ref().ok().map(|c| &c.analysis))
+        .collect();
+    if voters.is_empty() {
+        let errors: Vec<String> = members
+            .iter()
+            .filter_map(|m| m.outcome.as_ref().err().map(|e| format!("{}: {}", m.backend, e)))
+            .collect();
+        return Err(anyhow::anyhow!("Every ensemble member failed\n{}", errors.join("\n")));
+    }
+
+    let mut tally: Vec<(String, usize, f32)> = Vec::new();
+    for analysis in &voters {
+        match tally.iter_mut().find(|(t, _, _)| *t == analysis.avalanche_type) {
+            Some(entry) => {
+                entry.1 += 1;
+                entry.2 += analysis.confidence_level;
+            }
+            None => tally.push((analysis.avalanche_type.clone(), 1, analysis.confidence_level)),
+        }
+    }
+    tally.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.total_cmp(&a.2)));
+    let (winner, _, winner_confidence) = tally[0].clone();
+
+    let mut analysis = voters
+        .iter()
+        .filter(|a| a.avalanche_type == winner)
+        .max_by(|a, b| a.confidence_level.total_cmp(&b.confidence_level))
+        .map(|a| (*a).clone())
+        .unwrap();
+    // Mean winner confidence times vote share reduces to this
+    analysis.confidence_level = winner_confidence / voters.len() as f32;
+
+    Ok(Ensemble {
+        members,
+        analysis,
+        votes: tally.into_iter().map(|(t, n, _)| (t, n)).collect(),
+    })
+}
@@ -0,0 +1,208 @@
+use crate::scoring::TypeScores;
+
+/// Why a classification failed, so callers can react to the kind of failure
+/// rather than parse messages
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AnalysisError {
+    InvalidApiKey { backend: &'static str },
+    RateLimited { backend: &'static str, retry_after: Option<u64> },
+    Timeout,
+    /// Connection refused, DNS failure, ...
+    Unreachable { detail: String },
+    /// Any other non-success reply from the service
+    Api { backend: &'static str, status: u16, message: String },
+    EmptyResponse,
+    /// The reply wasn't the JSON the prompt asks for
+    ParseFailure { raw: String, reason: String },
+    /// Top two type scores too close to call
+    Ambiguous { scores: TypeScores },
+    InsufficientEvidence { scores: TypeScores },
+    /// The model's type disagrees with what its own characteristics score as
+    ValidationConflict { scores: TypeScores, claimed: String, expected: String },
+    InvalidValue { field: &'static str, value: String },
+    UnknownBackend(String),
+    /// Every backend of a fallback chain failed, in the order tried
+    FallbackExhausted { attempts: Vec<crate::fallback::FailedAttempt> },
+}
+
+impl AnalysisError {
+    /// What the user can do about it, where there is something
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            AnalysisError::InvalidApiKey { .. } => Some("Check your API key."),
+            AnalysisError::RateLimited { .. } => {
+                Some("Wait a moment and try again, or check your plan's rate limits.")
+            }
+            AnalysisError::Timeout => Some("The service is slow to respond; try again."),
+            AnalysisError::Unreachable { .. } => {
+                Some("Check your connection, and the server URL for local or custom backends.")
+            }
+            AnalysisError::ParseFailure { .. } | AnalysisError::EmptyResponse => {
+                Some("Try again, or pick a model that supports JSON output.")
+            }
+            AnalysisError::Ambiguous { .. }
+            | AnalysisError::InsufficientEvidence { .. }
+            | AnalysisError::ValidationConflict { .. } => {
+                Some("Try a closer or clearer image of the release area.")
+            }
+            AnalysisError::FallbackExhausted { .. } => {
+                Some("Check each backend's settings and connection, or try again later.")
+            }
+            _ => None,
+        }
+    }
+
+    /// Stable snake_case name of the variant, for callers outside Rust
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AnalysisError::InvalidApiKey { .. } => "invalid_api_key",
+            AnalysisError::RateLimited { .. } => "rate_limited",
+            AnalysisError::Timeout => "timeout",
+            AnalysisError::Unreachable { .. } => "unreachable",
+            AnalysisError::Api { .. } => "api",
+            AnalysisError::EmptyResponse => "empty_response",
+            AnalysisError::ParseFailure { .. } => "parse_failure",
+            AnalysisError::Ambiguous { .. } => "ambiguous",
+            AnalysisError::InsufficientEvidence { .. } => "insufficient_evidence",
+            AnalysisError::ValidationConflict { .. } => "validation_conflict",
+            AnalysisError::InvalidValue { .. } => "invalid_value",
+            AnalysisError::UnknownBackend(_) => "unknown_backend",
+            AnalysisError::FallbackExhausted { .. } => "fallback_exhausted",
+        }
+    }
+
+    /// Worth retrying as-is later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            AnalysisError::RateLimited { .. } | AnalysisError::Timeout | AnalysisError::Unreachable { .. } => true,
+            AnalysisError::Api { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// The model answered but its answer could not be used; a different
+    /// model may well do better on the same image
+    pub fn is_model_failure(&self) -> bool {
+        matches!(
+            self,
+            AnalysisError::ParseFailure { .. }
+                | AnalysisError::EmptyResponse
+                | AnalysisError::Ambiguous { .. }
+                | AnalysisError::InsufficientEvidence { .. }
+                | AnalysisError::ValidationConflict { .. }
+                | AnalysisError::InvalidValue { .. }
+        )
+    }
+
+    /// Message followed by the hint, for showing to the user
+    pub fn user_message(&self) -> String {
+        match self.hint() {
+            Some(hint) => format!("{}\n{}", self, hint),
+            None => self.to_string(),
+        }
+    }
+
+    /// The error as the C interface and `--pipe` report it; see
+    /// failure_json
+    pub fn to_json(&self) -> serde_json::Value {
+        failure_json(self.kind(), self.to_string(), self.hint())
+    }
+}
+
+/// `{"ok": false, "error": {"kind": ..., "message": ..., "hint": ...}}`, the
+/// failure shape shared by the C interface and `--pipe`, for errors that are
+/// not an AnalysisError, e.g. a bad argument
+pub fn failure_json(kind: &str, message: String, hint: Option<&str>) -> serde_json::Value {
+    serde_json::json!({ "ok": false, "error": { "kind": kind, "message": message, "hint": hint } })
+}
+
+impl std::fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisError::InvalidApiKey { backend } => write!(f, "{} rejected the API key", backend),
+            AnalysisError::RateLimited { backend, retry_after: Some(secs) } => {
+                write!(f, "{} rate limit reached; retry in {}s", backend, secs)
+            }
+            AnalysisError::RateLimited { backend, retry_after: None } => write!(f, "{} rate limit reached", backend),
+            AnalysisError::Timeout => write!(f, "Request timed out"),
+            AnalysisError::Unreachable { detail } => write!(f, "Could not reach the service: {}", detail),
+            AnalysisError::Api { backend, status, message } => {
+                write!(f, "{} API error ({}): {}", backend, status, message)
+            }
+            AnalysisError::EmptyResponse => write!(f, "Empty API response"),
+            AnalysisError::ParseFailure { raw, reason } => {
+                write!(f, "JSON parse error: {}\nResponse: {}", reason, raw)
+            }
+            AnalysisError::Ambiguous { .. } => {
+                write!(f, "Classification uncertainty: Multiple types show similar characteristics")
+            }
+            AnalysisError::InsufficientEvidence { .. } => {
+                write!(f, "Insufficient characteristic evidence for classification")
+            }
+            AnalysisError::ValidationConflict { scores, claimed, expected } => write!(
+                f,
+                "Inconsistent classification: Visual characteristics strongly indicate {} (score: {}) but classified as {}",
+                expected,
+                scores.for_type(expected).unwrap_or_default(),
+                claimed
+            ),
+            AnalysisError::InvalidValue { field, value } => write!(f, "Invalid {}: {}", field, value),
+            AnalysisError::UnknownBackend(backend) => write!(f, "Unknown backend: {}", backend),
+            AnalysisError::FallbackExhausted { attempts } if attempts.is_empty() => {
+                write!(f, "No backend is configured")
+            }
+            AnalysisError::FallbackExhausted { attempts } => {
+                write!(f, "Every backend in the fallback chain failed:")?;
+                for attempt in attempts {
+                    write!(f, "\n• {}", attempt.summary())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+impl From<reqwest::Error> for AnalysisError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            AnalysisError::Timeout
+        } else {
+            AnalysisError::Unreachable { detail: err.to_string() }
+        }
+    }
+}
+
+/// Reads a reply body, turning HTTP failures into their error kinds. The
+/// message is taken from the usual {"error": {"message"}} or {"error"} shapes.
+pub(crate) async fn read_body(backend: &'static str, response: reqwest::Response) -> Result<String, AnalysisError> {
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    let body = response.text().await?;
+    if status.is_success() {
+        return Ok(body);
+    }
+    match status.as_u16() {
+        401 | 403 => Err(AnalysisError::InvalidApiKey { backend }),
+        429 => Err(AnalysisError::RateLimited { backend, retry_after }),
+        408 | 504 => Err(AnalysisError::Timeout),
+        status => {
+            let json: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+            let message = json["error"]["message"]
+                .as_str()
+                .or_else(|| json["error"].as_str())
+                .map_or(body.clone(), str::to_string);
+            Err(AnalysisError::Api { backend, status, message })
+        }
+    }
+}
+
+pub(crate) fn parse_failure(raw: &str, err: impl std::fmt::Display) -> AnalysisError {
+    AnalysisError::ParseFailure { raw: raw.to_string(), reason: err.to_string() }
+}
@@ -0,0 +1,141 @@
+use crate::camera::Roi;
+use crate::error::{parse_failure, AnalysisError};
+use crate::provider::{extract_reply, VisionProvider};
+use serde::{Deserialize, Serialize};
+
+// Asset outlines drawn onto the frame sent with the exposure prompt
+pub const OUTLINE_COLOR: [u8; 3] = [0, 255, 255];
+
+const EXPOSURE_PROMPT: &str = r#"This camera frame shows fresh avalanche activity. Exposed assets are outlined in CYAN; the outlines are overlays, not part of the scene. Locate the avalanche: its release area, track, debris and any powder cloud. Return a JSON object with this structure:
+{
+    "activity_box": {"x": 0.0-1.0, "y": 0.0-1.0, "width": 0.0-1.0, "height": 0.0-1.0} | null,
+    "affected_assets": string[]
+}
+
+GUIDELINES:
+- activity_box is the smallest box around all visible avalanche activity, as fractions of the image width and height measured from the top-left corner. Use null if you cannot locate it.
+- affected_assets lists the names of outlined assets that the debris or powder cloud touches or has clearly run over. Only use names from the list below.
+
+ASSETS:"#;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssetKind {
+    #[default]
+    LiftTower,
+    Building,
+    Road,
+    Other,
+}
+
+impl AssetKind {
+    pub const ALL: [AssetKind; 4] = [AssetKind::LiftTower, AssetKind::Building, AssetKind::Road, AssetKind::Other];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AssetKind::LiftTower => "lift tower",
+            AssetKind::Building => "building",
+            AssetKind::Road => "road",
+            AssetKind::Other => "asset",
+        }
+    }
+}
+
+// Something that matters if an avalanche reaches it, marked on a camera's
+// full frame
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Asset {
+    pub name: String,
+    pub kind: AssetKind,
+    pub area: Roi,
+}
+
+// An asset that detected activity reached, kept with the analysis
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExposedAsset {
+    pub name: String,
+    pub kind: AssetKind,
+}
+
+impl ExposedAsset {
+    pub fn describe(&self) -> String {
+        format!("{} ({})", self.name, self.kind.label())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ExposureReply {
+    activity_box: Option<Roi>,
+    #[serde(default)]
+    affected_assets: Vec<String>,
+}
+
+fn overlaps(a: &Roi, b: &Roi) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+// The full frame with every asset outlined in OUTLINE_COLOR, as sent to
+// assess_exposure
+pub fn outline_assets(image_bytes: &[u8], assets: &[Asset]) -> anyhow::Result<Vec<u8>> {
+    let mut rgba = image::load_from_memory(image_bytes)?.to_rgba8();
+    let (w, h) = rgba.dimensions();
+    let thickness = (w.max(h) / 400).max(2);
+    let [r, g, b] = OUTLINE_COLOR;
+    for asset in assets {
+        let (x, y, width, height) = asset.area.rect(w, h);
+        for py in y..y + height {
+            for px in x..x + width {
+                let edge = px < x + thickness
+                    || px + thickness >= x + width
+                    || py < y + thickness
+                    || py + thickness >= y + height;
+                if edge {
+                    rgba.put_pixel(px, py, image::Rgba([r, g, b, 255]));
+                }
+            }
+        }
+    }
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgba8(rgba)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Png)?;
+    Ok(bytes)
+}
+
+// Asks the model where the activity is on the outlined full frame and
+// returns the assets it reaches. An asset counts when the model names it or
+// when its outline overlaps the reported activity box, so a vague box alone
+// is enough to flag it.
+pub async fn assess_exposure(
+    provider: &dyn VisionProvider,
+    outlined_frame: &[u8],
+    assets: &[Asset],
+) -> Result<Vec<ExposedAsset>, AnalysisError> {
+    if assets.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut prompt = EXPOSURE_PROMPT.to_string();
+    for asset in assets {
+        let a = &asset.area;
+        prompt.push_str(&format!(
+            "\n- \"{}\" ({}): x {:.2}-{:.2}, y {:.2}-{:.2}",
+            asset.name,
+            asset.kind.label(),
+            a.x,
+            a.x + a.width,
+            a.y,
+            a.y + a.height
+        ));
+    }
+
+    let response_text = provider.request(outlined_frame, &prompt).await?;
+    let content = extract_reply(provider.backend(), &response_text)?;
+    let reply: ExposureReply = serde_json::from_str(&content).map_err(|e| parse_failure(&content, e))?;
+
+    Ok(assets
+        .iter()
+        .filter(|asset| {
+            reply.affected_assets.iter().any(|n| n.trim().eq_ignore_ascii_case(&asset.name))
+                || reply.activity_box.as_ref().is_some_and(|b| overlaps(b, &asset.area))
+        })
+        .map(|asset| ExposedAsset { name: asset.name.clone(), kind: asset.kind })
+        .collect())
+}
@@ -0,0 +1,57 @@
+use crate::error::AnalysisError;
+use crate::provider::{classify, Classification, ClassifyOptions, VisionProvider};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// A fallback chain is an ordered list of backends tried one after another
+// until one produces a usable result. Any error moves on to the next, as
+// does an attempt that takes longer than ATTEMPT_TIMEOUT; the last backend
+// is waited on for as long as it takes, since there is nothing left to fall
+// back to. Failed attempts are kept with the result.
+
+pub const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(90);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedAttempt {
+    pub backend: String,
+    pub model: String,
+    pub error: String,
+}
+
+impl FailedAttempt {
+    // e.g. "openai gpt-4o: Request timed out"
+    pub fn summary(&self) -> String {
+        format!("{} {}: {}", self.backend, self.model, self.error.lines().next().unwrap_or_default())
+    }
+}
+
+pub async fn classify_with_fallback(
+    chain: &[Box<dyn VisionProvider>],
+    image_bytes: &[u8],
+    options: &ClassifyOptions,
+) -> Result<Classification, AnalysisError> {
+    let mut failed = Vec::new();
+    for (i, provider) in chain.iter().enumerate() {
+        let last = i + 1 == chain.len();
+        let attempt = classify(provider.as_ref(), image_bytes, options);
+        let result = if last {
+            attempt.await
+        } else {
+            tokio::time::timeout(ATTEMPT_TIMEOUT, attempt).await.unwrap_or(Err(AnalysisError::Timeout))
+        };
+        match result {
+            Ok(mut classification) => {
+                classification.fallbacks = failed;
+                return Ok(classification);
+            }
+            // Without a chain the error is the backend's own, as before
+            Err(err) if last && failed.is_empty() => return Err(err),
+            Err(err) => failed.push(FailedAttempt {
+                backend: provider.backend().to_string(),
+                model: provider.model().to_string(),
+                error: err.to_string(),
+            }),
+        }
+    }
+    Err(AnalysisError::FallbackExhausted { attempts: failed })
+}
@@ -0,0 +1,53 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Byte counters a UI can poll while a download runs
+#[derive(Debug, Default)]
+pub struct Progress {
+    received: AtomicU64,
+    // 0 until the server reports a length
+    total: AtomicU64,
+}
+
+impl Progress {
+    // Fraction done, None while the total is unknown
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.total.load(Ordering::Relaxed);
+        (total > 0).then(|| self.received.load(Ordering::Relaxed) as f32 / total as f32)
+    }
+
+    pub fn received(&self) -> u64 {
+        self.received.load(Ordering::Relaxed)
+    }
+}
+
+// Largest image accepted from a URL
+const MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+// Downloads an image over HTTPS, reporting progress as chunks arrive, and
+// checks the bytes decode as an image before handing them back
+pub async fn download_image(client: &reqwest::Client, url: &str, progress: &Progress) -> anyhow::Result<Vec<u8>> {
+    let parsed = reqwest::Url::parse(url.trim())?;
+    if parsed.scheme() != "https" {
+        return Err(anyhow::anyhow!("Only https:// URLs are supported"));
+    }
+
+    let mut response = client.get(parsed).send().await?.error_for_status()?;
+    if let Some(length) = response.content_length() {
+        if length > MAX_BYTES {
+            return Err(anyhow::anyhow!("Image is too large ({} MB)", length / (1024 * 1024)));
+        }
+        progress.total.store(length, Ordering::Relaxed);
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() as u64 > MAX_BYTES {
+            return Err(anyhow::anyhow!("Image is too large"));
+        }
+        progress.received.store(bytes.len() as u64, Ordering::Relaxed);
+    }
+
+    image::guess_format(&bytes).map_err(|_| anyhow::anyhow!("URL did not return an image"))?;
+    Ok(bytes)
+}
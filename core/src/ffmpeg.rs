@@ -0,0 +1,157 @@
+// Frames from live streams and video files through the ffmpeg command-line
+// tool, which speaks RTSP and every codec cameras and drones use without
+// linking against its libraries. ffmpeg must be installed and on PATH, or
+// named by the AVALANCHE_FFMPEG environment variable.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+// A stream that has not produced a frame by then is treated as down
+pub const GRAB_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Whether the URL is a stream ffmpeg has to grab from, rather than a
+// snapshot endpoint
+pub fn is_stream(url: &str) -> bool {
+    let url = url.trim_start().to_lowercase();
+    ["rtsp://", "rtsps://", "rtmp://"].iter().any(|scheme| url.starts_with(scheme))
+}
+
+fn program() -> String {
+    std::env::var("AVALANCHE_FFMPEG").unwrap_or_else(|_| "ffmpeg".to_string())
+}
+
+fn spawn(command: &mut tokio::process::Command) -> anyhow::Result<tokio::process::Child> {
+    command.spawn().map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => anyhow::anyhow!("ffmpeg was not found; install it or set AVALANCHE_FFMPEG"),
+        _ => err.into(),
+    })
+}
+
+// One frame from the stream as JPEG. RTSP goes over TCP, as UDP is often
+// dropped between a mountain camera and the office.
+pub async fn grab_frame(url: &str) -> anyhow::Result<Vec<u8>> {
+    let mut command = tokio::process::Command::new(program());
+    command.args(["-hide_banner", "-loglevel", "error"]);
+    if url.to_lowercase().starts_with("rtsp") {
+        command.args(["-rtsp_transport", "tcp"]);
+    }
+    command
+        .args(["-i", url, "-frames:v", "1", "-q:v", "2", "-f", "image2pipe", "-c:v", "mjpeg", "pipe:1"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let child = spawn(&mut command)?;
+    let output = tokio::time::timeout(GRAB_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| anyhow::anyhow!("No frame from the stream within {} s", GRAB_TIMEOUT.as_secs()))??;
+    if !output.status.success() || output.stdout.is_empty() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("ffmpeg could not read the stream: {}", message.lines().last().unwrap_or("no output"));
+    }
+    Ok(output.stdout)
+}
+
+pub const VIDEO_EXTENSIONS: [&str; 4] = ["mp4", "mov", "m4v", "mkv"];
+
+// A long clip is cut off here rather than queueing thousands of analyses
+pub const MAX_VIDEO_FRAMES: usize = 300;
+
+// Long enough to decode MAX_VIDEO_FRAMES from an hour of 4K footage
+pub const EXTRACT_TIMEOUT: Duration = Duration::from_secs(600);
+
+// How frames are picked from a video
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Sampling {
+    // One frame every so many seconds
+    Interval { secs: f32 },
+    // The first frame, then one wherever the picture changes by more than
+    // the threshold, ffmpeg's scene score from 0 to 1
+    SceneChange { threshold: f32 },
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling::Interval { secs: 5.0 }
+    }
+}
+
+impl Sampling {
+    fn filter(&self) -> String {
+        match self {
+            Sampling::Interval { secs } => format!("fps=1/{}", secs.max(0.1)),
+            Sampling::SceneChange { threshold } => {
+                format!("select='eq(n,0)+gt(scene,{})'", threshold.clamp(0.01, 1.0))
+            }
+        }
+    }
+}
+
+pub struct VideoFrame {
+    // Position in the clip
+    pub at_secs: f64,
+    pub jpeg: Vec<u8>,
+}
+
+// Removed when dropped, which also covers an extraction cancelled part way
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// Extracts frames from a video file as JPEGs, with where in the clip each
+// one is. ffmpeg's showinfo filter logs the time of every frame it writes.
+// Dropping the future stops ffmpeg.
+pub async fn extract_frames(path: &Path, sampling: Sampling) -> anyhow::Result<Vec<VideoFrame>> {
+    let dir = TempDir(std::env::temp_dir().join(format!(
+        "avalanche-video-{}-{}",
+        std::process::id(),
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_nanos())
+    )));
+    std::fs::create_dir_all(&dir.0)?;
+    extract_into(path, sampling, &dir.0).await
+}
+
+async fn extract_into(path: &Path, sampling: Sampling, dir: &Path) -> anyhow::Result<Vec<VideoFrame>> {
+    let mut command = tokio::process::Command::new(program());
+    command
+        .args(["-hide_banner", "-nostats", "-loglevel", "info", "-i"])
+        .arg(path)
+        .args(["-vf", &format!("{},showinfo", sampling.filter())])
+        .args(["-vsync", "vfr", "-q:v", "2", "-frames:v", &MAX_VIDEO_FRAMES.to_string()])
+        .arg(dir.join("frame-%05d.jpg"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let child = spawn(&mut command)?;
+    let output = tokio::time::timeout(EXTRACT_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| anyhow::anyhow!("ffmpeg took longer than {} min on the video", EXTRACT_TIMEOUT.as_secs() / 60))??;
+    let log = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        anyhow::bail!("ffmpeg could not read the video: {}", log.lines().last().unwrap_or("no output"));
+    }
+    let times: Vec<f64> = log
+        .lines()
+        .filter(|line| line.contains("showinfo"))
+        .filter_map(|line| line.split("pts_time:").nth(1)?.split_whitespace().next()?.parse().ok())
+        .collect();
+    let mut files: Vec<_> = std::fs::read_dir(dir)?.flatten().map(|entry| entry.path()).collect();
+    files.sort();
+    if files.is_empty() {
+        anyhow::bail!("No frames could be extracted from the video");
+    }
+    files
+        .into_iter()
+        .enumerate()
+        .map(|(i, file)| {
+            Ok(VideoFrame { at_secs: times.get(i).copied().unwrap_or_default(), jpeg: std::fs::read(file)? })
+        })
+        .collect()
+}
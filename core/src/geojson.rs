@@ -0,0 +1,72 @@
+// GeoJSON (RFC 7946) export of located analyses for GIS tools and web maps.
+// Each analysis contributes a point, plus its view cone, confirmed slope
+// and projected avalanche outline when known; the `feature` property tells
+// them apart.
+
+use crate::camera::Location;
+use crate::history::HistoryEntry;
+use serde_json::{json, Value};
+
+fn position(location: &Location) -> Value {
+    json!([location.longitude, location.latitude])
+}
+
+// Closed ring, as GeoJSON requires the first point repeated at the end
+fn polygon(points: &[Location]) -> Value {
+    let mut ring: Vec<Value> = points.iter().map(position).collect();
+    if points.first() != points.last() {
+        ring.push(position(&points[0]));
+    }
+    json!({ "type": "Polygon", "coordinates": [ring] })
+}
+
+fn feature(kind: &str, geometry: Value, mut properties: Value) -> Value {
+    properties["feature"] = json!(kind);
+    json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+}
+
+pub fn feature_collection<'a>(entries: impl IntoIterator<Item = &'a HistoryEntry>) -> Value {
+    let mut features = Vec::new();
+    for entry in entries {
+        let Some(location) = entry.location else {
+            continue;
+        };
+        let properties = json!({
+            "analysis_id": entry.id(),
+            "avalanche_type": entry.effective_type(),
+            "confidence": entry.analysis.confidence_level,
+            "analyzed_at": chrono::DateTime::from_timestamp(entry.snapshot.created_at as i64, 0).map(|t| t.to_rfc3339()),
+            "captured_at": entry.captured_at.and_then(|t| chrono::DateTime::from_timestamp(t, 0)).map(|t| t.to_rfc3339()),
+            "source": entry.source,
+            "location_from_track": entry.location_from_track,
+            "bearing": entry.bearing.map(crate::orientation::normalize),
+            "facing": entry.bearing.map(crate::orientation::compass_point),
+            "camera": entry.camera_info,
+            "exposed_assets": entry.exposure.iter().map(|a| a.describe()).collect::<Vec<_>>(),
+        });
+        features.push(feature("observation", json!({ "type": "Point", "coordinates": position(&location) }), properties.clone()));
+        if let Some(cone) = entry.view_cone() {
+            features.push(feature("view", polygon(&cone), json!({ "analysis_id": entry.id() })));
+        }
+        if let Some(slope) = entry.slope.as_ref().filter(|s| s.polygon.len() >= 3) {
+            features.push(feature(
+                "slope",
+                polygon(&slope.polygon),
+                json!({
+                    "analysis_id": entry.id(),
+                    "mean_slope_deg": slope.mean_slope_deg,
+                    "aspect_deg": slope.aspect_deg,
+                    "min_elevation_m": slope.min_elevation_m,
+                    "max_elevation_m": slope.max_elevation_m,
+                    "area_m2": slope.area_m2,
+                }),
+            ));
+        }
+        if let Some(outline) = entry.outline.as_ref().filter(|o| o.len() >= 3) {
+            let mut properties = properties;
+            properties["approximate"] = json!(true);
+            features.push(feature("avalanche", polygon(outline), properties));
+        }
+    }
+    json!({ "type": "FeatureCollection", "features": features })
+}
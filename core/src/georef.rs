@@ -0,0 +1,162 @@
+// Photogrammetry-lite: places the avalanche the model outlines on the photo
+// onto the terrain. The camera sits at the centre of the elevation grid,
+// looks along the recorded bearing and is tilted towards the confirmed
+// slope (or the terrain ahead); each outline point is cast as a ray until it
+// meets the ground. Good enough to put a rough polygon on a map, not for
+// measuring.
+
+use crate::camera::Location;
+use crate::corridor::Point;
+use crate::dem::Dem;
+use crate::error::{parse_failure, AnalysisError};
+use crate::orientation::{DEFAULT_FOV_DEG, DEFAULT_RANGE_M};
+use crate::provider::{extract_reply, VisionProvider};
+use crate::viewshed::{Slope, EYE_HEIGHT_M};
+use serde::Deserialize;
+
+const OUTLINE_PROMPT: &str = r#"This photo shows avalanche activity. Trace the outline of the avalanche path that is visible: release area, track and debris together. Return a JSON object with this structure:
+{
+    "outline": [{"x": 0.0-1.0, "y": 0.0-1.0}, ...] | null
+}
+
+GUIDELINES:
+- outline is a polygon of 4 to 16 points in order around the avalanche, as fractions of the image width and height measured from the top-left corner.
+- Follow the edges of the debris and the crown or release area; do not include sky or undisturbed slopes.
+- Use null if no avalanche is visible."#;
+
+// Points needed for a polygon after projection
+const MIN_POINTS: usize = 3;
+
+#[derive(Debug, Deserialize)]
+struct OutlineReply {
+    outline: Option<Vec<Point>>,
+}
+
+// The avalanche outline in image space, with the image's width / height
+#[derive(Debug, Clone)]
+pub struct ImageOutline {
+    pub points: Vec<Point>,
+    pub aspect: f32,
+}
+
+// Asks the model to trace the avalanche; None when it sees none
+pub async fn outline_avalanche(
+    provider: &dyn VisionProvider,
+    image_bytes: &[u8],
+) -> Result<Option<ImageOutline>, AnalysisError> {
+    let (width, height) = crate::photo_meta::dimensions(image_bytes)
+        .ok_or_else(|| AnalysisError::InvalidValue { field: "image", value: "unreadable".into() })?;
+    // The model is sent the photo upright, so sideways ones swap sides
+    let (width, height) =
+        if crate::photo_meta::orientation(image_bytes) >= 5 { (height, width) } else { (width, height) };
+    let response_text = provider.request(image_bytes, OUTLINE_PROMPT).await?;
+    let content = extract_reply(provider.backend(), &response_text)?;
+    let reply: OutlineReply = serde_json::from_str(&content).map_err(|e| parse_failure(&content, e))?;
+    Ok(reply
+        .outline
+        .filter(|points| points.len() >= MIN_POINTS)
+        .map(|points| ImageOutline { points, aspect: width as f32 / height.max(1) as f32 }))
+}
+
+// Where the photo's centre points, degrees clockwise from north and
+// degrees above the horizon
+#[derive(Debug, Clone, Copy)]
+pub struct Pose {
+    pub bearing: f32,
+    pub pitch: f32,
+    pub hfov: f32,
+}
+
+fn eye_height(grid: &Dem) -> f32 {
+    let center = (grid.size / 2) as f32;
+    grid.height_at(center, center) + EYE_HEIGHT_M
+}
+
+// Tilt towards the confirmed slope's centre when there is one, otherwise
+// the average angle up to the terrain ahead within the view range. `hfov`
+// comes from the photo's focal length when known.
+pub fn estimate_pose(grid: &Dem, bearing: f32, slope: Option<&Slope>, hfov: Option<f32>) -> Pose {
+    let eye = eye_height(grid);
+    let angle_to = |east: f32, north: f32| {
+        let (col, row) = grid.grid_position(east, north);
+        (grid.height_at(col, row) - eye).atan2(east.hypot(north).max(1.0)).to_degrees()
+    };
+    let pitch = match slope.filter(|s| !s.polygon.is_empty()) {
+        Some(slope) => {
+            let n = slope.polygon.len() as f32;
+            let (east, north) = slope
+                .polygon
+                .iter()
+                .map(|l| grid.offset_of(*l))
+                .fold((0.0, 0.0), |(e, n), (pe, pn)| (e + pe, n + pn));
+            angle_to(east / n, north / n)
+        }
+        None => {
+            let (sin, cos) = bearing.to_radians().sin_cos();
+            let distances: Vec<f32> = (2..=(DEFAULT_RANGE_M / 100.0) as usize).map(|i| i as f32 * 100.0).collect();
+            distances.iter().map(|d| angle_to(d * sin, d * cos)).sum::<f32>() / distances.len() as f32
+        }
+    };
+    Pose { bearing, pitch, hfov: hfov.unwrap_or(DEFAULT_FOV_DEG as f32) }
+}
+
+// Ground point (metres east and north of the camera) hit by the ray through
+// an image point, if it lands inside the grid
+fn cast(grid: &Dem, pose: &Pose, aspect: f32, point: Point) -> Option<(f32, f32)> {
+    let half_w = (pose.hfov.to_radians() / 2.0).tan();
+    let half_h = half_w / aspect.max(0.1);
+    let (u, v) = ((point.x - 0.5) * 2.0 * half_w, (0.5 - point.y) * 2.0 * half_h);
+    let (sin_b, cos_b) = pose.bearing.to_radians().sin_cos();
+    let (sin_p, cos_p) = pose.pitch.to_radians().sin_cos();
+    // Camera axes in east, north, up
+    let forward = [sin_b * cos_p, cos_b * cos_p, sin_p];
+    let right = [cos_b, -sin_b, 0.0];
+    let up = [-sin_b * sin_p, -cos_b * sin_p, cos_p];
+    let dir: Vec<f32> = (0..3).map(|i| forward[i] + u * right[i] + v * up[i]).collect();
+    let len = (dir[0].powi(2) + dir[1].powi(2) + dir[2].powi(2)).sqrt();
+
+    let eye = eye_height(grid);
+    let step = grid.spacing_m / 2.0;
+    let extent = (grid.size as f32 - 1.0) / 2.0 * grid.spacing_m;
+    let above = |t: f32| {
+        let (east, north, z) = (dir[0] / len * t, dir[1] / len * t, eye + dir[2] / len * t);
+        let (col, row) = grid.grid_position(east, north);
+        (z - grid.height_at(col, row), east, north)
+    };
+    let mut previous = 0.0;
+    let mut t = step;
+    loop {
+        let (clearance, east, north) = above(t);
+        if east.abs() > extent || north.abs() > extent {
+            return None;
+        }
+        if clearance <= 0.0 {
+            // Narrow down to where the ray meets the ground
+            let (mut low, mut high) = (previous, t);
+            for _ in 0..8 {
+                let mid = (low + high) / 2.0;
+                if above(mid).0 > 0.0 {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            let (_, east, north) = above(high);
+            return Some((east, north));
+        }
+        previous = t;
+        t += step;
+    }
+}
+
+// The outline projected onto the terrain; None when fewer than three of its
+// points land on the grid (e.g. the outline is mostly sky)
+pub fn project_outline(grid: &Dem, pose: &Pose, outline: &ImageOutline) -> Option<Vec<Location>> {
+    let polygon: Vec<Location> = outline
+        .points
+        .iter()
+        .filter_map(|p| cast(grid, pose, outline.aspect, *p))
+        .map(|(east, north)| grid.location_at(east, north))
+        .collect();
+    (polygon.len() >= MIN_POINTS).then_some(polygon)
+}
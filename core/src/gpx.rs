@@ -0,0 +1,153 @@
+// Minimal GPX reading: track and route points in order, plus standalone
+// waypoints. Extensions and everything else in the file are ignored.
+
+use crate::camera::Location;
+use quick_xml::events::{BytesStart, Event};
+
+// Photos taken this close to either end of a track still match its end point
+pub const MATCH_TOLERANCE_SECS: i64 = 5 * 60;
+// Longer gaps between track points are not interpolated across
+pub const MAX_GAP_SECS: i64 = 15 * 60;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpxPoint {
+    pub location: Location,
+    pub elevation: Option<f64>,
+    pub time: Option<i64>,  // unix seconds
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Gpx {
+    pub name: Option<String>,
+    // <trkpt> and <rtept> elements in file order
+    pub track: Vec<GpxPoint>,
+    // <wpt> elements
+    pub waypoints: Vec<GpxPoint>,
+}
+
+impl Gpx {
+    pub fn parse(xml: &str) -> anyhow::Result<Self> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut gpx = Gpx::default();
+        // Point being read and whether it is a waypoint
+        let mut current: Option<(GpxPoint, bool)> = None;
+        let mut field: Option<Vec<u8>> = None;
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => match e.local_name().as_ref() {
+                    b"trkpt" | b"rtept" | b"wpt" => {
+                        current = Some((start_point(&e)?, e.local_name().as_ref() == b"wpt"));
+                    }
+                    other => field = Some(other.to_vec()),
+                },
+                Event::Empty(e) => {
+                    if matches!(e.local_name().as_ref(), b"trkpt" | b"rtept") {
+                        gpx.track.push(start_point(&e)?);
+                    } else if e.local_name().as_ref() == b"wpt" {
+                        gpx.waypoints.push(start_point(&e)?);
+                    }
+                }
+                Event::Text(text) => {
+                    let text = text.unescape()?.trim().to_string();
+                    match (field.as_deref(), &mut current) {
+                        (Some(b"name"), Some((point, _))) => point.name = Some(text),
+                        (Some(b"ele"), Some((point, _))) => point.elevation = text.parse().ok(),
+                        (Some(b"time"), Some((point, _))) => {
+                            point.time = chrono::DateTime::parse_from_rfc3339(&text).ok().map(|t| t.timestamp());
+                        }
+                        // The first name outside any point names the file
+                        (Some(b"name"), None) if gpx.name.is_none() => gpx.name = Some(text),
+                        _ => {}
+                    }
+                }
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"trkpt" | b"rtept" | b"wpt" => match current.take() {
+                        Some((point, true)) => gpx.waypoints.push(point),
+                        Some((point, false)) => gpx.track.push(point),
+                        None => {}
+                    },
+                    _ => field = None,
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        if gpx.track.is_empty() && gpx.waypoints.is_empty() {
+            anyhow::bail!("GPX file has no track, route or waypoints");
+        }
+        Ok(gpx)
+    }
+
+    // Where the track was at a unix time, interpolated between the
+    // surrounding time-stamped points. None outside the recorded span (give
+    // or take MATCH_TOLERANCE_SECS) or across a gap longer than MAX_GAP_SECS,
+    // e.g. while the logger was switched off.
+    pub fn position_at(&self, time: i64) -> Option<Location> {
+        let mut timed: Vec<(i64, Location)> =
+            self.track.iter().filter_map(|p| Some((p.time?, p.location))).collect();
+        timed.sort_by_key(|(t, _)| *t);
+        let (first, last) = (timed.first()?, timed.last()?);
+        if time < first.0 {
+            return (first.0 - time <= MATCH_TOLERANCE_SECS).then_some(first.1);
+        }
+        if time > last.0 {
+            return (time - last.0 <= MATCH_TOLERANCE_SECS).then_some(last.1);
+        }
+        let after = timed.partition_point(|(t, _)| *t < time);
+        let (t1, b) = timed[after];
+        if after == 0 || t1 == time {
+            return Some(b);
+        }
+        let (t0, a) = timed[after - 1];
+        if t1 - t0 > MAX_GAP_SECS {
+            // Only snap to a point that is itself close in time
+            return if time - t0 <= MATCH_TOLERANCE_SECS {
+                Some(a)
+            } else if t1 - time <= MATCH_TOLERANCE_SECS {
+                Some(b)
+            } else {
+                None
+            };
+        }
+        let f = (time - t0) as f64 / (t1 - t0) as f64;
+        Some(Location {
+            latitude: a.latitude + (b.latitude - a.latitude) * f,
+            longitude: a.longitude + (b.longitude - a.longitude) * f,
+        })
+    }
+}
+
+fn start_point(element: &BytesStart) -> anyhow::Result<GpxPoint> {
+    let mut latitude = None;
+    let mut longitude = None;
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+        match attribute.key.local_name().as_ref() {
+            b"lat" => latitude = Some(attribute.unescape_value()?.parse::<f64>()?),
+            b"lon" => longitude = Some(attribute.unescape_value()?.parse::<f64>()?),
+            _ => {}
+        }
+    }
+    match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => Ok(GpxPoint {
+            location: Location { latitude, longitude },
+            elevation: None,
+            time: None,
+            name: None,
+        }),
+        _ => anyhow::bail!("GPX point without lat/lon"),
+    }
+}
+
+// Great-circle distance in metres
+pub fn distance_m(a: Location, b: Location) -> f64 {
+    const EARTH_RADIUS_M: f64 = 6_371_000.0;
+    let (lat1, lat2) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.longitude - a.longitude).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * h.sqrt().asin()
+}
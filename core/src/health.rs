@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+const BASE_BACKOFF_SECS: u64 = 60;
+const MAX_BACKOFF_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Default, Clone)]
+pub struct SourceHealth {
+    pub consecutive_failures: u32,
+    pub last_success: Option<u64>,  // unix seconds
+    pub last_error: Option<String>,
+    backoff_until: u64,
+    escalated: bool,
+}
+
+// Tracks failures per unattended source (scheduled job, camera, ...) so
+// callers can back off, spot stalls and escalate persistent problems
+pub struct HealthTracker {
+    sources: HashMap<String, SourceHealth>,
+    pub escalate_after: u32,
+}
+
+impl Default for HealthTracker {
+    fn default() -> Self {
+        Self {
+            sources: HashMap::new(),
+            escalate_after: 3,
+        }
+    }
+}
+
+impl HealthTracker {
+    pub fn get(&self, source: &str) -> Option<&SourceHealth> {
+        self.sources.get(source)
+    }
+
+    pub fn record_success(&mut self, source: &str, now: u64) {
+        let health = self.sources.entry(source.to_string()).or_default();
+        *health = SourceHealth {
+            last_success: Some(now),
+            ..Default::default()
+        };
+    }
+
+    // Returns true exactly once per failure streak, when it reaches the
+    // escalation threshold
+    pub fn record_failure(&mut self, source: &str, now: u64, error: impl Into<String>) -> bool {
+        let health = self.sources.entry(source.to_string()).or_default();
+        health.consecutive_failures += 1;
+        health.last_error = Some(error.into());
+
+        // Exponential backoff: 1, 2, 4, ... minutes, capped at an hour
+        let exponent = (health.consecutive_failures - 1).min(6);
+        let backoff = (BASE_BACKOFF_SECS << exponent).min(MAX_BACKOFF_SECS);
+        health.backoff_until = now + backoff;
+
+        if health.consecutive_failures >= self.escalate_after && !health.escalated {
+            health.escalated = true;
+            return true;
+        }
+        false
+    }
+
+    // False while the source is backing off after a failure
+    pub fn can_run(&self, source: &str, now: u64) -> bool {
+        self.sources.get(source).is_none_or(|h| now >= h.backoff_until)
+    }
+
+    // No success within `max_silence` seconds of the reference time, which
+    // is the last success or, failing that, when monitoring started
+    pub fn is_stalled(&self, source: &str, now: u64, started_at: u64, max_silence: u64) -> bool {
+        let last = self
+            .sources
+            .get(source)
+            .and_then(|h| h.last_success)
+            .unwrap_or(started_at);
+        now.saturating_sub(last) > max_silence
+    }
+
+    // Sources currently past the escalation threshold
+    pub fn escalated(&self) -> impl Iterator<Item = (&str, &SourceHealth)> {
+        self.sources
+            .iter()
+            .filter(|(_, h)| h.escalated)
+            .map(|(name, h)| (name.as_str(), h))
+    }
+}
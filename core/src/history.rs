@@ -0,0 +1,475 @@
+use crate::snapshot::{AnalysisSnapshot, BlobStore};
+use crate::model::AvalancheAnalysis;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+// Results below this confidence go to the review queue even when not flagged
+pub const LOW_CONFIDENCE: f32 = 50.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub snapshot: AnalysisSnapshot,
+    pub analysis: AvalancheAnalysis,
+    // Set on re-runs; always points at the original entry, not the previous version
+    pub parent_id: Option<String>,
+    // Reasons this entry was queued for human review; empty when not flagged
+    #[serde(default)]
+    pub flags: Vec<String>,
+    #[serde(default)]
+    pub review: Option<Review>,
+    // Camera name for monitored feeds; None for manual uploads and sweeps
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub location: Option<crate::camera::Location>,
+    // Marked assets the detected activity reached
+    #[serde(default)]
+    pub exposure: Vec<crate::exposure::ExposedAsset>,
+    // When the photo was taken, from EXIF; unix seconds
+    #[serde(default)]
+    pub captured_at: Option<i64>,
+    // Location was inferred by matching captured_at against a GPX track
+    // rather than read from the photo or camera profile
+    #[serde(default)]
+    pub location_from_track: bool,
+    // Degrees clockwise from true north the photo looks
+    #[serde(default)]
+    pub bearing: Option<f32>,
+    // Slope the photo shows, confirmed from the viewshed candidates
+    #[serde(default)]
+    pub slope: Option<crate::viewshed::Slope>,
+    // Approximate ground outline of the avalanche, projected from the
+    // model's outline on the photo
+    #[serde(default)]
+    pub outline: Option<Vec<crate::camera::Location>>,
+    // Camera model and focal length, from EXIF
+    #[serde(default)]
+    pub camera_info: Option<crate::photo_meta::CameraInfo>,
+    // Backends of the fallback chain that failed before the one recorded in
+    // the snapshot produced this result
+    #[serde(default)]
+    pub fallbacks: Vec<crate::fallback::FailedAttempt>,
+    // Models whose answers for the same image were unusable before this one,
+    // when the analysis was retried with another model
+    #[serde(default)]
+    pub retries: Vec<crate::fallback::FailedAttempt>,
+    // Site details appended to the prompt, e.g. a camera's start zone
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub site_context: String,
+    // Field observations sent with the photo
+    #[serde(default)]
+    pub observations: crate::observation::FieldObservations,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Accepted,
+    Corrected,
+    Rejected,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Review {
+    pub verdict: Verdict,
+    pub corrected_type: Option<String>,  // set when verdict is Corrected
+    pub reviewed_at: u64,                // unix seconds
+}
+
+impl HistoryEntry {
+    pub fn new(snapshot: AnalysisSnapshot, analysis: AvalancheAnalysis) -> Self {
+        Self {
+            snapshot,
+            analysis,
+            parent_id: None,
+            flags: Vec::new(),
+            review: None,
+            source: None,
+            location: None,
+            exposure: Vec::new(),
+            captured_at: None,
+            location_from_track: false,
+            bearing: None,
+            slope: None,
+            outline: None,
+            camera_info: None,
+            fallbacks: Vec::new(),
+            retries: Vec::new(),
+            site_context: String::new(),
+            observations: Default::default(),
+        }
+    }
+
+    // Options for analyzing the photo again with the current prompt and
+    // validation settings, sending the same site context, observations and
+    // weather as the first time
+    pub fn rerun_options(&self) -> crate::provider::ClassifyOptions {
+        crate::provider::ClassifyOptions::current()
+            .with_context(self.site_context.as_str())
+            .with_observations(self.observations.clone())
+            .with_weather(self.analysis.weather.clone())
+    }
+
+    // Fills the capture time, camera details and, when none is set yet, the
+    // location and bearing from the EXIF block of the stored original. For uploaded photos only; camera
+    // frames carry no useful EXIF.
+    pub fn read_photo_meta(&mut self) {
+        let Some(bytes) = BlobStore::open_default().ok().and_then(|store| self.snapshot.original(&store).ok()) else {
+            return;
+        };
+        self.apply_photo_meta(crate::photo_meta::PhotoMeta::read(&bytes));
+    }
+
+    // Same, from metadata read elsewhere, e.g. from the original of a photo
+    // that was cropped (and so lost its EXIF) before analysis
+    pub fn apply_photo_meta(&mut self, meta: crate::photo_meta::PhotoMeta) {
+        self.captured_at = meta.captured_at;
+        self.camera_info = meta.camera;
+        if self.location.is_none() {
+            self.location = meta.location;
+        }
+        if self.bearing.is_none() {
+            self.bearing = meta.bearing;
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.snapshot.id
+    }
+
+    // Unreviewed and either flagged or low confidence
+    pub fn needs_review(&self) -> bool {
+        self.review.is_none()
+            && (!self.flags.is_empty() || self.analysis.confidence_level < LOW_CONFIDENCE)
+    }
+
+    // Label to use downstream: the human correction when there is one, the
+    // AI output otherwise, and None for rejected entries. `analysis` itself
+    // is never modified so the original AI output stays available for audit.
+    pub fn effective_type(&self) -> Option<&str> {
+        match &self.review {
+            Some(Review { verdict: Verdict::Rejected, .. }) => None,
+            Some(Review { verdict: Verdict::Corrected, corrected_type: Some(t), .. }) => Some(t),
+            _ => Some(&self.analysis.avalanche_type),
+        }
+    }
+
+    // The AI analysis with the effective label applied. A correction only
+    // re-labels the type, so the characteristics it came with are marked as
+    // still being the AI's.
+    pub fn labeled_analysis(&self) -> Option<AvalancheAnalysis> {
+        let label = self.effective_type()?;
+        let mut analysis = self.analysis.clone();
+        if label != analysis.avalanche_type {
+            analysis.validation_warnings.push(format!(
+                "Corrected from {}; the characteristics were not re-labelled",
+                analysis.avalanche_type
+            ));
+            analysis.avalanche_type = label.to_string();
+            analysis.avalanche_present = label != "none";
+        }
+        Some(analysis)
+    }
+
+    // Outline of the viewed area for a map, when both location and bearing
+    // are known
+    pub fn view_cone(&self) -> Option<Vec<crate::camera::Location>> {
+        use crate::orientation::{view_cone, DEFAULT_FOV_DEG, DEFAULT_RANGE_M};
+        Some(view_cone(self.location?, self.bearing?, DEFAULT_FOV_DEG, DEFAULT_RANGE_M))
+    }
+}
+
+// An original analysis with its newest version
+pub struct Lineage<'a> {
+    pub original: &'a HistoryEntry,
+    // The original itself when it was never re-run
+    pub latest: &'a HistoryEntry,
+    // Including the original
+    pub versions: usize,
+}
+
+pub struct History {
+    path: PathBuf,
+    pub entries: Vec<HistoryEntry>,
+}
+
+// One row per entry. The full entry is kept as JSON so nothing is lost; the
+// other columns duplicate the parts worth querying with the sqlite3 CLI.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS analyses (
+    id TEXT PRIMARY KEY,
+    parent_id TEXT,
+    created_at INTEGER NOT NULL,  -- unix seconds
+    backend TEXT NOT NULL,
+    model TEXT NOT NULL,
+    image_hash TEXT NOT NULL,
+    label TEXT,                   -- reviewed label when there is one, NULL when rejected
+    confidence REAL NOT NULL,
+    analysis TEXT NOT NULL,       -- AvalancheAnalysis JSON as returned by the model
+    entry TEXT NOT NULL,          -- full HistoryEntry JSON
+    thumbnail BLOB                -- JPEG, NULL when the image was unavailable
+);
+CREATE INDEX IF NOT EXISTS analyses_created_at ON analyses (created_at);
+";
+
+const INSERT: &str = "
+INSERT INTO analyses
+    (id, parent_id, created_at, backend, model, image_hash, label, confidence, analysis, entry, thumbnail)
+VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+ON CONFLICT (id) DO UPDATE SET label = excluded.label, entry = excluded.entry
+";
+
+// Only the label and the entry JSON change once a row is written
+const UPDATE: &str = "UPDATE analyses SET label = ?2, entry = ?3 WHERE id = ?1";
+
+fn insert(conn: &rusqlite::Connection, entry: &HistoryEntry, thumbnail: Option<Vec<u8>>) -> anyhow::Result<()> {
+    conn.execute(
+        INSERT,
+        rusqlite::params![
+            entry.id(),
+            entry.parent_id,
+            entry.snapshot.created_at as i64,
+            entry.snapshot.backend,
+            entry.snapshot.model,
+            entry.snapshot.image_hash,
+            entry.effective_type(),
+            entry.analysis.confidence_level as f64,
+            serde_json::to_string(&entry.analysis)?,
+            serde_json::to_string(entry)?,
+            thumbnail,
+        ],
+    )?;
+    Ok(())
+}
+
+fn entry_thumbnail(entry: &HistoryEntry) -> Option<Vec<u8>> {
+    thumbnail(&entry.snapshot.image(&BlobStore::open_default().ok()?).ok()?)
+}
+
+impl History {
+    // Falls back to an empty history when the database can't be opened,
+    // with a message saying why or how many stored entries were unreadable
+    pub fn load_default() -> (Self, Option<String>) {
+        let path = crate::storage::data_dir().join("history.db");
+        match Self::open(path.clone()) {
+            Ok((history, 0)) => (history, None),
+            Ok((history, unreadable)) => {
+                let message = format!("{} history entries could not be read and are not shown", unreadable);
+                (history, Some(message))
+            }
+            Err(err) => {
+                let message = format!("Could not open the history, starting with an empty one: {}", err);
+                (Self { path, entries: Vec::new() }, Some(message))
+            }
+        }
+    }
+
+    // Opens the database, importing history.json from before history moved
+    // to SQLite when the database is still empty. Also returns how many rows
+    // could not be read; they stay in the database untouched.
+    pub fn open(path: PathBuf) -> anyhow::Result<(Self, usize)> {
+        let conn = Self::connect(&path)?;
+        let mut statement = conn.prepare("SELECT entry FROM analyses ORDER BY created_at, rowid")?;
+        let mut unreadable = 0;
+        let entries: Vec<HistoryEntry> = statement
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|json| {
+                let entry = json.ok().and_then(|json| serde_json::from_str(&json).ok());
+                if entry.is_none() {
+                    unreadable += 1;
+                }
+                entry
+            })
+            .collect();
+
+        let history = Self { path, entries };
+        let legacy = history.path.with_file_name("history.json");
+        if history.entries.is_empty() && unreadable == 0 && legacy.exists() {
+            let entries = serde_json::from_slice(&std::fs::read(&legacy)?)
+                .map_err(|err| anyhow::anyhow!("{} could not be imported: {}", legacy.display(), err))?;
+            let history = Self { entries, ..history };
+            history.save()?;
+            return Ok((history, 0));
+        }
+        Ok((history, unreadable))
+    }
+
+    fn connect(path: &std::path::Path) -> anyhow::Result<rusqlite::Connection> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = rusqlite::Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(conn)
+    }
+
+    // Writes every entry, for bulk changes like an import or a track match.
+    // Existing rows get their label and entry JSON refreshed; thumbnails are
+    // only made for rows that are new. Single entries go through push() and
+    // update(), which touch one row.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let mut conn = Self::connect(&self.path)?;
+        let stored: std::collections::HashSet<String> = conn
+            .prepare("SELECT id FROM analyses")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        let tx = conn.transaction()?;
+        for entry in &self.entries {
+            let thumbnail = if stored.contains(entry.id()) { None } else { entry_thumbnail(entry) };
+            insert(&tx, entry, thumbnail)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Writes back an entry changed after it was added, e.g. reviewed,
+    // flagged or located
+    pub fn update(&self, entry: &HistoryEntry) -> anyhow::Result<()> {
+        self.update_all([entry])
+    }
+
+    pub fn update_all<'a>(&self, entries: impl IntoIterator<Item = &'a HistoryEntry>) -> anyhow::Result<()> {
+        let mut conn = Self::connect(&self.path)?;
+        let tx = conn.transaction()?;
+        for entry in entries {
+            tx.execute(UPDATE, rusqlite::params![entry.id(), entry.effective_type(), serde_json::to_string(entry)?])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Stored JPEG thumbnail of the entry's image
+    pub fn thumbnail(&self, id: &str) -> Option<Vec<u8>> {
+        let conn = Self::connect(&self.path).ok()?;
+        conn.query_row("SELECT thumbnail FROM analyses WHERE id = ?1", [id], |row| row.get(0))
+            .ok()
+            .flatten()
+    }
+
+    // Infers locations for photos without GPS from a GPX track of the same
+    // day, shifting capture times by `clock_offset_secs` to correct a camera
+    // clock that was off. Earlier track matches are redone so a corrected
+    // offset takes effect. Returns how many entries were located.
+    pub fn locate_from_track(&mut self, track: &crate::gpx::Gpx, clock_offset_secs: i64) -> usize {
+        let mut located = 0;
+        for entry in &mut self.entries {
+            if entry.location.is_some() && !entry.location_from_track {
+                continue;
+            }
+            let Some(captured_at) = entry.captured_at else {
+                continue;
+            };
+            if let Some(location) = track.position_at(captured_at + clock_offset_secs) {
+                entry.location = Some(location);
+                entry.location_from_track = true;
+                located += 1;
+            }
+        }
+        located
+    }
+
+    // Adds an entry, writing only its row
+    pub fn push(&mut self, entry: HistoryEntry) -> anyhow::Result<()> {
+        let thumbnail = entry_thumbnail(&entry);
+        self.entries.push(entry);
+        let conn = Self::connect(&self.path)?;
+        insert(&conn, &self.entries[self.entries.len() - 1], thumbnail)
+    }
+
+    // Original analyses, i.e. entries that are not re-runs of another entry
+    pub fn originals(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter().filter(|e| e.parent_id.is_none())
+    }
+
+    // Runs outlier detection over the given entries as one batch, replaces
+    // their review flags with the result and writes them back
+    pub fn flag_batch(&mut self, ids: &[String]) -> anyhow::Result<()> {
+        let indices: Vec<usize> = ids
+            .iter()
+            .filter_map(|id| self.entries.iter().position(|e| e.id() == id))
+            .collect();
+        let analyses: Vec<&AvalancheAnalysis> = indices.iter().map(|&i| &self.entries[i].analysis).collect();
+        let flags = crate::outliers::detect(&analyses);
+        for (&i, entry_flags) in indices.iter().zip(flags) {
+            self.entries[i].flags = entry_flags;
+        }
+        self.update_all(indices.iter().map(|&i| &self.entries[i]))
+    }
+
+    // Entries awaiting review, most informative first
+    pub fn review_queue(&self) -> Vec<&HistoryEntry> {
+        let mut queue: Vec<(f32, &HistoryEntry)> = self
+            .entries
+            .iter()
+            .filter(|e| e.needs_review())
+            .map(|e| (crate::sampler::informativeness(self, e), e))
+            .collect();
+        queue.sort_by(|a, b| b.0.total_cmp(&a.0));
+        queue.into_iter().map(|(_, e)| e).collect()
+    }
+
+    // Queues the most informative unreviewed entries for human labeling
+    pub fn queue_samples(&mut self, count: usize) -> anyhow::Result<()> {
+        let mut queued = Vec::new();
+        for id in crate::sampler::sample(self, count) {
+            if let Some(i) = self.entries.iter().position(|e| e.id() == id) {
+                let flags = &mut self.entries[i].flags;
+                if !flags.iter().any(|f| f == crate::sampler::SAMPLER_FLAG) {
+                    flags.push(crate::sampler::SAMPLER_FLAG.to_string());
+                    queued.push(i);
+                }
+            }
+        }
+        self.update_all(queued.iter().map(|&i| &self.entries[i]))
+    }
+
+    pub fn set_review(&mut self, id: &str, review: Review) -> anyhow::Result<()> {
+        let Some(i) = self.entries.iter().position(|e| e.id() == id) else {
+            return Ok(());
+        };
+        self.entries[i].review = Some(review);
+        self.update(&self.entries[i])
+    }
+
+    // The original entry followed by every re-run, oldest first
+    pub fn versions(&self, original_id: &str) -> Vec<&HistoryEntry> {
+        let mut versions: Vec<&HistoryEntry> = self
+            .entries
+            .iter()
+            .filter(|e| e.id() == original_id || e.parent_id.as_deref() == Some(original_id))
+            .collect();
+        versions.sort_by_key(|e| e.snapshot.created_at);
+        versions
+    }
+
+    // Every original with its newest version, in history order, from one
+    // pass over the entries rather than a versions() scan per original
+    pub fn lineages(&self) -> Vec<Lineage<'_>> {
+        let mut reruns: HashMap<&str, (&HistoryEntry, usize)> = HashMap::new();
+        for entry in &self.entries {
+            if let Some(parent_id) = entry.parent_id.as_deref() {
+                let (latest, count) = reruns.entry(parent_id).or_insert((entry, 0));
+                if entry.snapshot.created_at >= latest.snapshot.created_at {
+                    *latest = entry;
+                }
+                *count += 1;
+            }
+        }
+        self.originals()
+            .map(|original| {
+                let (latest, reruns) = reruns.get(original.id()).copied().unwrap_or((original, 0));
+                Lineage { original, latest, versions: reruns + 1 }
+            })
+            .collect()
+    }
+}
+
+fn thumbnail(image_bytes: &[u8]) -> Option<Vec<u8>> {
+    let image = crate::photo_meta::load_upright(image_bytes).ok()?.thumbnail(128, 96).to_rgb8();
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Jpeg(80))
+        .ok()?;
+    Some(bytes)
+}
@@ -0,0 +1,106 @@
+//! Avalanche classification without the GUI: the analysis data model, the
+//! vision provider call, characteristic scoring and validation, plus the
+//! history, snapshot and dataset tooling built on top of them.
+//!
+//! Other Rust projects can embed the classifier through [`classify`]: pick a
+//! [`VisionProvider`], configure it and the classification with their
+//! builders, and get back a validated [`AvalancheAnalysis`] or an
+//! [`AnalysisError`] saying what went wrong.
+//!
+//! ```no_run
+//! use avalanche_classifier_core::models::UploadEncoding;
+//! use avalanche_classifier_core::{classify, AnalysisError, ClassifyOptions, OpenAiProvider};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let provider = OpenAiProvider::new(std::env::var("OPENAI_API_KEY")?)
+//!     .with_model("gpt-4o")
+//!     .with_upload(UploadEncoding::default().with_max_px(1536));
+//! let options = ClassifyOptions::default()
+//!     .with_context("Start zone: NE-facing bowl at 2400 m")
+//!     .with_soft_validation(true);
+//!
+//! let photo = std::fs::read("slope.jpg")?;
+//! match classify(&provider, &photo, &options).await {
+//!     Ok(classification) => {
+//!         let analysis = classification.analysis;
+//!         println!("{} ({:.0}%)", analysis.avalanche_type, analysis.confidence_level);
+//!         for warning in &analysis.validation_warnings {
+//!             println!("warning: {}", warning);
+//!         }
+//!     }
+//!     Err(err) if err.is_transient() => println!("try again later: {}", err),
+//!     Err(AnalysisError::InvalidApiKey { backend }) => println!("{} rejected the key", backend),
+//!     Err(err) => println!("{}", err.user_message()),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`classify_image`] uses the process-wide settings the app configures
+//! instead (`provider::set_analysis_prompt` and friends), and
+//! [`prepare_image`] runs just the preprocessing, to see what a backend
+//! would be sent.
+
+pub mod alerting;
+pub mod archive;
+pub mod audit;
+pub mod bulletin;
+pub mod bundle;
+pub mod camera;
+pub mod canary;
+pub mod checksum;
+pub mod compare;
+pub mod conditions;
+pub mod corridor;
+pub mod dataset;
+pub mod decode;
+pub mod dem;
+pub mod ensemble;
+pub mod error;
+pub mod exposure;
+pub mod fallback;
+pub mod fetch;
+pub mod ffmpeg;
+pub mod geojson;
+pub mod georef;
+pub mod gpx;
+pub mod health;
+pub mod history;
+pub mod manifest;
+pub mod maptiles;
+pub mod model;
+pub mod missions;
+pub mod models;
+pub mod observation;
+pub mod orientation;
+pub mod outliers;
+pub mod pdf;
+pub mod photo_meta;
+pub mod provider;
+pub mod report;
+pub mod sampler;
+pub mod schedule;
+pub mod scoreboard;
+pub mod scoring;
+pub mod slope_check;
+pub mod snapshot;
+pub mod storage;
+pub mod superres;
+pub mod textdiff;
+pub mod trip;
+pub mod verification;
+pub mod viewshed;
+pub mod weather;
+pub mod zones;
+
+pub use error::AnalysisError;
+pub use model::{
+    Annotations, AvalancheAnalysis, MovementPattern, SnowTexture, TerrainFeatures, VisualCharacteristics,
+    AVALANCHE_TYPES,
+};
+pub use provider::{
+    classify, classify_image, classify_image_with_context, parse_analysis, parse_analysis_with, prepare_image,
+    AnthropicProvider, Backend, Classification, ClassifyOptions, OllamaProvider, OpenAiProvider, PreparedImage, Turn,
+    VisionProvider,
+};
+pub use scoring::TypeScores;
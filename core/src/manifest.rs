@@ -0,0 +1,249 @@
+use crate::model::AvalancheAnalysis;
+use crate::scoring::{ScoringConfig, TypeScores};
+use crate::snapshot::{AnalysisSnapshot, BlobStore};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// A batch manifest records what a research run analyzed and what came out:
+// each input's hash, the configuration and model versions in use, and the
+// result per item, in the order the run processed them. The order is a
+// seeded shuffle of the inputs sorted by file name, so the same files and
+// seed always run in the same order, and re-running a manifest shows
+// whether anything changed since.
+
+pub const EXTENSION: &str = "json";
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub created_at: u64, // unix seconds
+    pub seed: u64,
+    pub config: RunConfig,
+    pub items: Vec<ManifestItem>,
+}
+
+// Everything outside the photos that shapes a result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunConfig {
+    // Version of the classifier that ran the batch
+    pub classifier_version: String,
+    // Backends tried in order, e.g. ["openai", "ollama"]
+    pub backends: Vec<String>,
+    // "built-in" or "custom" and the start of the edited prompt's hash
+    pub prompt: String,
+    pub prompt_hash: String,
+    pub soft_validation: bool,
+    pub conflict_rounds: u32,
+    pub upload: crate::models::UploadEncoding,
+    pub scoring: ScoringConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestItem {
+    pub path: PathBuf,
+    // sha256 of the file as read; None when it could not be read
+    pub image_hash: Option<String>,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Result(ItemResult),
+    Error(String),
+}
+
+// The parts of an analysis a study reports on, and which model produced it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemResult {
+    pub backend: String,
+    pub model: String,
+    // The exact model version the service reported answering with, e.g.
+    // "gpt-4o-2024-08-06"; None when the reply did not say
+    pub model_version: Option<String>,
+    // The recorded exchange, for tracing a result back to the raw reply
+    pub snapshot_id: String,
+    pub avalanche_type: String,
+    pub confidence_level: f32,
+    pub type_scores: Option<TypeScores>,
+    pub avalanche_problem: Option<String>,
+    pub danger_level: Option<u8>,
+    pub destructive_size: Option<u8>,
+}
+
+impl ItemResult {
+    pub fn new(analysis: &AvalancheAnalysis, snapshot: &AnalysisSnapshot, store: Option<&BlobStore>) -> Self {
+        Self {
+            backend: snapshot.backend.clone(),
+            model: snapshot.model.clone(),
+            model_version: store.and_then(|store| served_model(store, snapshot)),
+            snapshot_id: snapshot.id.clone(),
+            avalanche_type: analysis.avalanche_type.clone(),
+            confidence_level: analysis.confidence_level,
+            type_scores: analysis.type_scores,
+            avalanche_problem: analysis.avalanche_problem.clone(),
+            danger_level: analysis.danger_level,
+            destructive_size: analysis.destructive_size,
+        }
+    }
+}
+
+// The model the service says answered, from the recorded raw response. All
+// three built-in backends put it in a top-level "model" field.
+pub fn served_model(store: &BlobStore, snapshot: &AnalysisSnapshot) -> Option<String> {
+    let response = store.get(&snapshot.response_hash).ok()?;
+    let json: serde_json::Value = serde_json::from_slice(&response).ok()?;
+    json["model"].as_str().map(str::to_string)
+}
+
+// SplitMix64, small and stable across platforms and releases, unlike the
+// generators of general-purpose crates
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+// The files sorted by name (then full path, for equal names), then shuffled
+// with the seed. The same set of files and seed give the same order whatever
+// order they were picked or dropped in.
+pub fn seeded_order(mut paths: Vec<PathBuf>, seed: u64) -> Vec<PathBuf> {
+    paths.sort_by(|a, b| a.file_name().cmp(&b.file_name()).then_with(|| a.cmp(b)));
+    let mut rng = SplitMix64(seed);
+    for i in (1..paths.len()).rev() {
+        let j = (rng.next() % (i as u64 + 1)) as usize;
+        paths.swap(i, j);
+    }
+    paths
+}
+
+impl Manifest {
+    pub fn new(seed: u64, config: RunConfig, items: Vec<ManifestItem>) -> Self {
+        let created_at =
+            std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Self { version: FORMAT_VERSION, created_at, seed, config, items }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let manifest: Manifest = serde_json::from_slice(&std::fs::read(path)?)?;
+        if manifest.version > FORMAT_VERSION {
+            anyhow::bail!("the manifest was written by a newer version (format {})", manifest.version);
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    // What a re-run changed against this manifest, one line per difference;
+    // empty when the re-run reproduced it. The confidence the model states
+    // counts too, since a study should know when it moved. Snapshot ids
+    // cover the time of the analysis and always differ.
+    pub fn differences(&self, rerun: &Manifest) -> Vec<String> {
+        let mut differences = Vec::new();
+        let (before, after) = (&self.config, &rerun.config);
+        let mut config = |name: &str, changed: bool, was: String, now: String| {
+            if changed {
+                differences.push(format!("{} changed: {} → {}", name, was, now));
+            }
+        };
+        config(
+            "Classifier version",
+            before.classifier_version != after.classifier_version,
+            before.classifier_version.clone(),
+            after.classifier_version.clone(),
+        );
+        config("Backends", before.backends != after.backends, before.backends.join(", "), after.backends.join(", "));
+        config(
+            "Prompt",
+            before.prompt_hash != after.prompt_hash,
+            before.prompt.clone(),
+            after.prompt.clone(),
+        );
+        config(
+            "Soft validation",
+            before.soft_validation != after.soft_validation,
+            before.soft_validation.to_string(),
+            after.soft_validation.to_string(),
+        );
+        config(
+            "Conflict rounds",
+            before.conflict_rounds != after.conflict_rounds,
+            before.conflict_rounds.to_string(),
+            after.conflict_rounds.to_string(),
+        );
+        config("Upload encoding", before.upload != after.upload, format!("{:?}", before.upload), format!("{:?}", after.upload));
+        config("Scoring rules", before.scoring != after.scoring, format!("{:?}", before.scoring), format!("{:?}", after.scoring));
+
+        if self.items.len() != rerun.items.len() {
+            differences.push(format!("{} items → {}", self.items.len(), rerun.items.len()));
+        }
+        for (was, now) in self.items.iter().zip(&rerun.items) {
+            let name = was.path.display();
+            if was.path != now.path {
+                differences.push(format!("{}: item order changed, now {}", name, now.path.display()));
+                continue;
+            }
+            if was.image_hash != now.image_hash {
+                differences.push(format!("{}: file contents changed", name));
+            }
+            match (&was.outcome, &now.outcome) {
+                (Outcome::Result(was), Outcome::Result(now)) => {
+                    for line in result_differences(was, now) {
+                        differences.push(format!("{}: {}", name, line));
+                    }
+                }
+                (Outcome::Error(was), Outcome::Error(now)) if was == now => {}
+                (Outcome::Error(was), Outcome::Error(now)) => {
+                    differences.push(format!("{}: failed differently: {} → {}", name, was, now));
+                }
+                (Outcome::Result(_), Outcome::Error(err)) => differences.push(format!("{}: now fails: {}", name, err)),
+                (Outcome::Error(_), Outcome::Result(_)) => differences.push(format!("{}: now succeeds", name)),
+            }
+        }
+        differences
+    }
+}
+
+fn result_differences(was: &ItemResult, now: &ItemResult) -> Vec<String> {
+    let mut lines = Vec::new();
+    if (&was.backend, &was.model) != (&now.backend, &now.model) {
+        lines.push(format!("answered by {} {} → {} {}", was.backend, was.model, now.backend, now.model));
+    }
+    if was.model_version != now.model_version {
+        lines.push(format!(
+            "model version {} → {}",
+            was.model_version.as_deref().unwrap_or("unknown"),
+            now.model_version.as_deref().unwrap_or("unknown")
+        ));
+    }
+    if was.avalanche_type != now.avalanche_type {
+        lines.push(format!("type {} → {}", was.avalanche_type, now.avalanche_type));
+    }
+    if was.type_scores != now.type_scores {
+        lines.push("type scores changed".to_string());
+    }
+    if was.avalanche_problem != now.avalanche_problem {
+        lines.push(format!("problem {:?} → {:?}", was.avalanche_problem, now.avalanche_problem));
+    }
+    if (was.danger_level, was.destructive_size) != (now.danger_level, now.destructive_size) {
+        lines.push(format!(
+            "danger level / size {:?} / {:?} → {:?} / {:?}",
+            was.danger_level, was.destructive_size, now.danger_level, now.destructive_size
+        ));
+    }
+    if was.confidence_level != now.confidence_level {
+        lines.push(format!("confidence {:.0}% → {:.0}%", was.confidence_level, now.confidence_level));
+    }
+    lines
+}
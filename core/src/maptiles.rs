@@ -0,0 +1,69 @@
+// Web Mercator tile math and downloads for the map view. Tiles come from
+// the OpenStreetMap tile servers, whose usage policy asks that the
+// application identify itself, that tiles be cached and that nothing be
+// downloaded in bulk; the map only fetches tiles it is about to draw.
+
+use crate::camera::Location;
+
+pub const TILE_SIZE: f64 = 256.0;
+pub const MIN_ZOOM: u8 = 2;
+pub const MAX_ZOOM: u8 = 17;
+pub const ATTRIBUTION: &str = "© OpenStreetMap contributors";
+const TILE_URL: &str = "https://tile.openstreetmap.org";
+// Web Mercator stops short of the poles
+const MAX_LATITUDE: f64 = 85.051_128_78;
+
+// Position in pixels on the whole world map at `zoom`, from its top-left
+// corner
+pub fn project(location: Location, zoom: u8) -> (f64, f64) {
+    let size = TILE_SIZE * f64::from(1u32 << zoom);
+    let latitude = location.latitude.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    let x = (location.longitude + 180.0) / 360.0 * size;
+    let y = (1.0 - (latitude.tan() + 1.0 / latitude.cos()).ln() / std::f64::consts::PI) / 2.0 * size;
+    (x, y)
+}
+
+// The location at a world map pixel, the inverse of project
+pub fn unproject(x: f64, y: f64, zoom: u8) -> Location {
+    let size = TILE_SIZE * f64::from(1u32 << zoom);
+    let longitude = x / size * 360.0 - 180.0;
+    let latitude = (std::f64::consts::PI * (1.0 - 2.0 * y / size)).sinh().atan().to_degrees();
+    Location { latitude, longitude }
+}
+
+// Centre and the closest zoom showing every location in a view of the given
+// size in pixels
+pub fn fit(locations: &[Location], width: f64, height: f64) -> Option<(Location, u8)> {
+    let first = locations.first()?;
+    let (mut west, mut east, mut south, mut north) =
+        (first.longitude, first.longitude, first.latitude, first.latitude);
+    for location in locations {
+        west = west.min(location.longitude);
+        east = east.max(location.longitude);
+        south = south.min(location.latitude);
+        north = north.max(location.latitude);
+    }
+    let zoom = (MIN_ZOOM..=MAX_ZOOM)
+        .rev()
+        .find(|&zoom| {
+            let (left, top) = project(Location { latitude: north, longitude: west }, zoom);
+            let (right, bottom) = project(Location { latitude: south, longitude: east }, zoom);
+            right - left <= width * 0.9 && bottom - top <= height * 0.9
+        })
+        .unwrap_or(MIN_ZOOM);
+    let center = Location { latitude: (south + north) / 2.0, longitude: (west + east) / 2.0 };
+    Some((center, zoom))
+}
+
+// One tile's PNG
+pub async fn fetch_tile(client: &reqwest::Client, zoom: u8, x: u32, y: u32) -> anyhow::Result<Vec<u8>> {
+    let bytes = client
+        .get(format!("{}/{}/{}/{}.png", TILE_URL, zoom, x, y))
+        .header(reqwest::header::USER_AGENT, concat!("avalanche-classifier/", env!("CARGO_PKG_VERSION")))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(bytes.to_vec())
+}
@@ -0,0 +1,140 @@
+use crate::history::History;
+use crate::verification::ControlVerification;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+
+// Destructive size scale used in control work
+pub const RESULT_SIZES: [&str; 10] = ["No result", "D1", "D1.5", "D2", "D2.5", "D3", "D3.5", "D4", "D4.5", "D5"];
+
+// One avalanche control shot and the verification photos analyzed for it
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MissionRecord {
+    pub id: u64,
+    pub created_at: u64,  // unix seconds
+    pub area: String,
+    // Where the charge went, e.g. "Path 3 start zone, hand charge"
+    pub placement: String,
+    pub charge_kg: f32,
+    pub result_size: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    // History entries filed as verification of this shot
+    #[serde(default)]
+    pub analysis_ids: Vec<String>,
+    #[serde(default)]
+    pub verifications: Vec<ControlVerification>,
+}
+
+pub struct MissionLog {
+    path: PathBuf,
+    pub missions: Vec<MissionRecord>,
+}
+
+impl MissionLog {
+    pub fn load_default() -> Self {
+        let path = crate::storage::data_dir().join("missions.json");
+        let missions = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self { path, missions }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&self.missions)?)?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, mut record: MissionRecord) -> anyhow::Result<u64> {
+        record.id = self.missions.iter().map(|m| m.id).max().unwrap_or(0) + 1;
+        record.created_at = crate::storage::unix_now();
+        let id = record.id;
+        self.missions.push(record);
+        self.save()?;
+        Ok(id)
+    }
+
+    // Links an analysis to a mission as verification, once
+    pub fn file(&mut self, mission_id: u64, analysis_id: &str) -> anyhow::Result<()> {
+        let mission = self
+            .missions
+            .iter_mut()
+            .find(|m| m.id == mission_id)
+            .ok_or_else(|| anyhow::anyhow!("No mission #{}", mission_id))?;
+        if !mission.analysis_ids.iter().any(|id| id == analysis_id) {
+            mission.analysis_ids.push(analysis_id.to_string());
+            crate::audit::record("mission_filed", format!("analysis {} to mission #{}", analysis_id, mission_id))?;
+        }
+        self.save()
+    }
+
+    // Adds a control verification to the mission, taking its result size
+    // when none was entered by hand
+    pub fn record_verification(&mut self, mission_id: u64, verification: ControlVerification) -> anyhow::Result<()> {
+        let mission = self
+            .missions
+            .iter_mut()
+            .find(|m| m.id == mission_id)
+            .ok_or_else(|| anyhow::anyhow!("No mission #{}", mission_id))?;
+        if mission.result_size.is_none() {
+            mission.result_size = Some(verification.result_size.clone().unwrap_or_else(|| RESULT_SIZES[0].to_string()));
+        }
+        crate::audit::record("mission_verified", format!("mission #{}: {}", mission_id, verification.summary()))?;
+        mission.verifications.push(verification);
+        self.save()
+    }
+}
+
+// Writes one CSV row per filed analysis (or per mission without any), with
+// the analysis result looked up in history
+pub fn export_csv(log: &MissionLog, history: &History, path: &std::path::Path) -> anyhow::Result<usize> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(
+        file,
+        "mission_id,created_at,area,placement,charge_kg,result_size,notes,analysis_id,avalanche_type,confidence_level"
+    )?;
+    let mut rows = 0;
+    for mission in &log.missions {
+        let mission_columns = [
+            mission.id.to_string(),
+            mission.created_at.to_string(),
+            csv_field(&mission.area),
+            csv_field(&mission.placement),
+            mission.charge_kg.to_string(),
+            csv_field(mission.result_size.as_deref().unwrap_or("")),
+            csv_field(&mission.notes),
+        ]
+        .join(",");
+        if mission.analysis_ids.is_empty() {
+            writeln!(file, "{},,,", mission_columns)?;
+            rows += 1;
+        }
+        for id in &mission.analysis_ids {
+            let entry = history.entries.iter().find(|e| e.id() == id);
+            writeln!(
+                file,
+                "{},{},{},{}",
+                mission_columns,
+                csv_field(id),
+                csv_field(entry.and_then(|e| e.effective_type()).unwrap_or("")),
+                entry.map_or(String::new(), |e| e.analysis.confidence_level.to_string())
+            )?;
+            rows += 1;
+        }
+    }
+    file.flush()?;
+    crate::checksum::write_sidecar(path)?;
+    Ok(rows)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
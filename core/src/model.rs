@@ -0,0 +1,383 @@
+use crate::camera::Roi;
+use crate::corridor::Point;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// What the snow in the slide looks like
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct SnowTexture {
+    pub granular: bool,
+    pub blocky: bool,
+    pub fluffy: bool,
+    /// "low", "medium" or "high"
+    pub density: String,
+    /// Moist or wet snow: dull, clumped, sticky-looking
+    #[serde(default)]
+    pub wet: bool,
+    /// Soaked through with water, flowing like slush
+    #[serde(default)]
+    pub water_saturated: bool,
+}
+
+/// How the avalanche started and spread
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct MovementPattern {
+    /// "point", "wide" or "undefined"
+    pub starting_width: String,
+    /// "fan", "linear", "chaotic" or "none"
+    pub propagation: String,
+    /// Significant vertical displacement
+    pub vertical_movement: bool,
+    /// Significant sideways spread
+    pub lateral_spread: bool,
+}
+
+/// The slope the avalanche ran on
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct TerrainFeatures {
+    /// Free text, e.g. "steep (35-45°)"
+    pub slope_angle: Option<String>,
+    /// "smooth", "rough" or "variable"
+    pub surface_roughness: String,
+    /// Trees, rocks and the like holding the snowpack
+    pub anchoring_points: bool,
+    /// The terrain rolls over
+    pub convex_rollover: bool,
+    /// Direction the slope faces, one of ASPECTS, as judged from shadows,
+    /// sun effects and vegetation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(schema_with = "aspect_schema")]
+    pub aspect: Option<String>,
+    /// One of ELEVATION_BANDS, relative to the treeline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(schema_with = "elevation_band_schema")]
+    pub elevation_band: Option<String>,
+}
+
+/// The eight aspects of an avalanche bulletin's aspect rose
+pub const ASPECTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+/// The elevation bands bulletins rate separately
+pub const ELEVATION_BANDS: [&str; 3] = ["below-treeline", "near-treeline", "above-treeline"];
+
+/// Display name of an elevation band id, e.g. "Near treeline"
+pub fn elevation_band_label(band: &str) -> &str {
+    match band {
+        "below-treeline" => "Below treeline",
+        "near-treeline" => "Near treeline",
+        "above-treeline" => "Above treeline",
+        other => other,
+    }
+}
+
+/// Estimated height of the crown, the depth of snow that broke away
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct FractureDepth {
+    pub min_cm: u32,
+    pub max_cm: u32,
+    /// One of ESTIMATE_CONFIDENCES
+    #[schemars(schema_with = "estimate_confidence_schema")]
+    pub confidence: String,
+    /// What the estimate was scaled from, e.g. "skier in the start zone"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale_cue: Option<String>,
+}
+
+/// How sure a measured estimate is: "low" without a scale reference in
+/// the photo, "high" with one right at the crown
+pub const ESTIMATE_CONFIDENCES: [&str; 3] = ["low", "medium", "high"];
+
+/// Crown heights above this are taken for a misread rather than an estimate
+pub const MAX_FRACTURE_DEPTH_CM: u32 = 1000;
+
+impl FractureDepth {
+    /// The range as it reads in a report, e.g. "30–60 cm" or "about 50 cm"
+    pub fn label(&self) -> String {
+        if self.min_cm == self.max_cm {
+            format!("about {} cm", self.min_cm)
+        } else {
+            format!("{}–{} cm", self.min_cm, self.max_cm)
+        }
+    }
+
+    /// The range with its confidence, e.g. "30–60 cm, low confidence"
+    pub fn detailed_label(&self) -> String {
+        format!("{}, {} confidence", self.label(), self.confidence)
+    }
+
+    /// Coarse class the problem scoring goes by: "shallow" when the middle
+    /// of the range is under half a meter, "deep" from a meter on, and
+    /// "moderate" in between
+    pub fn class(&self) -> &'static str {
+        match (self.min_cm + self.max_cm) / 2 {
+            0..=49 => "shallow",
+            50..=99 => "moderate",
+            _ => "deep",
+        }
+    }
+}
+
+/// Everything the model reports seeing, which the type is scored from
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct VisualCharacteristics {
+    pub powder_cloud: bool,
+    pub fracture_line: bool,
+    /// Estimated crown height; analyses made before the prompt asked for a
+    /// range in centimeters had a "shallow" or "deep" here, which is dropped
+    #[serde(default, deserialize_with = "lenient")]
+    pub fracture_depth: Option<FractureDepth>,
+    pub point_release: bool,
+    /// An open crack where the whole snowpack has crept downhill
+    #[serde(default)]
+    pub glide_crack: bool,
+    /// Bare ground or grass showing in the bed surface
+    #[serde(default)]
+    pub ground_exposed: bool,
+    /// A cornice broke off the ridge above
+    #[serde(default)]
+    pub cornice_failure: bool,
+    /// Wind pillows, sastrugi, scoured ridges or snow plumes
+    #[serde(default)]
+    pub wind_features: bool,
+    /// A fresh layer of new snow over the terrain
+    #[serde(default)]
+    pub recent_snowfall: bool,
+    /// e.g. "fan-shaped", "linear" or "none"
+    pub debris_pattern: String,
+    pub snow_texture: SnowTexture,
+    pub movement_pattern: MovementPattern,
+    pub terrain: TerrainFeatures,
+}
+
+/// Where the model places the avalanche's parts on the photo, as fractions
+/// of the image width and height from the top-left corner
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+pub struct Annotations {
+    /// Polyline along the crown
+    #[serde(default)]
+    pub fracture_line: Option<Vec<Point>>,
+    #[serde(default)]
+    pub start_zone: Option<Roi>,
+    /// Polygon around the deposit
+    #[serde(default)]
+    pub debris: Option<Vec<Point>>,
+}
+
+impl Annotations {
+    /// Clamps points onto the photo and drops shapes too small to draw
+    pub fn sanitized(self) -> Option<Self> {
+        let clamp = |points: Vec<Point>| -> Vec<Point> {
+            points
+                .into_iter()
+                .filter(|p| p.x.is_finite() && p.y.is_finite())
+                .map(|p| Point { x: p.x.clamp(0.0, 1.0), y: p.y.clamp(0.0, 1.0) })
+                .collect()
+        };
+        let annotations = Self {
+            fracture_line: self.fracture_line.map(clamp).filter(|line| line.len() >= 2),
+            start_zone: self.start_zone.and_then(|zone| {
+                let x = zone.x.clamp(0.0, 1.0);
+                let y = zone.y.clamp(0.0, 1.0);
+                let zone = Roi { x, y, width: zone.width.min(1.0 - x), height: zone.height.min(1.0 - y) };
+                (zone.width > 0.0 && zone.height > 0.0).then_some(zone)
+            }),
+            debris: self.debris.map(clamp).filter(|polygon| polygon.len() >= 3),
+        };
+        (annotations != Self::default()).then_some(annotations)
+    }
+
+    /// The same shapes in the coordinates of a larger frame, for annotations
+    /// made on a crop of it
+    pub fn within(&self, region: &Roi) -> Self {
+        let map = |p: &Point| Point { x: region.x + p.x * region.width, y: region.y + p.y * region.height };
+        Self {
+            fracture_line: self.fracture_line.as_ref().map(|line| line.iter().map(map).collect()),
+            start_zone: self.start_zone.map(|zone| Roi {
+                x: region.x + zone.x * region.width,
+                y: region.y + zone.y * region.height,
+                width: zone.width * region.width,
+                height: zone.height * region.height,
+            }),
+            debris: self.debris.as_ref().map(|polygon| polygon.iter().map(map).collect()),
+        }
+    }
+}
+
+/// A validated classification of one photo, as the model answered it plus
+/// what validation worked out
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct AvalancheAnalysis {
+    pub avalanche_present: bool,
+    /// One of AVALANCHE_TYPES
+    #[schemars(schema_with = "avalanche_type_schema")]
+    pub avalanche_type: String,
+    /// 0 to 100
+    pub confidence_level: f32,
+    pub terrain_features: Vec<String>,
+    pub visual_characteristics: VisualCharacteristics,
+    /// EAWS danger level, 1 (low) to 5 (very high); missing from analyses
+    /// made before the prompt asked for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub danger_level: Option<u8>,
+    /// EAWS destructive size, 1 (D1) to 5 (D5); null when no avalanche is
+    /// present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destructive_size: Option<u8>,
+    /// Why the model chose that danger level and size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hazard_rationale: Option<String>,
+    /// One of AVALANCHE_PROBLEMS, the forecaster's view of the slide; null
+    /// when none applies or no avalanche is present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(schema_with = "avalanche_problem_schema")]
+    pub avalanche_problem: Option<String>,
+    /// Missing from analyses made before the prompt asked for it; a
+    /// malformed answer is dropped rather than failing the analysis
+    #[serde(default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Annotations>,
+    /// Characteristic scores per type, worked out during validation rather
+    /// than asked of the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub type_scores: Option<crate::scoring::TypeScores>,
+    /// Weather before the photo was taken, when it was looked up and sent
+    /// with the prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub weather: Option<crate::weather::WeatherSummary>,
+    /// Checks the classification failed that soft validation let through,
+    /// to be shown as warnings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(skip)]
+    pub validation_warnings: Vec<String>,
+}
+
+impl AvalancheAnalysis {
+    /// Only the fields the prompt asks the model for, without the scores,
+    /// weather and warnings added afterwards
+    pub fn model_reply(&self) -> Self {
+        Self {
+            type_scores: None,
+            weather: None,
+            validation_warnings: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// The types a classification may name
+pub const AVALANCHE_TYPES: [&str; 9] =
+    ["powder", "loose-snow", "slab", "wet-loose", "wet-slab", "glide", "cornice", "slush", "none"];
+
+/// Name of an EAWS danger level, e.g. "Considerable" for 3
+pub fn danger_level_name(level: u8) -> &'static str {
+    match level {
+        1 => "Low",
+        2 => "Moderate",
+        3 => "Considerable",
+        4 => "High",
+        5 => "Very high",
+        _ => "Unknown",
+    }
+}
+
+/// Name of an EAWS destructive size, e.g. "Large" for D3
+pub fn destructive_size_name(size: u8) -> &'static str {
+    match size {
+        1 => "Small",
+        2 => "Medium",
+        3 => "Large",
+        4 => "Very large",
+        5 => "Extremely large",
+        _ => "Unknown",
+    }
+}
+
+/// Display name of a type id, e.g. "Wet slab" for "wet-slab"; unknown ids
+/// come back as they are
+pub fn type_label(avalanche_type: &str) -> &str {
+    match avalanche_type {
+        "powder" => "Powder",
+        "loose-snow" => "Loose snow",
+        "slab" => "Slab",
+        "wet-loose" => "Wet loose",
+        "wet-slab" => "Wet slab",
+        "glide" => "Glide",
+        "cornice" => "Cornice fall",
+        "slush" => "Slush flow",
+        "none" => "No avalanche",
+        other => other,
+    }
+}
+
+/// Restricts the generated schema to AVALANCHE_TYPES, so structured outputs
+/// cannot answer anything else
+fn avalanche_type_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    schemars::schema::SchemaObject {
+        instance_type: Some(schemars::schema::InstanceType::String.into()),
+        enum_values: Some(AVALANCHE_TYPES.iter().map(|t| serde_json::json!(t)).collect()),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// The avalanche problems a classification may name
+pub const AVALANCHE_PROBLEMS: [&str; 6] =
+    ["wind-slab", "persistent-slab", "storm-slab", "wet-loose", "glide", "cornice"];
+
+/// Display name of a problem id, e.g. "Persistent slab"; unknown ids come
+/// back as they are
+pub fn problem_label(problem: &str) -> &str {
+    match problem {
+        "wind-slab" => "Wind slab",
+        "persistent-slab" => "Persistent slab",
+        "storm-slab" => "Storm slab",
+        "wet-loose" => "Wet loose",
+        "glide" => "Glide",
+        "cornice" => "Cornice",
+        other => other,
+    }
+}
+
+/// Restricts the generated schema to AVALANCHE_PROBLEMS or null
+fn avalanche_problem_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    nullable_enum_schema(&AVALANCHE_PROBLEMS)
+}
+
+fn estimate_confidence_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    schemars::schema::SchemaObject {
+        instance_type: Some(schemars::schema::InstanceType::String.into()),
+        enum_values: Some(ESTIMATE_CONFIDENCES.iter().map(|c| serde_json::json!(c)).collect()),
+        ..Default::default()
+    }
+    .into()
+}
+
+fn aspect_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    nullable_enum_schema(&ASPECTS)
+}
+
+fn elevation_band_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    nullable_enum_schema(&ELEVATION_BANDS)
+}
+
+/// A string schema allowing only the given values, or null
+fn nullable_enum_schema(allowed: &[&str]) -> schemars::schema::Schema {
+    let mut values: Vec<serde_json::Value> = allowed.iter().map(|v| serde_json::json!(v)).collect();
+    values.push(serde_json::Value::Null);
+    schemars::schema::SchemaObject {
+        instance_type: Some(
+            vec![schemars::schema::InstanceType::String, schemars::schema::InstanceType::Null].into(),
+        ),
+        enum_values: Some(values),
+        ..Default::default()
+    }
+    .into()
+}
+
+fn lenient<'de, D: serde::Deserializer<'de>, T: serde::de::DeserializeOwned>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
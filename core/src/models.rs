@@ -0,0 +1,190 @@
+use crate::provider::Backend;
+use serde::{Deserialize, Serialize};
+
+/// What a hosted model accepts, so requests can adapt to it
+#[derive(Debug, Clone, Copy)]
+pub struct ModelInfo {
+    pub id: &'static str,
+    pub backend: Backend,
+    pub label: &'static str,
+    /// Longest image side the model makes use of; larger images are downscaled
+    pub max_image_px: u32,
+    /// OpenAI image detail level ("high", "low" or "auto")
+    pub detail: &'static str,
+    pub json_mode: bool,
+    /// Accepts a strict json_schema response_format (OpenAI structured outputs)
+    pub structured_outputs: bool,
+    /// Reasoning models take max_completion_tokens instead of max_tokens and
+    /// spend part of the budget thinking
+    pub reasoning: bool,
+    /// USD per million input and output tokens
+    pub price_per_mtok: (f64, f64),
+}
+
+pub const CATALOG: [ModelInfo; 8] = [
+    ModelInfo { id: "gpt-4o-mini", backend: Backend::OpenAi, label: "GPT-4o mini", max_image_px: 2048, detail: "high", json_mode: true, structured_outputs: true, reasoning: false, price_per_mtok: (0.15, 0.60) },
+    ModelInfo { id: "gpt-4o", backend: Backend::OpenAi, label: "GPT-4o", max_image_px: 2048, detail: "high", json_mode: true, structured_outputs: true, reasoning: false, price_per_mtok: (2.50, 10.00) },
+    ModelInfo { id: "gpt-4.1", backend: Backend::OpenAi, label: "GPT-4.1", max_image_px: 2048, detail: "high", json_mode: true, structured_outputs: true, reasoning: false, price_per_mtok: (2.00, 8.00) },
+    ModelInfo { id: "gpt-4.1-mini", backend: Backend::OpenAi, label: "GPT-4.1 mini", max_image_px: 2048, detail: "high", json_mode: true, structured_outputs: true, reasoning: false, price_per_mtok: (0.40, 1.60) },
+    ModelInfo { id: "o4-mini", backend: Backend::OpenAi, label: "o4-mini (reasoning)", max_image_px: 2048, detail: "high", json_mode: true, structured_outputs: true, reasoning: true, price_per_mtok: (1.10, 4.40) },
+    ModelInfo { id: "o3", backend: Backend::OpenAi, label: "o3 (reasoning)", max_image_px: 2048, detail: "high", json_mode: true, structured_outputs: true, reasoning: true, price_per_mtok: (2.00, 8.00) },
+    ModelInfo { id: "claude-3-5-sonnet-latest", backend: Backend::Anthropic, label: "Claude 3.5 Sonnet", max_image_px: 1568, detail: "auto", json_mode: false, structured_outputs: false, reasoning: false, price_per_mtok: (3.00, 15.00) },
+    ModelInfo { id: "claude-3-5-haiku-latest", backend: Backend::Anthropic, label: "Claude 3.5 Haiku", max_image_px: 1568, detail: "auto", json_mode: false, structured_outputs: false, reasoning: false, price_per_mtok: (0.80, 4.00) },
+];
+
+pub fn lookup(id: &str) -> Option<&'static ModelInfo> {
+    CATALOG.iter().find(|m| m.id == id)
+}
+
+pub fn for_backend(backend: Backend) -> impl Iterator<Item = &'static ModelInfo> {
+    CATALOG.iter().filter(move |m| m.backend == backend)
+}
+
+/// Format photos are re-encoded to before upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum UploadFormat {
+    Jpeg,
+    WebP,
+}
+
+impl UploadFormat {
+    pub const ALL: [UploadFormat; 2] = [UploadFormat::Jpeg, UploadFormat::WebP];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            UploadFormat::Jpeg => "JPEG",
+            UploadFormat::WebP => "WebP",
+        }
+    }
+
+    fn image_format(self) -> image::ImageFormat {
+        match self {
+            UploadFormat::Jpeg => image::ImageFormat::Jpeg,
+            UploadFormat::WebP => image::ImageFormat::WebP,
+        }
+    }
+}
+
+/// How photos are prepared before they are sent to a model. A full-size
+/// phone photo is several megabytes of base64 the model downscales anyway.
+///
+/// ```
+/// use avalanche_classifier_core::models::{UploadEncoding, UploadFormat};
+///
+/// let upload = UploadEncoding::default().with_max_px(1024).with_format(UploadFormat::WebP).with_quality(80);
+/// assert_eq!(upload.max_px, 1024);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadEncoding {
+    /// Longest side; the model's own limit applies when it is lower
+    pub max_px: u32,
+    pub format: UploadFormat,
+    pub quality: u8,  // 1-100
+}
+
+impl Default for UploadEncoding {
+    fn default() -> Self {
+        Self { max_px: 2048, format: UploadFormat::Jpeg, quality: 85 }
+    }
+}
+
+impl UploadEncoding {
+    pub const MIN_PX: u32 = 256;
+
+    pub fn with_max_px(mut self, max_px: u32) -> Self {
+        self.max_px = max_px;
+        self
+    }
+
+    pub fn with_format(mut self, format: UploadFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_quality(mut self, quality: u8) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Downscales to the smaller of `max_px` and `model_max_px`, turns
+    /// sideways phone photos upright (a slope on its side throws off the
+    /// model's angle reasoning) and re-encodes. Images already small,
+    /// upright and in the target format, or undecodable, are passed through
+    /// untouched. Decoding waits for room in the shared decode budget.
+    pub async fn prepare(&self, image_bytes: &[u8], model_max_px: Option<u32>) -> Vec<u8> {
+        let max_px = model_max_px.map_or(self.max_px, |m| m.min(self.max_px)).max(Self::MIN_PX);
+        // Decided from the header, so passed-through images are never decoded
+        let Some((width, height)) = crate::photo_meta::dimensions(image_bytes) else {
+            return image_bytes.to_vec();
+        };
+        let oversized = width.max(height) > max_px;
+        if !oversized
+            && crate::photo_meta::orientation(image_bytes) == 1
+            && image::guess_format(image_bytes).ok() == Some(self.format.image_format())
+        {
+            return image_bytes.to_vec();
+        }
+
+        let _permit =
+            crate::decode::permit(crate::decode::estimate_for(width, height, image_bytes.len() as u64)).await;
+        let Ok(image) = crate::photo_meta::load_upright(image_bytes) else {
+            return image_bytes.to_vec();
+        };
+        let resized = if oversized {
+            image.resize(max_px, max_px, image::imageops::FilterType::Lanczos3)
+        } else {
+            image
+        };
+        let rgb = resized.to_rgb8();
+        let quality = self.quality.clamp(1, 100);
+        match self.format {
+            UploadFormat::Jpeg => {
+                let mut bytes = Vec::new();
+                match rgb.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Jpeg(quality)) {
+                    Ok(()) => bytes,
+                    Err(_) => image_bytes.to_vec(),
+                }
+            }
+            UploadFormat::WebP => {
+                webp::Encoder::from_rgb(&rgb, rgb.width(), rgb.height()).encode(f32::from(quality)).to_vec()
+            }
+        }
+    }
+}
+
+/// What UploadEncoding::prepare did to a photo, for the result's provenance,
+/// e.g. "turned upright, resized 4032×3024 → 2048×1536, re-encoded as JPEG"
+pub fn describe_preparation(original: &[u8], prepared: &[u8]) -> String {
+    if original == prepared {
+        return "sent as-is".to_string();
+    }
+    let mut steps = Vec::new();
+    if crate::photo_meta::orientation(original) != 1 {
+        steps.push("turned upright".to_string());
+    }
+    let before = crate::photo_meta::dimensions(original);
+    let after = crate::photo_meta::dimensions(prepared);
+    if let (Some((w, h)), Some((new_w, new_h))) = (before, after) {
+        // Turning upright swaps the sides without resizing
+        if w.max(h) != new_w.max(new_h) {
+            steps.push(format!("resized {}×{} → {}×{}", w, h, new_w, new_h));
+        }
+    }
+    let format = image::guess_format(prepared).ok();
+    if let Some(upload) = UploadFormat::ALL.into_iter().find(|f| Some(f.image_format()) == format) {
+        steps.push(format!("re-encoded as {}", upload.label()));
+    }
+    steps.join(", ")
+}
+
+/// MIME type of encoded image bytes, for data URLs and upload bodies
+pub fn media_type(image_bytes: &[u8]) -> &'static str {
+    match image::guess_format(image_bytes) {
+        Ok(image::ImageFormat::Png) => "image/png",
+        Ok(image::ImageFormat::WebP) => "image/webp",
+        Ok(image::ImageFormat::Gif) => "image/gif",
+        _ => "image/jpeg",
+    }
+}
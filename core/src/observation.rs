@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+// What an observer on site reports alongside a photo. Ground truth such as
+// a failed stability test or a night of wind loading changes how an
+// ambiguous photo should be read, so the observations go into the prompt
+// with the image rather than being kept beside the result.
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldObservations {
+    // New snow over the last 72 hours
+    pub new_snow_cm: Option<u32>,
+    // Direction the wind blows from, one of model::ASPECTS
+    pub wind_direction: Option<String>,
+    pub wind_strength: Option<WindStrength>,
+    pub temperature_trend: Option<TemperatureTrend>,
+    // Free text, e.g. "several D2 wind slabs on NE aspects yesterday"
+    pub recent_avalanches: String,
+    // Free text in the usual notation, e.g. "ECTP 14 down 40 cm on surface hoar"
+    pub stability_tests: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindStrength {
+    Calm,
+    Light,
+    Moderate,
+    Strong,
+    Extreme,
+}
+
+impl WindStrength {
+    pub const ALL: [WindStrength; 5] =
+        [WindStrength::Calm, WindStrength::Light, WindStrength::Moderate, WindStrength::Strong, WindStrength::Extreme];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WindStrength::Calm => "Calm",
+            WindStrength::Light => "Light",
+            WindStrength::Moderate => "Moderate",
+            WindStrength::Strong => "Strong",
+            WindStrength::Extreme => "Extreme",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureTrend {
+    Falling,
+    Steady,
+    Rising,
+}
+
+impl TemperatureTrend {
+    pub const ALL: [TemperatureTrend; 3] = [TemperatureTrend::Falling, TemperatureTrend::Steady, TemperatureTrend::Rising];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TemperatureTrend::Falling => "Falling",
+            TemperatureTrend::Steady => "Steady",
+            TemperatureTrend::Rising => "Rising",
+        }
+    }
+}
+
+impl FieldObservations {
+    pub fn is_empty(&self) -> bool {
+        self.lines().is_empty()
+    }
+
+    // One line per observation made, e.g. "Wind: strong from NW"
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(cm) = self.new_snow_cm {
+            lines.push(format!("New snow in the last 72 hours: {} cm", cm));
+        }
+        match (self.wind_strength, self.wind_direction.as_deref().filter(|d| !d.is_empty())) {
+            (Some(WindStrength::Calm), _) => lines.push("Wind: calm".to_string()),
+            (Some(strength), Some(direction)) => {
+                lines.push(format!("Wind: {} from {}", strength.label().to_lowercase(), direction))
+            }
+            (Some(strength), None) => lines.push(format!("Wind: {}", strength.label().to_lowercase())),
+            (None, Some(direction)) => lines.push(format!("Wind: from {}", direction)),
+            (None, None) => {}
+        }
+        if let Some(trend) = self.temperature_trend {
+            lines.push(format!("Air temperature trend: {}", trend.label().to_lowercase()));
+        }
+        if !self.recent_avalanches.trim().is_empty() {
+            lines.push(format!("Recent avalanche activity: {}", self.recent_avalanches.trim()));
+        }
+        if !self.stability_tests.trim().is_empty() {
+            lines.push(format!("Stability test results: {}", self.stability_tests.trim()));
+        }
+        lines
+    }
+
+    // The section appended to the prompt; None when nothing was observed
+    pub fn prompt_section(&self) -> Option<String> {
+        let lines = self.lines();
+        if lines.is_empty() {
+            return None;
+        }
+        let mut section = String::from(
+            "FIELD OBSERVATIONS (reported by an observer on site; when the photo alone is ambiguous, \
+             let them decide between the readings it allows, e.g. fresh wind loading favors a wind slab \
+             and a rising temperature wet snow, but never report features the photo does not show):",
+        );
+        for line in lines {
+            section.push_str("\n- ");
+            section.push_str(&line);
+        }
+        Some(section)
+    }
+}
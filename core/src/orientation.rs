@@ -0,0 +1,51 @@
+// Which way a photo was looking: compass bearings and the ground area a
+// view covers, for placing an analysis on a map.
+
+use crate::camera::Location;
+
+// Horizontal field of view assumed when drawing a view cone; roughly a
+// phone's main camera
+pub const DEFAULT_FOV_DEG: f64 = 60.0;
+// How far a view cone reaches; about the distance slopes are still
+// readable in a photo
+pub const DEFAULT_RANGE_M: f64 = 1500.0;
+
+const POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+];
+
+// Bearing in degrees clockwise from true north, into 0..360
+pub fn normalize(bearing: f32) -> f32 {
+    bearing.rem_euclid(360.0)
+}
+
+// 16-point compass name, e.g. "NE"
+pub fn compass_point(bearing: f32) -> &'static str {
+    POINTS[((normalize(bearing) as f64 / 22.5).round() as usize) % 16]
+}
+
+// The point reached by travelling `distance_m` from `from` on `bearing`
+pub fn destination(from: Location, bearing: f64, distance_m: f64) -> Location {
+    const EARTH_RADIUS_M: f64 = 6_371_000.0;
+    let angular = distance_m / EARTH_RADIUS_M;
+    let bearing = bearing.to_radians();
+    let lat1 = from.latitude.to_radians();
+    let lon1 = from.longitude.to_radians();
+    let lat2 = (lat1.sin() * angular.cos() + lat1.cos() * angular.sin() * bearing.cos()).asin();
+    let lon2 = lon1
+        + (bearing.sin() * angular.sin() * lat1.cos()).atan2(angular.cos() - lat1.sin() * lat2.sin());
+    Location { latitude: lat2.to_degrees(), longitude: (lon2.to_degrees() + 540.0) % 360.0 - 180.0 }
+}
+
+// Closed polygon outlining what the camera saw: the camera position, then
+// an arc `range_m` away spanning `fov_deg` around the bearing
+pub fn view_cone(from: Location, bearing: f32, fov_deg: f64, range_m: f64) -> Vec<Location> {
+    const ARC_STEPS: usize = 12;
+    let start = bearing as f64 - fov_deg / 2.0;
+    let mut polygon = vec![from];
+    for step in 0..=ARC_STEPS {
+        polygon.push(destination(from, start + fov_deg * step as f64 / ARC_STEPS as f64, range_m));
+    }
+    polygon.push(from);
+    polygon
+}
@@ -0,0 +1,79 @@
+use crate::scoring;
+use crate::model::AvalancheAnalysis;
+
+// Batches smaller than this are too small for the statistical checks. No
+// z-score in a batch of n can exceed (n-1)/√n, which only passes
+// Z_THRESHOLD from n = 6.
+const MIN_BATCH_FOR_STATS: usize = 6;
+const Z_THRESHOLD: f32 = 2.0;
+
+// Returns review reasons for each analysis in the batch, in input order.
+// An empty list means the analysis looks normal.
+pub fn detect(batch: &[&AvalancheAnalysis]) -> Vec<Vec<String>> {
+    let scores: Vec<scoring::TypeScores> = batch.iter().map(|a| scoring::type_scores(a)).collect();
+    let mut flags: Vec<Vec<String>> = vec![Vec::new(); batch.len()];
+
+    // Internal consistency of each analysis on its own
+    for (i, analysis) in batch.iter().enumerate() {
+        let avalanche_type = analysis.avalanche_type.as_str();
+        if analysis.avalanche_present && avalanche_type == "none" {
+            flags[i].push("Avalanche reported present but type is none".to_string());
+        }
+        if !analysis.avalanche_present && avalanche_type != "none" {
+            flags[i].push(format!("Classified as {} but no avalanche reported", avalanche_type));
+        }
+        if let Some(own) = scores[i].for_type(avalanche_type) {
+            if own == 0 {
+                flags[i].push(format!("Classified as {} with no {} indicators", avalanche_type, avalanche_type));
+            }
+            for other in scoring::SCORED_TYPES {
+                if other != avalanche_type && scores[i].for_type(other).unwrap_or(0) > own {
+                    flags[i].push(format!("{} indicators outweigh {}", other, avalanche_type));
+                }
+            }
+        }
+    }
+
+    if batch.len() < MIN_BATCH_FOR_STATS {
+        return flags;
+    }
+
+    // Confidence relative to the rest of the batch
+    let confidences: Vec<f32> = batch.iter().map(|a| a.confidence_level).collect();
+    for (i, z) in z_scores(&confidences).into_iter().enumerate() {
+        if z.abs() > Z_THRESHOLD {
+            flags[i].push(format!("Confidence {:.0}% is unusual for this batch", confidences[i]));
+        }
+    }
+
+    // Indicator strength relative to other analyses of the same type
+    for avalanche_type in scoring::SCORED_TYPES {
+        let members: Vec<usize> = (0..batch.len())
+            .filter(|&i| batch[i].avalanche_type == avalanche_type)
+            .collect();
+        if members.len() < MIN_BATCH_FOR_STATS {
+            continue;
+        }
+        let values: Vec<f32> = members
+            .iter()
+            .map(|&i| scores[i].for_type(avalanche_type).unwrap_or(0) as f32)
+            .collect();
+        for (&i, z) in members.iter().zip(z_scores(&values)) {
+            if z < -Z_THRESHOLD {
+                flags[i].push(format!("Unusually weak {} indicators for this batch", avalanche_type));
+            }
+        }
+    }
+
+    flags
+}
+
+fn z_scores(values: &[f32]) -> Vec<f32> {
+    let n = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let std_dev = (values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n).sqrt();
+    if std_dev == 0.0 {
+        return vec![0.0; values.len()];
+    }
+    values.iter().map(|v| (v - mean) / std_dev).collect()
+}
@@ -0,0 +1,266 @@
+// One-page PDF version of the report for attaching to incident records:
+// the analyzed photo, the classification and the full snow and terrain
+// breakdown. Uses the PDF built-in Helvetica, so text is reduced to ASCII.
+
+use crate::history::HistoryEntry;
+use crate::model::{AvalancheAnalysis, FractureDepth};
+use printpdf::{
+    BuiltinFont, ColorBits, ColorSpace, Image, ImageFilter, ImageTransform, ImageXObject, IndirectFontRef, Mm,
+    PdfDocument, PdfLayerReference, Px,
+};
+
+const PAGE_W: f32 = 210.0;
+const PAGE_H: f32 = 297.0;
+const MARGIN: f32 = 18.0;
+const LINE: f32 = 4.8;
+const MAX_IMAGE_H: f32 = 95.0;
+// Embedded photos are downscaled to this many pixels on the long side
+const MAX_IMAGE_PX: u32 = 1600;
+// Helvetica at 10 pt fits roughly this many characters across the page
+const WRAP_CHARS: usize = 95;
+
+struct Cursor {
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,  // mm from the bottom edge
+}
+
+impl Cursor {
+    fn fits(&self) -> bool {
+        self.y > MARGIN
+    }
+
+    fn text(&mut self, text: &str, size: f32, x: f32, bold: bool) {
+        if self.fits() {
+            let font = if bold { &self.bold } else { &self.regular };
+            self.layer.use_text(ascii(text), size, Mm(x), Mm(self.y), font);
+        }
+    }
+
+    fn heading(&mut self, text: &str, x: f32) {
+        self.y -= 2.0;
+        self.text(text, 12.0, x, true);
+        self.y -= LINE + 1.0;
+    }
+
+    fn row(&mut self, label: &str, value: &str, x: f32) {
+        self.text(label, 10.0, x, false);
+        self.text(value, 10.0, x + 38.0, true);
+        self.y -= LINE;
+    }
+
+    fn paragraph(&mut self, text: &str, x: f32) {
+        for line in wrap(&ascii(text), WRAP_CHARS) {
+            self.text(&line, 10.0, x, false);
+            self.y -= LINE;
+        }
+    }
+}
+
+// Replaces the typography this app produces with ASCII and drops anything
+// else the built-in font cannot encode
+fn ascii(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            '–' | '—' => Some('-'),
+            '·' | '•' => Some('*'),
+            '‘' | '’' => Some('\''),
+            '“' | '”' => Some('"'),
+            '°' => Some('d'),
+            c if c.is_ascii() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.len() + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+// Renders the report. `entry` adds provenance, location, bearing and
+// exposed assets when the analysis was recorded in history.
+pub fn render_pdf(
+    analysis: &AvalancheAnalysis,
+    image_bytes: Option<&[u8]>,
+    entry: Option<&HistoryEntry>,
+) -> anyhow::Result<Vec<u8>> {
+    let (doc, page, layer) = PdfDocument::new("Avalanche Analysis Report", Mm(PAGE_W), Mm(PAGE_H), "Report");
+    let mut cursor = Cursor {
+        layer: doc.get_page(page).get_layer(layer),
+        regular: doc.add_builtin_font(BuiltinFont::Helvetica)?,
+        bold: doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+        y: PAGE_H - MARGIN,
+    };
+    let left = MARGIN;
+    let right = PAGE_W / 2.0 + 4.0;
+
+    cursor.text("Avalanche Analysis Report", 18.0, left, true);
+    cursor.y -= LINE + 2.0;
+    let mut provenance = format!("Generated {}", chrono::Local::now().format("%Y-%m-%d %H:%M"));
+    if let Some(snapshot) = entry.map(|e| &e.snapshot) {
+        provenance.push_str(&format!(
+            " * {} {} * snapshot {}",
+            snapshot.backend,
+            snapshot.model,
+            &snapshot.id[..12.min(snapshot.id.len())]
+        ));
+    }
+    cursor.text(&provenance, 9.0, left, false);
+    cursor.y -= LINE;
+    if let Some(entry) = entry {
+        let mut place = Vec::new();
+        if let Some(at) = entry.captured_at.and_then(|t| chrono::DateTime::from_timestamp(t, 0)) {
+            place.push(format!("Taken {}", at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")));
+        }
+        if let Some(location) = entry.location {
+            place.push(format!("at {:.5}, {:.5}", location.latitude, location.longitude));
+        }
+        if let Some(bearing) = entry.bearing {
+            place.push(format!(
+                "facing {} ({:.0} deg)",
+                crate::orientation::compass_point(bearing),
+                crate::orientation::normalize(bearing)
+            ));
+        }
+        if let Some(source) = &entry.source {
+            place.push(format!("camera {}", source));
+        }
+        if let Some(info) = &entry.camera_info {
+            place.push(format!("with {}", info.describe()));
+        }
+        if !place.is_empty() {
+            cursor.text(&place.join(" "), 9.0, left, false);
+            cursor.y -= LINE;
+        }
+        if let Some(slope) = &entry.slope {
+            cursor.text(&format!("Slope: {}", slope.describe()), 9.0, left, false);
+            cursor.y -= LINE;
+        }
+    }
+    cursor.y -= 2.0;
+
+    if let Some(bytes) = image_bytes {
+        let photo = crate::photo_meta::load_upright(bytes)?;
+        let photo = if photo.width().max(photo.height()) > MAX_IMAGE_PX {
+            photo.thumbnail(MAX_IMAGE_PX, MAX_IMAGE_PX)
+        } else {
+            photo
+        };
+        let photo = image::DynamicImage::ImageRgb8(photo.to_rgb8());
+        // Embedded as JPEG; printpdf would otherwise store raw pixels
+        let mut jpeg = Vec::new();
+        photo.write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageOutputFormat::Jpeg(85))?;
+        let xobject = ImageXObject {
+            width: Px(photo.width() as usize),
+            height: Px(photo.height() as usize),
+            color_space: ColorSpace::Rgb,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: jpeg,
+            image_filter: Some(ImageFilter::DCT),
+            smask: None,
+            clipping_bbox: None,
+        };
+        let (w, h) = (photo.width() as f32, photo.height() as f32);
+        let width_mm = (PAGE_W - 2.0 * MARGIN).min(MAX_IMAGE_H * w / h);
+        let height_mm = width_mm * h / w;
+        let dpi = w / (width_mm / 25.4);
+        cursor.y -= height_mm;
+        Image::from(xobject).add_to_layer(
+            cursor.layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(left + (PAGE_W - 2.0 * MARGIN - width_mm) / 2.0)),
+                translate_y: Some(Mm(cursor.y)),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        );
+        cursor.y -= LINE;
+    }
+
+    cursor.heading("Classification", left);
+    let label = entry.and_then(HistoryEntry::effective_type).unwrap_or(&analysis.avalanche_type);
+    cursor.row("Avalanche present", yes_no(analysis.avalanche_present), left);
+    cursor.row("Type", label, left);
+    if label != analysis.avalanche_type {
+        cursor.row("Model output", &analysis.avalanche_type, left);
+    }
+    cursor.row("Confidence", &format!("{:.0}%", analysis.confidence_level), left);
+
+    // Breakdown in two columns
+    let visual = &analysis.visual_characteristics;
+    let top = cursor.y;
+    cursor.heading("Snow Analysis", left);
+    cursor.row("Granular", yes_no(visual.snow_texture.granular), left);
+    cursor.row("Blocky", yes_no(visual.snow_texture.blocky), left);
+    cursor.row("Fluffy", yes_no(visual.snow_texture.fluffy), left);
+    cursor.row("Density", &visual.snow_texture.density, left);
+    cursor.heading("Movement Pattern", left);
+    cursor.row("Initial release", &visual.movement_pattern.starting_width, left);
+    cursor.row("Propagation", &visual.movement_pattern.propagation, left);
+    cursor.row("Vertical movement", yes_no(visual.movement_pattern.vertical_movement), left);
+    cursor.row("Lateral spread", yes_no(visual.movement_pattern.lateral_spread), left);
+    let left_bottom = cursor.y;
+
+    cursor.y = top;
+    cursor.heading("Terrain Analysis", right);
+    cursor.row("Slope", visual.terrain.slope_angle.as_deref().unwrap_or("unknown"), right);
+    cursor.row("Surface", &visual.terrain.surface_roughness, right);
+    cursor.row("Anchoring points", yes_no(visual.terrain.anchoring_points), right);
+    cursor.row("Convex rollover", yes_no(visual.terrain.convex_rollover), right);
+    cursor.heading("Release and Debris", right);
+    cursor.row("Powder cloud", yes_no(visual.powder_cloud), right);
+    cursor.row("Fracture line", yes_no(visual.fracture_line), right);
+    let depth = visual.fracture_depth.as_ref().map_or_else(|| "n/a".to_string(), FractureDepth::detailed_label);
+    cursor.row("Fracture depth", &depth, right);
+    cursor.row("Point release", yes_no(visual.point_release), right);
+    cursor.y = cursor.y.min(left_bottom);
+    cursor.row("Debris pattern", &visual.debris_pattern, left);
+
+    if !analysis.terrain_features.is_empty() {
+        cursor.heading("Additional Observations", left);
+        for feature in &analysis.terrain_features {
+            cursor.paragraph(&format!("- {}", feature), left);
+        }
+    }
+    if let Some(entry) = entry.filter(|e| !e.exposure.is_empty()) {
+        cursor.heading("Exposed Assets", left);
+        for asset in &entry.exposure {
+            cursor.paragraph(&format!("- {}", asset.describe()), left);
+        }
+    }
+    if !cursor.fits() {
+        cursor.layer.use_text(
+            "Report truncated; see the JSON export for the full analysis",
+            8.0,
+            Mm(left),
+            Mm(MARGIN / 2.0),
+            &cursor.regular,
+        );
+    }
+
+    Ok(doc.save_to_bytes()?)
+}
@@ -0,0 +1,278 @@
+// Capture details read from a photo's EXIF block. Everything is optional:
+// screenshots, re-encoded images and many phone exports carry none of it.
+
+use crate::camera::Location;
+use exif::{In, Reader, Tag, Value};
+use serde::{Deserialize, Serialize};
+
+// Film diagonal the 35 mm equivalent focal length refers to: 36 × 24 mm
+const FULL_FRAME_WIDTH_MM: f32 = 36.0;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhotoMeta {
+    // Unix seconds. EXIF times are camera-local; without an offset tag they
+    // are read in this machine's time zone.
+    pub captured_at: Option<i64>,
+    pub location: Option<Location>,
+    // Degrees clockwise from north the lens pointed, from GPSImgDirection
+    pub bearing: Option<f32>,
+    pub camera: Option<CameraInfo>,
+}
+
+// The camera and lens setting a photo was taken with
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CameraInfo {
+    // Make and model, e.g. "Canon EOS R5"
+    pub model: Option<String>,
+    pub focal_length_mm: Option<f32>,
+    // What the lens would be on a full-frame camera; phones report a few
+    // millimetres actual, so this is the comparable number
+    pub focal_length_35mm: Option<f32>,
+}
+
+impl CameraInfo {
+    // "Canon EOS R5 at 70 mm" or "Apple iPhone 13 at 5.1 mm (26 mm equiv.)"
+    pub fn describe(&self) -> String {
+        let focal = match (self.focal_length_mm, self.focal_length_35mm) {
+            (Some(actual), Some(equiv)) if (actual - equiv).abs() >= 1.0 => {
+                Some(format!("{:.1} mm ({:.0} mm equiv.)", actual, equiv))
+            }
+            (Some(mm), _) | (None, Some(mm)) => Some(format!("{:.0} mm", mm)),
+            (None, None) => None,
+        };
+        self.model.iter().cloned().chain(focal).collect::<Vec<_>>().join(" at ")
+    }
+
+    // Horizontal field of view in degrees for a landscape photo, when the
+    // 35 mm equivalent is known
+    pub fn horizontal_fov(&self) -> Option<f32> {
+        self.focal_length_35mm
+            .filter(|f| *f > 0.0)
+            .map(|f| 2.0 * (FULL_FRAME_WIDTH_MM / (2.0 * f)).atan().to_degrees())
+    }
+}
+
+impl PhotoMeta {
+    pub fn read(image_bytes: &[u8]) -> Self {
+        let Ok(exif) = Reader::new().read_from_container(&mut std::io::Cursor::new(image_bytes)) else {
+            return Self::default();
+        };
+        Self {
+            captured_at: captured_at(&exif),
+            location: location(&exif),
+            bearing: bearing(&exif),
+            camera: camera(&exif),
+        }
+    }
+}
+
+// ISO base media brands of HEIF stills: HEIC (the iPhone default) and AVIF
+const HEIF_BRANDS: [&[u8; 4]; 9] = [b"heic", b"heix", b"heim", b"heis", b"hevc", b"mif1", b"msf1", b"avif", b"avis"];
+
+// File extensions offered when picking photos. HEIC and AVIF are listed
+// even without the `heif` feature, so picking one explains what is missing.
+pub const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "webp", "heic", "heif", "avif"];
+
+// Whether files with this extension can be decoded in this build
+pub fn decodable_extension(ext: &str) -> bool {
+    let ext = ext.to_lowercase();
+    let heif = ["heic", "heif", "avif"].contains(&ext.as_str());
+    IMAGE_EXTENSIONS.contains(&ext.as_str()) && (cfg!(feature = "heif") || !heif)
+}
+
+// Whether the bytes are a HEIF container, from the brands in its ftyp box
+pub fn is_heif(image_bytes: &[u8]) -> bool {
+    if image_bytes.get(4..8) != Some(b"ftyp") {
+        return false;
+    }
+    let size = image_bytes.get(..4).map_or(0, |s| u32::from_be_bytes([s[0], s[1], s[2], s[3]]) as usize);
+    // Major brand at 8, minor version at 12, compatible brands from 16
+    let brands = image_bytes.get(8..size.min(image_bytes.len())).unwrap_or_default();
+    brands.chunks_exact(4).enumerate().any(|(i, brand)| i != 1 && HEIF_BRANDS.iter().any(|b| &b[..] == brand))
+}
+
+// Pixel size from the header, without decoding
+pub fn dimensions(image_bytes: &[u8]) -> Option<(u32, u32)> {
+    if is_heif(image_bytes) {
+        return heif::dimensions(image_bytes);
+    }
+    image::io::Reader::new(std::io::Cursor::new(image_bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+// EXIF orientation, 1 (upright) to 8. Phones store pixels as the sensor
+// saw them and record the rotation here instead of turning the image.
+pub fn orientation(image_bytes: &[u8]) -> u32 {
+    // HEIF carries its own rotation, which the decoder applies
+    if is_heif(image_bytes) {
+        return 1;
+    }
+    Reader::new()
+        .read_from_container(&mut std::io::Cursor::new(image_bytes))
+        .ok()
+        .and_then(|exif| exif.get_field(Tag::Orientation, In::PRIMARY)?.value.get_uint(0))
+        .filter(|o| (1..=8).contains(o))
+        .unwrap_or(1)
+}
+
+// Decodes an image and turns it the way its EXIF orientation says, so it
+// looks as it did in the viewfinder
+pub fn load_upright(image_bytes: &[u8]) -> anyhow::Result<image::DynamicImage> {
+    if is_heif(image_bytes) {
+        return heif::decode(image_bytes);
+    }
+    let image = image::load_from_memory(image_bytes).map_err(|err| match err {
+        image::ImageError::Unsupported(_) => {
+            anyhow::anyhow!("unsupported image format; use JPEG, PNG, WebP, GIF, TIFF or BMP ({})", err)
+        }
+        other => other.into(),
+    })?;
+    Ok(match orientation(image_bytes) {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    })
+}
+
+#[cfg(feature = "heif")]
+mod heif {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    pub fn dimensions(image_bytes: &[u8]) -> Option<(u32, u32)> {
+        let handle = HeifContext::read_from_bytes(image_bytes).ok()?.primary_image_handle().ok()?;
+        Some((handle.width(), handle.height()))
+    }
+
+    pub fn decode(image_bytes: &[u8]) -> anyhow::Result<image::DynamicImage> {
+        let handle = HeifContext::read_from_bytes(image_bytes)?.primary_image_handle()?;
+        let decoded = LibHeif::new().decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)?;
+        let plane = decoded.planes().interleaved.ok_or_else(|| anyhow::anyhow!("HEIF image has no RGB plane"))?;
+        // Rows are padded to `stride` bytes
+        let row = plane.width as usize * 3;
+        let pixels: Vec<u8> = plane.data.chunks(plane.stride).flat_map(|r| &r[..row]).copied().collect();
+        image::RgbImage::from_raw(plane.width, plane.height, pixels)
+            .map(image::DynamicImage::ImageRgb8)
+            .ok_or_else(|| anyhow::anyhow!("HEIF image data is truncated"))
+    }
+}
+
+#[cfg(not(feature = "heif"))]
+mod heif {
+    pub fn dimensions(_image_bytes: &[u8]) -> Option<(u32, u32)> {
+        None
+    }
+
+    pub fn decode(_image_bytes: &[u8]) -> anyhow::Result<image::DynamicImage> {
+        anyhow::bail!("HEIC and AVIF photos need a build with the `heif` feature (libheif); convert to JPEG or rebuild")
+    }
+}
+
+fn ascii(exif: &exif::Exif, tag: Tag) -> Option<&[u8]> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(parts) => parts.first().map(Vec::as_slice),
+        _ => None,
+    }
+}
+
+// Trimmed text; cameras pad these with spaces or NULs
+fn text(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    let value = String::from_utf8_lossy(ascii(exif, tag)?);
+    let value = value.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn camera(exif: &exif::Exif) -> Option<CameraInfo> {
+    let model = match (text(exif, Tag::Make), text(exif, Tag::Model)) {
+        // Many models already start with the make ("Canon EOS R5")
+        (Some(make), Some(model)) if model.to_lowercase().starts_with(&make.to_lowercase()) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => model.or(make),
+    };
+    let focal_length_mm = exif.get_field(Tag::FocalLength, In::PRIMARY).and_then(|f| match &f.value {
+        Value::Rational(parts) => parts.first().map(|r| r.to_f64() as f32),
+        _ => None,
+    });
+    let focal_length_35mm = exif
+        .get_field(Tag::FocalLengthIn35mmFilm, In::PRIMARY)
+        .and_then(|f| f.value.get_uint(0))
+        .map(|f| f as f32);
+    let positive = |f: Option<f32>| f.filter(|f| f.is_finite() && *f > 0.0);
+    let info = CameraInfo {
+        model,
+        focal_length_mm: positive(focal_length_mm),
+        focal_length_35mm: positive(focal_length_35mm),
+    };
+    (info != CameraInfo::default()).then_some(info)
+}
+
+fn captured_at(exif: &exif::Exif) -> Option<i64> {
+    use chrono::{FixedOffset, Local, NaiveDate, TimeZone};
+    let (time_tag, offset_tag) = if exif.get_field(Tag::DateTimeOriginal, In::PRIMARY).is_some() {
+        (Tag::DateTimeOriginal, Tag::OffsetTimeOriginal)
+    } else {
+        (Tag::DateTime, Tag::OffsetTime)
+    };
+    let mut time = exif::DateTime::from_ascii(ascii(exif, time_tag)?).ok()?;
+    if let Some(offset) = ascii(exif, offset_tag) {
+        let _ = time.parse_offset(offset);
+    }
+    let naive = NaiveDate::from_ymd_opt(time.year.into(), time.month.into(), time.day.into())?.and_hms_opt(
+        time.hour.into(),
+        time.minute.into(),
+        time.second.into(),
+    )?;
+    match time.offset {
+        Some(minutes) => FixedOffset::east_opt(i32::from(minutes) * 60)?
+            .from_local_datetime(&naive)
+            .single()
+            .map(|t| t.timestamp()),
+        None => Local.from_local_datetime(&naive).earliest().map(|t| t.timestamp()),
+    }
+}
+
+// Degrees, minutes, seconds as three rationals
+fn degrees(exif: &exif::Exif, tag: Tag) -> Option<f64> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(parts) if parts.len() == 3 => {
+            Some(parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3600.0)
+        }
+        _ => None,
+    }
+}
+
+fn bearing(exif: &exif::Exif) -> Option<f32> {
+    match &exif.get_field(Tag::GPSImgDirection, In::PRIMARY)?.value {
+        // Magnetic bearings (GPSImgDirectionRef "M") are taken as true; the
+        // declination is small next to the width of a view cone in most ranges
+        Value::Rational(parts) => parts
+            .first()
+            .map(|r| r.to_f64() as f32)
+            .filter(|b| b.is_finite())
+            .map(crate::orientation::normalize),
+        _ => None,
+    }
+}
+
+fn location(exif: &exif::Exif) -> Option<Location> {
+    let mut latitude = degrees(exif, Tag::GPSLatitude)?;
+    let mut longitude = degrees(exif, Tag::GPSLongitude)?;
+    if ascii(exif, Tag::GPSLatitudeRef) == Some(b"S") {
+        latitude = -latitude;
+    }
+    if ascii(exif, Tag::GPSLongitudeRef) == Some(b"W") {
+        longitude = -longitude;
+    }
+    // Some cameras write zeros while waiting for a fix
+    if !latitude.is_finite() || !longitude.is_finite() || (latitude == 0.0 && longitude == 0.0) {
+        return None;
+    }
+    Some(Location { latitude, longitude })
+}
@@ -0,0 +1,107 @@
+use super::{Turn, VisionProvider};
+use crate::error::{parse_failure, read_body, AnalysisError};
+
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+    upload: crate::models::UploadEncoding,
+}
+
+impl AnthropicProvider {
+    pub const DEFAULT_MODEL: &'static str = "claude-3-5-sonnet-latest";
+
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: super::http_client(),
+            api_key: api_key.into(),
+            model: Self::DEFAULT_MODEL.to_string(),
+            upload: Default::default(),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn with_upload(mut self, upload: crate::models::UploadEncoding) -> Self {
+        self.upload = upload;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl VisionProvider for AnthropicProvider {
+    fn backend(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn prepare(&self, image_bytes: &[u8]) -> Vec<u8> {
+        let max_px = crate::models::lookup(&self.model).map_or(1568, |m| m.max_image_px);
+        self.upload.prepare(image_bytes, Some(max_px)).await
+    }
+
+    async fn converse(&self, image_bytes: &[u8], prompt: &str, turns: &[Turn]) -> Result<String, AnalysisError> {
+        use base64::Engine;
+        let image_base64 = base64::engine::general_purpose::STANDARD.encode(image_bytes);
+
+        // Unlike OpenAI, the Messages API rejects a media type that doesn't match the bytes
+        let media_type = crate::models::media_type(image_bytes);
+
+        let mut messages = vec![serde_json::json!({
+            "role": "user",
+            "content": [
+                {"type": "image", "source": {
+                    "type": "base64",
+                    "media_type": media_type,
+                    "data": image_base64
+                }},
+                {"type": "text", "text": format!("{}\n\nRespond with the JSON object only.", prompt)}
+            ]
+        })];
+        for turn in turns {
+            messages.push(serde_json::json!({"role": "assistant", "content": turn.reply}));
+            messages.push(serde_json::json!({
+                "role": "user",
+                "content": format!("{}\n\nRespond with the JSON object only.", turn.followup)
+            }));
+        }
+
+        let response = self.client
+        .post("https://api.anthropic.com/v1/messages")
+        .header("x-api-key", &self.api_key)
+        .header("anthropic-version", "2023-06-01")
+        .json(&serde_json::json!({
+            "model": self.model,
+            "max_tokens": 1024,
+            "messages": messages
+        }))
+        .send()
+        .await?;
+
+        read_body("anthropic", response).await
+    }
+}
+
+// Messages responses carry a list of content blocks; the reply is the text
+// block. There is no JSON mode, so any prose or code fence around the object
+// is trimmed off.
+pub fn extract_content(response_text: &str) -> Result<String, AnalysisError> {
+    let json: serde_json::Value =
+        serde_json::from_str(response_text).map_err(|e| parse_failure(response_text, e))?;
+
+    let text = json["content"]
+        .as_array()
+        .and_then(|blocks| blocks.iter().find_map(|b| b["text"].as_str()))
+        .ok_or(AnalysisError::EmptyResponse)?;
+
+    match (text.find('{'), text.rfind('}')) {
+        (Some(start), Some(end)) if start < end => Ok(text[start..=end].to_string()),
+        _ => Ok(text.to_string()),
+    }
+}
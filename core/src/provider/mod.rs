@@ -0,0 +1,776 @@
+use crate::error::AnalysisError;
+use crate::model::{Annotations, AvalancheAnalysis};
+use crate::snapshot::{
+    elapsed_ms, hash_bytes, AnalysisSnapshot, BlobStore, EarlierResponse, Provenance, SnapshotImages, SnapshotResponses,
+    Timings,
+};
+
+mod anthropic;
+mod ollama;
+mod openai;
+
+pub use anthropic::AnthropicProvider;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiProvider;
+
+/// Backends selectable in the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub enum Backend {
+    OpenAi,
+    Anthropic,
+    Ollama,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::OpenAi, Backend::Anthropic, Backend::Ollama];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Backend::OpenAi => "OpenAI",
+            Backend::Anthropic => "Anthropic Claude",
+            Backend::Ollama => "Ollama (local)",
+        }
+    }
+
+    /// Short id recorded with snapshots, as VisionProvider::backend returns
+    pub fn id(&self) -> &'static str {
+        match self {
+            Backend::OpenAi => "openai",
+            Backend::Anthropic => "anthropic",
+            Backend::Ollama => "ollama",
+        }
+    }
+}
+
+/// Shared HTTP client; clones reuse one connection pool across providers
+pub fn http_client() -> reqwest::Client {
+    static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new).clone()
+}
+
+/// The built-in analysis prompt; it asks for JSON matching AvalancheAnalysis
+pub const ANALYSIS_PROMPT: &str = r#"Analyze this mountain terrain for avalanche characteristics with extreme detail. Return a JSON object with this structure:
+{
+    "avalanche_present": boolean,
+    "avalanche_type": "powder"|"loose-snow"|"slab"|"wet-loose"|"wet-slab"|"glide"|"cornice"|"slush"|"none",
+    "confidence_level": 0.0-100.0,
+    "terrain_features": string[],
+    "danger_level": 1-5,
+    "destructive_size": 1-5|null,
+    "hazard_rationale": string,
+    "avalanche_problem": "wind-slab"|"persistent-slab"|"storm-slab"|"wet-loose"|"glide"|"cornice"|null,
+    "visual_characteristics": {
+        "powder_cloud": boolean,
+        "fracture_line": boolean,
+        "fracture_depth": {"min_cm": integer, "max_cm": integer, "confidence": "low"|"medium"|"high", "scale_cue": string|null}|null,
+        "point_release": boolean,
+        "glide_crack": boolean,
+        "ground_exposed": boolean,
+        "cornice_failure": boolean,
+        "wind_features": boolean,
+        "recent_snowfall": boolean,
+        "debris_pattern": "fan-shaped"|"linear"|"scattered"|"none",
+        "snow_texture": {
+            "granular": boolean,
+            "blocky": boolean,
+            "fluffy": boolean,
+            "density": "low"|"medium"|"high",
+            "wet": boolean,
+            "water_saturated": boolean
+        },
+        "movement_pattern": {
+            "starting_width": "point"|"wide"|"undefined",
+            "propagation": "fan"|"linear"|"chaotic"|"none",
+            "vertical_movement": boolean,
+            "lateral_spread": boolean
+        },
+        "terrain": {
+            "slope_angle": "steep (>45°)"|"moderate (30-45°)"|"gentle (<30°)"|null,
+            "surface_roughness": "smooth"|"rough"|"variable",
+            "anchoring_points": boolean,
+            "convex_rollover": boolean,
+            "aspect": "N"|"NE"|"E"|"SE"|"S"|"SW"|"W"|"NW"|null,
+            "elevation_band": "below-treeline"|"near-treeline"|"above-treeline"|null
+        }
+    },
+    "annotations": {
+        "fracture_line": [{"x": 0.0-1.0, "y": 0.0-1.0}, ...]|null,
+        "start_zone": {"x": 0.0-1.0, "y": 0.0-1.0, "width": 0.0-1.0, "height": 0.0-1.0}|null,
+        "debris": [{"x": 0.0-1.0, "y": 0.0-1.0}, ...]|null
+    }
+}
+
+DETAILED ANALYSIS GUIDELINES:
+
+1. Snow Texture Analysis:
+   - Granular: Individual snow particles visible? Common in loose snow
+   - Blocky: Cohesive blocks or chunks? Typical of slab
+   - Fluffy: Light, airy appearance? Common in powder
+   - Density: Assess snow compactness
+   - Wet: Dull, clumped or sticky-looking snow, roller balls, dirty debris? Typical of wet snow avalanches
+   - Water_saturated: Snow soaked through and flowing like slush? Typical of slush flows
+
+2. Movement Pattern Analysis:
+   - Starting Width: Point source vs wide initial fracture
+   - Propagation: How the avalanche spreads
+   - Vertical Movement: Significant up/down motion
+   - Lateral Spread: Sideways expansion
+
+3. Terrain Analysis:
+   - Slope Angle: Critical for type determination
+   - Surface Roughness: Affects release pattern
+   - Anchoring Points: Trees/rocks that affect flow
+   - Convex Rollover: Terrain shape at release point
+   - Aspect: Direction the slope faces, inferred from shadows and the sun's position, sun crusts and melt, cornice orientation, and vegetation (denser forest on shady aspects); null if it cannot be judged
+   - Elevation Band: "below-treeline" in forest, "near-treeline" among scattered or stunted trees, "above-treeline" in open alpine terrain; null if no reference is visible
+   - Fracture Depth: Height of the crown in centimeters as a min-max range, scaled against anything of known size near the crown (a skier is about 170 cm tall, skis about 170 cm long, a mature conifer 10-20 m); confidence "high" with such a cue right at the crown, "medium" with one elsewhere on the slope, "low" without any; name the cue in scale_cue; null when there is no fracture line
+   - Glide Crack: Open crack where the whole snowpack has crept downhill
+   - Ground Exposed: Bare ground, grass or rock slabs showing in the bed surface
+   - Cornice Failure: A cornice has broken off the ridge above the path
+   - Wind Features: Wind pillows, sastrugi, scoured ridges or snow plumes blowing off the crest
+   - Recent Snowfall: A fresh layer of new snow over the terrain and older debris
+
+4. Hazard Rating (European Avalanche Warning Services scales):
+   - Danger_level: 1 low, 2 moderate, 3 considerable, 4 high, 5 very high, judged from the snowpack, recent activity and terrain visible
+   - Destructive_size: 1 (D1) relatively harmless to people, 2 (D2) could bury, injure or kill a person, 3 (D3) could bury and destroy a car or damage a truck, 4 (D4) could destroy a railway car or large truck, 5 (D5) largest known, could gouge the landscape; null when no avalanche is present
+   - Hazard_rationale: One or two sentences on what the level and size are based on
+
+5. Avalanche Problem (as forecasters name it; null when no avalanche is present or none fits):
+   - Wind-slab: Shallow slab in wind-loaded terrain, below ridges and cross-loaded gullies, with wind features nearby
+   - Storm-slab: Shallow slab of the recent snowfall releasing within the new snow, little wind effect
+   - Persistent-slab: Deep fracture on a buried weak layer, wide propagation, often in old, dense snow
+   - Wet-loose: Point releases in wet surface snow
+   - Glide: Full-depth release on smooth ground, glide cracks
+   - Cornice: Cornice fall, with or without a slab it triggered
+
+6. Locating Features (annotations):
+   - Coordinates are fractions of the image width and height, measured from the top-left corner
+   - Fracture Line: 2 to 12 points along the crown from one end to the other
+   - Start Zone: Box around the release area
+   - Debris: Polygon of 3 to 16 points around the deposit
+   - Use null for anything not visible, and null for all three when no avalanche is present
+
+AVALANCHE TYPE CHARACTERISTICS:
+
+LOOSE-SNOW Avalanche (dry snow):
+PRIMARY Indicators:
+- Starting_width: "point"
+- Propagation: "fan"
+- Snow_texture: granular=true, blocky=false
+- Debris_pattern: "fan-shaped"
+SECONDARY Indicators:
+- No distinct fracture line
+- Low to medium density
+- Often on steeper slopes
+- Minimal lateral spread
+
+SLAB Avalanche (dry snow):
+PRIMARY Indicators:
+- Fracture_line: true
+- Snow_texture: blocky=true
+- Starting_width: "wide"
+- Propagation: "linear"
+SECONDARY Indicators:
+- Medium to high density
+- Linear debris pattern
+- Moderate slope angles
+- Significant lateral spread
+
+POWDER Avalanche (dry snow):
+PRIMARY Indicators:
+- Powder_cloud: true
+- Snow_texture: fluffy=true
+- Vertical_movement: true
+SECONDARY Indicators:
+- Low density
+- Significant vertical displacement
+- Often on steep terrain
+- Chaotic propagation
+
+WET-LOOSE Avalanche:
+PRIMARY Indicators:
+- Snow_texture: wet=true, water_saturated=false
+- Starting_width: "point"
+- Propagation: "fan"
+- Debris_pattern: "fan-shaped"
+SECONDARY Indicators:
+- No distinct fracture line
+- High density
+- Often on steeper slopes
+
+WET-SLAB Avalanche:
+PRIMARY Indicators:
+- Snow_texture: wet=true, water_saturated=false
+- Fracture_line: true
+- Snow_texture: blocky=true
+- Starting_width: "wide"
+SECONDARY Indicators:
+- High density
+- Linear propagation
+- Linear debris pattern
+
+GLIDE Avalanche:
+PRIMARY Indicators:
+- Glide_crack: true
+- Ground_exposed: true
+- Starting_width: "wide"
+- Surface_roughness: "smooth"
+SECONDARY Indicators:
+- Wet snow
+- High density
+- Few anchoring points
+
+CORNICE Fall:
+PRIMARY Indicators:
+- Cornice_failure: true
+- Snow_texture: blocky=true
+- Vertical_movement: true
+SECONDARY Indicators:
+- High density
+- Point start below the ridge
+- Steep terrain
+
+SLUSH Flow:
+PRIMARY Indicators:
+- Snow_texture: water_saturated=true, wet=true
+- Slope_angle: "gentle (<30°)"
+SECONDARY Indicators:
+- High density
+- Significant lateral spread
+- Smooth bed surface
+
+Analyze ALL characteristics before classification. If mixed indicators present, weight PRIMARY indicators more heavily. A single PRIMARY indicator is not enough - require multiple matching characteristics for classification."#;
+
+/// The user's own analysis prompt, edited from the built-in one
+static CUSTOM_PROMPT: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
+
+/// Replaces the analysis prompt for every following analysis; None or a
+/// blank prompt restores ANALYSIS_PROMPT
+pub fn set_analysis_prompt(custom: Option<String>) {
+    let custom = custom.filter(|prompt| !prompt.trim().is_empty());
+    *CUSTOM_PROMPT.write().unwrap_or_else(std::sync::PoisonError::into_inner) = custom;
+}
+
+/// The prompt analyses are sent with
+pub fn analysis_prompt() -> String {
+    CUSTOM_PROMPT
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| ANALYSIS_PROMPT.to_string())
+}
+
+static SOFT_VALIDATION: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// In soft validation mode, type scores that are too close, too low or
+/// disagree with the model's type become warnings on the result instead of
+/// failing it; invalid values still fail
+pub fn set_soft_validation(soft: bool) {
+    SOFT_VALIDATION.store(soft, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Which prompt analyses are sent with: "built-in", or "custom" and the start
+/// of the edited prompt's hash so results from different edits can be told
+/// apart
+pub fn analysis_prompt_version() -> String {
+    prompt_version(CUSTOM_PROMPT.read().unwrap_or_else(std::sync::PoisonError::into_inner).as_deref())
+}
+
+fn prompt_version(custom: Option<&str>) -> String {
+    match custom {
+        Some(custom) => format!("custom {}", &hash_bytes(custom.as_bytes())[..8]),
+        None => "built-in".to_string(),
+    }
+}
+
+/// One exchange after the first: the model's reply and what was said back
+pub struct Turn {
+    pub reply: String,
+    pub followup: String,
+}
+
+static CONFLICT_ROUNDS: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(DEFAULT_CONFLICT_ROUNDS);
+
+pub const DEFAULT_CONFLICT_ROUNDS: u32 = 2;
+
+/// How many times a reply whose type contradicts its own characteristics is
+/// sent back to the model to re-examine before the conflict is reported; 0
+/// reports it straight away
+pub fn set_conflict_rounds(rounds: u32) {
+    CONFLICT_ROUNDS.store(rounds, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Asks the model to look again at the indicators of the type it claimed and
+/// the type its characteristics point to
+fn conflict_followup(scores: &crate::scoring::TypeScores, claimed: &str, expected: &str) -> String {
+    let indicators = |avalanche_type: &str| crate::scoring::primary_indicators(avalanche_type).join(", ");
+    let scored: Vec<String> =
+        scores.iter().filter(|&(_, score)| score > 0).map(|(t, score)| format!("{t} {score}")).collect();
+    format!(
+        "Your answer is inconsistent: you classified this as \"{claimed}\", but the characteristics you reported \
+         score highest for \"{expected}\" ({}). Look at the image again and check the indicators of both types: \
+         {claimed} ({}) and {expected} ({}). Correct whichever is wrong, the type or the characteristics, and reply \
+         with the complete JSON object again.",
+        scored.join(", "),
+        indicators(claimed),
+        indicators(expected),
+    )
+}
+
+/// A vision model backend. Implement `prepare` and `converse` to plug in
+/// another service; OpenAiProvider, AnthropicProvider and OllamaProvider
+/// cover the built-in ones.
+#[async_trait::async_trait]
+pub trait VisionProvider: Send + Sync {
+    /// Short backend id recorded with each snapshot, e.g. "openai"
+    fn backend(&self) -> &'static str;
+
+    fn model(&self) -> &str;
+
+    /// Resizes and re-encodes a photo the way this backend takes it
+    async fn prepare(&self, image_bytes: &[u8]) -> Vec<u8>;
+
+    /// Sends a prepared image and prompt followed by earlier turns of the
+    /// same conversation, returning the raw response body
+    async fn converse(&self, prepared: &[u8], prompt: &str, turns: &[Turn]) -> Result<String, AnalysisError>;
+
+    /// Sends a prepared image and prompt, returning the raw response body
+    async fn send(&self, prepared: &[u8], prompt: &str) -> Result<String, AnalysisError> {
+        self.converse(prepared, prompt, &[]).await
+    }
+
+    /// Sends one image and prompt, returning the raw response body
+    async fn request(&self, image_bytes: &[u8], prompt: &str) -> Result<String, AnalysisError> {
+        self.send(&self.prepare(image_bytes).await, prompt).await
+    }
+
+    async fn classify(&self, image_bytes: &[u8], prompt: &str) -> Result<AvalancheAnalysis, AnalysisError> {
+        let response_text = self.request(image_bytes, prompt).await?;
+        parse_response(self.backend(), &response_text)
+    }
+}
+
+/// Like VisionProvider::request, timing the image preparation (including
+/// any local super-resolution) and the network round trip separately, and
+/// returning the image as sent for the snapshot. The caller fills in
+/// parse_ms.
+pub async fn timed_request(
+    provider: &dyn VisionProvider,
+    image_bytes: &[u8],
+    prompt: &str,
+) -> Result<(String, Timings, PreparedImage), AnalysisError> {
+    let prepared = prepare_image(provider, image_bytes).await;
+    let started = std::time::Instant::now();
+    let response_text = provider.send(&prepared.bytes, prompt).await?;
+    let timings = Timings { preprocess_ms: prepared.elapsed_ms, network_ms: elapsed_ms(started), parse_ms: 0 };
+    Ok((response_text, timings, prepared))
+}
+
+/// A photo as a backend will be sent it
+pub struct PreparedImage {
+    pub bytes: Vec<u8>,
+    /// What was done to it, e.g. "resized 4032×3024 → 2048×1536, re-encoded
+    /// as JPEG"
+    pub description: String,
+    pub elapsed_ms: u64,
+}
+
+/// Runs the preprocessing an analysis does before upload: local
+/// super-resolution when configured, then the backend's own resizing and
+/// re-encoding
+pub async fn prepare_image(provider: &dyn VisionProvider, image_bytes: &[u8]) -> PreparedImage {
+    let started = std::time::Instant::now();
+    let upscaled = crate::superres::apply(image_bytes);
+    let source = upscaled.as_ref().map_or(image_bytes, |(bytes, _)| bytes.as_slice());
+    let bytes = provider.prepare(source).await;
+    let elapsed_ms = elapsed_ms(started);
+    let mut description = crate::models::describe_preparation(source, &bytes);
+    if let Some((_, upscaling)) = upscaled {
+        description = format!("{}, {}", upscaling, description);
+    }
+    PreparedImage { bytes, description, elapsed_ms }
+}
+
+/// How an image is classified. The defaults are the built-in prompt, no
+/// site context, field observations or weather, strict validation,
+/// DEFAULT_CONFLICT_ROUNDS follow-ups on a type conflict and no snapshot.
+#[derive(Debug, Clone)]
+pub struct ClassifyOptions {
+    prompt: Option<String>,
+    context: String,
+    observations: crate::observation::FieldObservations,
+    weather: Option<crate::weather::WeatherSummary>,
+    soft_validation: bool,
+    conflict_rounds: u32,
+    record_snapshot: bool,
+}
+
+impl Default for ClassifyOptions {
+    fn default() -> Self {
+        Self {
+            prompt: None,
+            context: String::new(),
+            observations: Default::default(),
+            weather: None,
+            soft_validation: false,
+            conflict_rounds: DEFAULT_CONFLICT_ROUNDS,
+            record_snapshot: false,
+        }
+    }
+}
+
+impl ClassifyOptions {
+    /// The process-wide settings (set_analysis_prompt, set_soft_validation,
+    /// set_conflict_rounds), recording a snapshot, as the app classifies
+    pub fn current() -> Self {
+        Self {
+            prompt: CUSTOM_PROMPT.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone(),
+            context: String::new(),
+            observations: Default::default(),
+            weather: None,
+            soft_validation: SOFT_VALIDATION.load(std::sync::atomic::Ordering::Relaxed),
+            conflict_rounds: CONFLICT_ROUNDS.load(std::sync::atomic::Ordering::Relaxed),
+            record_snapshot: true,
+        }
+    }
+
+    /// Replaces ANALYSIS_PROMPT. The reply must still follow its JSON schema.
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = Some(prompt.into()).filter(|prompt| !prompt.trim().is_empty());
+        self
+    }
+
+    /// A description of the site (e.g. a camera's start zone) appended to
+    /// the prompt
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = context.into();
+        self
+    }
+
+    /// What an observer on site reported (new snow, wind, temperature trend,
+    /// avalanche activity, stability tests), appended to the prompt for the
+    /// model to weigh when the photo is ambiguous
+    pub fn with_observations(mut self, observations: crate::observation::FieldObservations) -> Self {
+        self.observations = observations;
+        self
+    }
+
+    /// The weather before the photo was taken, appended to the prompt and
+    /// kept with the result as AvalancheAnalysis::weather
+    pub fn with_weather(mut self, weather: Option<crate::weather::WeatherSummary>) -> Self {
+        self.weather = weather;
+        self
+    }
+
+    /// Returns ambiguous, weak or conflicting classifications with
+    /// validation_warnings instead of failing them
+    pub fn with_soft_validation(mut self, soft: bool) -> Self {
+        self.soft_validation = soft;
+        self
+    }
+
+    /// Times a type conflict is put back to the model before it fails
+    pub fn with_conflict_rounds(mut self, rounds: u32) -> Self {
+        self.conflict_rounds = rounds;
+        self
+    }
+
+    /// Keeps the image, prompt and raw response in the data directory's blob
+    /// store, as Classification::snapshot
+    pub fn with_snapshot(mut self, record: bool) -> Self {
+        self.record_snapshot = record;
+        self
+    }
+
+    fn prompt(&self) -> String {
+        let mut prompt = self.prompt.clone().unwrap_or_else(|| ANALYSIS_PROMPT.to_string());
+        if !self.context.trim().is_empty() {
+            prompt = format!(
+                "{}\n\nSITE CONTEXT (background only, never overrides what is visible):\n{}",
+                prompt,
+                self.context.trim()
+            );
+        }
+        if let Some(weather) = &self.weather {
+            prompt = format!("{}\n\n{}", prompt, weather.prompt_section());
+        }
+        if let Some(section) = self.observations.prompt_section() {
+            prompt = format!("{}\n\n{}", prompt, section);
+        }
+        prompt
+    }
+}
+
+/// A classified image
+pub struct Classification {
+    pub analysis: AvalancheAnalysis,
+    /// The recorded exchange, when the options asked for one and the blob
+    /// store could be written
+    pub snapshot: Option<AnalysisSnapshot>,
+    /// Backends of a fallback chain that failed before this one answered
+    pub fallbacks: Vec<crate::fallback::FailedAttempt>,
+}
+
+/// Classifies with the process-wide settings; see ClassifyOptions::current
+pub async fn classify_image(
+    provider: &dyn VisionProvider,
+    image_bytes: &[u8],
+) -> Result<Classification, AnalysisError> {
+    classify(provider, image_bytes, &ClassifyOptions::current()).await
+}
+
+/// Like classify_image, with a description of the site (e.g. a camera's start
+/// zone) appended to the prompt
+pub async fn classify_image_with_context(
+    provider: &dyn VisionProvider,
+    image_bytes: &[u8],
+    context: &str,
+) -> Result<Classification, AnalysisError> {
+    classify(provider, image_bytes, &ClassifyOptions::current().with_context(context)).await
+}
+
+/// Prepares the image, asks the model, and parses and validates its reply
+pub async fn classify(
+    provider: &dyn VisionProvider,
+    image_bytes: &[u8],
+    options: &ClassifyOptions,
+) -> Result<Classification, AnalysisError> {
+    let prompt = options.prompt();
+    let prepared = prepare_image(provider, image_bytes).await;
+    let started = std::time::Instant::now();
+    let mut response_text = provider.send(&prepared.bytes, &prompt).await?;
+    let mut timings = Timings { preprocess_ms: prepared.elapsed_ms, network_ms: elapsed_ms(started), parse_ms: 0 };
+    let started = std::time::Instant::now();
+    let parse = |response_text: &str, soft: bool| {
+        parse_analysis_with(&extract_content(provider.backend(), response_text)?, soft)
+    };
+    // Validated strictly first so a conflict is put back to the model even
+    // when soft validation would only have warned about it
+    let mut parsed = parse(&response_text, false);
+    timings.parse_ms = elapsed_ms(started);
+
+    // A type that contradicts the reply's own characteristics is put back to
+    // the model rather than wasting the request. Chat APIs keep no state,
+    // so every round resends the image with the conversation so far. A
+    // failed round ends the exchange with the reply already in hand.
+    let mut turns = Vec::new();
+    let mut earlier = Vec::new();
+    let mut conflicts = Vec::new();
+    while let Err(err @ AnalysisError::ValidationConflict { scores, claimed, expected }) = &parsed {
+        if turns.len() >= options.conflict_rounds as usize {
+            break;
+        }
+        let conflict = err.to_string();
+        turns.push(Turn {
+            reply: extract_content(provider.backend(), &response_text)?,
+            followup: conflict_followup(scores, claimed, expected),
+        });
+        let started = std::time::Instant::now();
+        let reply = provider.converse(&prepared.bytes, &prompt, &turns).await;
+        timings.network_ms += elapsed_ms(started);
+        let Ok(reply) = reply else {
+            turns.pop();
+            break;
+        };
+        conflicts.push(conflict);
+        earlier.push(EarlierResponse {
+            raw_response: std::mem::replace(&mut response_text, reply),
+            followup: turns[turns.len() - 1].followup.clone(),
+        });
+        parsed = parse(&response_text, false);
+    }
+    if options.soft_validation && parsed.is_err() {
+        parsed = parse(&response_text, true);
+    }
+
+    // Snapshot the raw exchange whether or not it validated so rejected
+    // responses are kept too
+    let snapshot = if options.record_snapshot {
+        let provenance = Provenance {
+            prompt: prompt_version(options.prompt.as_deref()),
+            site_context: !options.context.trim().is_empty(),
+            field_observations: !options.observations.is_empty(),
+            weather: options.weather.is_some(),
+            preprocessing: prepared.description,
+            conflicts,
+            earlier_responses: Vec::new(),
+        };
+        BlobStore::open_default()
+            .and_then(|store| {
+                let images = SnapshotImages { sent: &prepared.bytes, original: image_bytes };
+                let responses = SnapshotResponses { last: &response_text, earlier: &earlier };
+                AnalysisSnapshot::record(&store, provider, images, &prompt, responses, timings, provenance)
+            })
+            .ok()
+    } else {
+        None
+    };
+
+    let mut analysis = parsed?;
+    analysis.weather = options.weather.clone();
+    Ok(Classification { analysis, snapshot, fallbacks: Vec::new() })
+}
+
+/// An aspect as bulletins write it, e.g. "NE" for "north-east" or "Northeast"
+fn normalize_aspect(aspect: &str) -> String {
+    let compact: String = aspect.chars().filter(|c| c.is_alphabetic()).collect::<String>().to_uppercase();
+    let abbreviated = compact
+        .replace("NORTH", "N")
+        .replace("SOUTH", "S")
+        .replace("EAST", "E")
+        .replace("WEST", "W");
+    match abbreviated.as_str() {
+        "" => aspect.to_string(),
+        _ => abbreviated,
+    }
+}
+
+/// Pulls the model's text reply out of a raw response body from the given backend
+pub fn extract_content(backend: &str, response_text: &str) -> Result<String, AnalysisError> {
+    match backend {
+        "openai" => openai::extract_content(response_text),
+        "anthropic" => anthropic::extract_content(response_text),
+        "ollama" => ollama::extract_content(response_text),
+        other => Err(AnalysisError::UnknownBackend(other.to_string())),
+    }
+}
+
+/// Like extract_content, without the analysis-specific repairs some backends
+/// apply, for prompts with a different reply schema
+pub fn extract_reply(backend: &str, response_text: &str) -> Result<String, AnalysisError> {
+    match backend {
+        "ollama" => ollama::extract_text(response_text),
+        other => extract_content(other, response_text),
+    }
+}
+
+pub fn parse_response(backend: &str, response_text: &str) -> Result<AvalancheAnalysis, AnalysisError> {
+    parse_analysis(&extract_content(backend, response_text)?)
+}
+
+/// Parses and validates the model's JSON reply
+pub fn parse_analysis(content: &str) -> Result<AvalancheAnalysis, AnalysisError> {
+    parse_analysis_with(content, SOFT_VALIDATION.load(std::sync::atomic::Ordering::Relaxed))
+}
+
+/// Like parse_analysis, choosing soft or strict validation rather than
+/// using the process-wide setting
+pub fn parse_analysis_with(content: &str, soft: bool) -> Result<AvalancheAnalysis, AnalysisError> {
+    let mut analysis: AvalancheAnalysis =
+        serde_json::from_str(content).map_err(|e| crate::error::parse_failure(content, e))?;
+    analysis.annotations = analysis.annotations.take().and_then(Annotations::sanitized);
+    // Worked out below or by the caller, whatever the reply says
+    analysis.type_scores = None;
+    analysis.weather = None;
+    analysis.validation_warnings.clear();
+
+    // Validate and score the avalanche type based on detailed characteristics
+    if analysis.avalanche_present {
+        let thresholds = crate::scoring::config().thresholds;
+        let scores = crate::scoring::type_scores(&analysis);
+
+        // Determine highest scoring type
+        let detected_type = analysis.avalanche_type.as_str();
+        let (expected_type, highest_score) = scores.best();
+
+        // Require a minimum score difference for classification; a tie for
+        // the top counts as no difference
+        let (_, second_highest_score) = scores.top_two();
+
+        // If scores are too close or score is too low, classification is unreliable
+        let failed = if (highest_score - second_highest_score) < thresholds.min_margin {
+            Some(AnalysisError::Ambiguous { scores })
+        } else if highest_score < thresholds.min_score {
+            Some(AnalysisError::InsufficientEvidence { scores })
+        } else if detected_type != expected_type {
+            // Verify classification matches highest scoring type
+            Some(AnalysisError::ValidationConflict {
+                scores,
+                claimed: detected_type.to_string(),
+                expected: expected_type.to_string(),
+            })
+        } else {
+            None
+        };
+        match failed {
+            Some(err) if soft => {
+                analysis.validation_warnings.push(err.to_string());
+            }
+            Some(err) => return Err(err),
+            None => {}
+        }
+        analysis.type_scores = Some(scores);
+    }
+
+    if !crate::model::AVALANCHE_TYPES.contains(&analysis.avalanche_type.as_str()) {
+        return Err(AnalysisError::InvalidValue {
+            field: "avalanche type",
+            value: analysis.avalanche_type.clone(),
+        });
+    }
+
+    if analysis.confidence_level < 0.0 || analysis.confidence_level > 100.0 {
+        return Err(AnalysisError::InvalidValue {
+            field: "confidence level",
+            value: analysis.confidence_level.to_string(),
+        });
+    }
+
+    if let Some(level) = analysis.danger_level.filter(|level| !(1..=5).contains(level)) {
+        return Err(AnalysisError::InvalidValue { field: "danger level", value: level.to_string() });
+    }
+
+    if let Some(size) = analysis.destructive_size.filter(|size| !(1..=5).contains(size)) {
+        return Err(AnalysisError::InvalidValue { field: "destructive size", value: size.to_string() });
+    }
+
+    if let Some(depth) = &mut analysis.visual_characteristics.fracture_depth {
+        if depth.min_cm > depth.max_cm {
+            std::mem::swap(&mut depth.min_cm, &mut depth.max_cm);
+        }
+        if depth.max_cm == 0 || depth.max_cm > crate::model::MAX_FRACTURE_DEPTH_CM {
+            return Err(AnalysisError::InvalidValue { field: "fracture depth", value: depth.label() });
+        }
+        depth.confidence = depth.confidence.trim().to_lowercase();
+        if !crate::model::ESTIMATE_CONFIDENCES.contains(&depth.confidence.as_str()) {
+            return Err(AnalysisError::InvalidValue {
+                field: "fracture depth confidence",
+                value: depth.confidence.clone(),
+            });
+        }
+    }
+
+    let terrain = &mut analysis.visual_characteristics.terrain;
+    terrain.aspect = terrain.aspect.take().map(|aspect| normalize_aspect(&aspect));
+    if let Some(aspect) = terrain.aspect.as_ref().filter(|a| !crate::model::ASPECTS.contains(&a.as_str())) {
+        return Err(AnalysisError::InvalidValue { field: "aspect", value: aspect.clone() });
+    }
+    terrain.elevation_band = terrain.elevation_band.take().map(|band| band.trim().to_lowercase().replace([' ', '_'], "-"));
+    if let Some(band) = terrain.elevation_band.as_ref().filter(|b| !crate::model::ELEVATION_BANDS.contains(&b.as_str())) {
+        return Err(AnalysisError::InvalidValue { field: "elevation band", value: band.clone() });
+    }
+
+    if let Some(problem) = &analysis.avalanche_problem {
+        if !crate::model::AVALANCHE_PROBLEMS.contains(&problem.as_str()) {
+            return Err(AnalysisError::InvalidValue { field: "avalanche problem", value: problem.clone() });
+        }
+    }
+
+    // The problem is a forecaster's judgment layered on the type, so
+    // characteristics pointing to another one only warn, in either mode
+    if let (Some(problem), Some(scores)) = (&analysis.avalanche_problem, &analysis.type_scores) {
+        let thresholds = crate::scoring::config().thresholds;
+        let problems = crate::scoring::problem_scores(&analysis, scores);
+        let own = problems.iter().find(|(p, _)| p == problem).map_or(0, |&(_, score)| score);
+        let (best, highest) = problems.iter().fold(problems[0], |best, &next| if next.1 > best.1 { next } else { best });
+        if best != problem && highest >= thresholds.min_score && highest - own >= thresholds.min_margin {
+            analysis.validation_warnings.push(format!(
+                "Visual characteristics point to a {} problem (score: {}) rather than {} (score: {})",
+                best, highest, problem, own
+            ));
+        }
+    }
+
+    Ok(analysis)
+}
@@ -0,0 +1,193 @@
+use super::{Turn, VisionProvider};
+use crate::error::{parse_failure, read_body, AnalysisError};
+
+pub struct OllamaProvider {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    upload: crate::models::UploadEncoding,
+}
+
+impl OllamaProvider {
+    pub const DEFAULT_URL: &'static str = "http://localhost:11434";
+    pub const DEFAULT_MODEL: &'static str = "llava";
+
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            client: super::http_client(),
+            base_url: base_url.into(),
+            model: model.into(),
+            upload: Default::default(),
+        }
+    }
+
+    pub fn with_upload(mut self, upload: crate::models::UploadEncoding) -> Self {
+        self.upload = upload;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl VisionProvider for OllamaProvider {
+    fn backend(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn prepare(&self, image_bytes: &[u8]) -> Vec<u8> {
+        // Vision models under llama.cpp decode JPEG and PNG only
+        let upload = crate::models::UploadEncoding { format: crate::models::UploadFormat::Jpeg, ..self.upload };
+        upload.prepare(image_bytes, None).await
+    }
+
+    async fn converse(&self, image_bytes: &[u8], prompt: &str, turns: &[Turn]) -> Result<String, AnalysisError> {
+        use base64::Engine;
+        let image_base64 = base64::engine::general_purpose::STANDARD.encode(image_bytes);
+
+        let mut messages = vec![serde_json::json!({
+            "role": "user",
+            "content": prompt,
+            "images": [image_base64]
+        })];
+        for turn in turns {
+            messages.push(serde_json::json!({"role": "assistant", "content": turn.reply}));
+            messages.push(serde_json::json!({"role": "user", "content": turn.followup}));
+        }
+
+        let response = self.client
+        .post(format!("{}/api/chat", self.base_url.trim_end_matches('/')))
+        .json(&serde_json::json!({
+            "model": self.model,
+            "format": "json",
+            "stream": false,
+            "messages": messages
+        }))
+        .send()
+        .await
+        .map_err(|e| match AnalysisError::from(e) {
+            AnalysisError::Unreachable { detail } => AnalysisError::Unreachable {
+                detail: format!("Ollama at {}: {}", self.base_url, detail),
+            },
+            other => other,
+        })?;
+
+        read_body("ollama", response).await
+    }
+}
+
+// Fallback values for anything a small local model leaves out. Missing
+// confidence becomes 0 so the result lands in the review queue.
+const SKELETON: &str = r#"{
+    "avalanche_present": false,
+    "avalanche_type": "none",
+    "confidence_level": 0.0,
+    "terrain_features": [],
+    "danger_level": null,
+    "destructive_size": null,
+    "hazard_rationale": null,
+    "avalanche_problem": null,
+    "visual_characteristics": {
+        "powder_cloud": false,
+        "fracture_line": false,
+        "fracture_depth": null,
+        "point_release": false,
+        "glide_crack": false,
+        "ground_exposed": false,
+        "cornice_failure": false,
+        "wind_features": false,
+        "recent_snowfall": false,
+        "debris_pattern": "none",
+        "snow_texture": {"granular": false, "blocky": false, "fluffy": false, "density": "medium", "wet": false, "water_saturated": false},
+        "movement_pattern": {"starting_width": "undefined", "propagation": "none", "vertical_movement": false, "lateral_spread": false},
+        "terrain": {"slope_angle": null, "surface_roughness": "variable", "anchoring_points": false, "convex_rollover": false, "aspect": null, "elevation_band": null}
+    }
+}"#;
+
+// The model's reply with any prose around the JSON object trimmed off
+pub fn extract_text(response_text: &str) -> Result<String, AnalysisError> {
+    let json: serde_json::Value =
+        serde_json::from_str(response_text).map_err(|e| parse_failure(response_text, e))?;
+    let text = json["message"]["content"]
+        .as_str()
+        .ok_or(AnalysisError::EmptyResponse)?;
+
+    Ok(match (text.find('{'), text.rfind('}')) {
+        (Some(start), Some(end)) if start < end => text[start..=end].to_string(),
+        _ => text.to_string(),
+    })
+}
+
+// Local vision models follow the schema loosely, so the reply is merged onto
+// a complete skeleton and common spelling variants are normalized before the
+// usual strict parsing and validation run
+pub fn extract_content(response_text: &str) -> Result<String, AnalysisError> {
+    let object = extract_text(response_text)?;
+    let reply: serde_json::Value = serde_json::from_str(&object).map_err(|e| parse_failure(&object, e))?;
+
+    let mut merged: serde_json::Value = serde_json::from_str(SKELETON).expect("skeleton is valid JSON");
+    merge(&mut merged, reply);
+
+    if let Some(avalanche_type) = merged["avalanche_type"].as_str() {
+        let normalized = avalanche_type.trim().to_lowercase().replace([' ', '_'], "-");
+        // The display names of the wet-snow types stand in for their ids
+        let normalized = match normalized.as_str() {
+            "cornice-fall" => "cornice".to_string(),
+            "slush-flow" => "slush".to_string(),
+            "glide-avalanche" => "glide".to_string(),
+            _ => normalized,
+        };
+        merged["avalanche_type"] = serde_json::Value::String(normalized);
+    }
+    if let Some(problem) = merged["avalanche_problem"].as_str() {
+        let normalized = problem.trim().to_lowercase().replace([' ', '_'], "-");
+        merged["avalanche_problem"] = match normalized.as_str() {
+            "" | "none" | "null" => serde_json::Value::Null,
+            _ => serde_json::Value::String(normalized),
+        };
+    }
+    // Sizes come back as "D3" or "3" as often as 3
+    for field in ["danger_level", "destructive_size"] {
+        if let Some(text) = merged[field].as_str() {
+            let level = text.trim().trim_start_matches(['D', 'd']).parse::<u8>().ok();
+            merged[field] = serde_json::json!(level);
+        }
+    }
+    // Some models answer 0-1 instead of 0-100
+    if let Some(confidence) = merged["confidence_level"].as_f64() {
+        if confidence > 0.0 && confidence <= 1.0 {
+            merged["confidence_level"] = serde_json::json!(confidence * 100.0);
+        }
+    }
+
+    Ok(merged.to_string())
+}
+
+// Overlays `value` onto `base`, keeping base entries the reply omits or
+// returns with the wrong JSON type
+fn merge(base: &mut serde_json::Value, value: serde_json::Value) {
+    use serde_json::Value;
+    match (base, value) {
+        (Value::Object(base), Value::Object(value)) => {
+            for (key, child) in value {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, child),
+                    None => {
+                        base.insert(key, child);
+                    }
+                }
+            }
+        }
+        (base @ Value::Null, value) => *base = value,
+        (base, value) if same_kind(base, &value) => *base = value,
+        // A null for a nullable field is a valid answer
+        (base, Value::Null) => *base = Value::Null,
+        _ => {}
+    }
+}
+
+fn same_kind(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
@@ -0,0 +1,165 @@
+use super::{Turn, VisionProvider};
+use crate::error::{parse_failure, read_body, AnalysisError};
+
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+    upload: crate::models::UploadEncoding,
+}
+
+impl OpenAiProvider {
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.openai.com/v1";
+    pub const DEFAULT_MODEL: &'static str = "gpt-4o-mini";
+
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: super::http_client(),
+            api_key: api_key.into(),
+            base_url: Self::DEFAULT_BASE_URL.to_string(),
+            model: Self::DEFAULT_MODEL.to_string(),
+            upload: Default::default(),
+        }
+    }
+
+    // Any server speaking the chat-completions API works here, e.g. vLLM,
+    // a LiteLLM proxy or OpenRouter
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn with_upload(mut self, upload: crate::models::UploadEncoding) -> Self {
+        self.upload = upload;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl VisionProvider for OpenAiProvider {
+    fn backend(&self) -> &'static str {
+        "openai"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn prepare(&self, image_bytes: &[u8]) -> Vec<u8> {
+        let info = crate::models::lookup(&self.model);
+        self.upload.prepare(image_bytes, info.map(|m| m.max_image_px)).await
+    }
+
+    async fn converse(&self, image_bytes: &[u8], prompt: &str, turns: &[Turn]) -> Result<String, AnalysisError> {
+        use base64::Engine;
+
+        // Unknown models (e.g. on a custom endpoint) get conservative defaults
+        let info = crate::models::lookup(&self.model);
+        let image_base64 = base64::engine::general_purpose::STANDARD.encode(image_bytes);
+
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": [{
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": prompt},
+                    {"type": "image_url", "image_url": {
+                        "url": format!("data:{};base64,{}", crate::models::media_type(image_bytes), image_base64),
+                        "detail": info.map_or("high", |m| m.detail)
+                    }}
+                ]
+            }]
+        });
+        for turn in turns {
+            body["messages"].as_array_mut().expect("messages is an array").extend([
+                serde_json::json!({"role": "assistant", "content": turn.reply}),
+                serde_json::json!({"role": "user", "content": turn.followup}),
+            ]);
+        }
+        // Structured outputs force the reply into exactly our schema; models
+        // without it (e.g. on a custom endpoint) still get plain JSON mode
+        if info.is_some_and(|m| m.structured_outputs) {
+            body["response_format"] = serde_json::json!({
+                "type": "json_schema",
+                "json_schema": { "name": "avalanche_analysis", "strict": true, "schema": analysis_schema() }
+            });
+        } else if info.is_none_or(|m| m.json_mode) {
+            body["response_format"] = serde_json::json!({ "type": "json_object" });
+        }
+        if info.is_some_and(|m| m.reasoning) {
+            // Leave room for reasoning tokens on top of the ~600 token answer
+            body["max_completion_tokens"] = serde_json::json!(4000);
+        } else {
+            body["max_tokens"] = serde_json::json!(600);
+        }
+
+        let response = self.client
+        .post(format!("{}/chat/completions", self.base_url.trim_end_matches('/')))
+        .header("Authorization", format!("Bearer {}", self.api_key))
+        .json(&body)
+        .send()
+        .await?;
+
+        read_body("openai", response).await
+    }
+}
+
+// JSON schema of AvalancheAnalysis in the form strict mode accepts: every
+// property listed as required (optional ones are nullable instead), no
+// additional properties, no references and none of the keywords it rejects
+pub fn analysis_schema() -> serde_json::Value {
+    static SCHEMA: std::sync::OnceLock<serde_json::Value> = std::sync::OnceLock::new();
+    SCHEMA
+        .get_or_init(|| {
+            let settings = schemars::gen::SchemaSettings::draft07().with(|s| {
+                s.inline_subschemas = true;
+                s.option_add_null_type = true;
+            });
+            let schema = settings.into_generator().into_root_schema_for::<crate::model::AvalancheAnalysis>();
+            let mut schema = serde_json::to_value(schema).unwrap_or_default();
+            make_strict(&mut schema);
+            schema
+        })
+        .clone()
+}
+
+fn make_strict(schema: &mut serde_json::Value) {
+    let Some(object) = schema.as_object_mut() else {
+        if let Some(items) = schema.as_array_mut() {
+            items.iter_mut().for_each(make_strict);
+        }
+        return;
+    };
+    for keyword in ["$schema", "title", "format", "default", "definitions", "minimum", "maximum"] {
+        object.remove(keyword);
+    }
+    if let Some(properties) = object.get("properties").and_then(|p| p.as_object()) {
+        let required: Vec<serde_json::Value> = properties.keys().cloned().map(serde_json::Value::String).collect();
+        object.insert("required".to_string(), serde_json::Value::Array(required));
+        object.insert("additionalProperties".to_string(), serde_json::Value::Bool(false));
+    }
+    for (keyword, value) in object.iter_mut() {
+        match (keyword.as_str(), value.as_object_mut()) {
+            // Field names, each mapping to a schema
+            ("properties", Some(fields)) => fields.values_mut().for_each(make_strict),
+            _ => make_strict(value),
+        }
+    }
+}
+
+// Chat-completions responses carry the reply in choices[0].message.content
+pub fn extract_content(response_text: &str) -> Result<String, AnalysisError> {
+    let json: serde_json::Value =
+        serde_json::from_str(response_text).map_err(|e| parse_failure(response_text, e))?;
+
+    json["choices"][0]["message"]["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or(AnalysisError::EmptyResponse)
+}
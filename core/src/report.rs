@@ -0,0 +1,341 @@
+use crate::camera::Location;
+use crate::exposure::ExposedAsset;
+use crate::history::HistoryEntry;
+use crate::model::AvalancheAnalysis;
+use crate::photo_meta::CameraInfo;
+use crate::snapshot::AnalysisSnapshot;
+use serde::Serialize;
+
+// Built-in template used when no custom template has been loaded
+pub const DEFAULT_TEMPLATE: &str = r#"AVALANCHE RISK REPORT
+=====================
+
+Avalanche present: {% if avalanche_present %}yes{% else %}no{% endif %}
+Type: {{ avalanche_type }}
+Confidence: {{ confidence_level | round(precision=0) }}%
+
+Snow Analysis
+-------------
+Granular: {{ visual_characteristics.snow_texture.granular }}
+Blocky: {{ visual_characteristics.snow_texture.blocky }}
+Fluffy: {{ visual_characteristics.snow_texture.fluffy }}
+Density: {{ visual_characteristics.snow_texture.density }}
+
+Movement Pattern
+----------------
+Initial Release: {{ visual_characteristics.movement_pattern.starting_width }}
+Propagation: {{ visual_characteristics.movement_pattern.propagation }}
+Vertical Movement: {{ visual_characteristics.movement_pattern.vertical_movement }}
+Lateral Spread: {{ visual_characteristics.movement_pattern.lateral_spread }}
+
+Terrain Analysis
+----------------
+Slope: {{ visual_characteristics.terrain.slope_angle | default(value="unknown") }}
+Surface: {{ visual_characteristics.terrain.surface_roughness }}
+Anchoring Points: {{ visual_characteristics.terrain.anchoring_points }}
+Convex Rollover: {{ visual_characteristics.terrain.convex_rollover }}
+{% if terrain_features %}
+Additional Observations
+-----------------------
+{% for feature in terrain_features %}- {{ feature }}
+{% endfor %}{% endif %}{% if exposed_assets %}
+EXPOSED ASSETS
+--------------
+{% for asset in exposed_assets %}- {{ asset.name }} ({{ asset.kind }})
+{% endfor %}{% endif %}"#;
+
+// Renders an analysis through a Tera template. Every field of the analysis
+// is available to the template under its JSON name, and the fracture depth
+// estimate also as text under `fracture_depth`.
+pub fn render_report(template: &str, analysis: &AvalancheAnalysis) -> anyhow::Result<String> {
+    render_report_with_exposure(template, analysis, &[])
+}
+
+// As render_report, with the assets the activity reached available as
+// `exposed_assets` (each with `name` and `kind`)
+pub fn render_report_with_exposure(
+    template: &str,
+    analysis: &AvalancheAnalysis,
+    exposure: &[ExposedAsset],
+) -> anyhow::Result<String> {
+    let context = report_context(analysis, exposure)?;
+    tera::Tera::one_off(template, &context, false)
+        .map_err(|e| anyhow::anyhow!("Report template error: {}", e))
+}
+
+fn report_context(analysis: &AvalancheAnalysis, exposure: &[ExposedAsset]) -> anyhow::Result<tera::Context> {
+    let mut context = tera::Context::from_serialize(analysis)?;
+    let exposed: Vec<serde_json::Value> = exposure
+        .iter()
+        .map(|a| serde_json::json!({ "name": a.name, "kind": a.kind.label() }))
+        .collect();
+    context.insert("exposed_assets", &exposed);
+    // The estimate as one line of text, beside the structured field
+    if let Some(depth) = &analysis.visual_characteristics.fracture_depth {
+        context.insert("fracture_depth", &depth.detailed_label());
+    }
+    Ok(context)
+}
+
+// Self-contained report formats for pasting into a wiki: the photo is
+// inlined as a data URI and the characteristics are laid out as tables
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentFormat {
+    Html,
+    Markdown,
+}
+
+impl DocumentFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocumentFormat::Html => "html",
+            DocumentFormat::Markdown => "md",
+        }
+    }
+
+    fn template(&self) -> &'static str {
+        match self {
+            DocumentFormat::Html => HTML_TEMPLATE,
+            DocumentFormat::Markdown => MARKDOWN_TEMPLATE,
+        }
+    }
+}
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Avalanche Analysis Report</title>
+<style>
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; max-width: 820px; margin: 2em auto; color: #1c1c1e; }
+img { max-width: 100%; border-radius: 6px; }
+table { border-collapse: collapse; margin: 0.5em 0 1.5em; min-width: 50%; }
+th, td { text-align: left; padding: 4px 12px 4px 0; border-bottom: 1px solid #e5e5ea; }
+th { color: #8e8e93; font-weight: normal; }
+.meta { color: #8e8e93; font-size: 0.9em; }
+.warning { color: #ff3b30; font-weight: bold; }
+</style>
+</head>
+<body>
+<h1>Avalanche Analysis Report</h1>
+<p class="meta">{% for item in meta %}{{ item }}{% if not loop.last %} · {% endif %}{% endfor %}</p>
+{% if image %}<img src="{{ image | safe }}" alt="Analyzed photo">{% endif %}
+<h2>Classification</h2>
+<table>
+<tr><th>Avalanche present</th><td>{% if avalanche_present %}yes{% else %}no{% endif %}</td></tr>
+<tr><th>Type</th><td>{{ label }}</td></tr>{% if label != avalanche_type %}
+<tr><th>Model output</th><td>{{ avalanche_type }}</td></tr>{% endif %}
+<tr><th>Confidence</th><td>{{ confidence_level | round(precision=0) }}%</td></tr>
+</table>
+{% if exposed_assets %}<p class="warning">Activity reached {% for asset in exposed_assets %}{{ asset.name }} ({{ asset.kind }}){% if not loop.last %}, {% endif %}{% endfor %}</p>{% endif %}
+<h2>Snow Analysis</h2>
+<table>
+<tr><th>Granular</th><td>{{ visual_characteristics.snow_texture.granular }}</td></tr>
+<tr><th>Blocky</th><td>{{ visual_characteristics.snow_texture.blocky }}</td></tr>
+<tr><th>Fluffy</th><td>{{ visual_characteristics.snow_texture.fluffy }}</td></tr>
+<tr><th>Density</th><td>{{ visual_characteristics.snow_texture.density }}</td></tr>
+</table>
+<h2>Movement Pattern</h2>
+<table>
+<tr><th>Initial release</th><td>{{ visual_characteristics.movement_pattern.starting_width }}</td></tr>
+<tr><th>Propagation</th><td>{{ visual_characteristics.movement_pattern.propagation }}</td></tr>
+<tr><th>Vertical movement</th><td>{{ visual_characteristics.movement_pattern.vertical_movement }}</td></tr>
+<tr><th>Lateral spread</th><td>{{ visual_characteristics.movement_pattern.lateral_spread }}</td></tr>
+</table>
+<h2>Release and Debris</h2>
+<table>
+<tr><th>Powder cloud</th><td>{{ visual_characteristics.powder_cloud }}</td></tr>
+<tr><th>Fracture line</th><td>{{ visual_characteristics.fracture_line }}</td></tr>
+<tr><th>Fracture depth</th><td>{{ fracture_depth | default(value="n/a") }}</td></tr>
+<tr><th>Point release</th><td>{{ visual_characteristics.point_release }}</td></tr>
+<tr><th>Debris pattern</th><td>{{ visual_characteristics.debris_pattern }}</td></tr>
+</table>
+<h2>Terrain Analysis</h2>
+<table>
+<tr><th>Slope</th><td>{{ visual_characteristics.terrain.slope_angle | default(value="unknown") }}</td></tr>
+<tr><th>Surface</th><td>{{ visual_characteristics.terrain.surface_roughness }}</td></tr>
+<tr><th>Anchoring points</th><td>{{ visual_characteristics.terrain.anchoring_points }}</td></tr>
+<tr><th>Convex rollover</th><td>{{ visual_characteristics.terrain.convex_rollover }}</td></tr>
+</table>
+{% if terrain_features %}<h2>Additional Observations</h2>
+<ul>
+{% for feature in terrain_features %}<li>{{ feature }}</li>
+{% endfor %}</ul>{% endif %}
+</body>
+</html>
+"#;
+
+const MARKDOWN_TEMPLATE: &str = r#"# Avalanche Analysis Report
+
+{% for item in meta %}{{ item }}{% if not loop.last %} · {% endif %}{% endfor %}
+{% if image %}
+![Analyzed photo]({{ image }})
+{% endif %}
+## Classification
+
+| | |
+|---|---|
+| Avalanche present | {% if avalanche_present %}yes{% else %}no{% endif %} |
+| Type | {{ label }} |{% if label != avalanche_type %}
+| Model output | {{ avalanche_type }} |{% endif %}
+| Confidence | {{ confidence_level | round(precision=0) }}% |
+{% if exposed_assets %}
+**Activity reached {% for asset in exposed_assets %}{{ asset.name }} ({{ asset.kind }}){% if not loop.last %}, {% endif %}{% endfor %}**
+{% endif %}
+## Snow Analysis
+
+| | |
+|---|---|
+| Granular | {{ visual_characteristics.snow_texture.granular }} |
+| Blocky | {{ visual_characteristics.snow_texture.blocky }} |
+| Fluffy | {{ visual_characteristics.snow_texture.fluffy }} |
+| Density | {{ visual_characteristics.snow_texture.density }} |
+
+## Movement Pattern
+
+| | |
+|---|---|
+| Initial release | {{ visual_characteristics.movement_pattern.starting_width }} |
+| Propagation | {{ visual_characteristics.movement_pattern.propagation }} |
+| Vertical movement | {{ visual_characteristics.movement_pattern.vertical_movement }} |
+| Lateral spread | {{ visual_characteristics.movement_pattern.lateral_spread }} |
+
+## Release and Debris
+
+| | |
+|---|---|
+| Powder cloud | {{ visual_characteristics.powder_cloud }} |
+| Fracture line | {{ visual_characteristics.fracture_line }} |
+| Fracture depth | {{ fracture_depth | default(value="n/a") }} |
+| Point release | {{ visual_characteristics.point_release }} |
+| Debris pattern | {{ visual_characteristics.debris_pattern }} |
+
+## Terrain Analysis
+
+| | |
+|---|---|
+| Slope | {{ visual_characteristics.terrain.slope_angle | default(value="unknown") }} |
+| Surface | {{ visual_characteristics.terrain.surface_roughness }} |
+| Anchoring points | {{ visual_characteristics.terrain.anchoring_points }} |
+| Convex rollover | {{ visual_characteristics.terrain.convex_rollover }} |
+{% if terrain_features %}
+## Additional Observations
+
+{% for feature in terrain_features %}- {{ feature }}
+{% endfor %}{% endif %}"#;
+
+// Photos are downscaled to this many pixels on the long side before inlining
+const INLINE_IMAGE_PX: u32 = 1600;
+
+fn data_uri(image_bytes: &[u8]) -> anyhow::Result<String> {
+    use base64::Engine;
+    let photo = crate::photo_meta::load_upright(image_bytes)?;
+    let photo = if photo.width().max(photo.height()) > INLINE_IMAGE_PX {
+        photo.thumbnail(INLINE_IMAGE_PX, INLINE_IMAGE_PX)
+    } else {
+        photo
+    };
+    let mut jpeg = Vec::new();
+    image::DynamicImage::ImageRgb8(photo.to_rgb8())
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageOutputFormat::Jpeg(85))?;
+    Ok(format!("data:image/jpeg;base64,{}", base64::engine::general_purpose::STANDARD.encode(jpeg)))
+}
+
+// Renders a self-contained HTML or Markdown report. `entry` adds the
+// reviewed label, provenance, location, bearing and exposed assets when
+// the analysis was recorded in history.
+pub fn render_document(
+    format: DocumentFormat,
+    analysis: &AvalancheAnalysis,
+    image_bytes: Option<&[u8]>,
+    entry: Option<&HistoryEntry>,
+) -> anyhow::Result<String> {
+    let exposure = entry.map_or(&[][..], |e| e.exposure.as_slice());
+    let mut context = report_context(analysis, exposure)?;
+    let label = entry.and_then(HistoryEntry::effective_type).unwrap_or(&analysis.avalanche_type);
+    context.insert("label", label);
+
+    let mut meta = vec![format!("Generated {}", chrono::Local::now().format("%Y-%m-%d %H:%M"))];
+    if let Some(entry) = entry {
+        meta.push(format!("{} {}", entry.snapshot.backend, entry.snapshot.model));
+        meta.push(format!("snapshot {}", &entry.snapshot.id[..12.min(entry.snapshot.id.len())]));
+        if let Some(at) = entry.captured_at.and_then(|t| chrono::DateTime::from_timestamp(t, 0)) {
+            meta.push(format!("taken {}", at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")));
+        }
+        if let Some(location) = entry.location {
+            meta.push(format!("{:.5}, {:.5}", location.latitude, location.longitude));
+        }
+        if let Some(bearing) = entry.bearing {
+            meta.push(format!(
+                "facing {} ({:.0}°)",
+                crate::orientation::compass_point(bearing),
+                crate::orientation::normalize(bearing)
+            ));
+        }
+        if let Some(source) = &entry.source {
+            meta.push(format!("camera {}", source));
+        }
+        if let Some(info) = &entry.camera_info {
+            meta.push(info.describe());
+        }
+        if let Some(slope) = &entry.slope {
+            meta.push(slope.describe());
+        }
+    }
+    context.insert("meta", &meta);
+    context.insert("image", &image_bytes.map(data_uri).transpose()?);
+
+    tera::Tera::one_off(format.template(), &context, format == DocumentFormat::Html)
+        .map_err(|e| anyhow::anyhow!("Report template error: {}", e))
+}
+
+// Machine-readable counterpart of the text report
+#[derive(Serialize)]
+struct AnalysisExport<'a> {
+    analysis: &'a AvalancheAnalysis,
+    // Null when the exchange could not be recorded
+    snapshot_id: Option<&'a str>,
+    backend: Option<&'a str>,
+    model: Option<&'a str>,
+    timestamp: Option<String>,  // RFC 3339, UTC
+    image_hash: Option<&'a str>,
+    exposed_assets: &'a [ExposedAsset],
+    // When and where the photo was taken
+    captured_at: Option<String>,  // RFC 3339, UTC
+    location: Option<Location>,
+    location_from_track: bool,
+    // Degrees clockwise from true north, and its compass point
+    bearing: Option<f32>,
+    facing: Option<&'static str>,
+    camera: Option<&'a CameraInfo>,
+}
+
+// The full analysis plus where it came from, as pretty-printed JSON
+pub fn export_json(
+    analysis: &AvalancheAnalysis,
+    snapshot: Option<&AnalysisSnapshot>,
+    entry: Option<&HistoryEntry>,
+) -> anyhow::Result<String> {
+    let bearing = entry.and_then(|e| e.bearing);
+    let export = AnalysisExport {
+        analysis,
+        snapshot_id: snapshot.map(|s| s.id.as_str()),
+        backend: snapshot.map(|s| s.backend.as_str()),
+        model: snapshot.map(|s| s.model.as_str()),
+        timestamp: snapshot
+            .and_then(|s| chrono::DateTime::from_timestamp(s.created_at as i64, 0))
+            .map(|t| t.to_rfc3339()),
+        image_hash: snapshot.map(|s| s.image_hash.as_str()),
+        exposed_assets: entry.map_or(&[][..], |e| e.exposure.as_slice()),
+        captured_at: entry
+            .and_then(|e| e.captured_at)
+            .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+            .map(|t| t.to_rfc3339()),
+        location: entry.and_then(|e| e.location),
+        location_from_track: entry.is_some_and(|e| e.location_from_track),
+        bearing: bearing.map(crate::orientation::normalize),
+        facing: bearing.map(crate::orientation::compass_point),
+        camera: entry.and_then(|e| e.camera_info.as_ref()),
+    };
+    Ok(serde_json::to_string_pretty(&export)?)
+}
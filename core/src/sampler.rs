@@ -0,0 +1,49 @@
+use crate::history::{History, HistoryEntry};
+use crate::scoring;
+
+pub const SAMPLER_FLAG: &str = "Selected by active-learning sampler";
+
+// How much a human label for this entry would teach us. Combines low model
+// confidence, disagreement between analyses of the same image (other
+// models, prompts or backends) and how close the top two type scores are.
+pub fn informativeness(history: &History, entry: &HistoryEntry) -> f32 {
+    let uncertainty = 1.0 - (entry.analysis.confidence_level / 100.0).clamp(0.0, 1.0);
+
+    let same_image: Vec<&HistoryEntry> = history
+        .entries
+        .iter()
+        .filter(|e| e.snapshot.original_hash() == entry.snapshot.original_hash())
+        .collect();
+    let disagreement = if same_image.len() > 1 {
+        let differing = same_image
+            .iter()
+            .filter(|e| e.analysis.avalanche_type != entry.analysis.avalanche_type)
+            .count();
+        differing as f32 / (same_image.len() - 1) as f32
+    } else {
+        0.0
+    };
+
+    let scores = scoring::type_scores(&entry.analysis);
+    let (highest, second) = scores.top_two();
+    let ambiguity = 1.0 / (1.0 + (highest - second) as f32);
+
+    uncertainty + disagreement + ambiguity
+}
+
+// Ids of the `count` most informative unreviewed entries not already
+// waiting in the review queue, best first
+pub fn sample(history: &History, count: usize) -> Vec<String> {
+    let mut candidates: Vec<(f32, &HistoryEntry)> = history
+        .entries
+        .iter()
+        .filter(|e| e.review.is_none() && !e.needs_review())
+        .map(|e| (informativeness(history, e), e))
+        .collect();
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    candidates
+        .into_iter()
+        .take(count)
+        .map(|(_, e)| e.id().to_string())
+        .collect()
+}
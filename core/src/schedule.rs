@@ -0,0 +1,149 @@
+use chrono::{Datelike, Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// How far back a missed run is looked for after the app was closed
+const CATCH_UP_MINUTES: i64 = 7 * 24 * 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum JobKind {
+    // Classifies every image in the folder that isn't already in history
+    FolderSweep { folder: PathBuf },
+}
+
+impl JobKind {
+    pub fn describe(&self) -> String {
+        match self {
+            JobKind::FolderSweep { folder } => format!("Sweep {}", folder.display()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Job {
+    pub name: String,
+    pub cron: String,
+    pub kind: JobKind,
+    pub enabled: bool,
+    pub last_run: Option<u64>,  // unix seconds
+}
+
+impl Job {
+    // Due when a matching minute has passed since the last run. Runs missed
+    // while the app was closed are made up once, not once per missed slot.
+    pub fn is_due(&self, now: u64) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let Ok(cron) = Cron::parse(&self.cron) else {
+            return false;
+        };
+        let now_minute = (now / 60) as i64;
+        let since = match self.last_run {
+            Some(last) => (last / 60) as i64 + 1,
+            None => now_minute,  // new jobs wait for their first slot
+        };
+        (since.max(now_minute - CATCH_UP_MINUTES)..=now_minute).any(|minute| cron.matches(minute * 60))
+    }
+}
+
+pub struct Schedule {
+    path: PathBuf,
+    pub jobs: Vec<Job>,
+}
+
+impl Schedule {
+    pub fn load_default() -> Self {
+        let path = crate::storage::data_dir().join("schedule.json");
+        let jobs = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self { path, jobs }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&self.jobs)?)?;
+        Ok(())
+    }
+}
+
+// Standard five-field cron expression (minute hour day-of-month month
+// day-of-week) evaluated in local time. Each field takes *, */n, a, a-b and
+// comma-separated lists of those.
+pub struct Cron {
+    fields: [Vec<bool>; 5],
+}
+
+impl Cron {
+    pub fn parse(expr: &str) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = expr.split_whitespace().collect();
+        if parts.len() != 5 {
+            return Err(anyhow::anyhow!("Cron expression needs 5 fields: minute hour day month weekday"));
+        }
+        let ranges = [(0, 59), (0, 23), (1, 31), (1, 12), (0, 6)];
+        let mut fields: [Vec<bool>; 5] = Default::default();
+        for (i, (part, (min, max))) in parts.iter().zip(ranges).enumerate() {
+            fields[i] = parse_field(part, min, max)?;
+        }
+        Ok(Self { fields })
+    }
+
+    pub fn matches(&self, unix_seconds: i64) -> bool {
+        let Some(time) = Local.timestamp_opt(unix_seconds, 0).single() else {
+            return false;
+        };
+        let values = [
+            time.minute() as usize,
+            time.hour() as usize,
+            time.day() as usize,
+            time.month() as usize,
+            time.weekday().num_days_from_sunday() as usize,
+        ];
+        values.iter().zip(&self.fields).all(|(v, field)| field[*v])
+    }
+}
+
+fn parse_field(field: &str, min: usize, max: usize) -> anyhow::Result<Vec<bool>> {
+    let invalid = || anyhow::anyhow!("Invalid cron field: {}", field);
+    let mut allowed = vec![false; max + 1];
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>().map_err(|_| invalid())?),
+            None => (item, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (a.parse().map_err(|_| invalid())?, b.parse().map_err(|_| invalid())?)
+        } else {
+            let value = range.parse().map_err(|_| invalid())?;
+            (value, value)
+        };
+        if step == 0 || start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step) {
+            allowed[value] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+// Image files in the folder, sorted by name
+pub fn folder_images(folder: &std::path::Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut images: Vec<PathBuf> = std::fs::read_dir(folder)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(crate::photo_meta::decodable_extension)
+        })
+        .collect();
+    images.sort();
+    Ok(images)
+}
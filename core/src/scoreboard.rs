@@ -0,0 +1,79 @@
+use crate::history::{History, Verdict};
+
+// Priced from the model catalog. Models not in it, such as local ones, are
+// treated as free.
+pub fn cost_usd(model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+    crate::models::lookup(model).map_or(0.0, |info| {
+        let (input, output) = info.price_per_mtok;
+        (input_tokens as f64 * input + output_tokens as f64 * output) / 1_000_000.0
+    })
+}
+
+pub struct BackendStats {
+    pub backend: String,
+    pub model: String,
+    pub analyses: usize,
+    pub reviewed: usize,
+    // Share of human-labeled results the AI got right; None until something is reviewed
+    pub accuracy: Option<f32>,
+    pub avg_latency_ms: Option<f32>,
+    pub total_cost_usd: f64,
+}
+
+// One row per backend/model pair, most used first
+pub fn scoreboard(history: &History) -> Vec<BackendStats> {
+    let mut rows: Vec<BackendStats> = Vec::new();
+    let mut correct: Vec<usize> = Vec::new();
+    let mut latency_sum: Vec<(u64, usize)> = Vec::new();
+
+    for entry in &history.entries {
+        let snapshot = &entry.snapshot;
+        let i = match rows.iter().position(|r| r.backend == snapshot.backend && r.model == snapshot.model) {
+            Some(i) => i,
+            None => {
+                rows.push(BackendStats {
+                    backend: snapshot.backend.clone(),
+                    model: snapshot.model.clone(),
+                    analyses: 0,
+                    reviewed: 0,
+                    accuracy: None,
+                    avg_latency_ms: None,
+                    total_cost_usd: 0.0,
+                });
+                correct.push(0);
+                latency_sum.push((0, 0));
+                rows.len() - 1
+            }
+        };
+
+        rows[i].analyses += 1;
+        match entry.review.as_ref().map(|r| r.verdict) {
+            Some(Verdict::Accepted) => {
+                rows[i].reviewed += 1;
+                correct[i] += 1;
+            }
+            Some(Verdict::Corrected) => rows[i].reviewed += 1,
+            _ => {}
+        }
+        if let Some(latency) = snapshot.latency_ms {
+            latency_sum[i].0 += latency;
+            latency_sum[i].1 += 1;
+        }
+        rows[i].total_cost_usd += cost_usd(
+            &snapshot.model,
+            snapshot.input_tokens.unwrap_or(0),
+            snapshot.output_tokens.unwrap_or(0),
+        );
+    }
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        if row.reviewed > 0 {
+            row.accuracy = Some(correct[i] as f32 / row.reviewed as f32);
+        }
+        if latency_sum[i].1 > 0 {
+            row.avg_latency_ms = Some(latency_sum[i].0 as f32 / latency_sum[i].1 as f32);
+        }
+    }
+    rows.sort_by_key(|r| std::cmp::Reverse(r.analyses));
+    rows
+}
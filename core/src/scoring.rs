@@ -0,0 +1,369 @@
+use crate::model::{AvalancheAnalysis, FractureDepth};
+use serde::{Deserialize, Serialize};
+use std::sync::{PoisonError, RwLock};
+
+/// The rule engine's weights and thresholds. Avalanche professionals can tune
+/// them in scoring.toml in the data directory; anything left out keeps its
+/// default.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    pub weights: Weights,
+    pub thresholds: Thresholds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Weights {
+    /// Points per primary indicator present
+    pub primary: i32,
+    /// Points per secondary indicator present
+    pub secondary: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Thresholds {
+    /// How far the top type must lead the runner-up
+    pub min_margin: i32,
+    /// Lowest score the top type may have
+    pub min_score: i32,
+}
+
+impl ScoringConfig {
+    pub const DEFAULT: ScoringConfig = ScoringConfig {
+        weights: Weights { primary: 3, secondary: 1 },
+        thresholds: Thresholds { min_margin: 3, min_score: 6 },
+    };
+
+    /// Most points any one type can score: loose snow, slab, wet loose, wet
+    /// slab and glide each have four primary and three secondary indicators
+    pub fn max_score(&self) -> i32 {
+        4 * self.weights.primary + 3 * self.weights.secondary
+    }
+
+    fn check(self) -> anyhow::Result<Self> {
+        let Weights { primary, secondary } = self.weights;
+        let Thresholds { min_margin, min_score } = self.thresholds;
+        if primary < 0 || secondary < 0 || min_margin < 0 || min_score < 0 {
+            anyhow::bail!("weights and thresholds must not be negative");
+        }
+        Ok(self)
+    }
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig::DEFAULT
+    }
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        ScoringConfig::DEFAULT.weights
+    }
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        ScoringConfig::DEFAULT.thresholds
+    }
+}
+
+static CONFIG: RwLock<ScoringConfig> = RwLock::new(ScoringConfig::DEFAULT);
+
+pub fn path() -> std::path::PathBuf {
+    crate::storage::data_dir().join("scoring.toml")
+}
+
+/// The weights and thresholds in use
+pub fn config() -> ScoringConfig {
+    *CONFIG.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Reads scoring.toml and uses it from then on. Returns whether the file
+/// exists; without it the defaults apply. A file that cannot be read leaves
+/// the weights in use unchanged.
+pub fn reload() -> anyhow::Result<bool> {
+    let text = match std::fs::read_to_string(path()) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            *CONFIG.write().unwrap_or_else(PoisonError::into_inner) = ScoringConfig::DEFAULT;
+            return Ok(false);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let config = toml::from_str::<ScoringConfig>(&text)
+        .map_err(|err| anyhow::anyhow!("{}", err.message()))?
+        .check()?;
+    *CONFIG.write().unwrap_or_else(PoisonError::into_inner) = config;
+    Ok(true)
+}
+
+/// Writes the defaults to scoring.toml as a starting point for editing
+pub fn write_defaults() -> anyhow::Result<()> {
+    let text = format!(
+        "# Avalanche type scoring rules. Each type scores the primary weight for\n\
+         # every primary indicator present and the secondary weight for every\n\
+         # secondary one. A classification needs its type to lead the runner-up\n\
+         # by min_margin and score at least min_score.\n\n{}",
+        toml::to_string_pretty(&ScoringConfig::DEFAULT)?
+    );
+    std::fs::write(path(), text)?;
+    Ok(())
+}
+
+/// The types the rule engine scores, in the order TypeScores lists them;
+/// "none" is left to the model
+pub const SCORED_TYPES: [&str; 8] =
+    ["powder", "loose-snow", "slab", "wet-loose", "wet-slab", "glide", "cornice", "slush"];
+
+/// Analyses scored before the wet-snow types were added lack their scores,
+/// which read as zero
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TypeScores {
+    pub powder: i32,
+    pub loose_snow: i32,
+    pub slab: i32,
+    #[serde(default)]
+    pub wet_loose: i32,
+    #[serde(default)]
+    pub wet_slab: i32,
+    #[serde(default)]
+    pub glide: i32,
+    #[serde(default)]
+    pub cornice: i32,
+    #[serde(default)]
+    pub slush: i32,
+}
+
+impl TypeScores {
+    pub fn for_type(&self, avalanche_type: &str) -> Option<i32> {
+        match avalanche_type {
+            "powder" => Some(self.powder),
+            "loose-snow" => Some(self.loose_snow),
+            "slab" => Some(self.slab),
+            "wet-loose" => Some(self.wet_loose),
+            "wet-slab" => Some(self.wet_slab),
+            "glide" => Some(self.glide),
+            "cornice" => Some(self.cornice),
+            "slush" => Some(self.slush),
+            _ => None,
+        }
+    }
+
+    /// Each scored type with its score, in SCORED_TYPES order
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, i32)> + '_ {
+        SCORED_TYPES.into_iter().map(|t| (t, self.for_type(t).unwrap_or_default()))
+    }
+
+    /// The highest scoring type and its score; the first listed wins a tie
+    pub fn best(&self) -> (&'static str, i32) {
+        self.iter().fold(("powder", self.powder), |best, next| if next.1 > best.1 { next } else { best })
+    }
+
+    /// The top score and the runner-up's; equal when the top is tied
+    pub fn top_two(&self) -> (i32, i32) {
+        let mut ranked: Vec<i32> = self.iter().map(|(_, score)| score).collect();
+        ranked.sort_unstable_by(|a, b| b.cmp(a));
+        (ranked[0], ranked[1])
+    }
+}
+
+/// The reply fields behind each type's primary indicators, for pointing the
+/// model at what to look at again
+pub fn primary_indicators(avalanche_type: &str) -> &'static [&'static str] {
+    match avalanche_type {
+        "powder" => &["powder_cloud", "snow_texture.fluffy", "movement_pattern.vertical_movement"],
+        "loose-snow" => &[
+            "movement_pattern.starting_width = point",
+            "movement_pattern.propagation = fan",
+            "snow_texture.granular",
+            "debris_pattern = fan-shaped",
+        ],
+        "slab" => &[
+            "fracture_line",
+            "snow_texture.blocky",
+            "movement_pattern.starting_width = wide",
+            "movement_pattern.propagation = linear",
+        ],
+        "wet-loose" => &[
+            "snow_texture.wet",
+            "movement_pattern.starting_width = point",
+            "movement_pattern.propagation = fan",
+            "debris_pattern = fan-shaped",
+        ],
+        "wet-slab" => &[
+            "snow_texture.wet",
+            "fracture_line",
+            "snow_texture.blocky",
+            "movement_pattern.starting_width = wide",
+        ],
+        "glide" => &[
+            "glide_crack",
+            "ground_exposed",
+            "movement_pattern.starting_width = wide",
+            "terrain.surface_roughness = smooth",
+        ],
+        "cornice" => &["cornice_failure", "snow_texture.blocky", "movement_pattern.vertical_movement"],
+        "slush" => &["snow_texture.water_saturated", "snow_texture.wet", "terrain.slope_angle = gentle"],
+        _ => &[],
+    }
+}
+
+/// Characteristic scores for each avalanche type: the primary weight per
+/// primary indicator, the secondary weight per secondary indicator. Wet snow
+/// rules out the dry types, saturated snow the wet loose and wet slab types,
+/// and a glide crack wet slab; glide, cornice and slush score only when
+/// their defining feature was seen.
+pub fn type_scores(analysis: &AvalancheAnalysis) -> TypeScores {
+    let Weights { primary, secondary } = config().weights;
+    let chars = &analysis.visual_characteristics;
+    let snow = &chars.snow_texture;
+    let movement = &chars.movement_pattern;
+    let steep = chars.terrain.slope_angle.as_ref().is_some_and(|a| a.starts_with("steep"));
+    let gentle = chars.terrain.slope_angle.as_ref().is_some_and(|a| a.starts_with("gentle"));
+    let wet_only = snow.wet && !snow.water_saturated;
+
+    let powder = if snow.wet { 0 } else {
+        let mut score = 0i32;
+        if chars.powder_cloud { score += primary; }  // Primary
+        if snow.fluffy { score += primary; }        // Primary
+        if movement.vertical_movement { score += primary; }  // Primary
+        if snow.density == "low" { score += secondary; }      // Secondary
+        if movement.propagation == "chaotic" { score += secondary; }  // Secondary
+        if steep { score += secondary; }                      // Secondary
+        score
+    };
+
+    let loose_snow = if snow.wet { 0 } else {
+        let mut score = 0i32;
+        if movement.starting_width == "point" { score += primary; }  // Primary
+        if movement.propagation == "fan" { score += primary; }      // Primary
+        if snow.granular { score += primary; }                      // Primary
+        if chars.debris_pattern == "fan-shaped" { score += primary; }  // Primary
+        if !chars.fracture_line { score += secondary; }               // Secondary
+        if snow.density == "low" { score += secondary; }              // Secondary
+        if steep { score += secondary; }                              // Secondary
+        score
+    };
+
+    let slab = if snow.wet { 0 } else {
+        let mut score = 0i32;
+        if chars.fracture_line { score += primary; }               // Primary
+        if snow.blocky { score += primary; }                      // Primary
+        if movement.starting_width == "wide" { score += primary; } // Primary
+        if movement.propagation == "linear" { score += primary; }  // Primary
+        if snow.density == "high" { score += secondary; }           // Secondary
+        if chars.debris_pattern == "linear" { score += secondary; }  // Secondary
+        if movement.lateral_spread { score += secondary; }           // Secondary
+        score
+    };
+
+    let wet_loose = if !wet_only { 0 } else {
+        let mut score = primary;                                     // Primary: wet
+        if movement.starting_width == "point" { score += primary; }  // Primary
+        if movement.propagation == "fan" { score += primary; }      // Primary
+        if chars.debris_pattern == "fan-shaped" { score += primary; }  // Primary
+        if !chars.fracture_line { score += secondary; }               // Secondary
+        if snow.density == "high" { score += secondary; }             // Secondary
+        if steep { score += secondary; }                              // Secondary
+        score
+    };
+
+    let wet_slab = if !wet_only || chars.glide_crack { 0 } else {
+        let mut score = primary;                                     // Primary: wet
+        if chars.fracture_line { score += primary; }               // Primary
+        if snow.blocky { score += primary; }                      // Primary
+        if movement.starting_width == "wide" { score += primary; } // Primary
+        if snow.density == "high" { score += secondary; }           // Secondary
+        if movement.propagation == "linear" { score += secondary; }  // Secondary
+        if chars.debris_pattern == "linear" { score += secondary; }  // Secondary
+        score
+    };
+
+    let glide = if !chars.glide_crack && !chars.ground_exposed { 0 } else {
+        let mut score = 0i32;
+        if chars.glide_crack { score += primary; }                  // Primary
+        if chars.ground_exposed { score += primary; }               // Primary
+        if movement.starting_width == "wide" { score += primary; } // Primary
+        if chars.terrain.surface_roughness == "smooth" { score += primary; }  // Primary
+        if snow.wet { score += secondary; }                         // Secondary
+        if snow.density == "high" { score += secondary; }           // Secondary
+        if !chars.terrain.anchoring_points { score += secondary; }  // Secondary
+        score
+    };
+
+    let cornice = if !chars.cornice_failure { 0 } else {
+        let mut score = primary;                                     // Primary: cornice failure
+        if snow.blocky { score += primary; }                      // Primary
+        if movement.vertical_movement { score += primary; }       // Primary
+        if snow.density == "high" { score += secondary; }           // Secondary
+        if movement.starting_width == "point" { score += secondary; }  // Secondary
+        if steep { score += secondary; }                            // Secondary
+        score
+    };
+
+    let slush = if !snow.water_saturated { 0 } else {
+        let mut score = primary;                                     // Primary: saturated
+        if snow.wet { score += primary; }                           // Primary
+        if gentle { score += primary; }                             // Primary
+        if snow.density == "high" { score += secondary; }           // Secondary
+        if movement.lateral_spread { score += secondary; }          // Secondary
+        if chars.terrain.surface_roughness == "smooth" { score += secondary; }  // Secondary
+        score
+    };
+
+    TypeScores { powder, loose_snow, slab, wet_loose, wet_slab, glide, cornice, slush }
+}
+
+/// Characteristic scores for each avalanche problem, in AVALANCHE_PROBLEMS
+/// order, weighted like the types. The slab problems need dry snow and a
+/// fracture line and are told apart by wind features, fresh snow and
+/// fracture depth; wet loose, glide and cornice share their type's score.
+pub fn problem_scores(analysis: &AvalancheAnalysis, types: &TypeScores) -> [(&'static str, i32); 6] {
+    let Weights { primary, secondary } = config().weights;
+    let chars = &analysis.visual_characteristics;
+    let snow = &chars.snow_texture;
+    let depth = chars.fracture_depth.as_ref().map(FractureDepth::class).unwrap_or_default();
+    let dry_slab = !snow.wet && chars.fracture_line;
+
+    let wind_slab = if !dry_slab { 0 } else {
+        let mut score = primary;                                     // Primary: fracture line
+        if chars.wind_features { score += primary; }                // Primary
+        if depth == "shallow" { score += primary; }                 // Primary
+        if snow.blocky { score += secondary; }                      // Secondary
+        if chars.terrain.convex_rollover { score += secondary; }    // Secondary
+        if snow.density != "low" { score += secondary; }            // Secondary
+        score
+    };
+
+    let storm_slab = if !dry_slab { 0 } else {
+        let mut score = primary;                                     // Primary: fracture line
+        if chars.recent_snowfall { score += primary; }              // Primary
+        if depth == "shallow" { score += primary; }                 // Primary
+        if snow.density == "low" { score += secondary; }            // Secondary
+        if snow.fluffy { score += secondary; }                      // Secondary
+        if !chars.wind_features { score += secondary; }             // Secondary
+        score
+    };
+
+    let persistent_slab = if !dry_slab { 0 } else {
+        let mut score = primary;                                     // Primary: fracture line
+        if depth == "deep" { score += primary; }                    // Primary
+        if chars.movement_pattern.starting_width == "wide" { score += primary; }  // Primary
+        if snow.density == "high" { score += secondary; }           // Secondary
+        if chars.movement_pattern.lateral_spread { score += secondary; }  // Secondary
+        if !chars.recent_snowfall { score += secondary; }           // Secondary
+        score
+    };
+
+    [
+        ("wind-slab", wind_slab),
+        ("persistent-slab", persistent_slab),
+        ("storm-slab", storm_slab),
+        ("wet-loose", types.wet_loose),
+        ("glide", types.glide),
+        ("cornice", types.cornice),
+    ]
+}
@@ -0,0 +1,124 @@
+// Checks the model's visual slope angle and aspect against the terrain. The
+// slope is measured from the elevation grid around the photo location, or
+// taken from the slope confirmed from the viewshed candidates when there is
+// one, since the photo location is where the camera stood rather than the
+// slope it shows.
+
+use crate::camera::Location;
+use crate::dem::Dem;
+use crate::history::HistoryEntry;
+use crate::model::ASPECTS;
+
+// A 3×3 grid at the DEM's own resolution, for central differences around
+// the point
+const GRID_SIZE: usize = 3;
+const GRID_SPACING_M: f32 = 90.0;
+
+// Measured angles this far outside the model's class still count as agreeing;
+// a 90 m DEM smooths out short steep rolls
+pub const SLOPE_TOLERANCE_DEG: f32 = 5.0;
+// More than one octant apart on the aspect rose
+pub const ASPECT_TOLERANCE_DEG: f32 = 67.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measured {
+    // The terrain at the photo location
+    PhotoLocation,
+    // The slope confirmed for the entry
+    ConfirmedSlope,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlopeCheck {
+    pub measured: Measured,
+    pub slope_deg: f32,
+    // Downslope direction, degrees clockwise from north
+    pub aspect_deg: f32,
+    // Where the model's estimates and the terrain disagree, one sentence each
+    pub discrepancies: Vec<String>,
+}
+
+impl SlopeCheck {
+    // "38° NE-facing at the photo location"
+    pub fn describe(&self) -> String {
+        format!(
+            "{:.0}° {}-facing {}",
+            self.slope_deg,
+            crate::orientation::compass_point(self.aspect_deg),
+            match self.measured {
+                Measured::PhotoLocation => "at the photo location",
+                Measured::ConfirmedSlope => "on the confirmed slope",
+            }
+        )
+    }
+}
+
+// Measures the entry's slope and compares it with what the model saw;
+// None when the entry has neither a location nor a confirmed slope
+pub async fn check(client: &reqwest::Client, entry: &HistoryEntry) -> Option<anyhow::Result<SlopeCheck>> {
+    let (measured, slope_deg, aspect_deg) = match (&entry.slope, entry.location) {
+        (Some(slope), _) => (Measured::ConfirmedSlope, slope.mean_slope_deg, slope.aspect_deg),
+        (None, Some(location)) => match measure(client, location).await {
+            Ok((slope, aspect)) => (Measured::PhotoLocation, slope, aspect),
+            Err(err) => return Some(Err(err)),
+        },
+        (None, None) => return None,
+    };
+    let terrain = &entry.analysis.visual_characteristics.terrain;
+    let mut discrepancies = Vec::new();
+    if let Some(estimate) = &terrain.slope_angle {
+        if let Some((low, high)) = slope_range(estimate) {
+            if slope_deg < low - SLOPE_TOLERANCE_DEG || slope_deg > high + SLOPE_TOLERANCE_DEG {
+                discrepancies.push(format!("Terrain is {:.0}° steep but the model saw \"{}\"", slope_deg, estimate));
+            }
+        }
+    }
+    if let Some(aspect) = &terrain.aspect {
+        if let Some(index) = ASPECTS.iter().position(|a| a == aspect) {
+            let diff = (aspect_deg - index as f32 * 45.0 + 540.0).rem_euclid(360.0) - 180.0;
+            if diff.abs() > ASPECT_TOLERANCE_DEG {
+                discrepancies.push(format!(
+                    "Terrain faces {} but the model saw {}",
+                    crate::orientation::compass_point(aspect_deg),
+                    aspect
+                ));
+            }
+        }
+    }
+    Some(Ok(SlopeCheck { measured, slope_deg, aspect_deg, discrepancies }))
+}
+
+// Slope angle and aspect in degrees at a point
+pub async fn measure(client: &reqwest::Client, location: Location) -> anyhow::Result<(f32, f32)> {
+    let grid = Dem::fetch(client, location, GRID_SIZE, GRID_SPACING_M).await?;
+    let center = GRID_SIZE / 2;
+    Ok((grid.slope_deg(center, center), grid.aspect_deg(center, center)))
+}
+
+// The range of degrees a slope angle estimate stands for: its numbers when
+// it has them ("moderate (30-45°)", "steep (>45°)"), otherwise the classes
+// the prompt defines
+pub fn slope_range(estimate: &str) -> Option<(f32, f32)> {
+    let numbers: Vec<f32> = estimate
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .filter_map(|part| part.parse().ok())
+        .collect();
+    match numbers.as_slice() {
+        [low, high, ..] => Some((low.min(*high), low.max(*high))),
+        [one] if estimate.contains('>') => Some((*one, 90.0)),
+        [one] if estimate.contains('<') => Some((0.0, *one)),
+        [one] => Some((*one, *one)),
+        [] => {
+            let estimate = estimate.to_lowercase();
+            if estimate.starts_with("steep") {
+                Some((45.0, 90.0))
+            } else if estimate.starts_with("moderate") {
+                Some((30.0, 45.0))
+            } else if estimate.starts_with("gentle") {
+                Some((0.0, 30.0))
+            } else {
+                None
+            }
+        }
+    }
+}
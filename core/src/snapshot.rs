@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+// Content-addressed store: each blob lives at blobs/<sha256>.zst (or
+// blobs/<sha256> when compression would not shrink it, as with most JPEGs,
+// and for blobs from before compression) and is never rewritten once
+// present, so a hash always refers to the same bytes.
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    pub fn open(root: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(root.join("blobs"))?;
+        std::fs::create_dir_all(root.join("snapshots"))?;
+        Ok(Self { root })
+    }
+
+    pub fn open_default() -> anyhow::Result<Self> {
+        Self::open(crate::storage::data_dir())
+    }
+
+    pub fn put(&self, bytes: &[u8]) -> anyhow::Result<String> {
+        let hash = hash_bytes(bytes);
+        let path = self.blob_path(&hash);
+        if !path.exists() && !path.with_extension("zst").exists() {
+            write_blob(&path, bytes)?;
+        }
+        Ok(hash)
+    }
+
+    pub fn get(&self, hash: &str) -> anyhow::Result<Vec<u8>> {
+        let path = self.blob_path(hash);
+        match std::fs::read(path.with_extension("zst")) {
+            Ok(compressed) => zstd::decode_all(compressed.as_slice())
+                .map_err(|e| anyhow::anyhow!("Corrupt blob {}: {}", hash, e)),
+            Err(_) => std::fs::read(path).map_err(|e| anyhow::anyhow!("Missing blob {}: {}", hash, e)),
+        }
+    }
+
+    // Compresses blobs stored raw before compression was introduced.
+    // Returns the bytes on disk before and after.
+    pub fn compact(&self) -> anyhow::Result<(u64, u64)> {
+        let (mut before, mut after) = (0, 0);
+        for file in std::fs::read_dir(self.root.join("blobs"))? {
+            let path = file?.path();
+            let len = std::fs::metadata(&path)?.len();
+            before += len;
+            if path.extension().is_some() {
+                after += len;
+                continue;
+            }
+            match compress(&std::fs::read(&path)?)? {
+                Some(compressed) => {
+                    write_atomic(&path.with_extension("zst"), &compressed)?;
+                    std::fs::remove_file(&path)?;
+                    after += compressed.len() as u64;
+                }
+                None => after += len,
+            }
+        }
+        Ok((before, after))
+    }
+
+    pub fn save_snapshot(&self, snapshot: &AnalysisSnapshot) -> anyhow::Result<()> {
+        let path = self.snapshot_path(&snapshot.id);
+        std::fs::write(path, serde_json::to_vec_pretty(snapshot)?)?;
+        Ok(())
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.root.join("blobs").join(hash)
+    }
+
+    fn snapshot_path(&self, id: &str) -> PathBuf {
+        self.root.join("snapshots").join(format!("{}.json", id))
+    }
+}
+
+// Everything needed to re-validate or re-parse a past analysis
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnalysisSnapshot {
+    pub id: String,
+    pub created_at: u64,  // unix seconds
+    #[serde(default = "default_backend")]
+    pub backend: String,
+    pub model: String,
+    // The image exactly as the model received it, after downscaling and
+    // re-encoding
+    pub image_hash: String,
+    // The photo as supplied, kept for its EXIF and for re-running
+    // preprocessing. None when preprocessing left it unchanged, and for
+    // snapshots taken before it was recorded, whose image is the original.
+    #[serde(default)]
+    pub original_hash: Option<String>,
+    pub prompt_hash: String,
+    pub response_hash: String,
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    #[serde(default)]
+    pub input_tokens: Option<u64>,
+    #[serde(default)]
+    pub output_tokens: Option<u64>,
+    // Missing from snapshots taken before the stages were timed, which only
+    // have latency_ms
+    #[serde(default)]
+    pub timings: Option<Timings>,
+    // Missing from snapshots taken before it was recorded
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+}
+
+// How the request behind a snapshot was put together
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Provenance {
+    // "built-in", "custom" followed by the start of the edited prompt's hash,
+    // or the name of a special-purpose prompt
+    pub prompt: String,
+    // Site details (e.g. a camera's start zone) were appended to the prompt
+    pub site_context: bool,
+    // An observer's field observations were appended to the prompt
+    #[serde(default)]
+    pub field_observations: bool,
+    // A summary of the weather before the photo was appended to the prompt
+    #[serde(default)]
+    pub weather: bool,
+    // What was done to the photo before upload, e.g. "resized 4032×3024 →
+    // 2048×1536, re-encoded as JPEG"
+    pub preprocessing: String,
+    // Validation conflicts put back to the model before this answer, one
+    // per follow-up round
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<String>,
+    // The replies put back to the model, oldest first; filled in by
+    // AnalysisSnapshot::record
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub earlier_responses: Vec<EarlierTurn>,
+}
+
+// A reply from before a follow-up round, as stored
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EarlierTurn {
+    pub response_hash: String,
+    // What the model was told about it
+    pub followup: String,
+}
+
+// Where an analysis spent its time
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct Timings {
+    // Resizing and re-encoding the photo for the backend
+    pub preprocess_ms: u64,
+    // From sending the request until the whole response arrived
+    pub network_ms: u64,
+    // Reading and validating the reply
+    pub parse_ms: u64,
+}
+
+impl Timings {
+    pub fn total_ms(&self) -> u64 {
+        self.preprocess_ms + self.network_ms + self.parse_ms
+    }
+
+    // e.g. "prep 120 ms · network 3.4 s · parse 2 ms"
+    pub fn summary(&self) -> String {
+        format!(
+            "prep {} · network {} · parse {}",
+            format_ms(self.preprocess_ms),
+            format_ms(self.network_ms),
+            format_ms(self.parse_ms)
+        )
+    }
+}
+
+pub fn format_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{} ms", ms)
+    } else {
+        format!("{:.1} s", ms as f64 / 1000.0)
+    }
+}
+
+// Milliseconds since `started`
+pub fn elapsed_ms(started: std::time::Instant) -> u64 {
+    started.elapsed().as_millis() as u64
+}
+
+// Blobs are written on the analysis path, so a fast level; responses still
+// shrink several times over
+const BLOB_ZSTD_LEVEL: i32 = 3;
+
+// Compressed bytes, or None when compression would not make them smaller
+fn compress(bytes: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+    let compressed = zstd::encode_all(bytes, BLOB_ZSTD_LEVEL)?;
+    Ok((compressed.len() < bytes.len()).then_some(compressed))
+}
+
+// Writes a blob compressed to <path>.zst, or raw to <path> when that is no
+// larger
+fn write_blob(path: &std::path::Path, bytes: &[u8]) -> anyhow::Result<()> {
+    match compress(bytes)? {
+        Some(compressed) => write_atomic(&path.with_extension("zst"), &compressed),
+        None => write_atomic(path, bytes),
+    }
+}
+
+// Writes to a temp file first so a crash never leaves a truncated blob
+fn write_atomic(path: &std::path::Path, contents: &[u8]) -> anyhow::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+// Snapshots recorded before backends were tracked all came from OpenAI
+fn default_backend() -> String {
+    "openai".to_string()
+}
+
+// The two images behind a snapshot
+pub struct SnapshotImages<'a> {
+    // What the backend was sent
+    pub sent: &'a [u8],
+    // What the caller passed in
+    pub original: &'a [u8],
+}
+
+// A raw reply from before a follow-up round and the follow-up sent after it
+pub struct EarlierResponse {
+    pub raw_response: String,
+    pub followup: String,
+}
+
+pub struct SnapshotResponses<'a> {
+    // The response the analysis was parsed from
+    pub last: &'a str,
+    // Replies the model was asked to reconsider, oldest first
+    pub earlier: &'a [EarlierResponse],
+}
+
+impl AnalysisSnapshot {
+    pub fn record(
+        store: &BlobStore,
+        provider: &dyn crate::provider::VisionProvider,
+        images: SnapshotImages,
+        prompt: &str,
+        responses: SnapshotResponses,
+        timings: Timings,
+        mut provenance: Provenance,
+    ) -> anyhow::Result<Self> {
+        let model = provider.model();
+        let image_hash = store.put(images.sent)?;
+        let original_hash =
+            if images.original == images.sent { None } else { Some(store.put(images.original)?) };
+        let prompt_hash = store.put(prompt.as_bytes())?;
+        let response_hash = store.put(responses.last.as_bytes())?;
+        for earlier in responses.earlier {
+            provenance.earlier_responses.push(EarlierTurn {
+                response_hash: store.put(earlier.raw_response.as_bytes())?,
+                followup: earlier.followup.clone(),
+            });
+        }
+        let created_at = crate::storage::unix_now();
+
+        // The id covers the inputs and the time so identical re-runs stay distinct
+        let id = hash_bytes(
+            format!("{}:{}:{}:{}:{}", created_at, model, image_hash, prompt_hash, response_hash)
+                .as_bytes(),
+        );
+        // Every round is billed, not just the one that was kept
+        let (input_tokens, output_tokens) = std::iter::once(responses.last)
+            .chain(responses.earlier.iter().map(|e| e.raw_response.as_str()))
+            .map(token_usage)
+            .fold((None, None), |(input, output), (i, o)| (add_tokens(input, i), add_tokens(output, o)));
+        let snapshot = Self {
+            id,
+            created_at,
+            backend: provider.backend().to_string(),
+            model: model.to_string(),
+            image_hash,
+            original_hash,
+            prompt_hash,
+            response_hash,
+            // Kept as the whole request, as before the stages were split
+            latency_ms: Some(timings.preprocess_ms + timings.network_ms),
+            input_tokens,
+            output_tokens,
+            timings: Some(timings),
+            provenance: Some(provenance),
+        };
+        store.save_snapshot(&snapshot)?;
+        Ok(snapshot)
+    }
+
+    pub fn image(&self, store: &BlobStore) -> anyhow::Result<Vec<u8>> {
+        store.get(&self.image_hash)
+    }
+
+    // The photo as supplied, for its EXIF or to analyze it afresh
+    pub fn original(&self, store: &BlobStore) -> anyhow::Result<Vec<u8>> {
+        store.get(self.original_hash())
+    }
+
+    pub fn original_hash(&self) -> &str {
+        self.original_hash.as_deref().unwrap_or(&self.image_hash)
+    }
+
+    // Every blob the snapshot refers to
+    pub fn blob_hashes(&self) -> impl Iterator<Item = &String> {
+        let earlier = self.provenance.iter().flat_map(|p| &p.earlier_responses).map(|turn| &turn.response_hash);
+        [&self.image_hash, &self.prompt_hash, &self.response_hash]
+            .into_iter()
+            .chain(&self.original_hash)
+            .chain(earlier)
+    }
+
+    pub fn prompt(&self, store: &BlobStore) -> anyhow::Result<String> {
+        Ok(String::from_utf8(store.get(&self.prompt_hash)?)?)
+    }
+
+    pub fn raw_response(&self, store: &BlobStore) -> anyhow::Result<String> {
+        Ok(String::from_utf8(store.get(&self.response_hash)?)?)
+    }
+
+    // Re-runs parsing and validation against the stored response, picking up
+    // any schema or scoring changes made since the analysis was recorded
+    pub fn reparse(&self, store: &BlobStore) -> anyhow::Result<crate::model::AvalancheAnalysis> {
+        Ok(crate::provider::parse_response(&self.backend, &self.raw_response(store)?)?)
+    }
+}
+
+// Reads token counts from a raw response, accepting the usage field names
+// of the OpenAI, Anthropic and Ollama APIs
+fn add_tokens(total: Option<u64>, tokens: Option<u64>) -> Option<u64> {
+    match (total, tokens) {
+        (Some(total), Some(tokens)) => Some(total + tokens),
+        (total, tokens) => total.or(tokens),
+    }
+}
+
+fn token_usage(raw_response: &str) -> (Option<u64>, Option<u64>) {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(raw_response) else {
+        return (None, None);
+    };
+    let first = |keys: &[&str]| {
+        keys.iter().find_map(|key| {
+            json.pointer(key).and_then(|v| v.as_u64())
+        })
+    };
+    (
+        first(&["/usage/prompt_tokens", "/usage/input_tokens", "/prompt_eval_count"]),
+        first(&["/usage/completion_tokens", "/usage/output_tokens", "/eval_count"]),
+    )
+}
+
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
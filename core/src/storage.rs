@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+// Root directory for everything the app persists between runs
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = DATA_DIR_OVERRIDE.get() {
+        return dir.clone();
+    }
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("avalanche-classifier")
+}
+
+// Redirects all persisted data to `dir`. Must be called before anything is
+// loaded; only the first call takes effect.
+pub fn set_data_dir(dir: PathBuf) {
+    let _ = DATA_DIR_OVERRIDE.set(dir);
+}
+
+// Portable mode keeps data in a folder next to the executable so the app can
+// run from removable media without touching the host's user directories
+pub fn portable_data_dir() -> anyhow::Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    let dir = exe
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Executable has no parent directory"))?
+        .join("avalanche-classifier-data");
+    Ok(dir)
+}
+
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
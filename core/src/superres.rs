@@ -0,0 +1,127 @@
+//! Optional local super-resolution for small frames (e.g. 640×360 webcam
+//! snapshots), run before upload so the model sees more than a few pixels of
+//! crown. Any ESRGAN-style ONNX model taking and returning a 1×3×H×W RGB
+//! tensor in 0..1 works; the model file is the user's to choose. Inference
+//! needs a build with the `superres` feature (tract), without which images
+//! are passed through unchanged.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{PoisonError, RwLock};
+
+pub const AVAILABLE: bool = cfg!(feature = "superres");
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub enabled: bool,
+    pub model: Option<PathBuf>,
+    /// Only images whose longest side is below this are upscaled
+    pub below_px: u32,
+}
+
+impl Config {
+    pub const DEFAULT_BELOW_PX: u32 = 800;
+
+    const DEFAULT: Config = Config { enabled: false, model: None, below_px: Config::DEFAULT_BELOW_PX };
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::DEFAULT
+    }
+}
+
+static CONFIG: RwLock<Config> = RwLock::new(Config::DEFAULT);
+
+/// Applies to every following analysis
+pub fn configure(config: Config) {
+    *CONFIG.write().unwrap_or_else(PoisonError::into_inner) = config;
+}
+
+/// Upscales the image when enabled, a model is set and the image is small
+/// enough, returning PNG bytes and a description for the provenance, e.g.
+/// "super-resolved 640×360 → 2560×1440 (realesr.onnx)". Anything that goes
+/// wrong leaves the image as it was, noting why.
+pub fn apply(image_bytes: &[u8]) -> Option<(Vec<u8>, String)> {
+    let config = CONFIG.read().unwrap_or_else(PoisonError::into_inner).clone();
+    let model = config.model.filter(|_| config.enabled)?;
+    let (width, height) = crate::photo_meta::dimensions(image_bytes)?;
+    if width.max(height) >= config.below_px {
+        return None;
+    }
+    let name = model.file_name().map_or_else(|| model.display().to_string(), |n| n.to_string_lossy().into_owned());
+    match engine::upscale(&model, image_bytes) {
+        Ok((bytes, (new_width, new_height))) => {
+            Some((bytes, format!("super-resolved {}×{} → {}×{} ({})", width, height, new_width, new_height, name)))
+        }
+        Err(err) => {
+            let reason = format!("{:#}", err);
+            Some((image_bytes.to_vec(), format!("super-resolution skipped: {}", reason.lines().next().unwrap_or_default())))
+        }
+    }
+}
+
+#[cfg(feature = "superres")]
+mod engine {
+    use std::path::{Path, PathBuf};
+    use std::sync::{Mutex, PoisonError};
+    use tract_onnx::prelude::*;
+
+    type Plan = TypedRunnableModel<TypedModel>;
+
+    struct Cached {
+        path: PathBuf,
+        size: (u32, u32),
+        plan: Plan,
+    }
+
+    /// The optimized model for the last path and input size; webcam frames
+    /// keep their size, so it is rarely rebuilt
+    static PLAN: Mutex<Option<Cached>> = Mutex::new(None);
+
+    fn plan(model: &Path, width: u32, height: u32) -> TractResult<Plan> {
+        let mut cached = PLAN.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(c) = cached.as_ref().filter(|c| c.path == model && c.size == (width, height)) {
+            return Ok(c.plan.clone());
+        }
+        let plan = tract_onnx::onnx()
+            .model_for_path(model)?
+            .with_input_fact(0, f32::fact([1, 3, height as usize, width as usize]).into())?
+            .into_optimized()?
+            .into_runnable()?;
+        *cached = Some(Cached { path: model.to_path_buf(), size: (width, height), plan: plan.clone() });
+        Ok(plan)
+    }
+
+    pub fn upscale(model: &Path, image_bytes: &[u8]) -> anyhow::Result<(Vec<u8>, (u32, u32))> {
+        let rgb = crate::photo_meta::load_upright(image_bytes)?.to_rgb8();
+        let (width, height) = rgb.dimensions();
+        let input: Tensor = tract_ndarray::Array4::from_shape_fn(
+            (1, 3, height as usize, width as usize),
+            |(_, c, y, x)| f32::from(rgb.get_pixel(x as u32, y as u32)[c]) / 255.0,
+        )
+        .into();
+        let outputs = plan(model, width, height)?.run(tvec!(input.into()))?;
+        let output = outputs[0].to_array_view::<f32>()?;
+        let shape = output.shape();
+        if shape.len() != 4 || shape[1] != 3 {
+            anyhow::bail!("model output has shape {:?}, expected 1×3×H×W", shape);
+        }
+        let (new_height, new_width) = (shape[2] as u32, shape[3] as u32);
+        let upscaled = image::RgbImage::from_fn(new_width, new_height, |x, y| {
+            let channel = |c: usize| (output[[0, c, y as usize, x as usize]].clamp(0.0, 1.0) * 255.0).round() as u8;
+            image::Rgb([channel(0), channel(1), channel(2)])
+        });
+        let mut bytes = Vec::new();
+        upscaled.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Png)?;
+        Ok((bytes, (new_width, new_height)))
+    }
+}
+
+#[cfg(not(feature = "superres"))]
+mod engine {
+    pub fn upscale(_model: &std::path::Path, _image_bytes: &[u8]) -> anyhow::Result<(Vec<u8>, (u32, u32))> {
+        anyhow::bail!("this build has no `superres` feature")
+    }
+}
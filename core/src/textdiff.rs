@@ -0,0 +1,87 @@
+// Line diff between two texts, e.g. a custom prompt against the built-in
+// one. Consecutive changed lines form a hunk that can be reverted on its own.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+impl Line<'_> {
+    pub fn is_change(&self) -> bool {
+        !matches!(self, Line::Same(_))
+    }
+}
+
+// Lines of `old` and `new` in order, from a longest common subsequence.
+// Quadratic, which is fine for prompt-sized texts.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    // common[i][j]: longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            lines.push(Line::Added(new[j]));
+            j += 1;
+        } else {
+            lines.push(Line::Removed(old[i]));
+            i += 1;
+        }
+    }
+    lines
+}
+
+// Hunk each line belongs to, None for unchanged lines
+pub fn hunk_ids(lines: &[Line]) -> Vec<Option<usize>> {
+    let mut next = 0;
+    let mut in_hunk = false;
+    lines
+        .iter()
+        .map(|line| {
+            if !line.is_change() {
+                in_hunk = false;
+                return None;
+            }
+            if !in_hunk {
+                in_hunk = true;
+                next += 1;
+            }
+            Some(next - 1)
+        })
+        .collect()
+}
+
+pub fn hunk_count(lines: &[Line]) -> usize {
+    hunk_ids(lines).into_iter().flatten().max().map_or(0, |last| last + 1)
+}
+
+// The new text with one hunk put back the way it was in the old one
+pub fn revert_hunk(lines: &[Line], hunk: usize) -> String {
+    lines
+        .iter()
+        .zip(hunk_ids(lines))
+        .filter_map(|(line, id)| match (line, id == Some(hunk)) {
+            (Line::Same(text), _) | (Line::Removed(text), true) | (Line::Added(text), false) => Some(*text),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
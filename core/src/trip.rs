@@ -0,0 +1,216 @@
+use crate::camera::Location;
+use crate::gpx::{distance_m, Gpx};
+use crate::history::{History, HistoryEntry};
+use crate::zones::{severity, worst_case, WorstCase};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// Routes without named points are cut into legs of about this length
+const DEFAULT_LEG_M: f64 = 1000.0;
+
+// Located analyses within this distance of a segment's points count for it
+// without being tagged
+pub const NEARBY_M: f64 = 250.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Segment {
+    pub name: String,
+    pub points: Vec<Location>,
+    // Analyses tagged to this segment by hand
+    #[serde(default)]
+    pub analysis_ids: Vec<String>,
+}
+
+impl Segment {
+    pub fn length_m(&self) -> f64 {
+        self.points.windows(2).map(|p| distance_m(p[0], p[1])).sum()
+    }
+
+    pub fn covers(&self, entry: &HistoryEntry) -> bool {
+        self.analysis_ids.iter().any(|id| id == entry.id())
+            || entry
+                .location
+                .is_some_and(|l| self.points.iter().any(|p| distance_m(*p, l) <= NEARBY_M))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TripPlan {
+    pub id: u64,
+    pub name: String,
+    pub created_at: u64,  // unix seconds
+    pub segments: Vec<Segment>,
+}
+
+impl TripPlan {
+    // Splits the route at its named points ("Col → Summit"), or into ~1 km
+    // legs when fewer than two points are named
+    pub fn from_gpx(name: impl Into<String>, gpx: &Gpx) -> anyhow::Result<Self> {
+        if gpx.track.len() < 2 {
+            anyhow::bail!("GPX file needs a track or route with at least two points");
+        }
+        let named: Vec<usize> = gpx
+            .track
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.name.as_ref().is_some_and(|n| !n.trim().is_empty()))
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut segments = Vec::new();
+        if named.len() >= 2 {
+            let mut cuts = named.clone();
+            // Keep the stretches before the first and after the last named point
+            if cuts[0] != 0 {
+                cuts.insert(0, 0);
+            }
+            if cuts[cuts.len() - 1] != gpx.track.len() - 1 {
+                cuts.push(gpx.track.len() - 1);
+            }
+            let label = |i: usize| {
+                gpx.track[i].name.clone().unwrap_or_else(|| if i == 0 { "Start".into() } else { "End".into() })
+            };
+            for pair in cuts.windows(2) {
+                segments.push(Segment {
+                    name: format!("{} → {}", label(pair[0]), label(pair[1])),
+                    points: gpx.track[pair[0]..=pair[1]].iter().map(|p| p.location).collect(),
+                    analysis_ids: Vec::new(),
+                });
+            }
+        } else {
+            let mut start = 0;
+            let mut travelled = 0.0;
+            let mut leg_start_m = 0.0;
+            for i in 1..gpx.track.len() {
+                travelled += distance_m(gpx.track[i - 1].location, gpx.track[i].location);
+                if travelled - leg_start_m >= DEFAULT_LEG_M || i == gpx.track.len() - 1 {
+                    segments.push(Segment {
+                        name: format!("km {:.1}–{:.1}", leg_start_m / 1000.0, travelled / 1000.0),
+                        points: gpx.track[start..=i].iter().map(|p| p.location).collect(),
+                        analysis_ids: Vec::new(),
+                    });
+                    start = i;
+                    leg_start_m = travelled;
+                }
+            }
+        }
+        Ok(Self { id: 0, name: name.into(), created_at: 0, segments })
+    }
+}
+
+pub struct TripPlans {
+    path: PathBuf,
+    pub plans: Vec<TripPlan>,
+}
+
+impl TripPlans {
+    pub fn load_default() -> Self {
+        let path = crate::storage::data_dir().join("trips.json");
+        let plans = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self { path, plans }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&self.plans)?)?;
+        Ok(())
+    }
+
+    pub fn add(&mut self, mut plan: TripPlan) -> anyhow::Result<u64> {
+        plan.id = self.plans.iter().map(|p| p.id).max().unwrap_or(0) + 1;
+        plan.created_at = crate::storage::unix_now();
+        let id = plan.id;
+        self.plans.push(plan);
+        self.save()?;
+        Ok(id)
+    }
+
+    // Tags an analysis to one segment of a plan, once
+    pub fn tag(&mut self, plan_id: u64, segment: usize, analysis_id: &str) -> anyhow::Result<()> {
+        let segment = self
+            .plans
+            .iter_mut()
+            .find(|p| p.id == plan_id)
+            .and_then(|p| p.segments.get_mut(segment))
+            .ok_or_else(|| anyhow::anyhow!("No such route segment"))?;
+        if !segment.analysis_ids.iter().any(|id| id == analysis_id) {
+            segment.analysis_ids.push(analysis_id.to_string());
+        }
+        self.save()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SegmentAssessment {
+    pub name: String,
+    pub length_m: f64,
+    pub analyses: usize,
+    pub worst: Option<WorstCase>,
+}
+
+impl SegmentAssessment {
+    pub fn severity(&self) -> Option<u8> {
+        self.worst.as_ref().map(|w| severity(&w.avalanche_type))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RouteAssessment {
+    pub plan: String,
+    pub segments: Vec<SegmentAssessment>,
+    // Indices of the segments sharing the highest severity above "none"
+    pub riskiest: Vec<usize>,
+}
+
+impl RouteAssessment {
+    // Plain-text version for trip notes
+    pub fn text(&self) -> String {
+        let mut out = format!("Route assessment: {}\n", self.plan);
+        for (i, segment) in self.segments.iter().enumerate() {
+            let marker = if self.riskiest.contains(&i) { "⚠ " } else { "" };
+            out.push_str(&format!("- {}{} ({:.1} km): ", marker, segment.name, segment.length_m / 1000.0));
+            match &segment.worst {
+                Some(worst) => out.push_str(&format!(
+                    "worst {} at {:.0}% from {} analyses\n",
+                    worst.avalanche_type, worst.confidence, segment.analyses
+                )),
+                None => out.push_str("no analyses\n"),
+            }
+        }
+        let unassessed = self.segments.iter().filter(|s| s.worst.is_none()).count();
+        if unassessed > 0 {
+            out.push_str(&format!("{} segments have no photo analysis\n", unassessed));
+        }
+        out
+    }
+}
+
+// Worst-case classification per segment from tagged and nearby analyses
+pub fn assess(plan: &TripPlan, history: &History) -> RouteAssessment {
+    let segments: Vec<SegmentAssessment> = plan
+        .segments
+        .iter()
+        .map(|segment| {
+            let covered: Vec<&HistoryEntry> = history.entries.iter().filter(|e| segment.covers(e)).collect();
+            SegmentAssessment {
+                name: segment.name.clone(),
+                length_m: segment.length_m(),
+                analyses: covered.len(),
+                worst: worst_case(&covered),
+            }
+        })
+        .collect();
+
+    let top = segments.iter().filter_map(SegmentAssessment::severity).max().unwrap_or(0);
+    let riskiest = if top == 0 {
+        Vec::new()
+    } else {
+        (0..segments.len()).filter(|&i| segments[i].severity() == Some(top)).collect()
+    };
+    RouteAssessment { plan: plan.name.clone(), segments, riskiest }
+}
@@ -0,0 +1,113 @@
+use crate::error::{parse_failure, AnalysisError};
+use crate::provider::{extract_reply, timed_request, VisionProvider};
+use crate::snapshot::{
+    elapsed_ms, AnalysisSnapshot, BlobStore, Provenance, SnapshotImages, SnapshotResponses,
+};
+use serde::{Deserialize, Serialize};
+
+pub const VERIFICATION_PROMPT: &str = r#"This photo was taken after an avalanche control shot (explosive placed in a start zone). Determine whether the shot produced a result. Return a JSON object with this structure:
+{
+    "result_produced": boolean,
+    "result_size": "D1"|"D1.5"|"D2"|"D2.5"|"D3"|"D3.5"|"D4"|"D4.5"|"D5"|null,
+    "propagation": "none"|"localized"|"propagated"|"full-path",
+    "crown_visible": boolean,
+    "debris_reached": "start-zone"|"track"|"runout"|"beyond-runout"|null,
+    "confidence_level": 0.0-100.0,
+    "observations": string[]
+}
+
+GUIDELINES:
+- A blast crater or sluffing confined to the crater is NOT a result: result_produced=false, propagation="localized" at most.
+- "localized": snow moved but the release did not spread beyond the immediate shot area.
+- "propagated": a fracture or release spread across the slope away from the shot point.
+- "full-path": the avalanche ran the track and reached the runout.
+- result_size follows the destructive size scale (D1 relatively harmless to people ... D5 largest known). Use null when there is no result.
+- Describe visible evidence in observations (crown line, debris, blast marks, tracks).
+- If the photo does not show the shot area clearly, say so in observations and keep confidence low."#;
+
+pub const PROPAGATION: [&str; 4] = ["none", "localized", "propagated", "full-path"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ControlVerification {
+    pub result_produced: bool,
+    pub result_size: Option<String>,
+    pub propagation: String,
+    pub crown_visible: bool,
+    pub debris_reached: Option<String>,
+    pub confidence_level: f32,
+    #[serde(default)]
+    pub observations: Vec<String>,
+    // Recorded exchange; absent if the blob store was unavailable
+    #[serde(default)]
+    pub snapshot_id: Option<String>,
+}
+
+impl ControlVerification {
+    pub fn summary(&self) -> String {
+        if self.result_produced {
+            format!(
+                "Result {} ({}), {:.0}% confidence",
+                self.result_size.as_deref().unwrap_or("size unknown"),
+                self.propagation,
+                self.confidence_level
+            )
+        } else {
+            format!("No result ({}), {:.0}% confidence", self.propagation, self.confidence_level)
+        }
+    }
+}
+
+pub async fn verify_control_result(
+    provider: &dyn VisionProvider,
+    image_bytes: &[u8],
+) -> Result<ControlVerification, AnalysisError> {
+    let (response_text, mut timings, prepared) = timed_request(provider, image_bytes, VERIFICATION_PROMPT).await?;
+    let started = std::time::Instant::now();
+    let parsed = extract_reply(provider.backend(), &response_text).and_then(|reply| parse_verification(&reply));
+    timings.parse_ms = elapsed_ms(started);
+
+    let provenance = Provenance {
+        prompt: "control verification".to_string(),
+        site_context: false,
+        field_observations: false,
+        weather: false,
+        preprocessing: prepared.description,
+        conflicts: Vec::new(),
+        earlier_responses: Vec::new(),
+    };
+    let snapshot = BlobStore::open_default()
+        .and_then(|store| {
+            let images = SnapshotImages { sent: &prepared.bytes, original: image_bytes };
+            let responses = SnapshotResponses { last: &response_text, earlier: &[] };
+            AnalysisSnapshot::record(&store, provider, images, VERIFICATION_PROMPT, responses, timings, provenance)
+        })
+        .ok();
+
+    let mut verification = parsed?;
+    verification.snapshot_id = snapshot.map(|s| s.id);
+    Ok(verification)
+}
+
+pub fn parse_verification(content: &str) -> Result<ControlVerification, AnalysisError> {
+    let mut verification: ControlVerification =
+        serde_json::from_str(content).map_err(|e| parse_failure(content, e))?;
+
+    if !PROPAGATION.contains(&verification.propagation.as_str()) {
+        return Err(AnalysisError::InvalidValue { field: "propagation", value: verification.propagation });
+    }
+    if !(0.0..=100.0).contains(&verification.confidence_level) {
+        return Err(AnalysisError::InvalidValue {
+            field: "confidence level",
+            value: verification.confidence_level.to_string(),
+        });
+    }
+    // A size only makes sense with a result
+    if !verification.result_produced {
+        verification.result_size = None;
+    } else if let Some(size) = &verification.result_size {
+        if !crate::missions::RESULT_SIZES[1..].contains(&size.as_str()) {
+            return Err(AnalysisError::InvalidValue { field: "result size", value: size.clone() });
+        }
+    }
+    Ok(verification)
+}
@@ -0,0 +1,174 @@
+// Which terrain the camera could actually see, and the slopes among it the
+// photo most likely shows. Works on the grid from `dem`, with the camera at
+// its centre.
+
+use crate::camera::Location;
+use crate::dem::Dem;
+use serde::{Deserialize, Serialize};
+
+// Camera height above the ground at the photo location
+pub const EYE_HEIGHT_M: f32 = 2.0;
+// Regions smaller than this many grid cells are treated as noise
+const MIN_CELLS: usize = 3;
+// Candidates offered for confirmation, largest first
+const MAX_CANDIDATES: usize = 5;
+
+// Line-of-sight visibility of every grid point from the centre
+pub fn viewshed(grid: &Dem) -> Vec<bool> {
+    let center = (grid.size / 2) as f32;
+    let eye = grid.height_at(center, center) + EYE_HEIGHT_M;
+    let mut visible = vec![false; grid.size * grid.size];
+    for row in 0..grid.size {
+        for col in 0..grid.size {
+            let (dc, dr) = (col as f32 - center, row as f32 - center);
+            let cells = dc.hypot(dr);
+            if cells < 1.0 {
+                visible[grid.index(col, row)] = true;
+                continue;
+            }
+            let target = (grid.height(col, row) - eye) / cells;
+            // Two samples per cell along the sight line
+            let steps = (cells * 2.0).ceil() as usize;
+            let blocked = (1..steps).any(|step| {
+                let t = step as f32 / steps as f32;
+                let h = grid.height_at(center + dc * t, center + dr * t);
+                (h - eye) / (cells * t) > target + 1e-3
+            });
+            visible[grid.index(col, row)] = !blocked;
+        }
+    }
+    visible
+}
+
+// Outline and summary of a slope, kept with an analysis once confirmed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Slope {
+    pub polygon: Vec<Location>,
+    pub area_m2: f32,
+    pub mean_slope_deg: f32,
+    // Downslope direction, degrees clockwise from north
+    pub aspect_deg: f32,
+    pub min_elevation_m: f32,
+    pub max_elevation_m: f32,
+    // From the camera to the nearest point of the slope
+    pub distance_m: f32,
+}
+
+impl Slope {
+    // "NE-facing 36° slope, 2450–2700 m, 12.0 ha, 800 m away"
+    pub fn describe(&self) -> String {
+        format!(
+            "{}-facing {:.0}° slope, {:.0}–{:.0} m, {:.1} ha, {:.0} m away",
+            crate::orientation::compass_point(self.aspect_deg),
+            self.mean_slope_deg,
+            self.min_elevation_m,
+            self.max_elevation_m,
+            self.area_m2 / 10_000.0,
+            self.distance_m
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SlopeCandidate {
+    pub slope: Slope,
+    // Grid indices making up the region, for drawing it
+    pub cells: Vec<usize>,
+}
+
+// Connected regions of visible start-zone-angle terrain in the camera's
+// view, largest first
+pub fn candidates(grid: &Dem, bearing: Option<f32>) -> Vec<SlopeCandidate> {
+    let visible = viewshed(grid);
+    let eligible: Vec<bool> = grid.highlight(bearing).iter().zip(&visible).map(|(h, v)| *h && *v).collect();
+
+    let mut seen = vec![false; eligible.len()];
+    let mut regions = Vec::new();
+    for start in 0..eligible.len() {
+        if !eligible[start] || seen[start] {
+            continue;
+        }
+        let mut region = Vec::new();
+        let mut stack = vec![start];
+        seen[start] = true;
+        while let Some(i) = stack.pop() {
+            region.push(i);
+            let (col, row) = (i % grid.size, i / grid.size);
+            let neighbours = [
+                (col > 0).then(|| i - 1),
+                (col + 1 < grid.size).then(|| i + 1),
+                (row > 0).then(|| i - grid.size),
+                (row + 1 < grid.size).then(|| i + grid.size),
+            ];
+            for n in neighbours.into_iter().flatten() {
+                if eligible[n] && !seen[n] {
+                    seen[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        if region.len() >= MIN_CELLS {
+            regions.push(region);
+        }
+    }
+    regions.sort_by_key(|r| std::cmp::Reverse(r.len()));
+    regions.truncate(MAX_CANDIDATES);
+    regions.into_iter().map(|cells| SlopeCandidate { slope: summarize(grid, &cells), cells }).collect()
+}
+
+fn summarize(grid: &Dem, cells: &[usize]) -> Slope {
+    let half = grid.spacing_m / 2.0;
+    let mut corners = Vec::with_capacity(cells.len() * 4);
+    let (mut slope_sum, mut aspect_x, mut aspect_y) = (0.0, 0.0, 0.0);
+    let (mut min_elevation, mut max_elevation, mut distance) = (f32::MAX, f32::MIN, f32::MAX);
+    for &i in cells {
+        let (col, row) = (i % grid.size, i / grid.size);
+        let (east, north) = grid.offset(col, row);
+        for (dx, dy) in [(-half, -half), (half, -half), (half, half), (-half, half)] {
+            corners.push((east + dx, north + dy));
+        }
+        slope_sum += grid.slope_deg(col, row);
+        // Aspects average as directions, so north-west and north-east give north
+        let aspect = grid.aspect_deg(col, row).to_radians();
+        aspect_x += aspect.sin();
+        aspect_y += aspect.cos();
+        let height = grid.height(col, row);
+        min_elevation = min_elevation.min(height);
+        max_elevation = max_elevation.max(height);
+        distance = distance.min(east.hypot(north));
+    }
+    Slope {
+        polygon: convex_hull(corners).into_iter().map(|(east, north)| grid.location_at(east, north)).collect(),
+        area_m2: cells.len() as f32 * grid.spacing_m * grid.spacing_m,
+        mean_slope_deg: slope_sum / cells.len() as f32,
+        aspect_deg: crate::orientation::normalize(aspect_x.atan2(aspect_y).to_degrees()),
+        min_elevation_m: min_elevation,
+        max_elevation_m: max_elevation,
+        distance_m: distance,
+    }
+}
+
+// Andrew's monotone chain; counter-clockwise, without repeating the first point
+fn convex_hull(mut points: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: (f32, f32), a: (f32, f32), b: (f32, f32)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(f32, f32)> = Vec::with_capacity(points.len() * 2);
+    for pass in 0..2 {
+        let start = hull.len();
+        let ordered: Box<dyn Iterator<Item = &(f32, f32)>> =
+            if pass == 0 { Box::new(points.iter()) } else { Box::new(points.iter().rev()) };
+        for &p in ordered {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point of each half starts the other
+        hull.pop();
+    }
+    hull
+}
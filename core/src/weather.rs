@@ -0,0 +1,187 @@
+// Weather in the days before a photo was taken, from Open-Meteo's hourly
+// model data (free, no API key). New snow, warming and wind loading are
+// what most avalanche cycles follow, so a summary goes into the prompt and
+// is kept with the result.
+
+use crate::camera::Location;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+// Reanalysis archive, for photos older than the forecast API keeps
+const ARCHIVE_URL: &str = "https://archive-api.open-meteo.com/v1/archive";
+const FORECAST_HISTORY_DAYS: i64 = 80;
+const HOURLY: &str = "temperature_2m,snowfall,wind_speed_10m,wind_direction_10m,wind_gusts_10m";
+
+// Hours summarized before the capture time
+pub const WINDOW_HOURS: i64 = 72;
+
+// An analysis does not wait longer than this for the weather
+pub const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize)]
+struct Reply {
+    hourly: Hourly,
+}
+
+#[derive(Deserialize)]
+struct Hourly {
+    time: Vec<i64>,
+    temperature_2m: Vec<Option<f32>>,
+    snowfall: Vec<Option<f32>>,
+    wind_speed_10m: Vec<Option<f32>>,
+    wind_direction_10m: Vec<Option<f32>>,
+    wind_gusts_10m: Vec<Option<f32>>,
+}
+
+// The WINDOW_HOURS before a photo, at its location
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeatherSummary {
+    // Unix seconds the summary ends at, the photo's capture time
+    pub until: i64,
+    pub snowfall_24h_cm: f32,
+    pub snowfall_72h_cm: f32,
+    // Air temperature at 2 m in °C: at the capture time, the range over the
+    // window, and the change over the last 24 hours
+    pub temperature_c: Option<f32>,
+    pub temperature_min_c: Option<f32>,
+    pub temperature_max_c: Option<f32>,
+    pub temperature_change_24h_c: Option<f32>,
+    // Hours above 0 °C in the last 24
+    pub hours_above_freezing_24h: u32,
+    // Wind at 10 m in km/h
+    pub wind_max_kmh: Option<f32>,
+    pub gust_max_kmh: Option<f32>,
+    // Speed-weighted mean direction the wind blew from, one of
+    // model::ASPECTS
+    pub wind_from: Option<String>,
+}
+
+// Fetches and summarizes the weather before `at` (unix seconds)
+pub async fn recent_weather(client: &reqwest::Client, location: Location, at: i64) -> anyhow::Result<WeatherSummary> {
+    let start = chrono::DateTime::from_timestamp(at - WINDOW_HOURS * 3600, 0)
+        .ok_or_else(|| anyhow::anyhow!("Capture time is out of range"))?;
+    let end = chrono::DateTime::from_timestamp(at, 0).ok_or_else(|| anyhow::anyhow!("Capture time is out of range"))?;
+    let age_days = (chrono::Utc::now() - end).num_days();
+    if age_days < 0 {
+        anyhow::bail!("Capture time is in the future");
+    }
+    let url = if age_days > FORECAST_HISTORY_DAYS { ARCHIVE_URL } else { FORECAST_URL };
+    let reply: Reply = client
+        .get(url)
+        .query(&[
+            ("latitude", location.latitude.to_string()),
+            ("longitude", location.longitude.to_string()),
+            ("hourly", HOURLY.to_string()),
+            ("start_date", start.format("%Y-%m-%d").to_string()),
+            ("end_date", end.format("%Y-%m-%d").to_string()),
+            ("timeformat", "unixtime".to_string()),
+            ("timezone", "GMT".to_string()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    summarize(&reply.hourly, at).ok_or_else(|| anyhow::anyhow!("No weather data before the capture time"))
+}
+
+// The weather before a photo, or None when it could not be had in
+// LOOKUP_TIMEOUT; a missing summary never holds up an analysis
+pub async fn lookup(client: &reqwest::Client, location: Location, at: i64) -> Option<WeatherSummary> {
+    tokio::time::timeout(LOOKUP_TIMEOUT, recent_weather(client, location, at)).await.ok()?.ok()
+}
+
+fn summarize(hourly: &Hourly, at: i64) -> Option<WeatherSummary> {
+    let hours: Vec<usize> = (0..hourly.time.len())
+        .filter(|&i| hourly.time[i] <= at && hourly.time[i] > at - WINDOW_HOURS * 3600)
+        .collect();
+    if hours.is_empty() {
+        return None;
+    }
+    let last_day: Vec<usize> = hours.iter().copied().filter(|&i| hourly.time[i] > at - 24 * 3600).collect();
+    let value = |series: &[Option<f32>], i: usize| series.get(i).copied().flatten();
+    let sum = |series: &[Option<f32>], hours: &[usize]| hours.iter().filter_map(|&i| value(series, i)).sum::<f32>();
+    let max = |series: &[Option<f32>]| hours.iter().filter_map(|&i| value(series, i)).reduce(f32::max);
+    let min = |series: &[Option<f32>]| hours.iter().filter_map(|&i| value(series, i)).reduce(f32::min);
+
+    let temperatures = &hourly.temperature_2m;
+    let now = hours.last().and_then(|&i| value(temperatures, i));
+    let day_before = last_day.first().and_then(|&i| value(temperatures, i));
+
+    // Directions are averaged as vectors, so N and NNW do not average to S
+    let (east, north) = hours.iter().fold((0.0f32, 0.0f32), |(east, north), &i| {
+        match (value(&hourly.wind_speed_10m, i), value(&hourly.wind_direction_10m, i)) {
+            (Some(speed), Some(direction)) => {
+                let radians = direction.to_radians();
+                (east + speed * radians.sin(), north + speed * radians.cos())
+            }
+            _ => (east, north),
+        }
+    });
+    let wind_from = (east.hypot(north) > 0.0).then(|| {
+        let degrees = east.atan2(north).to_degrees().rem_euclid(360.0);
+        crate::model::ASPECTS[((degrees + 22.5) / 45.0) as usize % 8].to_string()
+    });
+
+    Some(WeatherSummary {
+        until: at,
+        snowfall_24h_cm: sum(&hourly.snowfall, &last_day),
+        snowfall_72h_cm: sum(&hourly.snowfall, &hours),
+        temperature_c: now,
+        temperature_min_c: min(temperatures),
+        temperature_max_c: max(temperatures),
+        temperature_change_24h_c: now.zip(day_before).map(|(now, before)| now - before),
+        hours_above_freezing_24h: last_day.iter().filter(|&&i| value(temperatures, i).is_some_and(|t| t > 0.0)).count()
+            as u32,
+        wind_max_kmh: max(&hourly.wind_speed_10m),
+        gust_max_kmh: max(&hourly.wind_gusts_10m),
+        wind_from,
+    })
+}
+
+impl WeatherSummary {
+    // One line per measure, e.g. "New snow: 12 cm in 24 h, 35 cm in 72 h"
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines =
+            vec![format!("New snow: {:.0} cm in 24 h, {:.0} cm in 72 h", self.snowfall_24h_cm, self.snowfall_72h_cm)];
+        if let Some(now) = self.temperature_c {
+            let mut line = format!("Air temperature: {:.0} °C", now);
+            if let (Some(low), Some(high)) = (self.temperature_min_c, self.temperature_max_c) {
+                line.push_str(&format!(" ({:.0} to {:.0} °C over 72 h", low, high));
+                if let Some(change) = self.temperature_change_24h_c {
+                    line.push_str(&format!(", {:+.0} °C in 24 h", change));
+                }
+                line.push(')');
+            }
+            lines.push(line);
+        }
+        if self.hours_above_freezing_24h > 0 {
+            lines.push(format!("Above freezing for {} of the last 24 hours", self.hours_above_freezing_24h));
+        }
+        if let Some(wind) = self.wind_max_kmh {
+            let mut line = format!("Wind: up to {:.0} km/h", wind);
+            if let Some(gust) = self.gust_max_kmh {
+                line.push_str(&format!(", gusts {:.0} km/h", gust));
+            }
+            if let Some(from) = &self.wind_from {
+                line.push_str(&format!(", mostly from {}", from));
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    // The section appended to the prompt
+    pub fn prompt_section(&self) -> String {
+        let mut section = String::from(
+            "RECENT WEATHER (model data for the photo's location over the 72 hours before it was taken; \
+             background only, never overrides what is visible):",
+        );
+        for line in self.lines() {
+            section.push_str("\n- ");
+            section.push_str(&line);
+        }
+        section
+    }
+}
@@ -0,0 +1,217 @@
+use crate::camera::Location;
+use crate::history::{History, HistoryEntry};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// A piece of terrain that gets its own open/closed call, e.g. "North Bowl"
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Zone {
+    pub name: String,
+    // Outline in order; the last point connects back to the first
+    pub polygon: Vec<Location>,
+    // Analyses from these sources (camera names) count for the zone even
+    // when they carry no location
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+impl Zone {
+    // Even-odd ray casting; fine at ski-area scale where the polygon is
+    // small enough to treat lat/lon as planar
+    pub fn contains(&self, point: Location) -> bool {
+        let n = self.polygon.len();
+        if n < 3 {
+            return false;
+        }
+        let mut inside = false;
+        let mut j = n - 1;
+        for i in 0..n {
+            let (a, b) = (self.polygon[i], self.polygon[j]);
+            if (a.latitude > point.latitude) != (b.latitude > point.latitude) {
+                let crossing = a.longitude
+                    + (point.latitude - a.latitude) / (b.latitude - a.latitude) * (b.longitude - a.longitude);
+                if point.longitude < crossing {
+                    inside = !inside;
+                }
+            }
+            j = i;
+        }
+        inside
+    }
+
+    pub fn covers(&self, entry: &HistoryEntry) -> bool {
+        entry.location.is_some_and(|l| self.contains(l))
+            || entry.source.as_ref().is_some_and(|s| self.sources.contains(s))
+    }
+}
+
+// Parses one "lat, lon" pair per line, skipping blank lines
+pub fn parse_polygon(text: &str) -> anyhow::Result<Vec<Location>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (lat, lon) = line
+                .split_once(',')
+                .ok_or_else(|| anyhow::anyhow!("Expected \"lat, lon\" but got \"{}\"", line))?;
+            let latitude: f64 = lat.trim().parse()?;
+            let longitude: f64 = lon.trim().parse()?;
+            if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                anyhow::bail!("Coordinate out of range: {}", line);
+            }
+            Ok(Location { latitude, longitude })
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Area {
+    pub name: String,
+    pub zones: Vec<Zone>,
+}
+
+pub struct Areas {
+    path: PathBuf,
+    pub areas: Vec<Area>,
+}
+
+impl Areas {
+    pub fn load_default() -> Self {
+        let path = crate::storage::data_dir().join("areas.json");
+        let areas = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self { path, areas }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&self.areas)?)?;
+        Ok(())
+    }
+}
+
+// Higher is worse. Unknown labels rank just above "none" so they are not
+// hidden behind a clean result.
+pub fn severity(avalanche_type: &str) -> u8 {
+    match avalanche_type {
+        "none" => 0,
+        "loose-snow" | "wet-loose" | "cornice" => 2,
+        "powder" => 3,
+        "slab" | "wet-slab" | "glide" | "slush" => 4,
+        _ => 1,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorstCase {
+    pub avalanche_type: String,
+    pub confidence: f32,
+    pub analysis_id: String,
+    pub at: u64,
+}
+
+// Most severe effective label among the entries, higher confidence first
+// on ties. Rejected entries are skipped.
+pub fn worst_case(entries: &[&HistoryEntry]) -> Option<WorstCase> {
+    entries
+        .iter()
+        .filter_map(|e| Some((e, e.effective_type()?)))
+        .max_by(|(a, la), (b, lb)| {
+            severity(la)
+                .cmp(&severity(lb))
+                .then(a.analysis.confidence_level.total_cmp(&b.analysis.confidence_level))
+        })
+        .map(|(e, label)| WorstCase {
+            avalanche_type: label.to_string(),
+            confidence: e.analysis.confidence_level,
+            analysis_id: e.id().to_string(),
+            at: e.snapshot.created_at,
+        })
+}
+
+#[derive(Debug, Clone)]
+pub struct ZoneStatus {
+    pub zone: String,
+    pub analyses: usize,
+    pub unreviewed: usize,
+    pub worst: Option<WorstCase>,
+}
+
+impl ZoneStatus {
+    pub fn status(&self) -> &'static str {
+        match &self.worst {
+            None => "No data",
+            Some(w) if w.avalanche_type == "none" => "No activity observed",
+            Some(_) => "Avalanche activity",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TerrainSummary {
+    pub area: String,
+    pub since: u64,
+    pub zones: Vec<ZoneStatus>,
+    // Analyses in the window that fell in no zone of this area
+    pub unassigned: usize,
+}
+
+impl TerrainSummary {
+    // Plain-text version for pasting into meeting notes
+    pub fn text(&self) -> String {
+        let mut out = format!("Terrain status: {}\n", self.area);
+        for zone in &self.zones {
+            out.push_str(&format!("- {}: {}", zone.zone, zone.status()));
+            if let Some(worst) = &zone.worst {
+                out.push_str(&format!(
+                    " (worst: {} at {:.0}%, {} analyses",
+                    worst.avalanche_type, worst.confidence, zone.analyses
+                ));
+                if zone.unreviewed > 0 {
+                    out.push_str(&format!(", {} awaiting review", zone.unreviewed));
+                }
+                out.push(')');
+            }
+            out.push('\n');
+        }
+        if self.unassigned > 0 {
+            out.push_str(&format!("{} analyses outside any zone\n", self.unassigned));
+        }
+        out
+    }
+}
+
+// Worst-case classification per zone over the entries recorded since `since`.
+// Reviewed labels win over the AI output and rejected entries are left out.
+pub fn summarize(area: &Area, history: &History, since: u64) -> TerrainSummary {
+    let entries: Vec<&HistoryEntry> = history
+        .entries
+        .iter()
+        .filter(|e| e.snapshot.created_at >= since && e.effective_type().is_some())
+        .collect();
+
+    let zones = area
+        .zones
+        .iter()
+        .map(|zone| {
+            let covered: Vec<&HistoryEntry> = entries.iter().copied().filter(|e| zone.covers(e)).collect();
+            ZoneStatus {
+                zone: zone.name.clone(),
+                analyses: covered.len(),
+                unreviewed: covered.iter().filter(|e| e.needs_review()).count(),
+                worst: worst_case(&covered),
+            }
+        })
+        .collect();
+
+    let unassigned = entries
+        .iter()
+        .filter(|e| !area.zones.iter().any(|z| z.covers(e)))
+        .count();
+
+    TerrainSummary { area: area.name.clone(), since, zones, unassigned }
+}
@@ -0,0 +1,51 @@
+/*
+ * C interface to the avalanche classifier. Strings are NUL-terminated UTF-8
+ * JSON. Every function returns a newly allocated JSON string, either
+ *   {"ok": true, "analysis": {...}}
+ * or
+ *   {"ok": false, "error": {"kind": "...", "message": "...", "hint": ...}}
+ * which must be released with avalanche_string_free. NULL is returned only
+ * if the reply could not be allocated.
+ */
+#ifndef AVALANCHE_CLASSIFIER_H
+#define AVALANCHE_CLASSIFIER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Classifies an encoded photo (JPEG, PNG, WebP, ...). Blocks until the
+ * backend answers. config is a JSON object:
+ *   backend          "openai", "anthropic" or "ollama" (required)
+ *   api_key          defaults to OPENAI_API_KEY / ANTHROPIC_API_KEY
+ *   model            defaults to the backend's default model
+ *   base_url         OpenAI-compatible endpoint or Ollama server
+ *   upload           {"max_px": 2048, "format": "Jpeg", "quality": 85}
+ *   prompt           replaces the built-in analysis prompt
+ *   context          site description appended to the prompt
+ *   soft_validation  return weak results with warnings (default false)
+ *   conflict_rounds  follow-ups on a type conflict (default 2)
+ *   record_snapshot  keep the exchange in the data directory (default false)
+ * On success the reply also carries "snapshot" (null unless recorded).
+ */
+char *avalanche_classify_image_bytes(const char *config, const uint8_t *bytes, size_t len);
+
+/*
+ * Validates and scores a model reply already at hand (the JSON object the
+ * analysis prompt asks for), without any network call.
+ */
+char *avalanche_parse_analysis(const char *reply, bool soft);
+
+/* Releases a string returned by the functions above. NULL is ignored. */
+void avalanche_string_free(char *text);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
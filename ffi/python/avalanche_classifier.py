@@ -0,0 +1,74 @@
+"""ctypes bindings for the avalanche classifier's C interface.
+
+Build the library with `cargo build --release -p avalanche-classifier-ffi`
+and point AVALANCHE_CLASSIFIER_LIB at it, or pass its path to Classifier.
+
+    from avalanche_classifier import Classifier, AnalysisError
+
+    classifier = Classifier()
+    with open("slope.jpg", "rb") as photo:
+        analysis = classifier.classify(photo.read(), backend="openai", soft_validation=True)
+    print(analysis["avalanche_type"], analysis["type_scores"])
+
+    # Validation and scoring only, for replies collected earlier
+    analysis = classifier.parse_analysis(reply_json)
+"""
+
+import ctypes
+import json
+import os
+import sys
+
+
+class AnalysisError(Exception):
+    """A failed classification; kind is a stable name such as "ambiguous"."""
+
+    def __init__(self, kind, message, hint=None):
+        super().__init__(message if hint is None else f"{message}\n{hint}")
+        self.kind = kind
+        self.hint = hint
+
+
+def _default_path():
+    name = {"win32": "avalanche_classifier_ffi.dll", "darwin": "libavalanche_classifier_ffi.dylib"}.get(
+        sys.platform, "libavalanche_classifier_ffi.so"
+    )
+    here = os.path.dirname(os.path.abspath(__file__))
+    return os.environ.get("AVALANCHE_CLASSIFIER_LIB", os.path.join(here, "..", "..", "target", "release", name))
+
+
+class Classifier:
+    def __init__(self, library=None):
+        lib = ctypes.CDLL(library or _default_path())
+        lib.avalanche_classify_image_bytes.argtypes = [ctypes.c_char_p, ctypes.c_char_p, ctypes.c_size_t]
+        lib.avalanche_classify_image_bytes.restype = ctypes.c_void_p
+        lib.avalanche_parse_analysis.argtypes = [ctypes.c_char_p, ctypes.c_bool]
+        lib.avalanche_parse_analysis.restype = ctypes.c_void_p
+        lib.avalanche_string_free.argtypes = [ctypes.c_void_p]
+        lib.avalanche_string_free.restype = None
+        self._lib = lib
+
+    def _reply(self, pointer):
+        if not pointer:
+            raise MemoryError("the classifier could not allocate its reply")
+        try:
+            reply = json.loads(ctypes.string_at(pointer).decode("utf-8"))
+        finally:
+            self._lib.avalanche_string_free(pointer)
+        if not reply["ok"]:
+            error = reply["error"]
+            raise AnalysisError(error["kind"], error["message"], error.get("hint"))
+        return reply
+
+    def classify(self, image_bytes, **config):
+        """Classifies an encoded photo; config keys are those in avalanche_classifier.h."""
+        config_json = json.dumps(config).encode("utf-8")
+        return self._reply(
+            self._lib.avalanche_classify_image_bytes(config_json, image_bytes, len(image_bytes))
+        )["analysis"]
+
+    def parse_analysis(self, reply, soft=False):
+        """Validates and scores a model reply (a JSON string or dict) without calling any backend."""
+        if not isinstance(reply, str):
+            reply = json.dumps(reply)
+        return self._reply(self._lib.avalanche_parse_analysis(reply.encode("utf-8"), soft))["analysis"]
@@ -0,0 +1,207 @@
+//! C ABI over the analysis pipeline, for calling the classifier (or just its
+//! validation and scoring) from Python research pipelines and other
+//! languages. Everything crosses the boundary as UTF-8 JSON: a config object
+//! in, and out either `{"ok": true, "analysis": {...}}` or
+//! `{"ok": false, "error": {"kind": ..., "message": ..., "hint": ...}}`.
+//! Returned strings belong to the caller and go back through
+//! `avalanche_string_free`. See include/avalanche_classifier.h.
+
+use avalanche_classifier_core::error::failure_json;
+use avalanche_classifier_core::models::UploadEncoding;
+use avalanche_classifier_core::provider::{self, DEFAULT_CONFLICT_ROUNDS};
+use avalanche_classifier_core::{
+    AnthropicProvider, Backend, ClassifyOptions, OllamaProvider, OpenAiProvider, VisionProvider,
+};
+use serde::Deserialize;
+use std::ffi::{c_char, CStr, CString};
+
+// What avalanche_classify_image_bytes takes, as JSON. Only the backend is
+// required; API keys fall back to OPENAI_API_KEY / ANTHROPIC_API_KEY.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Config {
+    // "openai", "anthropic" or "ollama"
+    backend: String,
+    api_key: Option<String>,
+    model: Option<String>,
+    // OpenAI-compatible endpoint or Ollama server
+    base_url: Option<String>,
+    upload: UploadEncoding,
+    prompt: Option<String>,
+    context: String,
+    soft_validation: bool,
+    conflict_rounds: u32,
+    record_snapshot: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            backend: String::new(),
+            api_key: None,
+            model: None,
+            base_url: None,
+            upload: Default::default(),
+            prompt: None,
+            context: String::new(),
+            soft_validation: false,
+            conflict_rounds: DEFAULT_CONFLICT_ROUNDS,
+            record_snapshot: false,
+        }
+    }
+}
+
+impl Config {
+    fn api_key(&self, variable: &str) -> String {
+        self.api_key.clone().or_else(|| std::env::var(variable).ok()).unwrap_or_default()
+    }
+
+    fn provider(&self) -> Result<Box<dyn VisionProvider>, String> {
+        let backend = Backend::ALL
+            .into_iter()
+            .find(|b| b.id() == self.backend)
+            .ok_or_else(|| format!("unknown backend {:?}; use \"openai\", \"anthropic\" or \"ollama\"", self.backend))?;
+        Ok(match backend {
+            Backend::OpenAi => {
+                let mut openai = OpenAiProvider::new(self.api_key("OPENAI_API_KEY")).with_upload(self.upload);
+                if let Some(model) = &self.model {
+                    openai = openai.with_model(model);
+                }
+                if let Some(base_url) = &self.base_url {
+                    openai = openai.with_base_url(base_url);
+                }
+                Box::new(openai)
+            }
+            Backend::Anthropic => {
+                let mut anthropic =
+                    AnthropicProvider::new(self.api_key("ANTHROPIC_API_KEY")).with_upload(self.upload);
+                if let Some(model) = &self.model {
+                    anthropic = anthropic.with_model(model);
+                }
+                Box::new(anthropic)
+            }
+            Backend::Ollama => Box::new(
+                OllamaProvider::new(
+                    self.base_url.as_deref().unwrap_or(OllamaProvider::DEFAULT_URL),
+                    self.model.as_deref().unwrap_or(OllamaProvider::DEFAULT_MODEL),
+                )
+                .with_upload(self.upload),
+            ),
+            other => return Err(format!("the {} backend is not supported here", other.id())),
+        })
+    }
+
+    fn options(&self) -> ClassifyOptions {
+        let options = ClassifyOptions::default()
+            .with_context(self.context.as_str())
+            .with_soft_validation(self.soft_validation)
+            .with_conflict_rounds(self.conflict_rounds)
+            .with_snapshot(self.record_snapshot);
+        match &self.prompt {
+            Some(prompt) => options.with_prompt(prompt.as_str()),
+            None => options,
+        }
+    }
+}
+
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread().enable_all().build().expect("tokio runtime starts")
+    })
+}
+
+// Reads a NUL-terminated UTF-8 argument
+unsafe fn text<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, serde_json::Value> {
+    if ptr.is_null() {
+        return Err(failure_json("invalid_argument", format!("{} is null", name), None));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| failure_json("invalid_argument", format!("{} is not UTF-8", name), None))
+}
+
+// Hands a reply to the caller; runs the call behind a panic guard so a bug
+// never unwinds into foreign code
+fn respond(call: impl FnOnce() -> serde_json::Value + std::panic::UnwindSafe) -> *mut c_char {
+    let reply = std::panic::catch_unwind(call)
+        .unwrap_or_else(|_| failure_json("internal", "the classifier panicked".to_string(), None));
+    // serde_json escapes control characters, so the text holds no NUL
+    CString::new(reply.to_string()).map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Classifies an encoded photo (JPEG, PNG, ...) with the backend the JSON
+/// config names, returning the analysis or error as JSON.
+///
+/// # Safety
+///
+/// `config` must be a NUL-terminated string and `bytes` must point to `len`
+/// readable bytes. The result must be released with `avalanche_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn avalanche_classify_image_bytes(
+    config: *const c_char,
+    bytes: *const u8,
+    len: usize,
+) -> *mut c_char {
+    let config = text(config, "config");
+    let image = if bytes.is_null() || len == 0 { None } else { Some(std::slice::from_raw_parts(bytes, len)) };
+    respond(move || {
+        let config: Config = match config.map(serde_json::from_str) {
+            Ok(Ok(config)) => config,
+            Ok(Err(err)) => return failure_json("invalid_config", err.to_string(), None),
+            Err(failure) => return failure,
+        };
+        let Some(image) = image else {
+            return failure_json("invalid_argument", "no image bytes".to_string(), None);
+        };
+        let provider = match config.provider() {
+            Ok(provider) => provider,
+            Err(message) => return failure_json("invalid_config", message, None),
+        };
+        let options = config.options();
+        match runtime().block_on(avalanche_classifier_core::classify(provider.as_ref(), image, &options)) {
+            Ok(classification) => serde_json::json!({
+                "ok": true,
+                "analysis": classification.analysis,
+                "snapshot": classification.snapshot,
+            }),
+            Err(err) => err.to_json(),
+        }
+    })
+}
+
+/// Runs validation and type scoring on a model reply that is already at
+/// hand (the JSON object the analysis prompt asks for), without any network
+/// call. With `soft` set, ambiguous, weak or conflicting classifications come
+/// back with `validation_warnings` instead of an error.
+///
+/// # Safety
+///
+/// `reply` must be a NUL-terminated string. The result must be released with
+/// `avalanche_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn avalanche_parse_analysis(reply: *const c_char, soft: bool) -> *mut c_char {
+    let reply = text(reply, "reply");
+    respond(move || {
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(failure) => return failure,
+        };
+        match provider::parse_analysis_with(reply, soft) {
+            Ok(analysis) => serde_json::json!({ "ok": true, "analysis": analysis }),
+            Err(err) => err.to_json(),
+        }
+    })
+}
+
+/// Releases a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `text` must come from this library and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn avalanche_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
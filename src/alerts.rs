@@ -0,0 +1,210 @@
+use crate::monitor::format_time;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::alerting::{self, Escalator, Target};
+use avalanche_classifier_core::provider::http_client;
+use avalanche_classifier_core::{audit, storage};
+use eframe::egui;
+use poll_promise::Promise;
+
+// (incident id, escalation level, target name, delivery result)
+type Delivery = (u64, usize, String, anyhow::Result<()>);
+
+pub struct AlertsState {
+    escalator: Escalator,
+    sending: Vec<Promise<Delivery>>,
+}
+
+impl Default for AlertsState {
+    fn default() -> Self {
+        Self {
+            escalator: Escalator::load_default(),
+            sending: Vec::new(),
+        }
+    }
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn raise_alert(&mut self, source: &str, message: &str) {
+        if let Err(err) = self.alerts.escalator.raise(source, message, storage::unix_now()) {
+            self.error = Some(format!("Failed to record alert: {}", err));
+        }
+    }
+
+    // Alerts raised and not yet acknowledged
+    #[cfg(not(target_os = "linux"))]
+    pub(crate) fn open_alerts(&self) -> usize {
+        self.alerts.escalator.open().count()
+    }
+
+    // Delivers due notifications and logs how each one went
+    pub(crate) fn poll_escalations(&mut self, ctx: &egui::Context) {
+        if self.alerts.escalator.open().next().is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_secs(30));
+        }
+
+        let mut pending = Vec::new();
+        for promise in std::mem::take(&mut self.alerts.sending) {
+            match promise.try_take() {
+                Ok((id, level, target, result)) => {
+                    let kind = if level == 0 { "alert_notified" } else { "alert_escalated" };
+                    let logged = match result {
+                        Ok(()) => audit::record(kind, format!("#{} to {}", id, target)),
+                        Err(err) => {
+                            self.error = Some(format!("Alert #{} not delivered to {}: {}", id, target, err));
+                            audit::record("alert_delivery_failed", format!("#{} to {}: {}", id, target, err))
+                        }
+                    };
+                    if let Err(err) = logged {
+                        self.error = Some(format!("Failed to write audit log: {}", err));
+                    }
+                }
+                Err(promise) => pending.push(promise),
+            }
+        }
+        self.alerts.sending = pending;
+
+        for notification in self.alerts.escalator.due(storage::unix_now()) {
+            self.alerts.sending.push(crate::runtime::spawn(async move {
+                let result = alerting::send(&http_client(), &notification.target, &notification.text).await;
+                (notification.incident_id, notification.level, notification.target.name, result)
+            }));
+        }
+    }
+
+    pub(crate) fn alerts_ui(&mut self, ui: &mut egui::Ui, danger_color: egui::Color32, muted_color: egui::Color32) {
+        let open = self.alerts.escalator.open().count();
+        egui::CollapsingHeader::new(format!("Alerts ({} open)", open))
+            .default_open(open > 0)
+            .show(ui, |ui| {
+                let now = storage::unix_now();
+                let mut acknowledge = None;
+                for incident in self.alerts.escalator.open() {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "⚠ #{} {} · {}: {}",
+                                incident.id,
+                                format_time(incident.raised_at),
+                                incident.source,
+                                incident.message
+                            ))
+                            .size(13.0)
+                            .color(danger_color),
+                        );
+                        let ack_enabled = !self.settings.operator.trim().is_empty();
+                        if ui
+                            .add_enabled(ack_enabled, egui::Button::new("Acknowledge"))
+                            .on_disabled_hover_text("Enter your name below first")
+                            .clicked()
+                        {
+                            acknowledge = Some(incident.id);
+                        }
+                    });
+                    let reached = self
+                        .alerts
+                        .escalator
+                        .policy
+                        .target(incident.level)
+                        .map_or("end of chain".to_string(), |t| t.name.clone());
+                    let status = match incident.last_notified_at {
+                        Some(at) => format!("Notified {} {} min ago", reached, now.saturating_sub(at) / 60),
+                        None => "Not notified yet".to_string(),
+                    };
+                    ui.label(egui::RichText::new(status).size(13.0).color(muted_color));
+                }
+                if let Some(id) = acknowledge {
+                    let by = self.settings.operator.trim().to_string();
+                    if let Err(err) = self.alerts.escalator.acknowledge(id, &by, now) {
+                        self.error = Some(err.to_string());
+                    }
+                }
+
+                let acknowledged: Vec<_> = self
+                    .alerts
+                    .escalator
+                    .incidents
+                    .iter()
+                    .rev()
+                    .filter_map(|i| i.acknowledged.as_ref().map(|ack| (i, ack)))
+                    .take(5)
+                    .collect();
+                if !acknowledged.is_empty() {
+                    ui.add_space(4.0);
+                    for (incident, ack) in acknowledged {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "✔ #{} {}: acknowledged by {} at {}",
+                                incident.id,
+                                incident.source,
+                                ack.by,
+                                format_time(ack.at)
+                            ))
+                            .size(13.0)
+                            .color(muted_color),
+                        );
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.label("Your name");
+                    ui.text_edit_singleline(&mut self.settings.operator);
+                });
+
+                ui.add_space(8.0);
+                let mut changed = false;
+                egui::CollapsingHeader::new("Escalation Chain").default_open(false).show(ui, |ui| {
+                    let policy = &mut self.alerts.escalator.policy;
+                    ui.label(egui::RichText::new("1. Channel").size(13.0).strong());
+                    changed |= target_editor(ui, &mut policy.channel);
+                    ui.horizontal(|ui| {
+                        ui.label("Escalate if not acknowledged within");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut policy.ack_timeout_mins).clamp_range(1..=240).suffix(" min"))
+                            .changed();
+                    });
+                    let mut remove = None;
+                    for (i, contact) in policy.on_call.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(format!("{}. On call", i + 2)).size(13.0).strong());
+                            if ui.small_button("✕").clicked() {
+                                remove = Some(i);
+                            }
+                        });
+                        changed |= target_editor(ui, contact);
+                    }
+                    if let Some(i) = remove {
+                        policy.on_call.remove(i);
+                        changed = true;
+                    }
+                    if ui.small_button("➕ On-call Contact").clicked() {
+                        policy.on_call.push(Target::default());
+                        changed = true;
+                    }
+                    ui.label(
+                        egui::RichText::new("Webhooks receive a JSON POST with a \"text\" field")
+                            .size(13.0)
+                            .color(muted_color),
+                    );
+                });
+                if changed {
+                    if let Err(err) = self.alerts.escalator.save() {
+                        self.error = Some(err.to_string());
+                    }
+                }
+            });
+    }
+}
+
+fn target_editor(ui: &mut egui::Ui, target: &mut Target) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(egui::TextEdit::singleline(&mut target.name).hint_text("Name").desired_width(120.0))
+            .changed();
+        changed |= ui
+            .add(egui::TextEdit::singleline(&mut target.webhook_url).hint_text("Webhook URL"))
+            .changed();
+    });
+    changed
+}
@@ -0,0 +1,163 @@
+use crate::{runtime, AvalancheClassifier};
+use avalanche_classifier_core::{archive, checksum, snapshot::BlobStore};
+use eframe::egui;
+use poll_promise::Promise;
+
+#[derive(Default)]
+pub struct ArchiveState {
+    // Export runs off the UI thread; compressing a season of photos takes a while
+    exporting: Option<Promise<anyhow::Result<u64>>>,
+    last_result: Option<String>,
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn archive_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let selected = self.history_selection.len();
+        egui::CollapsingHeader::new("Observation Archives")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(if selected > 0 {
+                        format!("Exports the {} selected entries with their re-runs", selected)
+                    } else {
+                        "Exports the whole history; select entries to export only those".to_string()
+                    })
+                    .size(13.0)
+                    .color(muted_color),
+                );
+                ui.add_space(8.0);
+                let mut export = false;
+                let mut import = false;
+                let mut compact = false;
+                let mut verify = false;
+                ui.horizontal(|ui| {
+                    export = ui
+                        .add_enabled(
+                            self.archive.exporting.is_none() && !self.history.entries.is_empty(),
+                            egui::Button::new("📦 Export Archive"),
+                        )
+                        .on_hover_text("Entries, photos and raw responses in one compressed file to share for review")
+                        .clicked();
+                    import = ui.button("📥 Import Archive").clicked();
+                    compact = ui
+                        .button("🗜 Compact Storage")
+                        .on_hover_text("Compress photos and responses stored before compression was added")
+                        .clicked();
+                    verify = ui
+                        .button("🔍 Verify File")
+                        .on_hover_text("Check an exported file against the .sha256 checksum saved next to it")
+                        .clicked();
+                });
+
+                if export {
+                    self.start_archive_export();
+                }
+                if import {
+                    self.import_archive();
+                }
+                if compact {
+                    self.archive.last_result = Some(
+                        match BlobStore::open_default().and_then(|store| store.compact()) {
+                            Ok((before, after)) => format!("Storage compacted from {} to {}", megabytes(before), megabytes(after)),
+                            Err(err) => format!("Compacting failed: {}", err),
+                        },
+                    );
+                }
+
+                if verify {
+                    self.verify_export();
+                }
+
+                if let Some(promise) = self.archive.exporting.take() {
+                    match promise.try_take() {
+                        Ok(Ok(size)) => self.archive.last_result = Some(format!("Archive written ({})", megabytes(size))),
+                        Ok(Err(err)) => self.error = Some(format!("Archive export failed: {}", err)),
+                        Err(promise) => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Compressing archive...");
+                            });
+                            self.archive.exporting = Some(promise);
+                        }
+                    }
+                }
+                if let Some(message) = &self.archive.last_result {
+                    ui.label(egui::RichText::new(message).size(13.0));
+                }
+            });
+    }
+
+    fn start_archive_export(&mut self) {
+        let Some(path) = self.settings.export_dialog()
+            .add_filter("Observation Archive", &[archive::EXTENSION])
+            .set_file_name(format!("avalanche-observations.{}", archive::EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+        let entries: Vec<_> = if self.history_selection.is_empty() {
+            self.history.entries.clone()
+        } else {
+            self.history
+                .entries
+                .iter()
+                .filter(|e| {
+                    self.history_selection.contains(e.id())
+                        || e.parent_id.as_ref().is_some_and(|p| self.history_selection.contains(p))
+                })
+                .cloned()
+                .collect()
+        };
+        self.archive.last_result = None;
+        self.archive.exporting = Some(runtime::spawn(async move {
+            tokio::task::spawn_blocking(move || {
+                let store = BlobStore::open_default()?;
+                archive::export(&path, &entries.iter().collect::<Vec<_>>(), &store)
+            })
+            .await?
+        }));
+    }
+
+    fn import_archive(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Observation Archive", &[archive::EXTENSION])
+            .pick_file()
+        else {
+            return;
+        };
+        let result =
+            BlobStore::open_default().and_then(|store| archive::import(&path, &store, &mut self.history));
+        match result {
+            Ok(added) => {
+                self.archive.last_result = Some(if checksum::sidecar_path(&path).exists() {
+                    format!("Imported {} entries (checksum verified)", added)
+                } else {
+                    format!("Imported {} entries; no .sha256 file came with the archive to verify it", added)
+                })
+            }
+            Err(err) => self.error = Some(format!("Archive import failed: {}", err)),
+        }
+    }
+
+    // Any export (archive, dataset, mission log, GeoJSON) checked against
+    // its sidecar, e.g. after copying it off a USB stick
+    fn verify_export(&mut self) {
+        let Some(path) = rfd::FileDialog::new().pick_file() else {
+            return;
+        };
+        let name = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+        match checksum::verify(&path) {
+            Ok(checksum::Verification::Verified) => {
+                self.archive.last_result = Some(format!("{} is intact: checksum verified", name))
+            }
+            Ok(checksum::Verification::Unchecked) => {
+                self.archive.last_result = Some(format!("{} has no .sha256 file next to it to verify against", name))
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
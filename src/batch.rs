@@ -0,0 +1,415 @@
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::decode;
+use avalanche_classifier_core::history::HistoryEntry;
+use avalanche_classifier_core::manifest::{self, ItemResult, Manifest, ManifestItem, Outcome, RunConfig};
+use avalanche_classifier_core::model::AvalancheAnalysis;
+use avalanche_classifier_core::snapshot::{self, BlobStore};
+use avalanche_classifier_core::{fallback, provider, scoring, AnalysisError, Classification, ClassifyOptions};
+use eframe::egui;
+use poll_promise::Promise;
+use std::path::PathBuf;
+
+const MAX_CONCURRENCY: usize = 8;
+
+enum ItemStatus {
+    Queued,
+    Running(Box<Promise<Result<Classification, AnalysisError>>>, tokio::task::AbortHandle),
+    Done { analysis: Box<AvalancheAnalysis>, snapshot_id: Option<String> },
+    Failed(String),
+}
+
+struct BatchItem {
+    path: PathBuf,
+    status: ItemStatus,
+    // Peak decode memory, read from the file header when first needed
+    estimate: Option<u64>,
+    // sha256 of the file, once read
+    hash: Option<String>,
+    // Queued since the batch queue last drained, so part of the next manifest
+    in_run: bool,
+}
+
+#[derive(Default)]
+pub struct BatchState {
+    items: Vec<BatchItem>,
+    // History entries from the current run, flagged together once it drains
+    batch_ids: Vec<String>,
+    // Queued images held back to stay within the decode memory budget
+    throttled: bool,
+    // Seed and settings the current run started with, for its manifest
+    run: Option<(u64, RunConfig)>,
+    // Manifest of the last run that drained, ready to be saved
+    manifest: Option<Manifest>,
+    // The manifest the current run repeats, and what its re-run changed
+    rerun_of: Option<Manifest>,
+    rerun_differences: Option<Vec<String>>,
+}
+
+impl BatchState {
+    fn running(&self) -> usize {
+        self.items.iter().filter(|i| matches!(i.status, ItemStatus::Running(..))).count()
+    }
+
+    fn pending(&self) -> bool {
+        self.items.iter().any(|i| matches!(i.status, ItemStatus::Queued | ItemStatus::Running(..)))
+    }
+
+    fn cancel(&mut self) {
+        for item in &self.items {
+            if let ItemStatus::Running(_, abort) = &item.status {
+                abort.abort();
+            }
+        }
+        self.items.retain(|i| !matches!(i.status, ItemStatus::Queued | ItemStatus::Running(..)));
+    }
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn queue_images(&mut self, paths: Vec<PathBuf>) {
+        let seed = self.settings.batch_seed;
+        self.queue_in_order(manifest::seeded_order(paths, seed), seed);
+    }
+
+    fn queue_in_order(&mut self, paths: Vec<PathBuf>, seed: u64) {
+        if self.batch.run.is_none() {
+            self.batch.run = Some((seed, self.run_config()));
+        }
+        self.batch.items.extend(paths.into_iter().map(|path| BatchItem {
+            path,
+            status: ItemStatus::Queued,
+            estimate: None,
+            hash: None,
+            in_run: true,
+        }));
+    }
+
+    // Queues the files of a saved manifest again, in its order, to check that
+    // the run still gives the same results
+    pub(crate) fn rerun_manifest(&mut self) {
+        if self.batch.pending() {
+            self.error = Some("Wait for the batch queue to finish before re-running a manifest".to_string());
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new().add_filter("Batch manifest", &[manifest::EXTENSION]).pick_file() else {
+            return;
+        };
+        match Manifest::load(&path) {
+            Ok(manifest) => {
+                let paths = manifest.items.iter().map(|item| item.path.clone()).collect();
+                self.queue_in_order(paths, manifest.seed);
+                self.batch.rerun_of = Some(manifest);
+                self.batch.rerun_differences = None;
+            }
+            Err(err) => self.error = Some(format!("Could not read {}: {}", path.display(), err)),
+        }
+    }
+
+    fn save_manifest(&mut self) {
+        let Some(manifest) = &self.batch.manifest else {
+            return;
+        };
+        let Some(path) = self
+            .settings
+            .export_dialog()
+            .add_filter("Batch manifest", &[manifest::EXTENSION])
+            .set_file_name(format!("batch-manifest.{}", manifest::EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+        if let Err(err) = manifest.save(&path) {
+            self.error = Some(format!("Could not save the manifest: {}", err));
+        }
+    }
+
+    // Everything outside the photos that shapes a batch result
+    fn run_config(&self) -> RunConfig {
+        let mut backends = vec![self.settings.backend];
+        for backend in &self.settings.fallback_chain {
+            if !backends.contains(backend) && self.is_configured(*backend) {
+                backends.push(*backend);
+            }
+        }
+        RunConfig {
+            classifier_version: env!("CARGO_PKG_VERSION").to_string(),
+            backends: backends.into_iter().map(|b| b.id().to_string()).collect(),
+            prompt: provider::analysis_prompt_version(),
+            prompt_hash: snapshot::hash_bytes(provider::analysis_prompt().as_bytes()),
+            soft_validation: !self.settings.strict_validation,
+            conflict_rounds: self.settings.conflict_rounds,
+            upload: self.settings.upload,
+            scoring: scoring::config(),
+        }
+    }
+
+    // The manifest of the run that just drained, in queue order
+    fn finish_run(&mut self) {
+        let Some((seed, config)) = self.batch.run.take() else {
+            return;
+        };
+        let store = BlobStore::open_default().ok();
+        let items = self
+            .batch
+            .items
+            .iter_mut()
+            .filter_map(|item| std::mem::take(&mut item.in_run).then_some(&*item))
+            .map(|item| {
+                let outcome = match &item.status {
+                    ItemStatus::Done { analysis, snapshot_id } => {
+                        let snapshot = snapshot_id
+                            .as_ref()
+                            .and_then(|id| self.history.entries.iter().find(|e| e.id() == id))
+                            .map(|e| &e.snapshot);
+                        match snapshot {
+                            Some(snapshot) => Outcome::Result(ItemResult::new(analysis, snapshot, store.as_ref())),
+                            None => Outcome::Error("the result could not be recorded".to_string()),
+                        }
+                    }
+                    ItemStatus::Failed(message) => Outcome::Error(message.clone()),
+                    ItemStatus::Queued | ItemStatus::Running(..) => Outcome::Error("cancelled".to_string()),
+                };
+                ManifestItem { path: item.path.clone(), image_hash: item.hash.clone(), outcome }
+            })
+            .collect();
+        let manifest = Manifest::new(seed, config, items);
+        if let Some(original) = self.batch.rerun_of.take() {
+            self.batch.rerun_differences = Some(original.differences(&manifest));
+        }
+        self.batch.manifest = Some(manifest);
+    }
+
+    // Collects finished images and starts queued ones up to the concurrency
+    // limit. Batch items use the selected backend and its fallback chain,
+    // never the ensemble.
+    pub(crate) fn poll_batch(&mut self, ctx: &egui::Context) {
+        if !self.batch.pending() && self.batch.batch_ids.is_empty() {
+            self.finish_run();
+            return;
+        }
+        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+
+        for i in 0..self.batch.items.len() {
+            let ItemStatus::Running(promise, _) = &self.batch.items[i].status else {
+                continue;
+            };
+            let Some(result) = promise.ready() else {
+                continue;
+            };
+            let status = match result {
+                Ok(Classification { analysis, snapshot, fallbacks }) => {
+                    if let Some(snapshot) = snapshot {
+                        self.batch.batch_ids.push(snapshot.id.clone());
+                        let mut entry = HistoryEntry::new(snapshot.clone(), analysis.clone());
+                        entry.fallbacks = fallbacks.clone();
+                        entry.read_photo_meta();
+                        if let Err(err) = self.history.push(entry) {
+                            self.error = Some(err.to_string());
+                        }
+                    }
+                    ItemStatus::Done {
+                        analysis: Box::new(analysis.clone()),
+                        snapshot_id: snapshot.as_ref().map(|s| s.id.clone()),
+                    }
+                }
+                Err(err) => ItemStatus::Failed(err.user_message()),
+            };
+            self.batch.items[i].status = status;
+        }
+
+        self.batch.throttled = false;
+        if self.has_api_key() {
+            let limit = self.settings.batch_concurrency.clamp(1, MAX_CONCURRENCY);
+            let mut free = limit.saturating_sub(self.batch.running());
+            for i in 0..self.batch.items.len() {
+                if free == 0 {
+                    break;
+                }
+                if !matches!(self.batch.items[i].status, ItemStatus::Queued) {
+                    continue;
+                }
+                // Files stay on disk until their decode would fit
+                let item = &mut self.batch.items[i];
+                let estimate = *item.estimate.get_or_insert_with(|| decode::estimate_file(&item.path));
+                if !decode::has_room(estimate) {
+                    self.batch.throttled = true;
+                    break;
+                }
+                let bytes = match std::fs::read(&self.batch.items[i].path) {
+                    Ok(bytes) => {
+                        self.batch.items[i].hash = Some(snapshot::hash_bytes(&bytes));
+                        bytes
+                    }
+                    Err(err) => {
+                        self.batch.items[i].status = ItemStatus::Failed(err.to_string());
+                        continue;
+                    }
+                };
+                let chain = self.fallback_chain();
+                let (promise, abort) = crate::runtime::spawn_abortable(async move {
+                    let _slot = crate::runtime::analysis_slot(crate::runtime::Priority::Batch).await;
+                    fallback::classify_with_fallback(&chain, &bytes, &ClassifyOptions::current()).await
+                });
+                self.batch.items[i].status = ItemStatus::Running(Box::new(promise), abort);
+                free -= 1;
+            }
+        }
+
+        if !self.batch.pending() && !self.batch.batch_ids.is_empty() {
+            let ids = std::mem::take(&mut self.batch.batch_ids);
+            if let Err(err) = self.history.flag_batch(&ids) {
+                self.error = Some(err.to_string());
+            }
+        }
+        if !self.batch.pending() {
+            self.finish_run();
+        }
+    }
+
+    pub(crate) fn batch_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx: &egui::Context,
+        success_color: egui::Color32,
+        danger_color: egui::Color32,
+        muted_color: egui::Color32,
+    ) {
+        if self.batch.items.is_empty() {
+            return;
+        }
+        let done = self
+            .batch
+            .items
+            .iter()
+            .filter(|i| matches!(i.status, ItemStatus::Done { .. } | ItemStatus::Failed(_)))
+            .count();
+        let mut open = None;
+        let mut cancel = false;
+        let mut clear = false;
+        let mut save_manifest = false;
+        egui::CollapsingHeader::new(format!("Batch Queue ({}/{})", done, self.batch.items.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Parallel");
+                    ui.add(egui::DragValue::new(&mut self.settings.batch_concurrency).clamp_range(1..=MAX_CONCURRENCY));
+                    ui.label("Seed").on_hover_text("Images queued together run in an order shuffled with this seed");
+                    ui.add(egui::DragValue::new(&mut self.settings.batch_seed));
+                    if self.batch.pending() {
+                        cancel = ui.button("Cancel Batch").clicked();
+                    }
+                    clear = ui.button("Clear Finished").clicked();
+                });
+                if !self.has_api_key() && self.batch.pending() {
+                    ui.label(
+                        egui::RichText::new("Waiting for the selected backend to be configured")
+                            .size(13.0)
+                            .color(muted_color),
+                    );
+                }
+                if self.batch.throttled {
+                    ui.label(
+                        egui::RichText::new("Holding back further images to stay within the memory budget")
+                            .size(13.0)
+                            .color(muted_color),
+                    );
+                }
+                let waiting = crate::runtime::queued_analyses();
+                if waiting > 0 && self.batch.running() > 0 {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} analyses waiting for a free slot; photos analyzed by hand go first",
+                            waiting
+                        ))
+                        .size(13.0)
+                        .color(muted_color),
+                    );
+                }
+                if let Some(differences) = &self.batch.rerun_differences {
+                    if differences.is_empty() {
+                        ui.label(
+                            egui::RichText::new("Re-run matches the manifest: nothing changed")
+                                .size(13.0)
+                                .color(success_color),
+                        );
+                    } else {
+                        ui.label(
+                            egui::RichText::new(format!("Re-run differs from the manifest in {} ways:", differences.len()))
+                                .size(13.0)
+                                .color(danger_color),
+                        );
+                        for line in differences {
+                            ui.label(egui::RichText::new(format!("• {}", line)).size(13.0).color(muted_color));
+                        }
+                    }
+                }
+                if self.batch.manifest.is_some() && ui.button("💾 Save Manifest…").clicked() {
+                    save_manifest = true;
+                }
+                for (i, item) in self.batch.items.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let name = item.path.file_name().map_or_else(|| item.path.display().to_string(), |n| n.to_string_lossy().into_owned());
+                        ui.label(egui::RichText::new(name).size(13.0).strong()).on_hover_text(item.path.display().to_string());
+                        match &item.status {
+                            ItemStatus::Queued => {
+                                ui.label(egui::RichText::new("Queued").size(13.0).color(muted_color));
+                            }
+                            ItemStatus::Running(..) => {
+                                ui.spinner();
+                            }
+                            ItemStatus::Done { analysis, snapshot_id } => {
+                                let color = if analysis.avalanche_type == "none" { success_color } else { danger_color };
+                                ui.label(
+                                    egui::RichText::new(format!("{} · {:.0}%", analysis.avalanche_type, analysis.confidence_level))
+                                        .size(13.0)
+                                        .color(color),
+                                );
+                                if snapshot_id.as_ref().is_some_and(|id| self.history.entries.iter().any(|e| e.id() == id && e.needs_review())) {
+                                    ui.label(egui::RichText::new("needs review").size(13.0).color(muted_color));
+                                }
+                                if ui.small_button("Open").clicked() {
+                                    open = Some(i);
+                                }
+                            }
+                            ItemStatus::Failed(message) => {
+                                ui.label(egui::RichText::new("Failed").size(13.0).color(danger_color)).on_hover_text(message);
+                            }
+                        }
+                    });
+                }
+            });
+
+        if cancel {
+            self.batch.cancel();
+        }
+        if save_manifest {
+            self.save_manifest();
+        }
+        if clear {
+            self.batch.items.retain(|i| !matches!(i.status, ItemStatus::Done { .. } | ItemStatus::Failed(_)));
+        }
+        // Shows the image and its result as if it had been analyzed on its own
+        if let Some(i) = open {
+            let item = &self.batch.items[i];
+            if let ItemStatus::Done { analysis, snapshot_id } = &item.status {
+                let analysis = (**analysis).clone();
+                let snapshot = snapshot_id
+                    .as_ref()
+                    .and_then(|id| self.history.entries.iter().find(|e| e.id() == id))
+                    .map(|e| e.snapshot.clone());
+                match std::fs::read(&item.path) {
+                    Ok(bytes) => {
+                        self.load_image(ctx, bytes);
+                        if let Some(image_data) = &mut self.image_data {
+                            image_data.show_annotations(analysis.annotations.as_ref());
+                        }
+                        self.result = Some(analysis);
+                        self.snapshot = snapshot;
+                        self.ensemble = None;
+                        self.error = None;
+                    }
+                    Err(err) => self.error = Some(format!("Failed to open {}: {}", item.path.display(), err)),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+// Read-only viewer for observation archives (.avarchive), for handing
+// reports to staff. It opens an archive in memory and shows its entries; it
+// has no settings, keys or backends, writes nothing and cannot analyze.
+
+use avalanche_classifier_core::archive::{self, ArchiveContents};
+use avalanche_classifier_core::history::{HistoryEntry, Verdict};
+use avalanche_classifier_core::model::type_label;
+use avalanche_classifier_core::photo_meta;
+use eframe::egui;
+use poll_promise::Promise;
+use std::path::PathBuf;
+
+// Shared with the main app so both draw in the same colors
+#[path = "../palette.rs"]
+mod palette;
+use palette::Palette;
+
+// Photos are shown no larger than this, which keeps big archives light
+const MAX_TEXTURE_SIDE: u32 = 2048;
+
+struct Loaded {
+    path: PathBuf,
+    contents: ArchiveContents,
+    // Newest first
+    order: Vec<usize>,
+}
+
+#[derive(Default)]
+struct Viewer {
+    loading: Option<Promise<anyhow::Result<Loaded>>>,
+    archive: Option<Loaded>,
+    error: Option<String>,
+    selected: Option<usize>,
+    // Photo of the selected entry, or why it could not be shown
+    photo: Option<Result<egui::TextureHandle, String>>,
+    filter: String,
+}
+
+fn load(path: PathBuf) -> anyhow::Result<Loaded> {
+    let contents = archive::read(&path)?;
+    let mut order: Vec<usize> = (0..contents.index.entries.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(contents.index.entries[i].snapshot.created_at));
+    Ok(Loaded { path, contents, order })
+}
+
+fn format_time(unix: i64) -> String {
+    use chrono::TimeZone;
+    chrono::Local
+        .timestamp_opt(unix, 0)
+        .single()
+        .map_or_else(String::new, |t| t.format("%Y-%m-%d %H:%M").to_string())
+}
+
+fn type_color(avalanche_type: &str, palette: Palette) -> egui::Color32 {
+    palette.type_color(avalanche_type).unwrap_or(palette.muted)
+}
+
+impl Viewer {
+    fn open(&mut self, path: PathBuf) {
+        self.error = None;
+        self.loading = Some(Promise::spawn_thread("open_archive", move || load(path)));
+    }
+
+    fn select(&mut self, ctx: &egui::Context, index: usize) {
+        self.selected = Some(index);
+        self.photo = None;
+        let Some(loaded) = &self.archive else {
+            return;
+        };
+        let entry = &loaded.contents.index.entries[index];
+        let photo = match loaded.contents.blobs.get(&entry.snapshot.image_hash) {
+            None => Err("the photo was not included in this archive".to_string()),
+            Some(bytes) => photo_meta::load_upright(bytes).map_err(|err| err.to_string()).map(|image| {
+                let image = if image.width().max(image.height()) > MAX_TEXTURE_SIDE {
+                    image.thumbnail(MAX_TEXTURE_SIDE, MAX_TEXTURE_SIDE)
+                } else {
+                    image
+                };
+                let rgba = image.to_rgba8();
+                let size = [rgba.width() as usize, rgba.height() as usize];
+                let color_image = egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
+                ctx.load_texture(format!("viewer_{}", entry.id()), color_image, Default::default())
+            }),
+        };
+        self.photo = Some(photo);
+    }
+
+    fn entry_list(&mut self, ui: &mut egui::Ui, palette: Palette) {
+        let Some(loaded) = &self.archive else {
+            return;
+        };
+        ui.add(egui::TextEdit::singleline(&mut self.filter).hint_text("Filter by camera or type"));
+        ui.add_space(4.0);
+        let filter = self.filter.trim().to_lowercase();
+        let mut clicked = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for &i in &loaded.order {
+                let entry = &loaded.contents.index.entries[i];
+                let avalanche_type = entry.effective_type().unwrap_or(&entry.analysis.avalanche_type);
+                let source = entry.source.as_deref().unwrap_or("Manual upload");
+                if !filter.is_empty()
+                    && !source.to_lowercase().contains(&filter)
+                    && !type_label(avalanche_type).to_lowercase().contains(&filter)
+                {
+                    continue;
+                }
+                let taken = entry.captured_at.unwrap_or(entry.snapshot.created_at as i64);
+                let text = egui::RichText::new(format!(
+                    "{}  {}\n{} · {:.0}%",
+                    format_time(taken),
+                    source,
+                    type_label(avalanche_type),
+                    entry.analysis.confidence_level
+                ))
+                .size(13.0)
+                .color(type_color(avalanche_type, palette));
+                if ui.selectable_label(self.selected == Some(i), text).clicked() {
+                    clicked = Some(i);
+                }
+            }
+        });
+        if let Some(i) = clicked {
+            self.select(ui.ctx(), i);
+        }
+    }
+
+    fn entry_details(&self, ui: &mut egui::Ui, entry: &HistoryEntry, palette: Palette) {
+        let analysis = &entry.analysis;
+        let avalanche_type = entry.effective_type().unwrap_or(&analysis.avalanche_type);
+        ui.label(
+            egui::RichText::new(type_label(avalanche_type))
+                .size(24.0)
+                .strong()
+                .color(type_color(avalanche_type, palette)),
+        );
+        ui.label(format!("{:.0}% confidence", analysis.confidence_level));
+        if let Some(review) = &entry.review {
+            let verdict = match review.verdict {
+                Verdict::Accepted => "Accepted by a reviewer".to_string(),
+                Verdict::Corrected => format!(
+                    "Corrected by a reviewer from {}",
+                    type_label(&analysis.avalanche_type)
+                ),
+                Verdict::Rejected => "Rejected by a reviewer".to_string(),
+            };
+            ui.label(egui::RichText::new(verdict).color(palette.accent));
+        }
+        for flag in &entry.flags {
+            ui.label(egui::RichText::new(format!("⚠ {}", flag)).color(palette.warning));
+        }
+        for warning in &analysis.validation_warnings {
+            ui.label(egui::RichText::new(format!("⚠ {}", warning)).color(palette.warning));
+        }
+        ui.add_space(8.0);
+
+        egui::Grid::new("viewer_details").num_columns(2).spacing(egui::vec2(12.0, 4.0)).show(ui, |ui| {
+            let mut row = |label: &str, value: String| {
+                ui.label(egui::RichText::new(label).color(palette.muted));
+                ui.label(value);
+                ui.end_row();
+            };
+            row("Camera", entry.source.clone().unwrap_or_else(|| "Manual upload".to_string()));
+            if let Some(taken) = entry.captured_at {
+                row("Taken", format_time(taken));
+            }
+            row("Analyzed", format_time(entry.snapshot.created_at as i64));
+            if let Some(location) = &entry.location {
+                let note = if entry.location_from_track { " (from GPX track)" } else { "" };
+                row("Location", format!("{:.5}, {:.5}{}", location.latitude, location.longitude, note));
+            }
+            if let Some(bearing) = entry.bearing {
+                row("Facing", format!("{:.0}°", bearing));
+            }
+            row("Model", format!("{} {}", entry.snapshot.backend, entry.snapshot.model));
+            if let Some(provenance) = &entry.snapshot.provenance {
+                row("Prompt", provenance.prompt.clone());
+            }
+        });
+        ui.add_space(8.0);
+
+        let chars = &analysis.visual_characteristics;
+        let mut findings = Vec::new();
+        if chars.fracture_line {
+            findings.push(match &chars.fracture_depth {
+                Some(depth) => format!("Fracture line ({})", depth.label()),
+                None => "Fracture line".to_string(),
+            });
+        }
+        if chars.powder_cloud {
+            findings.push("Powder cloud".to_string());
+        }
+        if chars.point_release {
+            findings.push("Point release".to_string());
+        }
+        if !chars.debris_pattern.is_empty() && chars.debris_pattern != "none" {
+            findings.push(format!("Debris: {}", chars.debris_pattern));
+        }
+        if let Some(angle) = &chars.terrain.slope_angle {
+            findings.push(format!("Slope: {}", angle));
+        }
+        findings.extend(analysis.terrain_features.iter().cloned());
+        for finding in findings {
+            ui.label(format!("• {}", finding));
+        }
+        for asset in &entry.exposure {
+            ui.label(egui::RichText::new(format!("⚠ Reached {}", asset.describe())).color(palette.danger));
+        }
+    }
+}
+
+impl eframe::App for Viewer {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let palette = Palette::of(ctx);
+        if let Some(promise) = self.loading.take() {
+            match promise.try_take() {
+                Ok(Ok(loaded)) => {
+                    self.archive = Some(loaded);
+                    self.selected = None;
+                    self.photo = None;
+                    if let Some(&first) = self.archive.as_ref().and_then(|a| a.order.first()) {
+                        self.select(ctx, first);
+                    }
+                }
+                Ok(Err(err)) => self.error = Some(err.to_string()),
+                Err(promise) => self.loading = Some(promise),
+            }
+        }
+        // Archives dropped onto the window open too
+        let dropped = ctx.input(|i| i.raw.dropped_files.iter().find_map(|f| f.path.clone()));
+        if let Some(path) = dropped {
+            self.open(path);
+        }
+
+        egui::TopBottomPanel::top("viewer_toolbar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(self.loading.is_none(), egui::Button::new("📂 Open Archive…")).clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Observation archive", &[archive::EXTENSION])
+                        .pick_file()
+                    {
+                        self.open(path);
+                    }
+                }
+                if self.loading.is_some() {
+                    ui.spinner();
+                    ui.label("Checking and reading archive…");
+                } else if let Some(loaded) = &self.archive {
+                    let name = loaded.path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+                    ui.label(format!(
+                        "{} · {} entries · exported {}",
+                        name,
+                        loaded.contents.index.entries.len(),
+                        format_time(loaded.contents.index.created_at as i64)
+                    ));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(egui::RichText::new("Read-only").color(palette.muted));
+                });
+            });
+            if let Some(err) = &self.error {
+                ui.label(egui::RichText::new(format!("Could not open archive: {}", err)).color(palette.danger));
+            }
+        });
+
+        if self.archive.is_some() {
+            egui::SidePanel::left("viewer_entries").default_width(260.0).show(ctx, |ui| {
+                self.entry_list(ui, palette);
+            });
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let entry = self
+                .archive
+                .as_ref()
+                .zip(self.selected)
+                .map(|(loaded, i)| &loaded.contents.index.entries[i]);
+            let Some(entry) = entry else {
+                ui.centered_and_justified(|ui| {
+                    let text = if self.archive.is_some() {
+                        "Select an entry"
+                    } else {
+                        "Open an observation archive (.avarchive) or drop one here"
+                    };
+                    ui.label(egui::RichText::new(text).size(16.0).color(palette.muted));
+                });
+                return;
+            };
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                match &self.photo {
+                    Some(Ok(texture)) => {
+                        let size = texture.size_vec2();
+                        let scale = (ui.available_width() / size.x).min(1.0);
+                        ui.image((texture.id(), size * scale));
+                    }
+                    Some(Err(reason)) => {
+                        ui.label(
+                            egui::RichText::new(format!("Photo unavailable: {}", reason)).color(palette.muted),
+                        );
+                    }
+                    None => {}
+                }
+                ui.add_space(12.0);
+                self.entry_details(ui, entry, palette);
+            });
+        });
+    }
+}
+
+fn main() -> eframe::Result<()> {
+    let mut viewer = Viewer::default();
+    if let Some(path) = std::env::args_os().nth(1) {
+        viewer.open(PathBuf::from(path));
+    }
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_title("Avalanche Report Viewer")
+            .with_inner_size([1000.0, 720.0])
+            .with_drag_and_drop(true),
+        follow_system_theme: true,
+        ..Default::default()
+    };
+    eframe::run_native("Avalanche Report Viewer", options, Box::new(|_cc| Box::new(viewer)))
+}
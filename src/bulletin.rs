@@ -0,0 +1,111 @@
+use crate::palette::Palette;
+use avalanche_classifier_core::bulletin::{self, Agreement, Bulletin, BulletinSource};
+use avalanche_classifier_core::history::HistoryEntry;
+use avalanche_classifier_core::provider::http_client;
+use avalanche_classifier_core::{model, AvalancheAnalysis};
+use eframe::egui;
+use poll_promise::Promise;
+
+// Bulletins are published daily; a photo older than this is compared with
+// a rating that was not in force when it was taken
+const STALE_AFTER_SECS: i64 = 48 * 3600;
+
+#[derive(Default)]
+pub struct BulletinState {
+    // History entry the bulletin below was looked up for
+    entry_id: Option<String>,
+    promise: Option<Promise<anyhow::Result<Bulletin>>>,
+    bulletin: Option<Result<Bulletin, String>>,
+}
+
+// The current bulletin for the result's location beside the classification,
+// looked up once per result, with whether the photo bears out its rating
+pub(crate) fn bulletin_ui(
+    ui: &mut egui::Ui,
+    state: &mut BulletinState,
+    source: &BulletinSource,
+    analysis: &AvalancheAnalysis,
+    entry: Option<&HistoryEntry>,
+    palette: Palette,
+) {
+    if *source == BulletinSource::Off {
+        return;
+    }
+    let Some((entry, location)) = entry.and_then(|e| e.location.map(|l| (e, l))) else {
+        return;
+    };
+    if state.entry_id.as_deref() != Some(entry.id()) {
+        let source = source.clone();
+        state.entry_id = Some(entry.id().to_string());
+        state.bulletin = None;
+        state.promise = Some(crate::runtime::spawn(async move { bulletin::fetch(&http_client(), &source, location).await }));
+    }
+    if let Some(promise) = state.promise.take() {
+        match promise.try_take() {
+            Ok(result) => state.bulletin = Some(result.map_err(|err| err.to_string())),
+            Err(promise) => state.promise = Some(promise),
+        }
+    }
+
+    ui.add_space(8.0);
+    ui.group(|ui| {
+        ui.set_min_width(ui.available_width());
+        ui.label(egui::RichText::new("Regional Bulletin").size(16.0).strong());
+        ui.add_space(4.0);
+        let bulletin = match &state.bulletin {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(egui::RichText::new(format!("Fetching from {}…", source.label())).size(13.0));
+                });
+                return;
+            }
+            Some(Err(err)) => {
+                ui.label(egui::RichText::new(format!("Bulletin unavailable: {}", err)).size(13.0).color(palette.muted));
+                return;
+            }
+            Some(Ok(bulletin)) => bulletin,
+        };
+
+        ui.horizontal(|ui| {
+            if let Some(level) = bulletin.danger_level {
+                let (fill, text_color) = crate::hazard::danger_colors(level);
+                crate::hazard::badge(ui, fill, text_color, &level.to_string(), model::danger_level_name(level));
+            }
+            ui.vertical(|ui| {
+                ui.label(egui::RichText::new(&bulletin.region).size(14.0).strong());
+                ui.label(egui::RichText::new(&bulletin.publisher).size(13.0).color(palette.muted));
+                if let Some(link) = &bulletin.link {
+                    ui.hyperlink_to("Full bulletin", link);
+                }
+            });
+        });
+
+        let check = bulletin::cross_check(analysis, bulletin);
+        let color = match check.agreement {
+            Agreement::Confirms => palette.success,
+            Agreement::Contradicts => palette.danger,
+            Agreement::Inconclusive => palette.muted,
+        };
+        ui.add_space(4.0);
+        ui.label(egui::RichText::new(check.agreement.label()).size(14.0).color(color).strong());
+        for reason in &check.reasons {
+            ui.label(egui::RichText::new(format!("• {}", reason)).size(13.0));
+        }
+        if let Some(summary) = &bulletin.summary {
+            ui.add_space(4.0);
+            ui.label(egui::RichText::new(summary).size(13.0).color(palette.muted));
+        }
+        let age = entry.captured_at.map(|at| chrono::Utc::now().timestamp() - at);
+        if let Some(age) = age.filter(|&age| age > STALE_AFTER_SECS) {
+            ui.label(
+                egui::RichText::new(format!(
+                    "⚠ This is today's bulletin; the photo was taken {} days ago",
+                    age / 86400
+                ))
+                .size(13.0)
+                .color(palette.warning),
+            );
+        }
+    });
+}
@@ -0,0 +1,251 @@
+use crate::AvalancheClassifier;
+use eframe::egui;
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+
+pub const DEFAULT_HOTKEY: &str = "ctrl+shift+KeyA";
+
+pub struct CaptureState {
+    manager: Option<GlobalHotKeyManager>,
+    registered: Option<HotKey>,
+    pub hotkey_text: String,
+    status: Option<String>,
+    // Screenshot waiting for the user to pick a region
+    screenshot: Option<(image::RgbaImage, egui::TextureHandle)>,
+    drag_start: Option<egui::Pos2>,
+    selection: Option<egui::Rect>,  // in screenshot pixel coordinates
+}
+
+impl Default for CaptureState {
+    fn default() -> Self {
+        Self {
+            manager: None,
+            registered: None,
+            hotkey_text: DEFAULT_HOTKEY.to_string(),
+            status: None,
+            screenshot: None,
+            drag_start: None,
+            selection: None,
+        }
+    }
+}
+
+impl CaptureState {
+    // Listens for the hotkey enabled in an earlier session, if any
+    pub fn restore(hotkey: Option<&str>) -> Self {
+        let mut state = Self::default();
+        if let Some(hotkey) = hotkey {
+            state.hotkey_text = hotkey.to_string();
+            state.status = Some(match state.register() {
+                Ok(()) => format!("Listening for {}", hotkey),
+                Err(err) => err.to_string(),
+            });
+        }
+        state
+    }
+
+    // (Re)registers the configured hotkey, replacing any previous one
+    pub fn register(&mut self) -> anyhow::Result<()> {
+        let hotkey: HotKey = self
+            .hotkey_text
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid hotkey \"{}\": {}", self.hotkey_text, e))?;
+        if self.manager.is_none() {
+            self.manager = Some(GlobalHotKeyManager::new()?);
+        }
+        let manager = self.manager.as_ref().unwrap();
+        if let Some(previous) = self.registered.take() {
+            manager.unregister(previous)?;
+        }
+        manager.register(hotkey)?;
+        self.registered = Some(hotkey);
+        Ok(())
+    }
+
+    pub fn unregister(&mut self) -> anyhow::Result<()> {
+        if let (Some(manager), Some(hotkey)) = (&self.manager, self.registered.take()) {
+            manager.unregister(hotkey)?;
+        }
+        Ok(())
+    }
+}
+
+// Grabs the primary monitor
+fn capture_screen() -> anyhow::Result<image::RgbaImage> {
+    let monitors = xcap::Monitor::all()?;
+    let monitor = monitors
+        .iter()
+        .find(|m| m.is_primary())
+        .or_else(|| monitors.first())
+        .ok_or_else(|| anyhow::anyhow!("No monitor found"))?;
+    let captured = monitor.capture_image()?;
+    // xcap uses a newer image crate, so hand the pixels over raw
+    let (width, height) = (captured.width(), captured.height());
+    image::RgbaImage::from_raw(width, height, captured.into_raw())
+        .ok_or_else(|| anyhow::anyhow!("Screen capture returned a malformed image"))
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn poll_capture_hotkey(&mut self, ctx: &egui::Context) {
+        let Some(hotkey) = self.capture.registered else {
+            return;
+        };
+        // Hotkey events arrive while the window is unfocused, so keep polling
+        ctx.request_repaint_after(std::time::Duration::from_millis(200));
+
+        while let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+            if event.id != hotkey.id() || event.state != HotKeyState::Pressed {
+                continue;
+            }
+            match capture_screen() {
+                Ok(screenshot) => {
+                    let size = [screenshot.width() as usize, screenshot.height() as usize];
+                    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, screenshot.as_raw());
+                    let texture = ctx.load_texture("screen-capture", color_image, egui::TextureOptions::LINEAR);
+                    self.capture.screenshot = Some((screenshot, texture));
+                    self.capture.selection = None;
+                    self.capture.drag_start = None;
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                }
+                Err(err) => self.error = Some(format!("Screen capture failed: {}", err)),
+            }
+        }
+    }
+
+    pub(crate) fn capture_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        egui::CollapsingHeader::new("Screen Capture")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new("Press the hotkey anywhere, drag over the area to analyze")
+                        .size(13.0)
+                        .color(muted_color)
+                );
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.capture.hotkey_text)
+                            .desired_width(180.0)
+                            .hint_text(DEFAULT_HOTKEY)
+                    );
+                    let label = if self.capture.registered.is_some() {
+                        "Update Hotkey"
+                    } else {
+                        "Enable Hotkey"
+                    };
+                    if ui.button(label).clicked() {
+                        self.capture.status = Some(match self.capture.register() {
+                            Ok(()) => {
+                                self.settings.capture_hotkey = Some(self.capture.hotkey_text.clone());
+                                format!("Listening for {}", self.capture.hotkey_text)
+                            }
+                            Err(err) => err.to_string(),
+                        });
+                    }
+                    if self.capture.registered.is_some() && ui.button("Disable").clicked() {
+                        self.capture.status = match self.capture.unregister() {
+                            Ok(()) => None,
+                            Err(err) => Some(err.to_string()),
+                        };
+                        self.settings.capture_hotkey = None;
+                    }
+                });
+                if let Some(status) = &self.capture.status {
+                    ui.label(egui::RichText::new(status).size(13.0).color(muted_color));
+                }
+            });
+    }
+
+    pub(crate) fn capture_window(&mut self, ctx: &egui::Context) {
+        let Some((screenshot, texture)) = &self.capture.screenshot else {
+            return;
+        };
+        let image_size = egui::vec2(screenshot.width() as f32, screenshot.height() as f32);
+
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("Select Region")
+            .open(&mut open)
+            .default_size(ctx.screen_rect().size() * 0.9)
+            .show(ctx, |ui| {
+                let available = ui.available_size() - egui::vec2(0.0, 40.0);
+                let scale = (available.x / image_size.x).min(available.y / image_size.y).min(1.0);
+                let (rect, response) = ui.allocate_exact_size(image_size * scale, egui::Sense::drag());
+                ui.painter().image(
+                    texture.id(),
+                    rect,
+                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                    egui::Color32::WHITE,
+                );
+
+                // Track the drag in screenshot pixels so the crop is exact
+                let to_image = |pos: egui::Pos2| {
+                    ((pos - rect.min) / scale)
+                        .to_pos2()
+                        .clamp(egui::Pos2::ZERO, image_size.to_pos2())
+                };
+                if response.drag_started() {
+                    self.capture.drag_start = response.interact_pointer_pos().map(to_image);
+                }
+                if let (Some(start), Some(pos)) = (self.capture.drag_start, response.interact_pointer_pos()) {
+                    if response.dragged() {
+                        self.capture.selection = Some(egui::Rect::from_two_pos(start, to_image(pos)));
+                    }
+                }
+                if let Some(selection) = self.capture.selection {
+                    let on_screen = egui::Rect::from_min_max(
+                        rect.min + selection.min.to_vec2() * scale,
+                        rect.min + selection.max.to_vec2() * scale,
+                    );
+                    ui.painter().rect_stroke(
+                        on_screen,
+                        0.0,
+                        egui::Stroke::new(2.0, egui::Color32::from_rgb(0, 122, 255)),
+                    );
+                }
+
+                ui.horizontal(|ui| {
+                    let label = if self.capture.selection.is_some() {
+                        "Analyze Selection"
+                    } else {
+                        "Analyze Full Screen"
+                    };
+                    if ui.button(label).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            if let Some((screenshot, _)) = self.capture.screenshot.take() {
+                let cropped = match self.capture.selection.take() {
+                    Some(sel) if sel.width() >= 8.0 && sel.height() >= 8.0 => image::imageops::crop_imm(
+                        &screenshot,
+                        sel.min.x as u32,
+                        sel.min.y as u32,
+                        sel.width() as u32,
+                        sel.height() as u32,
+                    )
+                    .to_image(),
+                    _ => screenshot,
+                };
+                let mut bytes = Vec::new();
+                match cropped.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Png) {
+                    Ok(()) => {
+                        self.load_image(ctx, bytes);
+                        if self.analysis_ready() {
+                            self.start_analysis();
+                        }
+                    }
+                    Err(err) => self.error = Some(err.to_string()),
+                }
+            }
+        }
+        if !open || cancelled {
+            self.capture.screenshot = None;
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use avalanche_classifier_core::ensemble::{classify_ensemble, Ensemble};
+use avalanche_classifier_core::{history, provider, weather, Backend, ClassifyOptions};
+use eframe::egui;
+
+use crate::AvalancheClassifier;
+
+impl AvalancheClassifier {
+    // Backends with enough configuration to take part in an ensemble run
+    pub(crate) fn ensemble_backends(&self) -> Vec<Backend> {
+        Backend::ALL.into_iter().filter(|b| self.is_configured(*b)).collect()
+    }
+
+    pub(crate) fn start_ensemble(&mut self) {
+        let Some(image_data) = &mut self.image_data else {
+            return;
+        };
+        let image_bytes = match image_data.analysis_bytes() {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                self.error = Some(format!("Could not crop the image: {}", err));
+                return;
+            }
+        };
+        let providers: Vec<_> = self
+            .ensemble_backends()
+            .into_iter()
+            .map(|b| self.provider_for(b))
+            .collect();
+        let options = ClassifyOptions::current().with_observations(self.observation.observations.clone());
+        let weather_at = self.weather_lookup();
+
+        let (promise, abort) = crate::runtime::spawn_abortable(async move {
+            let _slot = crate::runtime::analysis_slot(crate::runtime::Priority::Interactive).await;
+            let weather = match weather_at {
+                Some((location, at)) => weather::lookup(&provider::http_client(), location, at).await,
+                None => None,
+            };
+            let options = options.with_weather(weather);
+            classify_ensemble(&providers, &image_bytes, &options).await
+        });
+        self.ensemble_promise = Some(promise);
+        self.analysis_abort = Some(abort);
+    }
+
+    pub(crate) fn ensemble_toggle_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        ui.checkbox(&mut self.settings.ensemble, "Ensemble (all configured backends)");
+        if self.settings.ensemble {
+            let labels: Vec<&str> = self.ensemble_backends().iter().map(|b| b.label()).collect();
+            let text = if labels.len() < 2 {
+                "Configure at least two backends to run an ensemble".to_string()
+            } else {
+                format!("Voting: {}", labels.join(", "))
+            };
+            ui.label(egui::RichText::new(text).size(13.0).color(muted_color));
+        }
+    }
+
+    // Collects a finished ensemble run; every member that produced a snapshot
+    // is kept in history like a single-backend analysis
+    pub(crate) fn poll_ensemble(&mut self, ui: &mut egui::Ui, cancel: &mut bool) {
+        let Some(promise) = self.ensemble_promise.take() else {
+            return;
+        };
+        match promise.try_take() {
+            Ok(result) => {
+                self.analysis_abort = None;
+                self.finish_ensemble(result);
+            }
+            Err(promise) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Running ensemble...");
+                    *cancel = ui.button("Cancel").clicked();
+                });
+                self.ensemble_promise = Some(promise);
+            }
+        }
+    }
+
+    fn finish_ensemble(&mut self, result: anyhow::Result<Ensemble>) {
+        match result {
+            Ok(ensemble) => {
+                for member in &ensemble.members {
+                    let Ok(classification) = &member.outcome else {
+                        continue;
+                    };
+                    let Some(snapshot) = &classification.snapshot else {
+                        continue;
+                    };
+                    let mut entry = history::HistoryEntry::new(snapshot.clone(), classification.analysis.clone());
+                    entry.observations = self.observation.observations.clone();
+                    self.fill_photo_meta(&mut entry);
+                    if let Err(err) = self.history.push(entry) {
+                        self.error = Some(err.to_string());
+                    }
+                }
+                self.result = Some(ensemble.analysis.clone());
+                if let Some(image_data) = &mut self.image_data {
+                    image_data.show_annotations(ensemble.analysis.annotations.as_ref());
+                }
+                self.snapshot = None;
+                self.ensemble = Some(ensemble);
+                self.error = None;
+            }
+            Err(err) => {
+                self.error = Some(err.to_string());
+                self.result = None;
+                self.snapshot = None;
+                self.ensemble = None;
+            }
+        }
+    }
+
+    pub(crate) fn ensemble_results_ui(
+        &self,
+        ui: &mut egui::Ui,
+        success_color: egui::Color32,
+        warning_color: egui::Color32,
+        muted_color: egui::Color32,
+    ) {
+        let Some(ensemble) = &self.ensemble else {
+            return;
+        };
+        ui.add_space(16.0);
+        ui.group(|ui| {
+            ui.label(egui::RichText::new("Ensemble").size(16.0).strong());
+            let votes: Vec<String> = ensemble
+                .votes
+                .iter()
+                .map(|(avalanche_type, n)| format!("{} × {}", avalanche_type, n))
+                .collect();
+            if ensemble.unanimous() {
+                ui.label(
+                    egui::RichText::new(format!("Unanimous: {}", votes.join(", ")))
+                        .size(13.0)
+                        .color(success_color),
+                );
+            } else {
+                ui.label(
+                    egui::RichText::new(format!("Members disagree: {}", votes.join(", ")))
+                        .size(13.0)
+                        .color(warning_color),
+                );
+            }
+            ui.add_space(4.0);
+            let dissenters: Vec<&str> = ensemble.dissenters().map(|m| m.backend).collect();
+            for member in &ensemble.members {
+                let (text, color) = match &member.outcome {
+                    Ok(c) => (
+                        format!(
+                            "{} ({}): {} at {:.0}%",
+                            member.backend, member.model, c.analysis.avalanche_type, c.analysis.confidence_level
+                        ),
+                        if dissenters.contains(&member.backend) { warning_color } else { muted_color },
+                    ),
+                    Err(e) => (format!("{} ({}): failed: {}", member.backend, member.model, e), warning_color),
+                };
+                ui.label(egui::RichText::new(text).size(13.0).color(color));
+            }
+        });
+    }
+}
@@ -0,0 +1,103 @@
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::{checksum, geojson};
+use avalanche_classifier_core::gpx::{Gpx, MATCH_TOLERANCE_SECS};
+use eframe::egui;
+
+#[derive(Default)]
+pub struct GeotagState {
+    // Added to photo capture times before matching, for a camera clock that
+    // was off or still on another time zone
+    clock_offset_mins: i32,
+    last_match: Option<String>,
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn geotag_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let entries = &self.history.entries;
+        let from_photo = entries.iter().filter(|e| e.location.is_some() && !e.location_from_track).count();
+        let from_track = entries.iter().filter(|e| e.location_from_track).count();
+        let unlocated = entries.iter().filter(|e| e.location.is_none()).count();
+        let matchable = entries.iter().filter(|e| e.location.is_none() && e.captured_at.is_some()).count();
+        let located = entries.len() - unlocated;
+
+        egui::CollapsingHeader::new("Photo Locations")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} located from GPS or camera · {} from a track · {} without location ({} with a capture time)",
+                        from_photo,
+                        from_track,
+                        unlocated,
+                        matchable
+                    ))
+                    .size(13.0)
+                    .color(muted_color),
+                );
+                ui.add_space(8.0);
+                let mut matched = false;
+                ui.horizontal(|ui| {
+                    ui.label("Camera clock offset");
+                    ui.add(egui::DragValue::new(&mut self.geotag.clock_offset_mins).suffix(" min"))
+                        .on_hover_text("Minutes to add to photo times so they line up with the GPS track");
+                    matched = ui
+                        .add_enabled(matchable + from_track > 0, egui::Button::new("📍 Match Photos to GPX Track"))
+                        .on_hover_text("Infer locations for photos without GPS from a track recorded the same day")
+                        .clicked();
+                });
+                let export = ui
+                    .add_enabled(located > 0, egui::Button::new("🗺 Export GeoJSON"))
+                    .on_hover_text("Observation points, view cones, slopes and avalanche outlines for a GIS or web map")
+                    .clicked();
+                if export {
+                    if let Some(path) = self.settings.export_dialog()
+                        .add_filter("GeoJSON", &["geojson"])
+                        .set_file_name("avalanche-observations.geojson")
+                        .save_file()
+                    {
+                        let collection = geojson::feature_collection(self.history.originals());
+                        let written = serde_json::to_string_pretty(&collection)
+                            .map_err(anyhow::Error::from)
+                            .and_then(|json| Ok(std::fs::write(&path, json)?))
+                            .and_then(|()| checksum::write_sidecar(&path));
+                        if let Err(err) = written {
+                            self.error = Some(format!("Failed to export GeoJSON: {}", err));
+                        }
+                    }
+                }
+                if matched {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("GPX", &["gpx"]).pick_file() {
+                        let offset = i64::from(self.geotag.clock_offset_mins) * 60;
+                        let result = std::fs::read_to_string(&path)
+                            .map_err(anyhow::Error::from)
+                            .and_then(|xml| Gpx::parse(&xml))
+                            .map(|gpx| self.history.locate_from_track(&gpx, offset));
+                        match result {
+                            Ok(located) => {
+                                self.geotag.last_match = Some(format!(
+                                    "Located {} photos from {}",
+                                    located,
+                                    path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned())
+                                ));
+                                if let Err(err) = self.history.save() {
+                                    self.error = Some(err.to_string());
+                                }
+                            }
+                            Err(err) => self.error = Some(format!("Failed to read track: {}", err)),
+                        }
+                    }
+                }
+                if let Some(message) = &self.geotag.last_match {
+                    ui.label(egui::RichText::new(message).size(13.0));
+                }
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Photos match when taken during the track or within {} minutes of either end",
+                        MATCH_TOLERANCE_SECS / 60
+                    ))
+                    .size(13.0)
+                    .color(muted_color),
+                );
+            });
+    }
+}
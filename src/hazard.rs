@@ -0,0 +1,50 @@
+use crate::palette::Palette;
+use avalanche_classifier_core::{model, AvalancheAnalysis};
+use eframe::egui;
+
+// The EAWS danger scale colors, kept as published rather than themed so the
+// badge reads the same as a bulletin
+pub(crate) fn danger_colors(level: u8) -> (egui::Color32, egui::Color32) {
+    match level {
+        1 => (egui::Color32::from_rgb(204, 255, 102), egui::Color32::BLACK),
+        2 => (egui::Color32::from_rgb(255, 255, 0), egui::Color32::BLACK),
+        3 => (egui::Color32::from_rgb(255, 153, 0), egui::Color32::BLACK),
+        4 => (egui::Color32::from_rgb(255, 0, 0), egui::Color32::WHITE),
+        _ => (egui::Color32::from_rgb(32, 32, 32), egui::Color32::WHITE),
+    }
+}
+
+pub(crate) fn badge(ui: &mut egui::Ui, fill: egui::Color32, text_color: egui::Color32, value: &str, caption: &str) {
+    egui::Frame::none()
+        .fill(fill)
+        .rounding(8.0)
+        .inner_margin(egui::vec2(12.0, 6.0))
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new(value).size(22.0).strong().color(text_color));
+                ui.label(egui::RichText::new(caption).size(12.0).color(text_color));
+            });
+        });
+}
+
+// Danger level and destructive size as badges under the type, with the
+// model's reasoning below; nothing for analyses made before the prompt asked
+// for them
+pub(crate) fn hazard_badges_ui(ui: &mut egui::Ui, analysis: &AvalancheAnalysis, palette: Palette) {
+    if analysis.danger_level.is_none() && analysis.destructive_size.is_none() {
+        return;
+    }
+    ui.horizontal(|ui| {
+        if let Some(level) = analysis.danger_level {
+            let (fill, text_color) = danger_colors(level);
+            badge(ui, fill, text_color, &level.to_string(), model::danger_level_name(level));
+        }
+        if let Some(size) = analysis.destructive_size {
+            let fill = if size >= 3 { palette.danger } else { palette.warning };
+            badge(ui, fill, egui::Color32::WHITE, &format!("D{}", size), model::destructive_size_name(size));
+        }
+    });
+    if let Some(rationale) = analysis.hazard_rationale.as_deref().filter(|r| !r.trim().is_empty()) {
+        ui.label(egui::RichText::new(rationale).size(13.0).color(palette.muted));
+    }
+}
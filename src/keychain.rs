@@ -0,0 +1,82 @@
+use avalanche_classifier_core::Backend;
+
+// API keys live in the system keychain (macOS Keychain, Windows Credential
+// Manager, Secret Service on Linux), never in settings.toml. For headless and
+// CI runs they can come from the environment or a .env file instead.
+const SERVICE: &str = "avalanche-classifier";
+
+// Where a key filled in at startup came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum KeySource {
+    Environment,
+    DotEnv,
+    Keychain,
+}
+
+pub(crate) fn env_var(backend: Backend) -> Option<&'static str> {
+    match backend {
+        Backend::OpenAi => Some("OPENAI_API_KEY"),
+        Backend::Anthropic => Some("ANTHROPIC_API_KEY"),
+        _ => None,
+    }
+}
+
+// The key to start with: the environment variable, then a .env file in the
+// working directory or a parent of it, then the keychain when keys are
+// remembered
+pub(crate) fn initial_key(backend: Backend, use_keychain: bool) -> Option<(String, KeySource)> {
+    let var = env_var(backend)?;
+    let non_empty = |key: &String| !key.trim().is_empty();
+    if let Some(key) = std::env::var(var).ok().filter(non_empty) {
+        return Some((key.trim().to_string(), KeySource::Environment));
+    }
+    let from_dotenv = dotenvy::dotenv_iter()
+        .ok()
+        .and_then(|mut vars| vars.find_map(|item| item.ok().filter(|(name, _)| name == var)))
+        .map(|(_, key)| key)
+        .filter(non_empty);
+    if let Some(key) = from_dotenv {
+        return Some((key.trim().to_string(), KeySource::DotEnv));
+    }
+    if use_keychain {
+        return load(backend).map(|key| (key, KeySource::Keychain));
+    }
+    None
+}
+
+// Enough of a key to tell which one it is, e.g. "sk-…9f2c"
+pub(crate) fn masked(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() < 12 {
+        return "••••".to_string();
+    }
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+fn entry(backend: Backend) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, backend.id())
+}
+
+// The saved key, None when there is none or no keychain to read it from
+pub(crate) fn load(backend: Backend) -> Option<String> {
+    entry(backend).and_then(|entry| entry.get_password()).ok()
+}
+
+// Saves the key, or removes it when empty
+pub(crate) fn store(backend: Backend, key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return forget(backend);
+    }
+    entry(backend)
+        .and_then(|entry| entry.set_password(key))
+        .map_err(|err| format!("Could not save the {} key to the system keychain: {}", backend.label(), err))
+}
+
+pub(crate) fn forget(backend: Backend) -> Result<(), String> {
+    match entry(backend).and_then(|entry| entry.delete_password()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(format!("Could not remove the {} key from the system keychain: {}", backend.label(), err)),
+    }
+}
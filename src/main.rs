@@ -1,99 +1,844 @@
+use avalanche_classifier_core::{
+    bundle, compare, fallback, dataset, history, model, orientation, pdf, photo_meta, provider, report, snapshot, AnalysisError,
+    Annotations, AvalancheAnalysis, Backend, Classification, ClassifyOptions,
+    VisionProvider,
+};
+use avalanche_classifier_core::exposure::ExposedAsset;
+use avalanche_classifier_core::camera::Roi;
 use eframe::egui;
 use poll_promise::Promise;
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct SnowTexture {
-    granular: bool,
-    blocky: bool,
-    fluffy: bool,
-    density: String,  // "low"|"medium"|"high"
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct MovementPattern {
-    starting_width: String,  // "point"|"wide"|"undefined"
-    propagation: String,     // "fan"|"linear"|"chaotic"|"none"
-    vertical_movement: bool, // true for significant vertical displacement
-    lateral_spread: bool,    // true for significant sideways spread
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct TerrainFeatures {
-    slope_angle: Option<String>,
-    surface_roughness: String,  // "smooth"|"rough"|"variable"
-    anchoring_points: bool,     // trees, rocks, etc.
-    convex_rollover: bool,      // terrain rolls over
-}
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct VisualCharacteristics {
-    powder_cloud: bool,
-    fracture_line: bool,
-    fracture_depth: Option<String>,  // "shallow"|"deep"|"variable"
-    point_release: bool,
-    debris_pattern: String,
-    snow_texture: SnowTexture,
-    movement_pattern: MovementPattern,
-    terrain: TerrainFeatures,
-}
+mod alerts;
+mod archive;
+mod batch;
+mod bulletin;
+mod capture;
+mod ensemble;
+mod geotag;
+mod hazard;
+mod keychain;
+mod map;
+mod missions;
+mod monitor;
+mod observation;
+mod palette;
+mod pipe;
+mod preview;
+mod presentation;
+mod provenance;
+mod prompt;
+mod retry;
+mod review;
+mod runtime;
+mod scheduler;
+mod scores;
+mod settings;
+mod slope_check;
+mod stats;
+mod terrain;
+mod terrain3d;
+#[cfg(not(target_os = "linux"))]
+mod tray;
+mod trips;
+mod update;
+mod url_loader;
+mod verification;
+mod video;
+mod watch;
+mod weather;
+mod webcam;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct AvalancheAnalysis {
-    avalanche_present: bool,
-    avalanche_type: String,
-    confidence_level: f32,
-    terrain_features: Vec<String>,
-    visual_characteristics: VisualCharacteristics,
-}
+// (original entry id, result) for each entry in a re-run batch
+type RerunResults = Vec<(String, anyhow::Result<Classification>)>;
 
 struct AvalancheClassifier {
+    settings: settings::Settings,
+    saved_settings: settings::Settings,
     openai_api_key: String,
+    anthropic_api_key: String,
     image_data: Option<ImageData>,
-    promise: Option<Promise<anyhow::Result<AvalancheAnalysis>>>,
+    promise: Option<Promise<Result<Classification, AnalysisError>>>,
+    // Cancels the in-flight analysis, single or ensemble
+    analysis_abort: Option<tokio::task::AbortHandle>,
     result: Option<AvalancheAnalysis>,
+    snapshot: Option<snapshot::AnalysisSnapshot>,
     error: Option<String>,
+    report_template: Option<String>,
+    history: history::History,
+    history_selection: std::collections::HashSet<String>,
+    // Loaded lazily from the history database; None when there is no thumbnail
+    history_thumbnails: std::collections::HashMap<String, Option<egui::TextureHandle>>,
+    rerun_promise: Option<Promise<RerunResults>>,
+    ensemble_promise: Option<Promise<anyhow::Result<avalanche_classifier_core::ensemble::Ensemble>>>,
+    ensemble: Option<avalanche_classifier_core::ensemble::Ensemble>,
+    compare_id: Option<String>,
+    compare_changed_only: bool,
+    review: review::ReviewState,
+    stats: stats::StatsState,
+    settings_open: bool,
+    presentation: presentation::PresentationState,
+    // Whether scoring.toml was found, or why it could not be read
+    scoring_file: Result<bool, String>,
+    // Keys filled in at startup and where they came from
+    initial_keys: Vec<(Backend, String, keychain::KeySource)>,
+    retry: retry::RetryState,
+    installed_models: Vec<bundle::BundleManifest>,
+    update: update::UpdateState,
+    capture: capture::CaptureState,
+    scheduler: scheduler::SchedulerState,
+    monitor: monitor::MonitorState,
+    archive: archive::ArchiveState,
+    prompt: prompt::PromptState,
+    alerts: alerts::AlertsState,
+    bulletin: bulletin::BulletinState,
+    map: map::MapState,
+    slope_check: slope_check::SlopeCheckState,
+    missions: missions::MissionsState,
+    observation: observation::ObservationState,
+    terrain: terrain::TerrainState,
+    trips: trips::TripsState,
+    #[cfg(not(target_os = "linux"))]
+    tray: tray::TrayState,
+    geotag: geotag::GeotagState,
+    terrain3d: terrain3d::Terrain3dState,
+    batch: batch::BatchState,
+    verification: verification::VerificationState,
+    url_loader: url_loader::UrlLoaderState,
+    video: video::VideoState,
+    watch: watch::WatchState,
+    webcam: webcam::WebcamState,
+    // Started without GPU acceleration, by flag or after it failed
+    software_rendering: bool,
 }
 
 struct ImageData {
     bytes: Vec<u8>,
     texture: Option<egui::TextureHandle>,
+    // Why there is no preview, when the photo could not be shown
+    preview_error: Option<String>,
+    // Part of the photo to analyze, dragged out on the preview
+    crop: Option<Roi>,
+    // Where the current drag began, as fractions of the photo
+    crop_start: Option<(f32, f32)>,
+    view: preview::PreviewView,
+    // Upright size of the photo itself, not of the preview texture
+    photo_size: egui::Vec2,
+    // Loaded when zooming in past the preview's resolution
+    full_texture: Option<egui::TextureHandle>,
+    // The preview and full-resolution photo through the flat light filter,
+    // made when first shown
+    flat_texture: Option<egui::TextureHandle>,
+    flat_full_texture: Option<egui::TextureHandle>,
+    // Part of the photo sent with the last analysis, the whole of it unless cropped
+    analyzed_region: Roi,
+    // Where the result places the fracture line, start zone and debris, in
+    // coordinates of the whole photo
+    overlay: Option<Annotations>,
+}
+
+impl ImageData {
+    // What is sent to the model: the marked region, or the whole photo.
+    // Remembers which, so the result's annotations can be placed.
+    fn analysis_bytes(&mut self) -> anyhow::Result<Vec<u8>> {
+        self.analyzed_region = self.crop.unwrap_or_default();
+        match &self.crop {
+            Some(roi) => roi.crop_photo(&self.bytes),
+            None => Ok(self.bytes.clone()),
+        }
+    }
+
+    // Draws a result's annotations over the preview, mapped from the region
+    // that was sent onto the whole photo
+    fn show_annotations(&mut self, annotations: Option<&Annotations>) {
+        self.overlay = annotations.map(|a| a.within(&self.analyzed_region));
+    }
 }
 
+// Previews are drawn at 400 px; anything larger only costs texture memory.
+// Zooming in past it loads the full-resolution photo.
+const PREVIEW_MAX_PX: u32 = 2048;
+
 impl AvalancheClassifier {
-    fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        setup_apple_style(&cc.egui_ctx);
+    fn new(cc: &eframe::CreationContext<'_>, software_rendering: bool) -> Self {
+        let (settings, settings_error) = settings::Settings::load();
+        setup_apple_style(&cc.egui_ctx, settings.theme.is_dark(cc.integration_info.system_theme));
+        settings.apply();
+        let scoring_file = avalanche_classifier_core::scoring::reload().map_err(|err| err.to_string());
+        let scoring_error =
+            scoring_file.as_ref().err().map(|err| format!("Could not read scoring.toml, using defaults: {}", err));
+        let initial_keys: Vec<(Backend, String, keychain::KeySource)> = [Backend::OpenAi, Backend::Anthropic]
+            .into_iter()
+            .filter_map(|backend| {
+                keychain::initial_key(backend, settings.remember_api_keys).map(|(key, source)| (backend, key, source))
+            })
+            .collect();
+        let initial_key = |backend: Backend| {
+            initial_keys.iter().find(|(b, _, _)| *b == backend).map_or_else(String::new, |(_, key, _)| key.clone())
+        };
+        let openai_api_key = initial_key(Backend::OpenAi);
+        let anthropic_api_key = initial_key(Backend::Anthropic);
+        let capture = capture::CaptureState::restore(settings.capture_hotkey.as_deref());
+        let (history, history_error) = history::History::load_default();
         Self {
-            openai_api_key: String::new(),
+            saved_settings: settings.clone(),
+            settings,
+            openai_api_key,
+            anthropic_api_key,
             image_data: None,
             promise: None,
+            analysis_abort: None,
             result: None,
-            error: None,
+            snapshot: None,
+            error: settings_error.or(scoring_error).or(history_error),
+            report_template: None,
+            history,
+            history_selection: Default::default(),
+            history_thumbnails: Default::default(),
+            rerun_promise: None,
+            ensemble_promise: None,
+            ensemble: None,
+            compare_id: None,
+            compare_changed_only: false,
+            review: Default::default(),
+            stats: Default::default(),
+            settings_open: false,
+            presentation: Default::default(),
+            scoring_file,
+            initial_keys,
+            retry: Default::default(),
+            installed_models: bundle::installed_models(),
+            update: Default::default(),
+            capture,
+            scheduler: Default::default(),
+            monitor: Default::default(),
+            alerts: Default::default(),
+            bulletin: Default::default(),
+            map: Default::default(),
+            slope_check: Default::default(),
+            missions: Default::default(),
+            observation: Default::default(),
+            terrain: Default::default(),
+            trips: Default::default(),
+            #[cfg(not(target_os = "linux"))]
+            tray: Default::default(),
+            geotag: Default::default(),
+            terrain3d: Default::default(),
+            batch: Default::default(),
+            verification: Default::default(),
+            url_loader: Default::default(),
+            video: Default::default(),
+            watch: Default::default(),
+            webcam: Default::default(),
+            archive: Default::default(),
+            prompt: Default::default(),
+            software_rendering,
         }
     }
 
     fn load_image(&mut self, ctx: &egui::Context, bytes: Vec<u8>) {
-        if let Ok(image) = image::load_from_memory(&bytes) {
-            let rgba = image.to_rgba8();
+        let image = match photo_meta::load_upright(&bytes) {
+            Ok(image) => image,
+            Err(err) => {
+                self.error = Some(format!("Could not read the image: {}", err));
+                return;
+            }
+        };
+        // Scaled down on the CPU first: a full-size photo can exceed the
+        // largest texture the graphics driver accepts, which would crash
+        // the renderer instead of showing anything
+        let photo_size = egui::vec2(image.width() as f32, image.height() as f32);
+        let max_side = u32::try_from(ctx.input(|i| i.max_texture_side)).unwrap_or(u32::MAX).min(PREVIEW_MAX_PX);
+        let (texture, preview_error) = if max_side < 64 {
+            (None, Some(format!("the graphics driver only allows {} px textures", max_side)))
+        } else {
+            let preview = if image.width().max(image.height()) > max_side {
+                image.thumbnail(max_side, max_side)
+            } else {
+                image
+            };
+            let rgba = preview.to_rgba8();
             let size = [rgba.width() as usize, rgba.height() as usize];
-            let pixels = rgba.into_vec();
-            let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
-            
-            self.image_data = Some(ImageData {
-                bytes,
-                texture: Some(ctx.load_texture(
-                    "uploaded-image",
-                    color_image,
-                    egui::TextureOptions::LINEAR
-                )),
+            let color_image = egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
+            (Some(ctx.load_texture("uploaded-image", color_image, egui::TextureOptions::LINEAR)), None)
+        };
+        self.retry = Default::default();
+        self.image_data = Some(ImageData {
+            bytes,
+            texture,
+            preview_error,
+            crop: None,
+            crop_start: None,
+            view: Default::default(),
+            photo_size,
+            full_texture: None,
+            flat_texture: None,
+            flat_full_texture: None,
+            analyzed_region: Roi::default(),
+            overlay: None,
+        });
+    }
+
+    fn start_analysis(&mut self) {
+        if self.settings.mode == verification::AnalysisMode::ControlVerification {
+            self.start_verification();
+            return;
+        }
+        if self.settings.ensemble {
+            self.start_ensemble();
+            return;
+        }
+        let Some(image_data) = &mut self.image_data else {
+            return;
+        };
+        let image_bytes = match image_data.analysis_bytes() {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                self.error = Some(format!("Could not crop the image: {}", err));
+                return;
+            }
+        };
+        let chain = self.fallback_chain();
+        self.retry.record(self.settings.backend, chain[0].model(), &image_bytes);
+        let options = ClassifyOptions::current().with_observations(self.observation.observations.clone());
+        let weather_at = self.weather_lookup();
+
+        let (promise, abort) = runtime::spawn_abortable(async move {
+            let _slot = runtime::analysis_slot(runtime::Priority::Interactive).await;
+            let weather = match weather_at {
+                Some((location, at)) => {
+                    avalanche_classifier_core::weather::lookup(&provider::http_client(), location, at).await
+                }
+                None => None,
+            };
+            let options = options.with_weather(weather);
+            fallback::classify_with_fallback(&chain, &image_bytes, &options).await
+        });
+        self.promise = Some(promise);
+        self.analysis_abort = Some(abort);
+    }
+
+    // Fills the entry's capture time, location and camera from the photo's
+    // EXIF. A cropped upload was re-encoded without it, so it is read from
+    // the original still on screen.
+    fn fill_photo_meta(&self, entry: &mut history::HistoryEntry) {
+        match self.image_data.as_ref().filter(|d| d.crop.is_some()) {
+            Some(image_data) => entry.apply_photo_meta(photo_meta::PhotoMeta::read(&image_data.bytes)),
+            None => entry.read_photo_meta(),
+        }
+    }
+
+    // Aborts the request and drops the pending result, leaving the previous
+    // result on screen
+    fn cancel_analysis(&mut self) {
+        if let Some(abort) = self.analysis_abort.take() {
+            abort.abort();
+        }
+        self.promise = None;
+        self.ensemble_promise = None;
+        self.verification.cancel();
+        self.error = None;
+    }
+}
+
+impl AvalancheClassifier {
+    // Backend built from the current settings
+    fn provider(&self) -> Box<dyn VisionProvider> {
+        self.provider_for(self.settings.backend)
+    }
+
+    fn provider_for(&self, backend: Backend) -> Box<dyn VisionProvider> {
+        self.provider_with_model(backend, self.settings.model(backend).to_string())
+    }
+
+    // The selected backend followed by the configured backends of the
+    // fallback chain, in order
+    fn fallback_chain(&self) -> Vec<Box<dyn VisionProvider>> {
+        let mut backends = vec![self.settings.backend];
+        for backend in &self.settings.fallback_chain {
+            if !backends.contains(backend) && self.is_configured(*backend) {
+                backends.push(*backend);
+            }
+        }
+        backends.into_iter().map(|backend| self.provider_for(backend)).collect()
+    }
+
+    // A backend from the current settings with a different model
+    fn provider_with_model(&self, backend: Backend, model: String) -> Box<dyn VisionProvider> {
+        let api_key = match backend {
+            Backend::OpenAi => self.openai_api_key.clone(),
+            Backend::Anthropic => self.anthropic_api_key.clone(),
+            _ => String::new(),
+        };
+        self.settings.provider(backend, model, api_key)
+    }
+
+    // Where the backend's key came from, while it is still the one loaded
+    // at startup
+    fn key_source(&self, backend: Backend) -> Option<keychain::KeySource> {
+        let current = match backend {
+            Backend::OpenAi => &self.openai_api_key,
+            Backend::Anthropic => &self.anthropic_api_key,
+            _ => return None,
+        };
+        self.initial_keys
+            .iter()
+            .find(|(b, key, _)| *b == backend && key == current)
+            .map(|(_, _, source)| *source)
+    }
+
+    // None for backends that don't take a key
+    fn api_key_mut(&mut self) -> Option<&mut String> {
+        match self.settings.backend {
+            Backend::OpenAi => Some(&mut self.openai_api_key),
+            Backend::Anthropic => Some(&mut self.anthropic_api_key),
+            _ => None,
+        }
+    }
+
+    // Whether the selected backend has what it needs to send a request
+    fn has_api_key(&self) -> bool {
+        self.is_configured(self.settings.backend)
+    }
+
+    fn is_configured(&self, backend: Backend) -> bool {
+        match backend {
+            Backend::OpenAi => {
+                !self.openai_api_key.is_empty()
+                    && !self.settings.openai_base_url.is_empty()
+                    && !self.settings.openai_model.is_empty()
+            }
+            Backend::Anthropic => {
+                !self.anthropic_api_key.is_empty() && !self.settings.anthropic_model.is_empty()
+            }
+            Backend::Ollama => !self.settings.ollama_url.is_empty() && !self.settings.ollama_model.is_empty(),
+            _ => false,
+        }
+    }
+
+    // Ready for an interactive analysis, single backend or ensemble
+    fn analysis_ready(&self) -> bool {
+        if self.settings.ensemble {
+            self.ensemble_backends().len() >= 2
+        } else {
+            self.has_api_key()
+        }
+    }
+
+    fn history_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let palette = palette::Palette::of(ui.ctx());
+        // Collect finished re-runs as new versions of their originals
+        if let Some(promise) = &self.rerun_promise {
+            if let Some(results) = promise.ready() {
+                let mut batch_ids = Vec::new();
+                let mut failures = Vec::new();
+                for (parent_id, result) in results {
+                    match result {
+                        Ok(Classification { analysis, snapshot: Some(snapshot), fallbacks }) => {
+                            batch_ids.push(snapshot.id.clone());
+                            let original = self.history.entries.iter().find(|e| e.id() == parent_id);
+                            let source = original.and_then(|e| e.source.clone());
+                            let location = original.and_then(|e| e.location);
+                            let captured_at = original.and_then(|e| e.captured_at);
+                            let location_from_track = original.is_some_and(|e| e.location_from_track);
+                            let bearing = original.and_then(|e| e.bearing);
+                            let camera_info = original.and_then(|e| e.camera_info.clone());
+                            let site_context = original.map(|e| e.site_context.clone()).unwrap_or_default();
+                            let observations = original.map(|e| e.observations.clone()).unwrap_or_default();
+                            let entry = history::HistoryEntry {
+                                parent_id: Some(parent_id.clone()),
+                                source,
+                                location,
+                                captured_at,
+                                location_from_track,
+                                bearing,
+                                camera_info,
+                                fallbacks: fallbacks.clone(),
+                                site_context,
+                                observations,
+                                ..history::HistoryEntry::new(snapshot.clone(), analysis.clone())
+                            };
+                            if let Err(err) = self.history.push(entry) {
+                                failures.push(format!("{}: {}", &parent_id[..12], err));
+                            }
+                        }
+                        Ok(_) => failures.push(format!("{}: the snapshot could not be stored", &parent_id[..12])),
+                        Err(err) => failures.push(format!("{}: {}", &parent_id[..12], err)),
+                    }
+                }
+                if let Err(err) = self.history.flag_batch(&batch_ids) {
+                    failures.push(err.to_string());
+                }
+                if !failures.is_empty() {
+                    self.error = Some(format!(
+                        "{} of {} re-runs failed:\n{}",
+                        failures.len(),
+                        results.len(),
+                        failures.join("\n")
+                    ));
+                }
+                self.rerun_promise = None;
+            } else {
+                // Keep polling even while the history section is collapsed
+                ui.ctx().request_repaint();
+            }
+        }
+
+        let mut open_entry = None;
+        egui::CollapsingHeader::new(format!("History ({})", self.history.originals().count()))
+            .default_open(false)
+            .show(ui, |ui| {
+                // Load a few thumbnails per frame so a long history doesn't stall the UI
+                let mut decode_budget = 4;
+                for lineage in self.history.lineages().into_iter().rev() {
+                    let (entry, latest, versions) = (lineage.original, lineage.latest, lineage.versions);
+                    if !self.history_thumbnails.contains_key(entry.id()) {
+                        if decode_budget > 0 {
+                            decode_budget -= 1;
+                            let thumbnail = self
+                                .history
+                                .thumbnail(entry.id())
+                                .and_then(|bytes| load_thumbnail(ui.ctx(), entry.id(), &bytes));
+                            self.history_thumbnails.insert(entry.id().to_string(), thumbnail);
+                        } else {
+                            ui.ctx().request_repaint();
+                        }
+                    }
+                    let mut selected = self.history_selection.contains(entry.id());
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut selected, "").changed() {
+                            if selected {
+                                self.history_selection.insert(entry.id().to_string());
+                            } else {
+                                self.history_selection.remove(entry.id());
+                            }
+                        }
+                        if let Some(Some(texture)) = self.history_thumbnails.get(entry.id()) {
+                            let thumbnail = egui::Image::new(texture)
+                                .fit_to_exact_size(egui::vec2(64.0, 48.0))
+                                .maintain_aspect_ratio(true)
+                                .rounding(4.0)
+                                .sense(egui::Sense::click());
+                            if ui.add(thumbnail).on_hover_text("Show this result").clicked() {
+                                open_entry = Some(latest.clone());
+                            }
+                        }
+                        let label = latest.effective_type().unwrap_or("rejected");
+                        ui.add(pill_label(label, type_color(label, palette, muted_color)));
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{:.0}% · {} · {}",
+                                latest.analysis.confidence_level,
+                                latest.snapshot.model,
+                                monitor::format_date_time(latest.snapshot.created_at)
+                            ))
+                            .size(13.0)
+                        );
+                        if !latest.fallbacks.is_empty() {
+                            ui.label(
+                                egui::RichText::new(format!("↪ {}", latest.snapshot.backend))
+                                    .size(13.0)
+                                    .color(palette.warning)
+                            )
+                            .on_hover_text(fallback_text(&latest.fallbacks));
+                        }
+                        if let Some((total, breakdown)) = timing_text(&latest.snapshot) {
+                            ui.label(egui::RichText::new(format!("⏱ {}", total)).size(13.0).color(muted_color))
+                                .on_hover_text(format!("{} via {}", breakdown, latest.snapshot.backend));
+                        }
+                        if ui.small_button("Open").clicked() {
+                            open_entry = Some(latest.clone());
+                        }
+                        if latest.effective_type() != Some(latest.analysis.avalanche_type.as_str()) {
+                            ui.label(
+                                egui::RichText::new("✎ Reviewed")
+                                    .size(13.0)
+                                    .color(muted_color)
+                            )
+                            .on_hover_text(format!("AI label: {}", latest.analysis.avalanche_type));
+                        }
+                        if !latest.flags.is_empty() {
+                            ui.label(
+                                egui::RichText::new("⚑ Review")
+                                    .size(13.0)
+                                    .color(palette.warning)
+                            )
+                            .on_hover_text(latest.flags.join("\n"));
+                        }
+                        if versions > 1 {
+                            ui.label(
+                                egui::RichText::new(format!("{} versions", versions))
+                                    .size(13.0)
+                                    .color(muted_color)
+                            );
+                            if ui.small_button("Compare").clicked() {
+                                self.compare_id = Some(entry.id().to_string());
+                            }
+                        }
+                    });
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Select All").clicked() {
+                        self.history_selection = self.history.originals().map(|e| e.id().to_string()).collect();
+                    }
+                    let can_rerun = !self.history_selection.is_empty()
+                        && self.has_api_key()
+                        && self.rerun_promise.is_none();
+                    if ui
+                        .add_enabled(can_rerun, egui::Button::new("↻ Re-analyze with Current Settings"))
+                        .clicked()
+                    {
+                        self.start_rerun();
+                    }
+                    if ui
+                        .add_enabled(!self.history_selection.is_empty(), egui::Button::new("⚑ Flag Outliers"))
+                        .clicked()
+                    {
+                        // Check the latest version of each selected entry as one batch
+                        let batch_ids: Vec<String> = self
+                            .history_selection
+                            .iter()
+                            .filter_map(|id| self.history.versions(id).last().map(|e| e.id().to_string()))
+                            .collect();
+                        if let Err(err) = self.history.flag_batch(&batch_ids) {
+                            self.error = Some(err.to_string());
+                        }
+                    }
+                });
+
+                if ui.button("⬇ Export Labeled Dataset").clicked() {
+                    if let Some(path) = self.settings.export_dialog()
+                        .add_filter("JSON Lines", &["jsonl"])
+                        .set_file_name("avalanche-labels.jsonl")
+                        .save_file()
+                    {
+                        if let Err(err) = dataset::export_labels(&self.history, &path) {
+                            self.error = Some(err.to_string());
+                        }
+                    }
+                }
+                if ui
+                    .button("⬇ Export Fine-Tuning Dataset")
+                    .on_hover_text("Reviewed entries in OpenAI chat fine-tuning JSONL format")
+                    .clicked()
+                {
+                    if let Some(path) = self.settings.export_dialog()
+                        .add_filter("JSON Lines", &["jsonl"])
+                        .set_file_name("avalanche-fine-tuning.jsonl")
+                        .save_file()
+                    {
+                        match snapshot::BlobStore::open_default()
+                            .and_then(|store| dataset::export_fine_tuning(&self.history, &store, &path))
+                        {
+                            Ok(export) => {
+                                let mut skipped = Vec::new();
+                                if export.skipped_corrected > 0 {
+                                    skipped.push(format!(
+                                        "{} corrected to another type (only the type was re-labelled)",
+                                        export.skipped_corrected
+                                    ));
+                                }
+                                if !export.missing_blobs.is_empty() {
+                                    skipped.push(format!(
+                                        "{} with a missing image or prompt: {}",
+                                        export.missing_blobs.len(),
+                                        export.missing_blobs.join(", ")
+                                    ));
+                                }
+                                if !skipped.is_empty() {
+                                    self.error = Some(format!(
+                                        "Exported {} entries, left out {}",
+                                        export.written,
+                                        skipped.join("; ")
+                                    ));
+                                }
+                            }
+                            Err(err) => self.error = Some(err.to_string()),
+                        }
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    let queued = self.history.review_queue().len();
+                    if queued > 0 && ui.button(format!("Review Queue ({})", queued)).clicked() {
+                        self.review.open = true;
+                    }
+                    if ui
+                        .button("🎯 Sample for Labeling")
+                        .on_hover_text("Queue the 10 most informative unreviewed entries")
+                        .clicked()
+                    {
+                        if let Err(err) = self.history.queue_samples(10) {
+                            self.error = Some(err.to_string());
+                        }
+                    }
+                    if ui.button("📊 Stats").clicked() {
+                        self.stats.open = true;
+                    }
+                });
+
+                if self.rerun_promise.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Re-analyzing selected entries...");
+                    });
+                }
             });
+        if let Some(entry) = open_entry {
+            self.open_history_entry(ui.ctx(), &entry);
+        }
+    }
+
+    // Restores the result view for a past analysis, with the reviewed label
+    // when there is one
+    fn open_history_entry(&mut self, ctx: &egui::Context, entry: &history::HistoryEntry) {
+        match snapshot::BlobStore::open_default().and_then(|store| entry.snapshot.image(&store)) {
+            Ok(bytes) => self.load_image(ctx, bytes),
+            Err(_) => self.image_data = None,
+        }
+        self.result = Some(entry.labeled_analysis().unwrap_or_else(|| entry.analysis.clone()));
+        if let Some(image_data) = &mut self.image_data {
+            image_data.show_annotations(entry.analysis.annotations.as_ref());
         }
+        self.snapshot = Some(entry.snapshot.clone());
+        self.ensemble = None;
+        self.error = None;
+        self.settings.mode = verification::AnalysisMode::Classification;
+    }
+
+    fn models_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        egui::CollapsingHeader::new(format!("Local Models ({})", self.installed_models.len()))
+            .default_open(false)
+            .show(ui, |ui| {
+                for manifest in &self.installed_models {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(format!("{} {}", manifest.name, manifest.version))
+                                .size(13.0)
+                                .strong()
+                        );
+                        if !manifest.description.is_empty() {
+                            ui.label(
+                                egui::RichText::new(&manifest.description)
+                                    .size(13.0)
+                                    .color(muted_color)
+                            );
+                        }
+                    });
+                }
+
+                ui.add_space(8.0);
+                if ui
+                    .button("📦 Import Model Bundle")
+                    .on_hover_text("Install a signed offline model bundle, e.g. from a USB drive")
+                    .clicked()
+                {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Model Bundle", &["tar", "avbundle"])
+                        .pick_file()
+                    {
+                        match bundle::trusted_keys().and_then(|keys| bundle::import_bundle(&path, &keys)) {
+                            Ok(_) => self.installed_models = bundle::installed_models(),
+                            Err(err) => self.error = Some(format!("Bundle import failed: {}", err)),
+                        }
+                    }
+                }
+            });
     }
+
+    fn compare_window(&mut self, ctx: &egui::Context) {
+        let Some(original_id) = self.compare_id.clone() else {
+            return;
+        };
+        let versions = self.history.versions(&original_id);
+        let analyses: Vec<&AvalancheAnalysis> = versions.iter().map(|e| &e.analysis).collect();
+        let rows = compare::diff_rows(&analyses);
+        let highlight = palette::Palette::of(ctx).warning;
+
+        let mut open = true;
+        egui::Window::new("Compare Versions")
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut self.compare_changed_only, "Changed fields only");
+                ui.add_space(8.0);
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("compare_grid").striped(true).show(ui, |ui| {
+                        ui.label(egui::RichText::new("Field").strong());
+                        for (i, entry) in versions.iter().enumerate() {
+                            ui.label(
+                                egui::RichText::new(format!("v{} · {}", i + 1, entry.snapshot.model)).strong()
+                            );
+                        }
+                        ui.end_row();
+
+                        for row in rows.iter().filter(|r| r.changed || !self.compare_changed_only) {
+                            ui.label(egui::RichText::new(&row.field).size(13.0));
+                            for value in &row.values {
+                                let text = egui::RichText::new(value).size(13.0);
+                                if row.changed {
+                                    ui.label(text.color(highlight).strong());
+                                } else {
+                                    ui.label(text);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        if !open {
+            self.compare_id = None;
+        }
+    }
+
+    fn start_rerun(&mut self) {
+        let store = match snapshot::BlobStore::open_default() {
+            Ok(store) => store,
+            Err(err) => {
+                self.error = Some(err.to_string());
+                return;
+            }
+        };
+
+        // Each original is asked again with what was sent alongside it the
+        // first time
+        let jobs: Vec<(String, anyhow::Result<Vec<u8>>, ClassifyOptions)> = self
+            .history
+            .originals()
+            .filter(|e| self.history_selection.contains(e.id()))
+            .map(|e| (e.id().to_string(), e.snapshot.original(&store), e.rerun_options()))
+            .collect();
+        let chain = self.fallback_chain();
+
+        self.rerun_promise = Some(runtime::spawn(async move {
+            let mut results = Vec::new();
+            for (parent_id, image, options) in jobs {
+                let result = match image {
+                    Ok(bytes) => {
+                        let _slot = runtime::analysis_slot(runtime::Priority::Batch).await;
+                        fallback::classify_with_fallback(&chain, &bytes, &options).await.map_err(Into::into)
+                    }
+                    Err(err) => Err(err),
+                };
+                results.push((parent_id, result));
+            }
+            results
+        }));
+    }
+}
+
+fn apple_visuals(dark: bool) -> egui::Visuals {
+    let mut visuals = if dark { egui::Visuals::dark() } else { egui::Visuals::light() };
+    visuals.hyperlink_color = palette::Palette::for_dark_mode(dark).accent;
+    visuals
 }
 
-fn setup_apple_style(ctx: &egui::Context) {
+fn setup_apple_style(ctx: &egui::Context, dark: bool) {
     let mut style = (*ctx.style()).clone();
-    style.visuals = egui::Visuals::light();
+    style.visuals = apple_visuals(dark);
     style.spacing.item_spacing = egui::vec2(10.0, 10.0);
     style.spacing.button_padding = egui::vec2(12.0, 6.0);
     style.text_styles = [
@@ -115,19 +860,52 @@ fn setup_apple_style(ctx: &egui::Context) {
 }
 
 impl eframe::App for AvalancheClassifier {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    // Keeps an edit still in a focused text field when the window closes
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.flush_settings();
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Also puts the style back after eframe swaps in its own visuals on a
+        // system theme change
+        let dark = self.settings.theme.is_dark(frame.info().system_theme);
+        if ctx.style().visuals != apple_visuals(dark) {
+            setup_apple_style(ctx, dark);
+        }
+        self.poll_capture_hotkey(ctx);
+        self.run_due_jobs(ctx);
+        #[cfg(not(target_os = "linux"))]
+        self.poll_tray(ctx);
+        self.poll_cameras(ctx);
+        self.poll_watch_folder(ctx);
+        self.poll_escalations(ctx);
+        self.poll_batch(ctx);
+        self.poll_video(ctx);
+        self.capture_window(ctx);
+        self.webcam_window(ctx);
+        self.compare_window(ctx);
+        self.review_window(ctx);
+        self.stats_window(ctx);
+        self.settings_window(ctx);
+        self.presentation_window(ctx);
+        self.live_window(ctx);
+        self.persist_settings(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            egui::ScrollArea::vertical().show(ui, |ui| {
+            // The wheel zooms the photo preview while the pointer is over it
+            let preview_hovered = self.image_data.as_ref().is_some_and(|d| d.view.hovered);
+            egui::ScrollArea::vertical().enable_scrolling(!preview_hovered).show(ui, |ui| {
                 // Clean, minimal container with elegant spacing
                 egui::Frame::none()
                     .inner_margin(egui::vec2(24.0, 16.0))
                     .show(ui, |ui| {
-                    // Refined color palette
-                    let accent_color = egui::Color32::from_rgb(0, 122, 255);  // iOS blue
-                    let success_color = egui::Color32::from_rgb(52, 199, 89); // iOS green
-                    let warning_color = egui::Color32::from_rgb(255, 149, 0); // iOS orange
-                    let danger_color = egui::Color32::from_rgb(255, 59, 48);  // iOS red
-                    let muted_color = egui::Color32::from_rgb(142, 142, 147); // iOS gray
+                    // Refined color palette, brighter on dark backgrounds
+                    let palette = palette::Palette::of(ui.ctx());
+                    let accent_color = palette.accent;
+                    let success_color = palette.success;
+                    let warning_color = palette.warning;
+                    let danger_color = palette.danger;
+                    let muted_color = palette.muted;
 
                     ui.vertical_centered_justified(|ui| {
                         ui.label(
@@ -135,21 +913,42 @@ impl eframe::App for AvalancheClassifier {
                                 .size(20.0)
                                 .strong()
                         );
+                        if self.software_rendering {
+                            ui.label(
+                                egui::RichText::new("Software rendering: animations may be slow")
+                                    .size(13.0)
+                                    .color(muted_color)
+                            );
+                        }
                         ui.add_space(16.0);
 
-                        // API Key Input
-                        ui.label("OpenAI API Key");
-                        ui.add(
-                            egui::TextEdit::singleline(&mut self.openai_api_key)
-                                .password(true)
-                                .hint_text("Enter your OpenAI API key")
-                        );
+                        // Backend in use; keys, models and everything else are in the settings window
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(format!("{} · {}", self.settings.backend.label(), self.provider().model()))
+                                    .size(13.0)
+                                    .color(muted_color)
+                            );
+                            if ui.button("⚙ Settings").clicked() {
+                                self.settings_open = true;
+                            }
+                        });
+                        if !self.has_api_key() {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "Set up {} in ⚙ Settings before analyzing",
+                                    self.settings.backend.label()
+                                ))
+                                .size(13.0)
+                                .color(warning_color)
+                            );
+                        }
                         ui.add_space(16.0);
 
                         // Upload Button
                         if ui.button("📁 Upload Mountain Image").clicked() {
                             if let Some(path) = rfd::FileDialog::new()
-                                .add_filter("Image", &["png", "jpg", "jpeg", "webp"])
+                                .add_filter("Image", &photo_meta::IMAGE_EXTENSIONS)
                                 .pick_file()
                             {
                                 if let Ok(bytes) = std::fs::read(&path) {
@@ -157,73 +956,196 @@ impl eframe::App for AvalancheClassifier {
                                 }
                             }
                         }
+                        if ui.button("🗂 Queue Multiple Images").clicked() {
+                            if let Some(paths) = rfd::FileDialog::new()
+                                .add_filter("Image", &photo_meta::IMAGE_EXTENSIONS)
+                                .pick_files()
+                            {
+                                self.queue_images(paths);
+                            }
+                        }
+                        if ui.button("📷 Capture from Camera").clicked() {
+                            self.webcam.open = true;
+                        }
+                        if ui
+                            .button("🔁 Re-run Batch Manifest")
+                            .on_hover_text("Analyze a saved batch again in its order and show what changed")
+                            .clicked()
+                        {
+                            self.rerun_manifest();
+                        }
+                        self.batch_ui(ui, ctx, success_color, danger_color, muted_color);
+                        self.video_ui(ui, ctx, muted_color);
+                        ui.add_space(8.0);
+                        self.url_loader_ui(ui, ctx, muted_color);
 
                         // Image Preview
-                        if let Some(image_data) = &self.image_data {
-                            if let Some(texture) = &image_data.texture {
+                        if let Some(image_data) = &mut self.image_data {
+                            if image_data.texture.is_some() {
                                 ui.add_space(16.0);
-                                let max_size = egui::vec2(400.0, 400.0);
-                                let image = egui::Image::new(texture)
-                                    .fit_to_exact_size(max_size)
-                                    .maintain_aspect_ratio(true);
-
                                 egui::Frame::group(ui.style())
                                     .inner_margin(10.0)
                                     .show(ui, |ui| {
-                                        ui.add(image);
+                                        preview::photo_preview(ui, image_data);
                                     });
+                                ui.horizontal(|ui| {
+                                    if image_data.view.zoom > 1.0 {
+                                        ui.label(
+                                            egui::RichText::new(format!("{:.0}%", image_data.view.zoom * 100.0))
+                                                .size(13.0)
+                                        );
+                                        if ui.small_button("⟲ Reset View").clicked() {
+                                            image_data.view.reset();
+                                        }
+                                    } else {
+                                        ui.label(
+                                            egui::RichText::new("Scroll to zoom, right-drag to pan")
+                                                .size(13.0)
+                                                .color(muted_color)
+                                        );
+                                    }
+                                    ui.checkbox(&mut image_data.view.flat_light, "Flat light")
+                                        .on_hover_text(
+                                            "Stretches contrast and exaggerates surface texture in overcast shots, \
+                                             to judge whether a fracture line is visible at all. Only the preview changes"
+                                        );
+                                    if image_data.overlay.is_some() {
+                                        ui.checkbox(&mut image_data.view.show_overlay, "Annotations")
+                                            .on_hover_text("Where the model places the fracture line, start zone and debris");
+                                        for (color, name) in [
+                                            (preview::FRACTURE_COLOR, "fracture"),
+                                            (preview::START_ZONE_COLOR, "start zone"),
+                                            (preview::DEBRIS_COLOR, "debris"),
+                                        ] {
+                                            ui.label(egui::RichText::new(format!("■ {}", name)).size(13.0).color(color));
+                                        }
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    if image_data.crop.is_some() {
+                                        ui.label(
+                                            egui::RichText::new("✂ Only the marked region will be analyzed")
+                                                .size(13.0)
+                                        );
+                                        if ui.small_button("Clear Crop").clicked() {
+                                            image_data.crop = None;
+                                        }
+                                    } else {
+                                        ui.label(
+                                            egui::RichText::new(
+                                                "Drag on the preview to analyze only the relevant slope"
+                                            )
+                                            .size(13.0)
+                                            .color(muted_color)
+                                        );
+                                    }
+                                });
+                            } else if let Some(reason) = &image_data.preview_error {
+                                ui.add_space(16.0);
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "Preview unavailable: {}. The photo can still be analyzed.",
+                                        reason
+                                    ))
+                                    .size(13.0)
+                                    .color(muted_color)
+                                );
                             }
                         }
+                        if self.image_data.is_some() {
+                            ui.add_space(8.0);
+                            self.presentation_ui(ui, muted_color);
+                        }
 
                         ui.add_space(16.0);
 
+                        // Prompt profile
+                        ui.horizontal(|ui| {
+                            for mode in verification::AnalysisMode::ALL {
+                                ui.selectable_value(&mut self.settings.mode, mode, mode.label());
+                            }
+                        });
+                        ui.add_space(8.0);
+                        self.observation_ui(ui, muted_color);
+                        ui.add_space(8.0);
+
                         // Analysis Button
                         let button = egui::Button::new(
                             egui::RichText::new("Analyze Image")
                                 .color(egui::Color32::WHITE)
                                 .size(16.0)
                         )
-                        .fill(egui::Color32::from_rgb(0, 122, 255))
+                        .fill(accent_color)
                         .rounding(6.0);
 
-                        let api_ready = !self.openai_api_key.is_empty() && self.image_data.is_some();
+                        let api_ready = self.analysis_ready() && self.image_data.is_some();
                         if ui.add_enabled(api_ready, button).clicked() {
-                            let api_key = self.openai_api_key.clone();
-                            let image_bytes = self.image_data.as_ref().unwrap().bytes.clone();
-                            
-                            self.promise = Some(Promise::spawn_thread("classify", move || {
-                                tokio::runtime::Runtime::new()
-                                    .unwrap()
-                                    .block_on(async {
-                                        classify_image(&api_key, &image_bytes).await
-                                    })
-                            }));
+                            self.start_analysis();
                         }
 
                         // Loading and Results
+                        let mut cancel = false;
+                        let mut file_into_mission = None;
+                        self.poll_ensemble(ui, &mut cancel);
+                        self.poll_verification(ui, &mut cancel);
                         if let Some(promise) = &self.promise {
                             match promise.ready() {
-                                Some(Ok(result)) => {
-                                    self.result = Some(result.clone());
+                                Some(Ok(classification)) => {
+                                    self.result = Some(classification.analysis.clone());
+                                    if let Some(image_data) = &mut self.image_data {
+                                        image_data.show_annotations(classification.analysis.annotations.as_ref());
+                                    }
+                                    self.ensemble = None;
+                                    self.snapshot = classification.snapshot.clone();
                                     self.error = None;
+                                    if let Some(snapshot) = &classification.snapshot {
+                                        let mut entry = history::HistoryEntry::new(
+                                            snapshot.clone(),
+                                            classification.analysis.clone(),
+                                        );
+                                        entry.fallbacks = classification.fallbacks.clone();
+                                        entry.retries = self.retry.failures.clone();
+                                        entry.observations = self.observation.observations.clone();
+                                        self.fill_photo_meta(&mut entry);
+                                        if let Err(err) = self.history.push(entry) {
+                                            self.error = Some(err.to_string());
+                                        }
+                                        file_into_mission = Some(snapshot.id.clone());
+                                    }
                                     self.promise = None;
+                                    self.analysis_abort = None;
                                 }
                                 Some(Err(err)) => {
-                                    self.error = Some(err.to_string());
+                                    self.error = Some(err.user_message());
+                                    self.retry.offer = err.is_model_failure();
+                                    self.retry.failed(err);
                                     self.result = None;
+                                    self.snapshot = None;
                                     self.promise = None;
+                                    self.analysis_abort = None;
                                 }
                                 None => {
                                     ui.horizontal(|ui| {
                                         ui.spinner();
                                         ui.label("Analyzing terrain features...");
+                                        cancel = ui.button("Cancel").clicked();
                                     });
                                 }
                             }
                         }
+                        if cancel {
+                            self.cancel_analysis();
+                        }
+                        if let Some(id) = file_into_mission {
+                            self.file_to_active_mission(&id);
+                        }
+
+                        self.ensemble_results_ui(ui, success_color, warning_color, muted_color);
+                        self.verification_results_ui(ui, success_color, warning_color, muted_color);
 
                         // Display Results
-                        if let Some(result) = &self.result {
+                        let classifying = self.settings.mode == verification::AnalysisMode::Classification;
+                        if let Some(result) = self.result.as_ref().filter(|_| classifying) {
                             ui.add_space(16.0);
                             let confidence_color = if result.confidence_level > 80.0 {
                                 success_color
@@ -233,13 +1155,8 @@ impl eframe::App for AvalancheClassifier {
                                 danger_color
                             };
 
-                            let (type_text, type_color) = match result.avalanche_type.as_str() {
-                                "powder" => ("Powder Avalanche", warning_color),
-                                "loose-snow" => ("Loose Snow Avalanche", warning_color),
-                                "slab" => ("Slab Avalanche", danger_color),
-                                "none" => ("No Avalanche Risk", success_color),
-                                _ => ("Unknown Type", muted_color),
-                            };
+                            let type_text = type_title(&result.avalanche_type);
+                            let type_color = type_color(&result.avalanche_type, palette, muted_color);
 
                             ui.vertical_centered(|ui| {
                                 ui.add_space(8.0);
@@ -275,8 +1192,12 @@ impl eframe::App for AvalancheClassifier {
                                             .fill(confidence_color)
                                     );
                                 });
+                                ui.add_space(8.0);
+                                hazard::hazard_badges_ui(ui, result, palette);
                                 ui.add_space(16.0);
                             });
+                            scores::type_scores_ui(ui, result, palette);
+                            ui.add_space(8.0);
 
                             // Analysis section with refined layout
                             ui.columns(2, |columns| {
@@ -303,7 +1224,29 @@ impl eframe::App for AvalancheClassifier {
                                             ui.add(pill_label("Fluffy", accent_color));
                                         }
                                     });
-                                    
+
+                                    let chars = &result.visual_characteristics;
+                                    if chars.fracture_line {
+                                        ui.add_space(4.0);
+                                        ui.horizontal(|ui| {
+                                            let text = match &chars.fracture_depth {
+                                                Some(depth) => format!("Fracture line {}", depth.label()),
+                                                None => "Fracture line".to_string(),
+                                            };
+                                            let pill = ui.add(pill_label(&text, accent_color));
+                                            if let Some(depth) = &chars.fracture_depth {
+                                                ui.label(
+                                                    egui::RichText::new(format!("{} confidence", depth.confidence))
+                                                        .size(12.0)
+                                                        .color(muted_color)
+                                                );
+                                                if let Some(cue) = &depth.scale_cue {
+                                                    pill.on_hover_text(format!("Scaled from: {}", cue));
+                                                }
+                                            }
+                                        });
+                                    }
+
                                     ui.add_space(4.0);
                                     // Density indicator
                                     let density_color = match snow.density.as_str() {
@@ -361,6 +1304,10 @@ impl eframe::App for AvalancheClassifier {
                                     );
                                     ui.add_space(8.0);
                                     
+                                    if let Some(problem) = &result.avalanche_problem {
+                                        info_row(ui, "Problem", model::problem_label(problem), accent_color, 13.0);
+                                    }
+
                                     let terrain = &result.visual_characteristics.terrain;
                                     if let Some(angle) = &terrain.slope_angle {
                                         let angle_color = if angle.starts_with("steep") {
@@ -374,6 +1321,12 @@ impl eframe::App for AvalancheClassifier {
                                     }
 
                                     info_row(ui, "Surface", &terrain.surface_roughness, muted_color, 13.0);
+                                    if let Some(aspect) = &terrain.aspect {
+                                        info_row(ui, "Aspect", aspect, muted_color, 13.0);
+                                    }
+                                    if let Some(band) = &terrain.elevation_band {
+                                        info_row(ui, "Elevation", model::elevation_band_label(band), muted_color, 13.0);
+                                    }
                                     
                                     ui.add_space(4.0);
                                     ui.horizontal(|ui| {
@@ -412,16 +1365,287 @@ impl eframe::App for AvalancheClassifier {
                                     }
                                 });
                             });
+                            if let Some(weather) = &result.weather {
+                                ui.add_space(8.0);
+                                weather::weather_ui(ui, weather, muted_color);
+                            }
+
+                            let entry_index = self
+                                .snapshot
+                                .as_ref()
+                                .and_then(|s| self.history.entries.iter().position(|e| e.id() == s.id));
+                            let exposure: Vec<ExposedAsset> = entry_index
+                                .map(|i| self.history.entries[i].exposure.clone())
+                                .unwrap_or_default();
+                            let bearing = entry_index.and_then(|i| self.history.entries[i].bearing);
+                            bulletin::bulletin_ui(
+                                ui,
+                                &mut self.bulletin,
+                                &self.settings.bulletin_source,
+                                result,
+                                entry_index.map(|i| &self.history.entries[i]),
+                                palette,
+                            );
+                            let discrepancies = slope_check::slope_check_ui(
+                                ui,
+                                &mut self.slope_check,
+                                entry_index.map(|i| &self.history.entries[i]),
+                                palette,
+                            );
+                            if let Some(i) = entry_index.filter(|_| !discrepancies.is_empty()) {
+                                let flags = &mut self.history.entries[i].flags;
+                                let before = flags.len();
+                                for discrepancy in discrepancies {
+                                    if !flags.contains(&discrepancy) {
+                                        flags.push(discrepancy);
+                                    }
+                                }
+                                if flags.len() > before {
+                                    if let Err(err) = self.history.update(&self.history.entries[i]) {
+                                        self.error = Some(err.to_string());
+                                    }
+                                }
+                            }
+                            if !exposure.is_empty() {
+                                ui.add_space(8.0);
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "⚠ Activity reached {}",
+                                        exposure.iter().map(ExposedAsset::describe).collect::<Vec<_>>().join(", ")
+                                    ))
+                                    .size(14.0)
+                                    .color(danger_color)
+                                    .strong()
+                                );
+                            }
+
+                            // Capture details from the photo's EXIF, or a matched track
+                            if let Some(entry) = entry_index.map(|i| &self.history.entries[i]) {
+                                let mut details = Vec::new();
+                                if let Some(at) = entry.captured_at.and_then(|t| chrono::DateTime::from_timestamp(t, 0)) {
+                                    details.push(format!("Taken {}", at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")));
+                                }
+                                if let Some(location) = entry.location {
+                                    details.push(format!(
+                                        "{:.5}, {:.5}{}",
+                                        location.latitude,
+                                        location.longitude,
+                                        if entry.location_from_track { " (from GPX track)" } else { "" }
+                                    ));
+                                }
+                                if let Some(info) = &entry.camera_info {
+                                    details.push(info.describe());
+                                }
+                                if !details.is_empty() {
+                                    ui.add_space(8.0);
+                                    ui.label(
+                                        egui::RichText::new(format!("📷 {}", details.join(" · ")))
+                                            .size(13.0)
+                                            .color(muted_color)
+                                    );
+                                }
+                            }
+
+                            // Orientation, so others can tell which slope this is
+                            if let Some(i) = entry_index {
+                                ui.add_space(8.0);
+                                let mut edited = bearing;
+                                let mut save = false;
+                                ui.horizontal(|ui| {
+                                    compass(ui, bearing, accent_color, muted_color);
+                                    let mut oriented = bearing.is_some();
+                                    if ui
+                                        .checkbox(&mut oriented, "Facing")
+                                        .on_hover_text("Which way the camera pointed, clockwise from true north")
+                                        .changed()
+                                    {
+                                        edited = oriented.then_some(0.0);
+                                        save = true;
+                                    }
+                                    if let Some(b) = &mut edited {
+                                        let response = ui.add(
+                                            egui::DragValue::new(b).speed(1.0).clamp_range(0.0..=359.0).suffix("°"),
+                                        );
+                                        save |= response.drag_released() || response.lost_focus();
+                                        ui.label(
+                                            egui::RichText::new(orientation::compass_point(*b))
+                                                .size(13.0)
+                                                .color(muted_color)
+                                        );
+                                    }
+                                });
+                                self.history.entries[i].bearing = edited;
+                                if save {
+                                    if let Err(err) = self.history.update(&self.history.entries[i]) {
+                                        self.error = Some(err.to_string());
+                                    }
+                                }
+                            }
+
+                            // Report export
+                            ui.add_space(16.0);
+                            ui.horizontal_wrapped(|ui| {
+                                if ui.button("📄 Load Report Template").clicked() {
+                                    if let Some(path) = rfd::FileDialog::new()
+                                        .add_filter("Tera Template", &["tera", "txt", "md", "html"])
+                                        .pick_file()
+                                    {
+                                        match std::fs::read_to_string(&path) {
+                                            Ok(template) => self.report_template = Some(template),
+                                            Err(err) => self.error = Some(err.to_string()),
+                                        }
+                                    }
+                                }
+                                if ui.button("💾 Save Report").clicked() {
+                                    let template = self
+                                        .report_template
+                                        .as_deref()
+                                        .unwrap_or(report::DEFAULT_TEMPLATE);
+                                    match report::render_report_with_exposure(template, result, &exposure) {
+                                        Ok(text) => {
+                                            if let Some(path) = self.settings.export_dialog()
+                                                .set_file_name("avalanche-report.txt")
+                                                .save_file()
+                                            {
+                                                if let Err(err) = std::fs::write(&path, text) {
+                                                    self.error = Some(err.to_string());
+                                                }
+                                            }
+                                        }
+                                        Err(err) => self.error = Some(err.to_string()),
+                                    }
+                                }
+                                if ui.button("⬇ Export JSON").clicked() {
+                                    match report::export_json(
+                                        result,
+                                        self.snapshot.as_ref(),
+                                        entry_index.map(|i| &self.history.entries[i]),
+                                    ) {
+                                        Ok(json) => {
+                                            if let Some(path) = self.settings.export_dialog()
+                                                .add_filter("JSON", &["json"])
+                                                .set_file_name("avalanche-analysis.json")
+                                                .save_file()
+                                            {
+                                                if let Err(err) = std::fs::write(&path, json) {
+                                                    self.error = Some(err.to_string());
+                                                }
+                                            }
+                                        }
+                                        Err(err) => self.error = Some(err.to_string()),
+                                    }
+                                }
+                                if ui.button("🖨 Save PDF").on_hover_text("One page with the photo, for incident reports").clicked() {
+                                    let image = analyzed_image(self.snapshot.as_ref(), self.image_data.as_ref());
+                                    let entry = entry_index.map(|i| &self.history.entries[i]);
+                                    match pdf::render_pdf(result, image.as_deref(), entry) {
+                                        Ok(bytes) => {
+                                            if let Some(path) = self.settings.export_dialog()
+                                                .add_filter("PDF", &["pdf"])
+                                                .set_file_name("avalanche-report.pdf")
+                                                .save_file()
+                                            {
+                                                if let Err(err) = std::fs::write(&path, bytes) {
+                                                    self.error = Some(err.to_string());
+                                                }
+                                            }
+                                        }
+                                        Err(err) => self.error = Some(format!("Failed to create PDF: {}", err)),
+                                    }
+                                }
+                                for (button, format) in [
+                                    ("🌐 Export HTML", report::DocumentFormat::Html),
+                                    ("📝 Export Markdown", report::DocumentFormat::Markdown),
+                                ] {
+                                    if !ui.button(button).on_hover_text("Self-contained, with the photo inlined, for the wiki").clicked() {
+                                        continue;
+                                    }
+                                    let image = analyzed_image(self.snapshot.as_ref(), self.image_data.as_ref());
+                                    let entry = entry_index.map(|i| &self.history.entries[i]);
+                                    match report::render_document(format, result, image.as_deref(), entry) {
+                                        Ok(text) => {
+                                            if let Some(path) = self.settings.export_dialog()
+                                                .add_filter(format.extension(), &[format.extension()])
+                                                .set_file_name(format!("avalanche-report.{}", format.extension()))
+                                                .save_file()
+                                            {
+                                                if let Err(err) = std::fs::write(&path, text) {
+                                                    self.error = Some(err.to_string());
+                                                }
+                                            }
+                                        }
+                                        Err(err) => self.error = Some(err.to_string()),
+                                    }
+                                }
+                            });
+                            if let Some(snapshot) = &self.snapshot {
+                                let entry = self.history.entries.iter().find(|e| e.snapshot.id == snapshot.id);
+                                provenance::provenance_ui(ui, snapshot, entry, warning_color, muted_color);
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        egui::RichText::new(format!("Snapshot {}", &snapshot.id[..12]))
+                                            .size(13.0)
+                                            .color(muted_color)
+                                    );
+                                    if let Some(timings) = snapshot.timings {
+                                        ui.label(
+                                            egui::RichText::new(format!("⏱ {}", timings.summary()))
+                                                .size(13.0)
+                                                .color(muted_color)
+                                        )
+                                        .on_hover_text(format!("{} in total", snapshot::format_ms(timings.total_ms())));
+                                    } else if let Some((total, breakdown)) = timing_text(snapshot) {
+                                        ui.label(egui::RichText::new(format!("⏱ {}", total)).size(13.0).color(muted_color))
+                                            .on_hover_text(breakdown);
+                                    }
+                                    if ui.small_button("↻ Re-validate").clicked() {
+                                        match snapshot::BlobStore::open_default()
+                                            .and_then(|store| snapshot.reparse(&store))
+                                        {
+                                            Ok(analysis) => {
+                                                if let Some(image_data) = &mut self.image_data {
+                                                    image_data.show_annotations(analysis.annotations.as_ref());
+                                                }
+                                                self.result = Some(analysis);
+                                            }
+                                            Err(err) => self.error = Some(err.to_string()),
+                                        }
+                                    }
+                                });
+                            }
+                            if self.report_template.is_some() {
+                                ui.label(
+                                    egui::RichText::new("Using custom report template")
+                                        .size(13.0)
+                                        .color(muted_color)
+                                );
+                            }
                         }
 
                         // Error Handling
                         if let Some(error) = &self.error {
                             ui.add_space(8.0);
-                            ui.colored_label(
-                                egui::Color32::from_rgb(255, 59, 48),
-                                error
-                            );
+                            ui.colored_label(danger_color, error);
                         }
+                        self.retry_ui(ui, muted_color);
+
+                        ui.add_space(16.0);
+                        self.history_ui(ui, muted_color);
+                        self.archive_ui(ui, muted_color);
+                        self.models_ui(ui, muted_color);
+                        self.capture_ui(ui, muted_color);
+                        self.scheduler_ui(ui, muted_color);
+                        self.watch_ui(ui, muted_color);
+                        self.monitor_ui(ui, muted_color);
+                        self.alerts_ui(ui, danger_color, muted_color);
+                        self.missions_ui(ui, accent_color, muted_color);
+                        self.terrain_ui(ui, muted_color);
+                        self.trips_ui(ui, danger_color, muted_color);
+                        self.geotag_ui(ui, muted_color);
+                        self.map_ui(ui, muted_color);
+                        self.terrain3d_ui(ui, accent_color, muted_color);
+                        ui.add_space(8.0);
+                        self.update_ui(ui, muted_color);
                     });
                 });
             });
@@ -429,230 +1653,88 @@ impl eframe::App for AvalancheClassifier {
     }
 }
 
-async fn classify_image(api_key: &str, image_bytes: &[u8]) -> anyhow::Result<AvalancheAnalysis> {
-    use base64::Engine;
-    let image_base64 = base64::engine::general_purpose::STANDARD.encode(image_bytes);
-    
-    let client = reqwest::Client::new();
-    let response = client
-    .post("https://api.openai.com/v1/chat/completions")
-    .header("Authorization", format!("Bearer {}", api_key))
-    .json(&serde_json::json!({
-        "model": "gpt-4o-mini",
-        "response_format": { "type": "json_object" },
-        "messages": [{
-            "role": "user",
-            "content": [
-                {"type": "text", "text": r#"Analyze this mountain terrain for avalanche characteristics with extreme detail. Return a JSON object with this structure:
-{
-    "avalanche_present": boolean,
-    "avalanche_type": "powder"|"loose-snow"|"slab"|"none",
-    "confidence_level": 0.0-100.0,
-    "terrain_features": string[],
-    "visual_characteristics": {
-        "powder_cloud": boolean,
-        "fracture_line": boolean,
-        "fracture_depth": "shallow"|"deep"|"variable"|null,
-        "point_release": boolean,
-        "debris_pattern": "fan-shaped"|"linear"|"scattered"|"none",
-        "snow_texture": {
-            "granular": boolean,
-            "blocky": boolean,
-            "fluffy": boolean,
-            "density": "low"|"medium"|"high"
-        },
-        "movement_pattern": {
-            "starting_width": "point"|"wide"|"undefined",
-            "propagation": "fan"|"linear"|"chaotic"|"none",
-            "vertical_movement": boolean,
-            "lateral_spread": boolean
-        },
-        "terrain": {
-            "slope_angle": "steep (>45°)"|"moderate (30-45°)"|"gentle (<30°)"|null,
-            "surface_roughness": "smooth"|"rough"|"variable",
-            "anchoring_points": boolean,
-            "convex_rollover": boolean
-        }
-    }
+// The image behind the current result; the stored copy is the one the model
+// was sent, after downscaling and re-encoding, the loaded one covers results
+// without a snapshot
+fn analyzed_image(snapshot: Option<&snapshot::AnalysisSnapshot>, image_data: Option<&ImageData>) -> Option<Vec<u8>> {
+    snapshot
+        .and_then(|s| s.image(&snapshot::BlobStore::open_default().ok()?).ok())
+        .or_else(|| image_data.map(|d| d.bytes.clone()))
 }
 
-DETAILED ANALYSIS GUIDELINES:
-
-1. Snow Texture Analysis:
-   - Granular: Individual snow particles visible? Common in loose snow
-   - Blocky: Cohesive blocks or chunks? Typical of slab
-   - Fluffy: Light, airy appearance? Common in powder
-   - Density: Assess snow compactness
-
-2. Movement Pattern Analysis:
-   - Starting Width: Point source vs wide initial fracture
-   - Propagation: How the avalanche spreads
-   - Vertical Movement: Significant up/down motion
-   - Lateral Spread: Sideways expansion
-
-3. Terrain Analysis:
-   - Slope Angle: Critical for type determination
-   - Surface Roughness: Affects release pattern
-   - Anchoring Points: Trees/rocks that affect flow
-   - Convex Rollover: Terrain shape at release point
-
-AVALANCHE TYPE CHARACTERISTICS:
-
-LOOSE-SNOW Avalanche:
-PRIMARY Indicators:
-- Starting_width: "point"
-- Propagation: "fan"
-- Snow_texture: granular=true, blocky=false
-- Debris_pattern: "fan-shaped"
-SECONDARY Indicators:
-- No distinct fracture line
-- Low to medium density
-- Often on steeper slopes
-- Minimal lateral spread
-
-SLAB Avalanche:
-PRIMARY Indicators:
-- Fracture_line: true
-- Snow_texture: blocky=true
-- Starting_width: "wide"
-- Propagation: "linear"
-SECONDARY Indicators:
-- Medium to high density
-- Linear debris pattern
-- Moderate slope angles
-- Significant lateral spread
-
-POWDER Avalanche:
-PRIMARY Indicators:
-- Powder_cloud: true
-- Snow_texture: fluffy=true
-- Vertical_movement: true
-SECONDARY Indicators:
-- Low density
-- Significant vertical displacement
-- Often on steep terrain
-- Chaotic propagation
-
-Analyze ALL characteristics before classification. If mixed indicators present, weight PRIMARY indicators more heavily. A single PRIMARY indicator is not enough - require multiple matching characteristics for classification."#},
-                {"type": "image_url", "image_url": {
-                    "url": format!("data:image/jpeg;base64,{}", image_base64),
-                    "detail": "high"
-                }}
-            ]
-        }],
-        "max_tokens": 600
-    }))
-    .send()
-    .await?;
-
-    let response_text = response.text().await?;
-    let json: serde_json::Value = serde_json::from_str(&response_text)?;
-    
-    let content = json["choices"][0]["message"]["content"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Empty API response"))?;
-
-    let analysis: AvalancheAnalysis = serde_json::from_str(content)
-        .map_err(|e| anyhow::anyhow!("JSON parse error: {}\nResponse: {}", e, content))?;
-
-    // Validate and score the avalanche type based on detailed characteristics
-    if analysis.avalanche_present {
-        let chars = &analysis.visual_characteristics;
-        let snow = &chars.snow_texture;
-        let movement = &chars.movement_pattern;
-        
-        // Calculate characteristic scores for each type
-        let powder_score = {
-            let mut score = 0i32;
-            if chars.powder_cloud { score += 3; }  // Primary
-            if snow.fluffy { score += 3; }        // Primary
-            if movement.vertical_movement { score += 3; }  // Primary
-            if snow.density == "low" { score += 1; }      // Secondary
-            if movement.propagation == "chaotic" { score += 1; }  // Secondary
-            if chars.terrain.slope_angle.as_ref().map_or(false, |a| a.starts_with("steep")) { score += 1; }
-            score
-        };
-
-        let loose_snow_score = {
-            let mut score = 0i32;
-            if movement.starting_width == "point" { score += 3; }  // Primary
-            if movement.propagation == "fan" { score += 3; }      // Primary
-            if snow.granular { score += 3; }                      // Primary
-            if chars.debris_pattern == "fan-shaped" { score += 3; }  // Primary
-            if !chars.fracture_line { score += 1; }               // Secondary
-            if snow.density == "low" { score += 1; }              // Secondary
-            if chars.terrain.slope_angle.as_ref().map_or(false, |a| a.starts_with("steep")) { score += 1; }
-            score
-        };
-
-        let slab_score = {
-            let mut score = 0i32;
-            if chars.fracture_line { score += 3; }               // Primary
-            if snow.blocky { score += 3; }                      // Primary
-            if movement.starting_width == "wide" { score += 3; } // Primary
-            if movement.propagation == "linear" { score += 3; }  // Primary
-            if snow.density == "high" { score += 1; }           // Secondary
-            if chars.debris_pattern == "linear" { score += 1; }  // Secondary
-            if movement.lateral_spread { score += 1; }           // Secondary
-            score
-        };
+fn load_thumbnail(ctx: &egui::Context, id: &str, bytes: &[u8]) -> Option<egui::TextureHandle> {
+    let rgba = image::load_from_memory(bytes).ok()?.to_rgba8();
+    let size = [rgba.width() as usize, rgba.height() as usize];
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &rgba.into_vec());
+    Some(ctx.load_texture(format!("thumbnail-{}", id), color_image, egui::TextureOptions::LINEAR))
+}
 
-        // Determine highest scoring type
-        let detected_type = analysis.avalanche_type.as_str();
-        let (highest_score, expected_type) = [
-            (powder_score, "powder"),
-            (loose_snow_score, "loose-snow"),
-            (slab_score, "slab")
-        ].iter()
-        .max_by_key(|&&(score, _)| score)
-        .copied()
-        .unwrap();
-
-        // Require a minimum score difference for classification
-        let second_highest_score = [powder_score, loose_snow_score, slab_score]
-            .iter()
-            .filter(|&&score| score != highest_score)
-            .max()
-            .copied()
-            .unwrap();
-
-        // If scores are too close or score is too low, classification is unreliable
-        if (highest_score - second_highest_score) < 3 {
-            return Err(anyhow::anyhow!(
-                "Classification uncertainty: Multiple types show similar characteristics"
-            ));
-        }
+// Heading for a result of the given type
+fn type_title(avalanche_type: &str) -> &'static str {
+    match avalanche_type {
+        "powder" => "Powder Avalanche",
+        "loose-snow" => "Loose Snow Avalanche",
+        "slab" => "Slab Avalanche",
+        "wet-loose" => "Wet Loose Avalanche",
+        "wet-slab" => "Wet Slab Avalanche",
+        "glide" => "Glide Avalanche",
+        "cornice" => "Cornice Fall",
+        "slush" => "Slush Flow",
+        "none" => "No Avalanche Risk",
+        _ => "Unknown Type",
+    }
+}
 
-        if highest_score < 6 {
-            return Err(anyhow::anyhow!(
-                "Insufficient characteristic evidence for classification"
-            ));
-        }
+// Badge colors matching the result view
+fn type_color(avalanche_type: &str, palette: palette::Palette, other: egui::Color32) -> egui::Color32 {
+    palette.type_color(avalanche_type).unwrap_or(other)
+}
 
-        // Verify classification matches highest scoring type
-        if detected_type != expected_type {
-            return Err(anyhow::anyhow!(
-                "Inconsistent classification: Visual characteristics strongly indicate {} (score: {}) but classified as {}", 
-                expected_type, highest_score, detected_type
-            ));
+// Small compass rose showing the view cone for a bearing
+fn compass(ui: &mut egui::Ui, bearing: Option<f32>, accent_color: egui::Color32, muted_color: egui::Color32) {
+    let size = 40.0;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let center = rect.center();
+    let radius = size / 2.0 - 2.0;
+    painter.circle_stroke(center, radius, egui::Stroke::new(1.0, muted_color));
+    painter.text(
+        center - egui::vec2(0.0, radius - 7.0),
+        egui::Align2::CENTER_CENTER,
+        "N",
+        egui::FontId::proportional(9.0),
+        muted_color,
+    );
+    if let Some(bearing) = bearing {
+        let half = (orientation::DEFAULT_FOV_DEG as f32 / 2.0).to_radians();
+        let start = bearing.to_radians() - half;
+        let mut points = vec![center];
+        for step in 0..=8 {
+            let angle = start + 2.0 * half * step as f32 / 8.0;
+            points.push(center + egui::vec2(angle.sin(), -angle.cos()) * radius);
         }
-    }
-
-    if !["powder", "loose-snow", "slab", "none"].contains(&analysis.avalanche_type.as_str()) {
-        return Err(anyhow::anyhow!(
-            "Invalid avalanche type: {}",
-            analysis.avalanche_type
+        painter.add(egui::Shape::convex_polygon(
+            points,
+            accent_color.linear_multiply(0.4),
+            egui::Stroke::new(1.0, accent_color),
         ));
     }
+}
 
-    if analysis.confidence_level < 0.0 || analysis.confidence_level > 100.0 {
-        return Err(anyhow::anyhow!(
-            "Invalid confidence level: {}",
-            analysis.confidence_level
-        ));
-    }
+// Which backends failed before the one that answered, one per line
+fn fallback_text(fallbacks: &[fallback::FailedAttempt]) -> String {
+    let tried: Vec<String> = fallbacks.iter().map(|attempt| format!("• {}", attempt.summary())).collect();
+    format!("Answered by a fallback backend after:\n{}", tried.join("\n"))
+}
 
-    Ok(analysis)
+// Total time of an analysis and, for hovering, where it went. Snapshots
+// from before the stages were timed only know the request as a whole.
+fn timing_text(snapshot: &snapshot::AnalysisSnapshot) -> Option<(String, String)> {
+    match (snapshot.timings, snapshot.latency_ms) {
+        (Some(timings), _) => Some((snapshot::format_ms(timings.total_ms()), timings.summary())),
+        (None, Some(latency)) => Some((snapshot::format_ms(latency), "Request time; no breakdown recorded".to_string())),
+        (None, None) => None,
+    }
 }
 
 // Helper function for consistent pill labels
@@ -692,17 +1774,55 @@ fn info_row(ui: &mut egui::Ui, label: &str, value: &str, color: egui::Color32, s
 }
 
 fn main() {
+    if std::env::args().any(|arg| arg == "--portable") {
+        match avalanche_classifier_core::storage::portable_data_dir() {
+            Ok(dir) => avalanche_classifier_core::storage::set_data_dir(dir),
+            Err(err) => eprintln!("Portable mode unavailable: {}", err),
+        }
+    }
+
+    // Headless worker for pipelines; no window is opened
+    if std::env::args().any(|arg| arg == "--pipe") {
+        std::process::exit(pipe::run());
+    }
+
+    // Broken GPU drivers either fail to create a context or render nothing;
+    // a software OpenGL context (e.g. Mesa llvmpipe) is slower but works
+    let software = std::env::args().any(|arg| arg == "--software-rendering");
+    let result = run(software);
+    let result = match result {
+        Err(err) if !software => {
+            eprintln!("Hardware rendering failed ({}); retrying with software rendering.", err);
+            eprintln!("Start with --software-rendering to skip this attempt next time.");
+            run(true)
+        }
+        result => result,
+    };
+    if let Err(err) = result {
+        eprintln!("Could not open the window: {}", err);
+        eprintln!("Check that OpenGL 2.0 or later is available, e.g. by installing Mesa's software renderer.");
+        std::process::exit(1);
+    }
+}
+
+fn run(software: bool) -> eframe::Result<()> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([600.0, 800.0])
             .with_close_button(false),
+        hardware_acceleration: if software {
+            eframe::HardwareAcceleration::Off
+        } else {
+            eframe::HardwareAcceleration::Preferred
+        },
+        // Report system light/dark changes for the "Follow system" theme
+        follow_system_theme: true,
         ..Default::default()
     };
 
     eframe::run_native(
         "Avalanche Detection and Risk Analyzer",
         options,
-        Box::new(|cc| Box::new(AvalancheClassifier::new(cc))),
+        Box::new(move |cc| Box::new(AvalancheClassifier::new(cc, software))),
     )
-    .unwrap();
 }
\ No newline at end of file
@@ -0,0 +1,204 @@
+use crate::palette::Palette;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::camera::Location;
+use avalanche_classifier_core::history::HistoryEntry;
+use avalanche_classifier_core::maptiles::{self, MAX_ZOOM, MIN_ZOOM, TILE_SIZE};
+use avalanche_classifier_core::provider::http_client;
+use eframe::egui;
+use poll_promise::Promise;
+use std::collections::HashMap;
+
+const MAP_HEIGHT: f32 = 320.0;
+// Tile downloads started per frame, so opening the map does not fire off
+// dozens of requests at once
+const TILE_REQUESTS_PER_FRAME: usize = 4;
+// Past this the cache keeps only the tiles of the current zoom
+const MAX_CACHED_TILES: usize = 400;
+const PIN_RADIUS: f32 = 6.0;
+
+enum Tile {
+    Loading(Promise<anyhow::Result<Vec<u8>>>),
+    Ready(egui::TextureHandle),
+    Failed,
+}
+
+#[derive(Default)]
+pub struct MapState {
+    // None until the map is first shown, when it fits all pins
+    center: Option<Location>,
+    zoom: u8,
+    // Keyed by (zoom, x, y)
+    tiles: HashMap<(u8, u32, u32), Tile>,
+}
+
+// A geotagged analysis: the original's location, the latest version's label
+struct Pin {
+    location: Location,
+    entry: HistoryEntry,
+}
+
+impl AvalancheClassifier {
+    // Every geotagged analysis in the history as a pin colored by type, on
+    // OpenStreetMap tiles; clicking a pin opens the result
+    pub(crate) fn map_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let palette = Palette::of(ui.ctx());
+        let pins: Vec<Pin> = self
+            .history
+            .lineages()
+            .into_iter()
+            .filter_map(|lineage| Some(Pin { location: lineage.original.location?, entry: lineage.latest.clone() }))
+            .collect();
+
+        let mut open_entry = None;
+        egui::CollapsingHeader::new(format!("Map ({})", pins.len()))
+            .default_open(false)
+            .show(ui, |ui| {
+                if pins.is_empty() {
+                    ui.label(
+                        egui::RichText::new("Analyses of photos with GPS, or matched to a GPX track, appear here")
+                            .size(13.0)
+                            .color(muted_color),
+                    );
+                    return;
+                }
+                let state = &mut self.map;
+                let locations: Vec<Location> = pins.iter().map(|pin| pin.location).collect();
+                let width = ui.available_width();
+                let mut fit = state.center.is_none();
+                ui.horizontal(|ui| {
+                    if ui.small_button("−").clicked() {
+                        state.zoom = state.zoom.saturating_sub(1).max(MIN_ZOOM);
+                    }
+                    if ui.small_button("+").clicked() {
+                        state.zoom = (state.zoom + 1).min(MAX_ZOOM);
+                    }
+                    fit |= ui.small_button("Fit All").clicked();
+                    ui.label(egui::RichText::new("Drag to pan, pinch to zoom").size(13.0).color(muted_color));
+                });
+                if fit {
+                    if let Some((center, zoom)) = maptiles::fit(&locations, f64::from(width), f64::from(MAP_HEIGHT)) {
+                        state.center = Some(center);
+                        state.zoom = zoom;
+                    }
+                }
+
+                let (rect, response) = ui.allocate_exact_size(egui::vec2(width, MAP_HEIGHT), egui::Sense::click_and_drag());
+                let pinch = ui.input(|i| i.zoom_delta());
+                if response.hovered() && pinch > 1.2 {
+                    state.zoom = (state.zoom + 1).min(MAX_ZOOM);
+                } else if response.hovered() && pinch < 0.8 {
+                    state.zoom = state.zoom.saturating_sub(1).max(MIN_ZOOM);
+                }
+                let zoom = state.zoom;
+                let Some(center) = state.center else {
+                    return;
+                };
+                let (mut center_x, mut center_y) = maptiles::project(center, zoom);
+                if response.dragged() {
+                    let delta = response.drag_delta();
+                    center_x -= f64::from(delta.x);
+                    center_y -= f64::from(delta.y);
+                    state.center = Some(maptiles::unproject(center_x, center_y, zoom));
+                }
+                // World pixel at the map's top-left corner
+                let left = center_x - f64::from(rect.width()) / 2.0;
+                let top = center_y - f64::from(rect.height()) / 2.0;
+                let to_screen = |x: f64, y: f64| rect.min + egui::vec2((x - left) as f32, (y - top) as f32);
+
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+                if state.tiles.len() > MAX_CACHED_TILES {
+                    state.tiles.retain(|key, _| key.0 == zoom);
+                }
+                let tiles_across = 1i64 << zoom;
+                let mut requests = 0;
+                let mut loading = false;
+                let tile_range = |from: f64, length: f32| {
+                    (from / TILE_SIZE).floor() as i64..=((from + f64::from(length)) / TILE_SIZE).floor() as i64
+                };
+                for tile_y in tile_range(top, rect.height()).filter(|y| (0..tiles_across).contains(y)) {
+                    for tile_x in tile_range(left, rect.width()) {
+                        let key = (zoom, tile_x.rem_euclid(tiles_across) as u32, tile_y as u32);
+                        let min = to_screen(tile_x as f64 * TILE_SIZE, tile_y as f64 * TILE_SIZE);
+                        let tile_rect = egui::Rect::from_min_size(min, egui::Vec2::splat(TILE_SIZE as f32));
+                        if let Some(Tile::Loading(promise)) = state.tiles.get(&key) {
+                            if let Some(result) = promise.ready() {
+                                let texture = result.as_ref().ok().and_then(|bytes| load_tile(ui.ctx(), key, bytes));
+                                state.tiles.insert(key, texture.map_or(Tile::Failed, Tile::Ready));
+                            }
+                        }
+                        match state.tiles.get(&key) {
+                            Some(Tile::Ready(texture)) => {
+                                let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                                painter.image(texture.id(), tile_rect, uv, egui::Color32::WHITE);
+                            }
+                            Some(Tile::Loading(_)) => loading = true,
+                            Some(Tile::Failed) => {}
+                            None if requests < TILE_REQUESTS_PER_FRAME => {
+                                requests += 1;
+                                loading = true;
+                                let (z, x, y) = key;
+                                state.tiles.insert(
+                                    key,
+                                    Tile::Loading(crate::runtime::spawn(async move {
+                                        maptiles::fetch_tile(&http_client(), z, x, y).await
+                                    })),
+                                );
+                            }
+                            None => loading = true,
+                        }
+                    }
+                }
+                if loading {
+                    ui.ctx().request_repaint();
+                }
+
+                let pointer = response.hover_pos();
+                let mut hovered = None;
+                for (i, pin) in pins.iter().enumerate() {
+                    let (x, y) = maptiles::project(pin.location, zoom);
+                    let position = to_screen(x, y);
+                    if !rect.expand(PIN_RADIUS).contains(position) {
+                        continue;
+                    }
+                    let label = pin.entry.effective_type().unwrap_or("rejected");
+                    let color = crate::type_color(label, palette, muted_color);
+                    painter.circle(position, PIN_RADIUS, color, egui::Stroke::new(1.5, egui::Color32::WHITE));
+                    if pointer.is_some_and(|p| p.distance(position) <= PIN_RADIUS + 2.0) {
+                        hovered = Some(i);
+                    }
+                }
+                painter.text(
+                    rect.right_bottom() - egui::vec2(4.0, 2.0),
+                    egui::Align2::RIGHT_BOTTOM,
+                    maptiles::ATTRIBUTION,
+                    egui::FontId::proportional(10.0),
+                    muted_color,
+                );
+
+                if let Some(pin) = hovered.map(|i| &pins[i]) {
+                    if response.clicked() {
+                        open_entry = Some(pin.entry.clone());
+                    }
+                    let label = pin.entry.effective_type().unwrap_or("rejected");
+                    response.on_hover_text_at_pointer(format!(
+                        "{} · {:.0}% · {}\nClick to open",
+                        crate::type_title(label),
+                        pin.entry.analysis.confidence_level,
+                        crate::monitor::format_date_time(pin.entry.snapshot.created_at)
+                    ));
+                }
+            });
+        if let Some(entry) = open_entry {
+            self.open_history_entry(ui.ctx(), &entry);
+        }
+    }
+}
+
+fn load_tile(ctx: &egui::Context, key: (u8, u32, u32), bytes: &[u8]) -> Option<egui::TextureHandle> {
+    let rgba = image::load_from_memory(bytes).ok()?.to_rgba8();
+    let size = [rgba.width() as usize, rgba.height() as usize];
+    let image = egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
+    let (z, x, y) = key;
+    Some(ctx.load_texture(format!("map-tile-{}-{}-{}", z, x, y), image, egui::TextureOptions::LINEAR))
+}
@@ -0,0 +1,147 @@
+use crate::monitor::format_time;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::missions::{self, MissionLog, MissionRecord, RESULT_SIZES};
+use avalanche_classifier_core::verification::ControlVerification;
+use eframe::egui;
+
+pub struct MissionsState {
+    log: MissionLog,
+    // Mission that new analyses are filed into automatically
+    active: Option<u64>,
+    draft: MissionRecord,
+}
+
+impl Default for MissionsState {
+    fn default() -> Self {
+        Self {
+            log: MissionLog::load_default(),
+            active: None,
+            draft: MissionRecord::default(),
+        }
+    }
+}
+
+impl AvalancheClassifier {
+    // Files a finished analysis into the active mission, if there is one
+    pub(crate) fn file_to_active_mission(&mut self, analysis_id: &str) {
+        if let Some(mission_id) = self.missions.active {
+            if let Err(err) = self.missions.log.file(mission_id, analysis_id) {
+                self.error = Some(format!("Failed to file into mission log: {}", err));
+            }
+        }
+    }
+
+    pub(crate) fn record_mission_verification(&mut self, verification: &ControlVerification) {
+        if let Some(mission_id) = self.missions.active {
+            if let Err(err) = self.missions.log.record_verification(mission_id, verification.clone()) {
+                self.error = Some(format!("Failed to record verification: {}", err));
+            }
+        }
+    }
+
+    pub(crate) fn missions_ui(&mut self, ui: &mut egui::Ui, accent_color: egui::Color32, muted_color: egui::Color32) {
+        egui::CollapsingHeader::new(format!("Control Missions ({})", self.missions.log.missions.len()))
+            .default_open(false)
+            .show(ui, |ui| {
+                let mut file_current = None;
+                for mission in self.missions.log.missions.iter().rev() {
+                    ui.horizontal(|ui| {
+                        let active = self.missions.active == Some(mission.id);
+                        if ui.radio(active, "").on_hover_text("File new analyses into this mission").clicked() {
+                            self.missions.active = if active { None } else { Some(mission.id) };
+                        }
+                        ui.label(
+                            egui::RichText::new(format!("#{} {}", mission.id, mission.area))
+                                .size(13.0)
+                                .strong()
+                                .color(if active { accent_color } else { ui.visuals().text_color() }),
+                        );
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{} · {} · {:.1} kg · {} · {} photos",
+                                format_time(mission.created_at),
+                                mission.placement,
+                                mission.charge_kg,
+                                mission.result_size.as_deref().unwrap_or("size pending"),
+                                mission.analysis_ids.len() + mission.verifications.len()
+                            ))
+                            .size(13.0)
+                            .color(muted_color),
+                        )
+                        .on_hover_text(
+                            mission
+                                .verifications
+                                .iter()
+                                .map(|v| v.summary())
+                                .collect::<Vec<_>>()
+                                .join("\n"),
+                        );
+                        if let Some(snapshot) = &self.snapshot {
+                            if !mission.analysis_ids.contains(&snapshot.id)
+                                && ui.small_button("📎").on_hover_text("File the current result here").clicked()
+                            {
+                                file_current = Some((mission.id, snapshot.id.clone()));
+                            }
+                        }
+                    });
+                }
+                if let Some((mission_id, analysis_id)) = file_current {
+                    if let Err(err) = self.missions.log.file(mission_id, &analysis_id) {
+                        self.error = Some(err.to_string());
+                    }
+                }
+                if let Some(id) = self.missions.active {
+                    ui.label(
+                        egui::RichText::new(format!("New analyses are filed into mission #{}", id))
+                            .size(13.0)
+                            .color(accent_color),
+                    );
+                }
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("New Mission").size(13.0).strong());
+                let draft = &mut self.missions.draft;
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut draft.area).hint_text("Area / path").desired_width(120.0));
+                    ui.add(egui::TextEdit::singleline(&mut draft.placement).hint_text("Explosive placement"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Charge");
+                    ui.add(egui::DragValue::new(&mut draft.charge_kg).speed(0.1).clamp_range(0.0..=100.0).suffix(" kg"));
+                    ui.label("Result");
+                    egui::ComboBox::from_id_source("mission-result")
+                        .selected_text(draft.result_size.as_deref().unwrap_or("Pending"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut draft.result_size, None, "Pending");
+                            for size in RESULT_SIZES {
+                                ui.selectable_value(&mut draft.result_size, Some(size.to_string()), size);
+                            }
+                        });
+                });
+                ui.add(egui::TextEdit::multiline(&mut draft.notes).desired_rows(2).hint_text("Notes"));
+                let valid = !draft.area.trim().is_empty();
+                let mut start = false;
+                ui.horizontal(|ui| {
+                    start = ui.add_enabled(valid, egui::Button::new("Start Mission")).clicked();
+                    if ui.button("⬇ Export Mission Log").clicked() {
+                        if let Some(path) = self.settings.export_dialog()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name("mission-log.csv")
+                            .save_file()
+                        {
+                            if let Err(err) = missions::export_csv(&self.missions.log, &self.history, &path) {
+                                self.error = Some(err.to_string());
+                            }
+                        }
+                    }
+                });
+                if start {
+                    let record = std::mem::take(&mut self.missions.draft);
+                    match self.missions.log.add(record) {
+                        Ok(id) => self.missions.active = Some(id),
+                        Err(err) => self.error = Some(err.to_string()),
+                    }
+                }
+            });
+    }
+}
@@ -0,0 +1,1003 @@
+use crate::palette::Palette;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::camera::{AlertRule, CameraProfile, Cameras, FrameSignature, Location, Mask, Roi};
+use avalanche_classifier_core::conditions::{self, SunTimes};
+use avalanche_classifier_core::exposure::{self, Asset, AssetKind, ExposedAsset};
+use avalanche_classifier_core::corridor::{self, Corridor, CorridorAssessment, Point};
+use avalanche_classifier_core::health::HealthTracker;
+use avalanche_classifier_core::history::HistoryEntry;
+use avalanche_classifier_core::provider::http_client;
+use avalanche_classifier_core::{classify_image_with_context, orientation, storage, Classification};
+use eframe::egui;
+use poll_promise::Promise;
+use std::collections::HashMap;
+
+// A frame fetch + classification still pending after this long is abandoned
+const POLL_TIMEOUT_SECS: u64 = 5 * 60;
+
+// Recorded analyses shown in each camera's timeline, most recent last
+const TIMELINE_LENGTH: usize = 48;
+
+const ALERT_TYPES: [Option<&str>; 9] = [
+    None,
+    Some("powder"),
+    Some("loose-snow"),
+    Some("slab"),
+    Some("wet-loose"),
+    Some("wet-slab"),
+    Some("glide"),
+    Some("cornice"),
+    Some("slush"),
+];
+
+// Marked assets that detected activity reached; empty when there was no
+// activity or the camera has no assets
+type Exposure = anyhow::Result<Vec<ExposedAsset>>;
+
+enum PollOutcome {
+    Analyzed(Box<Classification>, FrameSignature, Exposure),
+    // Runout assessment from a camera with a corridor
+    Corridor(Box<CorridorAssessment>, FrameSignature, Exposure),
+    // Difference from the last analyzed frame was under the camera's threshold
+    Unchanged,
+    // Too much precipitation to see the slope
+    Obscured,
+}
+
+struct Polled {
+    outcome: PollOutcome,
+    // The frame as the camera served it, for the live view; None when none
+    // was fetched
+    frame: Option<Vec<u8>>,
+}
+
+struct InFlight {
+    started_at: u64,
+    promise: Promise<anyhow::Result<Polled>>,
+    abort: tokio::task::AbortHandle,
+}
+
+pub(crate) struct CameraStatus {
+    pub at: u64,
+    pub avalanche_type: String,
+    pub confidence: f32,
+    // Set for corridor cameras
+    pub runout_reach: Option<String>,
+    // Frames skipped as unchanged since this analysis
+    pub skipped: u32,
+}
+
+pub struct MonitorState {
+    cameras: Cameras,
+    started_at: u64,
+    in_flight: HashMap<String, InFlight>,
+    health: HealthTracker,
+    latest: HashMap<String, CameraStatus>,
+    // Why a camera is currently not being polled, e.g. "night"
+    paused: HashMap<String, &'static str>,
+    // Live window: the latest frame per camera and when it was fetched,
+    // kept only while the window is open
+    live_open: bool,
+    live_frames: HashMap<String, (egui::TextureHandle, u64)>,
+    // Signature of the last analyzed frame per camera
+    signatures: HashMap<String, FrameSignature>,
+    // Profile being edited and its index, None for a new camera
+    editing: Option<(Option<usize>, CameraProfile)>,
+    // Raw frame fetched for painting the edited camera's mask
+    preview: Option<Promise<anyhow::Result<Vec<u8>>>>,
+    preview_texture: Option<egui::TextureHandle>,
+    paint_mode: PaintMode,
+    asset_draft: AssetDraft,
+}
+
+// What pointer input on the frame preview edits
+#[derive(Clone, Copy, PartialEq)]
+enum PaintMode {
+    Mask,
+    Corridor,
+    Assets,
+    Crop,
+}
+
+// Asset being marked on the preview; its area comes from a drag
+struct AssetDraft {
+    name: String,
+    kind: AssetKind,
+    // Drag start as fractions of the frame; also used while dragging out
+    // the crop, as only one mode is active at a time
+    drag_start: Option<(f32, f32)>,
+}
+
+impl Default for MonitorState {
+    fn default() -> Self {
+        Self {
+            cameras: Cameras::load_default(),
+            started_at: 0,
+            in_flight: HashMap::new(),
+            health: HealthTracker::default(),
+            latest: HashMap::new(),
+            paused: HashMap::new(),
+            live_open: false,
+            live_frames: HashMap::new(),
+            signatures: HashMap::new(),
+            editing: None,
+            preview: None,
+            preview_texture: None,
+            paint_mode: PaintMode::Mask,
+            asset_draft: AssetDraft { name: String::new(), kind: AssetKind::default(), drag_start: None },
+        }
+    }
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn poll_cameras(&mut self, ctx: &egui::Context) {
+        if !self.settings.monitoring {
+            return;
+        }
+        ctx.request_repaint_after(std::time::Duration::from_secs(5));
+        let now = storage::unix_now();
+
+        // Collect finished polls
+        let names: Vec<String> = self.monitor.in_flight.keys().cloned().collect();
+        for name in names {
+            let in_flight = self.monitor.in_flight.remove(&name).unwrap();
+            let result = match in_flight.promise.try_take() {
+                Ok(result) => result,
+                Err(promise) => {
+                    if now.saturating_sub(in_flight.started_at) > POLL_TIMEOUT_SECS {
+                        in_flight.abort.abort();
+                        self.record_camera_failure(&name, now, "Timed out".to_string());
+                    } else {
+                        self.monitor.in_flight.insert(name, InFlight { promise, ..in_flight });
+                    }
+                    continue;
+                }
+            };
+            let result = result.map(|Polled { outcome, frame }| {
+                if let Some(frame) = frame.filter(|_| self.monitor.live_open) {
+                    if let Some(texture) = load_texture(ctx, &frame) {
+                        self.monitor.live_frames.insert(name.clone(), (texture, now));
+                    }
+                }
+                outcome
+            });
+            match result {
+                Ok(PollOutcome::Obscured) => {
+                    self.monitor.health.record_success(&name, now);
+                    self.monitor.paused.insert(name, "heavy precipitation");
+                }
+                Ok(PollOutcome::Unchanged) => {
+                    self.monitor.health.record_success(&name, now);
+                    if let Some(status) = self.monitor.latest.get_mut(&name) {
+                        status.skipped += 1;
+                    }
+                }
+                Ok(PollOutcome::Analyzed(classification, signature, exposure)) => {
+                    let Classification { analysis, snapshot, .. } = *classification;
+                    self.monitor.health.record_success(&name, now);
+                    self.monitor.signatures.insert(name.clone(), signature);
+                    let messages: Vec<String> = self
+                        .monitor
+                        .cameras
+                        .cameras
+                        .iter()
+                        .filter(|c| c.name == name)
+                        .flat_map(|c| c.triggered_rules(&analysis))
+                        .map(|rule| {
+                            format!(
+                                "{} at {:.0}% (rule: {})",
+                                analysis.avalanche_type,
+                                analysis.confidence_level,
+                                rule.describe()
+                            )
+                        })
+                        .collect();
+                    for message in messages {
+                        self.raise_alert(&name, &message);
+                    }
+                    let exposure = self.alert_exposure(&name, exposure);
+                    self.monitor.latest.insert(
+                        name.clone(),
+                        CameraStatus {
+                            at: now,
+                            avalanche_type: analysis.avalanche_type.clone(),
+                            confidence: analysis.confidence_level,
+                            runout_reach: None,
+                            skipped: 0,
+                        },
+                    );
+                    if let Some(snapshot) = snapshot {
+                        let camera = self.monitor.cameras.cameras.iter().find(|c| c.name == name);
+                        let location = camera.and_then(|c| c.location);
+                        let bearing = camera.and_then(|c| c.bearing);
+                        let site_context = camera.map(|c| c.region.clone()).unwrap_or_default();
+                        let entry = HistoryEntry {
+                            source: Some(name),
+                            location,
+                            bearing,
+                            exposure,
+                            site_context,
+                            ..HistoryEntry::new(snapshot, analysis)
+                        };
+                        if let Err(err) = self.history.push(entry) {
+                            self.error = Some(err.to_string());
+                        }
+                    }
+                }
+                Ok(PollOutcome::Corridor(assessment, signature, exposure)) => {
+                    self.monitor.health.record_success(&name, now);
+                    self.monitor.signatures.insert(name.clone(), signature);
+                    if let Some(reason) = assessment.alert_reason() {
+                        self.raise_alert(&name, &reason);
+                    }
+                    self.alert_exposure(&name, exposure);
+                    self.monitor.latest.insert(
+                        name,
+                        CameraStatus {
+                            at: now,
+                            avalanche_type: assessment.avalanche_type.clone(),
+                            confidence: assessment.confidence_level,
+                            runout_reach: Some(assessment.runout_reach.clone()),
+                            skipped: 0,
+                        },
+                    );
+                }
+                Err(err) => self.record_camera_failure(&name, now, err.to_string()),
+            }
+        }
+
+        if !self.has_api_key() {
+            return;
+        }
+        let client = http_client();
+        for i in 0..self.monitor.cameras.cameras.len() {
+            let camera = &self.monitor.cameras.cameras[i];
+            if camera.is_dark(now) {
+                self.monitor.paused.insert(camera.name.clone(), "night");
+                continue;
+            }
+            if !camera.is_due(now)
+                || self.monitor.in_flight.contains_key(&camera.name)
+                || !self.monitor.health.can_run(&camera.name, now)
+            {
+                continue;
+            }
+            let camera = camera.clone();
+            self.monitor.cameras.cameras[i].last_polled = Some(now);
+            self.monitor.paused.remove(&camera.name);
+
+            let provider = self.provider();
+            let client = client.clone();
+            let name = camera.name.clone();
+            let previous = self.monitor.signatures.get(&name).cloned();
+            let (promise, abort) = crate::runtime::spawn_abortable(async move {
+                if camera.too_wet(&client).await {
+                    return Ok(Polled { outcome: PollOutcome::Obscured, frame: None });
+                }
+                let raw = camera.fetch_raw(&client).await?;
+                let frame = camera.prepare_frame(&raw)?;
+                let signature = FrameSignature::from_bytes(&frame)?;
+                if let Some(previous) = previous {
+                    if signature.difference(&previous) < camera.change_threshold {
+                        return Ok(Polled { outcome: PollOutcome::Unchanged, frame: Some(raw) });
+                    }
+                }
+                // Fetching and change detection don't need a slot; the
+                // model requests below do
+                let _slot = crate::runtime::analysis_slot(crate::runtime::Priority::Background).await;
+                // Asset coordinates are on the full frame, so exposure is
+                // checked on the raw frame rather than the cropped one
+                let check_exposure = |active: bool| {
+                    let (provider, assets, raw) = (&provider, &camera.assets, &raw);
+                    async move {
+                        if !active || assets.is_empty() {
+                            return Ok(Vec::new());
+                        }
+                        let outlined = exposure::outline_assets(raw, assets)?;
+                        Ok::<_, anyhow::Error>(exposure::assess_exposure(provider.as_ref(), &outlined, assets).await?)
+                    }
+                };
+                if camera.corridor.is_some() {
+                    let assessment = corridor::assess_corridor(provider.as_ref(), &frame, &camera.region).await?;
+                    let exposed = check_exposure(assessment.alert_reason().is_some()).await;
+                    let outcome = PollOutcome::Corridor(Box::new(assessment), signature, exposed);
+                    return Ok(Polled { outcome, frame: Some(raw) });
+                }
+                let classification =
+                    classify_image_with_context(provider.as_ref(), &frame, &camera.region).await?;
+                let exposed = check_exposure(classification.analysis.avalanche_present).await;
+                let outcome = PollOutcome::Analyzed(Box::new(classification), signature, exposed);
+                Ok(Polled { outcome, frame: Some(raw) })
+            });
+            self.monitor.in_flight.insert(name, InFlight { started_at: now, promise, abort });
+        }
+    }
+
+    // Raises an alert naming the assets the activity reached. A failed check
+    // is reported but doesn't count against the camera's health.
+    fn alert_exposure(&mut self, name: &str, exposure: Exposure) -> Vec<ExposedAsset> {
+        match exposure {
+            Ok(exposed) => {
+                if !exposed.is_empty() {
+                    let assets: Vec<String> = exposed.iter().map(ExposedAsset::describe).collect();
+                    self.raise_alert(name, &format!("Activity reached {}", assets.join(", ")));
+                }
+                exposed
+            }
+            Err(err) => {
+                self.error = Some(format!("Camera \"{}\": exposure check failed: {}", name, err));
+                Vec::new()
+            }
+        }
+    }
+
+    fn record_camera_failure(&mut self, name: &str, now: u64, error: String) {
+        if self.monitor.health.record_failure(name, now, error.clone()) {
+            self.error = Some(format!(
+                "Camera \"{}\" failed {} times in a row: {}",
+                name, self.monitor.health.escalate_after, error
+            ));
+        }
+    }
+
+    pub(crate) fn monitor_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let palette = Palette::of(ui.ctx());
+        let mut timelines: HashMap<&str, Vec<&HistoryEntry>> = HashMap::new();
+        for entry in self.history.originals() {
+            if let Some(source) = &entry.source {
+                timelines.entry(source.as_str()).or_default().push(entry);
+            }
+        }
+        let mut open_entry = None;
+        let state = &mut self.monitor;
+        let monitoring = &mut self.settings.monitoring;
+        let mut save = false;
+        egui::CollapsingHeader::new(format!("Monitoring ({} cameras)", state.cameras.cameras.len()))
+            .default_open(*monitoring)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let label = if *monitoring { "⏹ Stop Monitoring" } else { "▶ Start Monitoring" };
+                    if ui.button(label).clicked() {
+                        *monitoring = !*monitoring;
+                        state.started_at = storage::unix_now();
+                    }
+                    if ui.button("➕ Add Camera").clicked() {
+                        state.editing = Some((None, CameraProfile::default()));
+                        state.preview_texture = None;
+                    }
+                    if ui.selectable_label(state.live_open, "📡 Live").clicked() {
+                        state.live_open = !state.live_open;
+                    }
+                });
+
+                ui.add_space(8.0);
+                let mut remove = None;
+                for (i, camera) in state.cameras.cameras.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        save |= ui.checkbox(&mut camera.enabled, "").changed();
+                        ui.label(egui::RichText::new(&camera.name).size(13.0).strong());
+                        ui.label(
+                            egui::RichText::new(format!("every {}s", camera.interval_secs))
+                                .size(13.0)
+                                .color(muted_color),
+                        );
+                        if state.in_flight.contains_key(&camera.name) {
+                            ui.spinner();
+                        } else if let Some(reason) = state.paused.get(&camera.name) {
+                            ui.label(
+                                egui::RichText::new(format!("⏸ {}", reason))
+                                    .size(13.0)
+                                    .color(muted_color),
+                            );
+                        } else if let Some(status) = state.latest.get(&camera.name) {
+                            let reach = status.runout_reach.as_ref().map_or(String::new(), |r| format!(" · runout {}", r));
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "{} {:.0}%{} · {}",
+                                    status.avalanche_type,
+                                    status.confidence,
+                                    reach,
+                                    format_time(status.at)
+                                ))
+                                .size(13.0),
+                            );
+                            if status.skipped > 0 {
+                                ui.label(
+                                    egui::RichText::new(format!("{} unchanged frames skipped", status.skipped))
+                                        .size(13.0)
+                                        .color(muted_color),
+                                );
+                            }
+                        }
+                        if let Some(health) = state.health.get(&camera.name) {
+                            if health.consecutive_failures > 0 {
+                                ui.label(
+                                    egui::RichText::new(format!("⚠ {} failures", health.consecutive_failures))
+                                        .size(13.0)
+                                        .color(Palette::of(ui.ctx()).warning),
+                                )
+                                .on_hover_text(health.last_error.clone().unwrap_or_default());
+                            }
+                        }
+                        if ui.small_button("✏").clicked() {
+                            state.editing = Some((Some(i), camera.clone()));
+                            state.preview_texture = None;
+                        }
+                        if ui.small_button("✕").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                    if let Some(entries) = timelines.get(camera.name.as_str()) {
+                        let recent = &entries[entries.len().saturating_sub(TIMELINE_LENGTH)..];
+                        if let Some(entry) = timeline(ui, recent, palette, muted_color) {
+                            open_entry = Some(entry.clone());
+                        }
+                    }
+                }
+                if let Some(i) = remove {
+                    state.cameras.cameras.remove(i);
+                    save = true;
+                }
+
+                let mut close_editor = false;
+                if let Some((index, draft)) = &mut state.editing {
+                    ui.add_space(8.0);
+                    ui.group(|ui| {
+                        camera_editor(ui, draft, muted_color);
+
+                        ui.add_space(4.0);
+                        ui.label("Mask and markings");
+                        ui.horizontal(|ui| {
+                            let fetching = state.preview.is_some();
+                            if ui
+                                .add_enabled(!draft.url.is_empty() && !fetching, egui::Button::new("Fetch Frame"))
+                                .clicked()
+                            {
+                                let camera = draft.clone();
+                                state.preview = Some(crate::runtime::spawn(async move {
+                                    camera.fetch_raw(&http_client()).await
+                                }));
+                            }
+                            if fetching {
+                                ui.spinner();
+                            }
+                            if draft.mask.is_some() && ui.button("Clear Mask").clicked() {
+                                draft.mask = None;
+                            }
+                            if let Some(corridor) = &mut draft.corridor {
+                                if !corridor.points.is_empty() && ui.button("Clear Corridor").clicked() {
+                                    corridor.points.clear();
+                                }
+                            }
+                        });
+                        if let Some(promise) = state.preview.take() {
+                            match promise.try_take() {
+                                Ok(Ok(bytes)) => {
+                                    state.preview_texture = load_texture(ui.ctx(), &bytes);
+                                    if state.preview_texture.is_none() {
+                                        self.error = Some("Camera frame is not a readable image".to_string());
+                                    }
+                                }
+                                Ok(Err(err)) => self.error = Some(format!("Failed to fetch frame: {}", err)),
+                                Err(promise) => state.preview = Some(promise),
+                            }
+                        }
+                        if let Some(texture) = &state.preview_texture {
+                            if draft.corridor.is_none() && state.paint_mode == PaintMode::Corridor {
+                                state.paint_mode = PaintMode::Mask;
+                            }
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut state.paint_mode, PaintMode::Mask, "Paint Mask");
+                                if draft.corridor.is_some() {
+                                    ui.selectable_value(&mut state.paint_mode, PaintMode::Corridor, "Draw Corridor");
+                                }
+                                ui.selectable_value(&mut state.paint_mode, PaintMode::Assets, "Mark Assets");
+                                ui.selectable_value(&mut state.paint_mode, PaintMode::Crop, "Set Crop");
+                            });
+                            let hint = match state.paint_mode {
+                                PaintMode::Mask => "Drag to exclude areas, right-drag to include them again",
+                                PaintMode::Corridor => "Click along the road to add points, right-click to remove the last one",
+                                PaintMode::Assets => "Name the asset, then drag a box around it",
+                                PaintMode::Crop => "Drag around the slope to analyze; sky and valley are left out",
+                            };
+                            ui.label(egui::RichText::new(hint).size(13.0).color(muted_color));
+                            if state.paint_mode == PaintMode::Assets {
+                                let asset = &mut state.asset_draft;
+                                ui.horizontal(|ui| {
+                                    ui.add(egui::TextEdit::singleline(&mut asset.name).hint_text("e.g. Tower 7").desired_width(120.0));
+                                    egui::ComboBox::from_id_source("asset-kind")
+                                        .selected_text(asset.kind.label())
+                                        .show_ui(ui, |ui| {
+                                            for kind in AssetKind::ALL {
+                                                ui.selectable_value(&mut asset.kind, kind, kind.label());
+                                            }
+                                        });
+                                });
+                            }
+                            mask_painter(ui, texture, draft, state.paint_mode, &mut state.asset_draft);
+                        }
+                        if !draft.assets.is_empty() {
+                            ui.label("Exposed assets");
+                            let mut remove = None;
+                            for (i, asset) in draft.assets.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    ui.label(egui::RichText::new(&asset.name).size(13.0).strong());
+                                    ui.label(egui::RichText::new(asset.kind.label()).size(13.0).color(muted_color));
+                                    if ui.small_button("✕").clicked() {
+                                        remove = Some(i);
+                                    }
+                                });
+                            }
+                            if let Some(i) = remove {
+                                draft.assets.remove(i);
+                            }
+                        }
+                        let duplicate = state
+                            .cameras
+                            .cameras
+                            .iter()
+                            .enumerate()
+                            .any(|(i, c)| c.name == draft.name && Some(i) != *index);
+                        if duplicate {
+                            ui.label(
+                                egui::RichText::new("Another camera already has this name")
+                                    .size(13.0)
+                                    .color(muted_color),
+                            );
+                        }
+                        if draft.corridor.as_ref().is_some_and(|c| c.points.len() < 2) {
+                            ui.label(
+                                egui::RichText::new("Fetch a frame and draw the corridor with at least two points")
+                                    .size(13.0)
+                                    .color(muted_color),
+                            );
+                        }
+                        ui.horizontal(|ui| {
+                            let valid = !draft.name.is_empty()
+                                && !draft.url.is_empty()
+                                && !duplicate
+                                && draft.corridor.as_ref().is_none_or(|c| c.points.len() >= 2);
+                            if ui.add_enabled(valid, egui::Button::new("Save Camera")).clicked() {
+                                match index {
+                                    Some(i) => {
+                                        // Mask or crop may have changed, so compare afresh
+                                        state.signatures.remove(&state.cameras.cameras[*i].name);
+                                        state.cameras.cameras[*i] = draft.clone();
+                                    }
+                                    None => state.cameras.cameras.push(draft.clone()),
+                                }
+                                save = true;
+                                close_editor = true;
+                            }
+                            if ui.button("Cancel").clicked() {
+                                close_editor = true;
+                            }
+                        });
+                    });
+                }
+                if close_editor {
+                    state.editing = None;
+                    state.preview = None;
+                    state.preview_texture = None;
+                }
+            });
+
+        if save {
+            if let Err(err) = self.monitor.cameras.save() {
+                self.error = Some(err.to_string());
+            }
+        }
+        if let Some(entry) = open_entry {
+            self.open_history_entry(ui.ctx(), &entry);
+        }
+    }
+}
+
+impl AvalancheClassifier {
+    // The latest frame from each camera with its latest result, refreshed
+    // as the cameras are polled
+    pub(crate) fn live_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.monitor;
+        if !state.live_open {
+            state.live_frames.clear();
+            return;
+        }
+        let muted_color = Palette::of(ctx).muted;
+        egui::Window::new("Live")
+            .open(&mut state.live_open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                if !self.settings.monitoring {
+                    ui.label(
+                        egui::RichText::new("Start monitoring to fetch frames")
+                            .size(13.0)
+                            .color(muted_color),
+                    );
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for camera in state.cameras.cameras.iter().filter(|c| c.enabled) {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(&camera.name).size(15.0).strong());
+                            if avalanche_classifier_core::ffmpeg::is_stream(&camera.url) {
+                                ui.label(egui::RichText::new("stream").size(13.0).color(muted_color));
+                            }
+                            if state.in_flight.contains_key(&camera.name) {
+                                ui.spinner();
+                            }
+                        });
+                        match state.live_frames.get(&camera.name) {
+                            Some((texture, at)) => {
+                                let size = texture.size_vec2();
+                                let width = ui.available_width().min(size.x);
+                                ui.add(egui::Image::new((texture.id(), size * (width / size.x))));
+                                ui.label(
+                                    egui::RichText::new(format!("Frame from {}", format_time(*at)))
+                                        .size(13.0)
+                                        .color(muted_color),
+                                );
+                            }
+                            None => {
+                                ui.label(
+                                    egui::RichText::new("Waiting for the next frame")
+                                        .size(13.0)
+                                        .color(muted_color),
+                                );
+                            }
+                        }
+                        if let Some(reason) = state.paused.get(&camera.name) {
+                            ui.label(egui::RichText::new(format!("⏸ {}", reason)).size(13.0).color(muted_color));
+                        } else if let Some(status) = state.latest.get(&camera.name) {
+                            let reach =
+                                status.runout_reach.as_ref().map_or(String::new(), |r| format!(" · runout {}", r));
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "{} · {:.0}%{} · analyzed {}",
+                                    crate::type_title(&status.avalanche_type),
+                                    status.confidence,
+                                    reach,
+                                    format_time(status.at)
+                                ))
+                                .size(14.0),
+                            );
+                        }
+                        ui.add_space(12.0);
+                    }
+                });
+            });
+    }
+}
+
+// A camera's recorded analyses as a strip of cells colored by type, oldest
+// first, so changes over the day stand out; returns the entry clicked
+fn timeline<'a>(
+    ui: &mut egui::Ui,
+    entries: &[&'a HistoryEntry],
+    palette: Palette,
+    muted_color: egui::Color32,
+) -> Option<&'a HistoryEntry> {
+    let cell = egui::vec2(8.0, 14.0);
+    let mut clicked = None;
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 2.0;
+        for entry in entries {
+            let label = entry.effective_type().unwrap_or("rejected");
+            let color = if entry.analysis.avalanche_present {
+                crate::type_color(label, palette, muted_color)
+            } else {
+                muted_color.gamma_multiply(0.4)
+            };
+            let (rect, response) = ui.allocate_exact_size(cell, egui::Sense::click());
+            ui.painter().rect_filled(rect, 2.0, color);
+            let response = response.on_hover_text(format!(
+                "{} · {:.0}% · {}\nClick to open",
+                crate::type_title(label),
+                entry.analysis.confidence_level,
+                format_date_time(entry.snapshot.created_at)
+            ));
+            if response.clicked() {
+                clicked = Some(*entry);
+            }
+        }
+    });
+    clicked
+}
+
+fn camera_editor(ui: &mut egui::Ui, draft: &mut CameraProfile, muted_color: egui::Color32) {
+    ui.label("Name");
+    ui.text_edit_singleline(&mut draft.name);
+    ui.label("Snapshot or stream URL");
+    ui.add(egui::TextEdit::singleline(&mut draft.url).hint_text("https://example.com/cam/latest.jpg"));
+    if avalanche_classifier_core::ffmpeg::is_stream(&draft.url) {
+        ui.label(
+            egui::RichText::new("A frame is grabbed from the stream with ffmpeg at each poll")
+                .size(13.0)
+                .color(muted_color),
+        );
+    }
+    ui.horizontal(|ui| {
+        ui.label("Poll every");
+        ui.add(egui::DragValue::new(&mut draft.interval_secs).clamp_range(30..=86_400).suffix(" s"));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Skip frames changed by less than");
+        ui.add(egui::DragValue::new(&mut draft.change_threshold).speed(0.1).clamp_range(0.0..=50.0).suffix("%"));
+    });
+
+    let mut located = draft.location.is_some();
+    if ui.checkbox(&mut located, "Camera location").changed() {
+        draft.location = located.then_some(Location { latitude: 46.0, longitude: 7.0 });
+    }
+    if let Some(location) = &mut draft.location {
+        ui.horizontal(|ui| {
+            ui.label("Lat");
+            ui.add(egui::DragValue::new(&mut location.latitude).speed(0.01).clamp_range(-90.0..=90.0));
+            ui.label("Lon");
+            ui.add(egui::DragValue::new(&mut location.longitude).speed(0.01).clamp_range(-180.0..=180.0));
+        });
+        let mut oriented = draft.bearing.is_some();
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut oriented, "Facing").changed() {
+                draft.bearing = oriented.then_some(0.0);
+            }
+            if let Some(bearing) = &mut draft.bearing {
+                ui.add(egui::DragValue::new(bearing).speed(1.0).clamp_range(0.0..=359.0).suffix("°"));
+                ui.label(egui::RichText::new(orientation::compass_point(*bearing)).size(13.0).color(muted_color));
+            }
+        });
+        ui.checkbox(&mut draft.daylight_only, "Pause polling at night");
+        let sun = match conditions::sun_times(location.latitude, location.longitude, storage::unix_now() as i64) {
+            SunTimes::Times { sunrise, sunset } => {
+                format!("Today: sunrise {}, sunset {}", format_time(sunrise as u64), format_time(sunset as u64))
+            }
+            SunTimes::PolarDay => "Sun stays up today".to_string(),
+            SunTimes::PolarNight => "Sun stays down today".to_string(),
+        };
+        ui.label(egui::RichText::new(sun).size(13.0).color(muted_color));
+
+        let mut weather = draft.max_precipitation.is_some();
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut weather, "Pause above").changed() {
+                draft.max_precipitation = weather.then_some(2.0);
+            }
+            if let Some(limit) = &mut draft.max_precipitation {
+                ui.add(egui::DragValue::new(limit).speed(0.1).clamp_range(0.0..=50.0).suffix(" mm/h"));
+            }
+            ui.label("precipitation");
+        });
+    }
+
+    let mut crop = draft.roi.is_some();
+    if ui.checkbox(&mut crop, "Crop to region of interest").changed() {
+        draft.roi = crop.then(Roi::default);
+    }
+    if let Some(roi) = &mut draft.roi {
+        ui.horizontal(|ui| {
+            for (label, value) in [("x", &mut roi.x), ("y", &mut roi.y), ("w", &mut roi.width), ("h", &mut roi.height)] {
+                ui.label(label);
+                ui.add(egui::DragValue::new(value).speed(0.01).clamp_range(0.0..=1.0));
+            }
+        });
+        ui.label(egui::RichText::new("Fractions of the frame").size(13.0).color(muted_color));
+    }
+
+    ui.label("Region context");
+    ui.add(
+        egui::TextEdit::multiline(&mut draft.region)
+            .desired_rows(2)
+            .hint_text("e.g. NE-facing start zone above the access road, 2400 m"),
+    );
+
+    let mut highway = draft.corridor.is_some();
+    if ui
+        .checkbox(&mut highway, "Highway corridor profile")
+        .on_hover_text("Assess how far avalanches run toward a road or rail line and alert on activity above it")
+        .changed()
+    {
+        draft.corridor = highway.then(Corridor::default);
+    }
+    if draft.corridor.is_some() {
+        ui.label(
+            egui::RichText::new("Alerts fire on activity above the corridor; the rules below are not used")
+                .size(13.0)
+                .color(muted_color),
+        );
+        return;
+    }
+
+    ui.label("Alert rules");
+    let mut remove = None;
+    for (i, rule) in draft.alert_rules.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source(("alert-type", i))
+                .selected_text(rule.avalanche_type.as_deref().unwrap_or("any avalanche"))
+                .show_ui(ui, |ui| {
+                    for t in ALERT_TYPES {
+                        ui.selectable_value(
+                            &mut rule.avalanche_type,
+                            t.map(str::to_string),
+                            t.unwrap_or("any avalanche"),
+                        );
+                    }
+                });
+            ui.label("at ≥");
+            ui.add(egui::Slider::new(&mut rule.min_confidence, 0.0..=100.0).suffix("%"));
+            if ui.small_button("✕").clicked() {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove {
+        draft.alert_rules.remove(i);
+    }
+    if ui.small_button("➕ Rule").clicked() {
+        draft.alert_rules.push(AlertRule { avalanche_type: None, min_confidence: 70.0 });
+    }
+}
+
+// Shows the frame with the mask and ROI overlaid and paints mask cells under
+// the pointer
+fn mask_painter(
+    ui: &mut egui::Ui,
+    texture: &egui::TextureHandle,
+    draft: &mut CameraProfile,
+    mode: PaintMode,
+    asset: &mut AssetDraft,
+) {
+    let size = texture.size_vec2();
+    let scale = (400.0 / size.x).min(1.0);
+    let (response, painter) = ui.allocate_painter(size * scale, egui::Sense::click_and_drag());
+    let rect = response.rect;
+    painter.image(
+        texture.id(),
+        rect,
+        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+        egui::Color32::WHITE,
+    );
+
+    let to_frame = |pos: egui::Pos2| {
+        (
+            ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0),
+            ((pos.y - rect.top()) / rect.height()).clamp(0.0, 1.0),
+        )
+    };
+    let to_screen = |x: f32, y: f32| rect.min + egui::vec2(x * rect.width(), y * rect.height());
+    if mode == PaintMode::Corridor {
+        if let (Some(corridor), Some(pos)) = (&mut draft.corridor, response.interact_pointer_pos()) {
+            if response.clicked() {
+                let (x, y) = to_frame(pos);
+                corridor.points.push(Point { x, y });
+            } else if response.secondary_clicked() {
+                corridor.points.pop();
+            }
+        }
+    } else if mode == PaintMode::Assets {
+        let named = !asset.name.trim().is_empty();
+        if let Some(pos) = response.interact_pointer_pos() {
+            if response.drag_started() && named {
+                asset.drag_start = Some(to_frame(pos));
+            }
+            if let Some((x0, y0)) = asset.drag_start {
+                let (x1, y1) = to_frame(pos);
+                let area = Roi { x: x0.min(x1), y: y0.min(y1), width: (x1 - x0).abs(), height: (y1 - y0).abs() };
+                painter.rect_stroke(
+                    egui::Rect::from_two_pos(to_screen(x0, y0), to_screen(x1, y1)),
+                    0.0,
+                    egui::Stroke::new(2.0, egui::Color32::WHITE),
+                );
+                if response.drag_released() {
+                    asset.drag_start = None;
+                    if area.width > 0.01 && area.height > 0.01 {
+                        draft.assets.push(Asset {
+                            name: std::mem::take(&mut asset.name).trim().to_string(),
+                            kind: asset.kind,
+                            area,
+                        });
+                    }
+                }
+            }
+        }
+    } else if mode == PaintMode::Crop {
+        if let Some(pos) = response.interact_pointer_pos() {
+            if response.drag_started() {
+                asset.drag_start = Some(to_frame(pos));
+            }
+            if let Some((x0, y0)) = asset.drag_start {
+                let (x1, y1) = to_frame(pos);
+                if (x1 - x0).abs() > 0.02 && (y1 - y0).abs() > 0.02 {
+                    draft.roi =
+                        Some(Roi { x: x0.min(x1), y: y0.min(y1), width: (x1 - x0).abs(), height: (y1 - y0).abs() });
+                }
+            }
+        }
+        if response.drag_released() {
+            asset.drag_start = None;
+        }
+    } else if let Some(pos) = response.interact_pointer_pos() {
+        let exclude = ui.input(|i| i.pointer.primary_down());
+        let include = ui.input(|i| i.pointer.secondary_down());
+        if exclude || include {
+            let mask = draft.mask.get_or_insert_with(Mask::new);
+            let (x, y) = ((pos.x - rect.left()) / rect.width(), (pos.y - rect.top()) / rect.height());
+            if let Some((col, row)) = mask.cell_at(x, y) {
+                mask.set(col, row, exclude);
+            }
+        }
+    }
+
+    if let Some(mask) = &draft.mask {
+        let cell = egui::vec2(rect.width() / mask.cols as f32, rect.height() / mask.rows as f32);
+        for row in 0..mask.rows {
+            for col in 0..mask.cols {
+                if mask.is_excluded(col, row) {
+                    let min = rect.min + egui::vec2(col as f32 * cell.x, row as f32 * cell.y);
+                    painter.rect_filled(
+                        egui::Rect::from_min_size(min, cell),
+                        0.0,
+                        egui::Color32::from_rgba_unmultiplied(255, 59, 48, 110),
+                    );
+                }
+            }
+        }
+    }
+    if let Some(roi) = &draft.roi {
+        let min = rect.min + egui::vec2(roi.x * rect.width(), roi.y * rect.height());
+        painter.rect_stroke(
+            egui::Rect::from_min_size(min, egui::vec2(roi.width * rect.width(), roi.height * rect.height())),
+            0.0,
+            egui::Stroke::new(2.0, egui::Color32::from_rgb(0, 122, 255)),
+        );
+    }
+    let [r, g, b] = exposure::OUTLINE_COLOR;
+    for asset in &draft.assets {
+        let a = &asset.area;
+        painter.rect_stroke(
+            egui::Rect::from_two_pos(to_screen(a.x, a.y), to_screen(a.x + a.width, a.y + a.height)),
+            0.0,
+            egui::Stroke::new(2.0, egui::Color32::from_rgb(r, g, b)),
+        );
+        painter.text(
+            to_screen(a.x, a.y),
+            egui::Align2::LEFT_BOTTOM,
+            &asset.name,
+            egui::FontId::proportional(12.0),
+            egui::Color32::from_rgb(r, g, b),
+        );
+    }
+    if let Some(corridor) = &draft.corridor {
+        let [r, g, b] = corridor::LINE_COLOR;
+        let points: Vec<egui::Pos2> = corridor
+            .points
+            .iter()
+            .map(|p| rect.min + egui::vec2(p.x * rect.width(), p.y * rect.height()))
+            .collect();
+        painter.add(egui::Shape::line(points.clone(), egui::Stroke::new(3.0, egui::Color32::from_rgb(r, g, b))));
+        for point in points {
+            painter.circle_filled(point, 4.0, egui::Color32::from_rgb(r, g, b));
+        }
+    }
+}
+
+fn load_texture(ctx: &egui::Context, bytes: &[u8]) -> Option<egui::TextureHandle> {
+    let rgba = image::load_from_memory(bytes).ok()?.to_rgba8();
+    let size = [rgba.width() as usize, rgba.height() as usize];
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &rgba.into_vec());
+    Some(ctx.load_texture("camera-preview", color_image, egui::TextureOptions::LINEAR))
+}
+
+pub(crate) fn format_time(unix: u64) -> String {
+    use chrono::TimeZone;
+    chrono::Local
+        .timestamp_opt(unix as i64, 0)
+        .single()
+        .map_or_else(String::new, |t| t.format("%H:%M").to_string())
+}
+
+// Date and time, for lists that span several days
+pub(crate) fn format_date_time(unix: u64) -> String {
+    use chrono::TimeZone;
+    chrono::Local
+        .timestamp_opt(unix as i64, 0)
+        .single()
+        .map_or_else(String::new, |t| t.format("%b %d %H:%M").to_string())
+}
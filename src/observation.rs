@@ -0,0 +1,105 @@
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::model::ASPECTS;
+use avalanche_classifier_core::observation::{FieldObservations, TemperatureTrend, WindStrength};
+use eframe::egui;
+
+#[derive(Default)]
+pub struct ObservationState {
+    // Sent with every analysis until cleared, as observations usually hold
+    // for all photos of a tour
+    pub observations: FieldObservations,
+}
+
+impl AvalancheClassifier {
+    // Optional ground observations sent to the model with the photo
+    pub(crate) fn observation_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let observations = &mut self.observation.observations;
+        let title = if observations.is_empty() {
+            "Field Observations".to_string()
+        } else {
+            format!("Field Observations ({})", observations.lines().len())
+        };
+        egui::CollapsingHeader::new(title)
+            .id_source("field_observations")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(
+                        "What you see on site is sent with the photo and decides between readings it leaves open",
+                    )
+                    .size(13.0)
+                    .color(muted_color),
+                );
+                ui.add_space(4.0);
+                egui::Grid::new("field_observations_grid").num_columns(2).spacing(egui::vec2(12.0, 4.0)).show(ui, |ui| {
+                    ui.label("New snow (72 h)");
+                    ui.horizontal(|ui| {
+                        let mut known = observations.new_snow_cm.is_some();
+                        ui.checkbox(&mut known, "");
+                        match (known, observations.new_snow_cm) {
+                            (true, None) => observations.new_snow_cm = Some(0),
+                            (false, Some(_)) => observations.new_snow_cm = None,
+                            _ => {}
+                        }
+                        if let Some(cm) = &mut observations.new_snow_cm {
+                            ui.add(egui::DragValue::new(cm).clamp_range(0..=500).suffix(" cm"));
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Wind");
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source("wind_strength")
+                            .selected_text(observations.wind_strength.map_or("not observed", |s| s.label()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut observations.wind_strength, None, "not observed");
+                                for strength in WindStrength::ALL {
+                                    ui.selectable_value(&mut observations.wind_strength, Some(strength), strength.label());
+                                }
+                            });
+                        if observations.wind_strength != Some(WindStrength::Calm) {
+                            ui.label("from");
+                            egui::ComboBox::from_id_source("wind_direction")
+                                .width(60.0)
+                                .selected_text(observations.wind_direction.as_deref().unwrap_or("?"))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut observations.wind_direction, None, "?");
+                                    for aspect in ASPECTS {
+                                        ui.selectable_value(&mut observations.wind_direction, Some(aspect.to_string()), aspect);
+                                    }
+                                });
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Temperature");
+                    egui::ComboBox::from_id_source("temperature_trend")
+                        .selected_text(observations.temperature_trend.map_or("not observed", |t| t.label()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut observations.temperature_trend, None, "not observed");
+                            for trend in TemperatureTrend::ALL {
+                                ui.selectable_value(&mut observations.temperature_trend, Some(trend), trend.label());
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Recent avalanches");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut observations.recent_avalanches)
+                            .hint_text("e.g. several D2 wind slabs on NE aspects yesterday"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Stability tests");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut observations.stability_tests)
+                            .hint_text("e.g. ECTP 14 down 40 cm on surface hoar"),
+                    );
+                    ui.end_row();
+                });
+                if !observations.is_empty() && ui.button("Clear Observations").clicked() {
+                    *observations = FieldObservations::default();
+                }
+            });
+    }
+}
@@ -0,0 +1,56 @@
+use eframe::egui;
+
+// The iOS system colors the UI is drawn in. Dark backgrounds get Apple's
+// dark-mode variants, which are a little brighter so text and badges keep
+// their contrast. Overlays drawn on photos keep fixed colors.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Palette {
+    pub accent: egui::Color32,
+    pub success: egui::Color32,
+    pub warning: egui::Color32,
+    pub danger: egui::Color32,
+    pub muted: egui::Color32,
+}
+
+impl Palette {
+    pub const LIGHT: Palette = Palette {
+        accent: egui::Color32::from_rgb(0, 122, 255),  // iOS blue
+        success: egui::Color32::from_rgb(52, 199, 89), // iOS green
+        warning: egui::Color32::from_rgb(255, 149, 0), // iOS orange
+        danger: egui::Color32::from_rgb(255, 59, 48),  // iOS red
+        muted: egui::Color32::from_rgb(142, 142, 147), // iOS gray
+    };
+
+    pub const DARK: Palette = Palette {
+        accent: egui::Color32::from_rgb(10, 132, 255),
+        success: egui::Color32::from_rgb(48, 209, 88),
+        warning: egui::Color32::from_rgb(255, 159, 10),
+        danger: egui::Color32::from_rgb(255, 69, 58),
+        muted: egui::Color32::from_rgb(152, 152, 157),
+    };
+
+    pub fn for_dark_mode(dark: bool) -> Palette {
+        if dark {
+            Palette::DARK
+        } else {
+            Palette::LIGHT
+        }
+    }
+
+    // The palette matching the visuals currently in use
+    pub fn of(ctx: &egui::Context) -> Palette {
+        Palette::for_dark_mode(ctx.style().visuals.dark_mode)
+    }
+
+    // Badge color of an avalanche type by how dangerous it is: green for
+    // none, orange for the sluffing and falling types, red for slabs and
+    // the full-depth ones. None for types this build doesn't know.
+    pub fn type_color(&self, avalanche_type: &str) -> Option<egui::Color32> {
+        match avalanche_classifier_core::zones::severity(avalanche_type) {
+            0 => Some(self.success),
+            1 => None,
+            2 | 3 => Some(self.warning),
+            _ => Some(self.danger),
+        }
+    }
+}
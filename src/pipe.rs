@@ -0,0 +1,125 @@
+// Headless worker for shell and ETL pipelines, started with --pipe. Each
+// line on stdin is a JSON job, e.g.
+//   {"id": 7, "path": "/data/slope.jpg", "context": "NE aspect, 2400 m"}
+// and each job gets one line on stdout, in order, echoing its id:
+//   {"id": 7, "ok": true, "analysis": {...}, "snapshot": {...}}
+//   {"id": 7, "ok": false, "error": {"kind": "...", "message": "...", "hint": ...}}
+// Backend, model, prompt and validation come from settings.toml as in the
+// app; keys from the environment, a .env file or the keychain. Diagnostics go
+// to stderr so stdout stays machine-readable. The process exits at the end of
+// stdin.
+
+use crate::keychain;
+use crate::settings::Settings;
+use avalanche_classifier_core::fetch::{download_image, Progress};
+use avalanche_classifier_core::provider::{self, http_client};
+use avalanche_classifier_core::error::failure_json;
+use avalanche_classifier_core::{scoring, AnalysisError, Backend, ClassifyOptions};
+use serde::Deserialize;
+use std::io::{BufRead, Write};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Job {
+    // Anything; handed back untouched to match results to jobs
+    #[serde(default)]
+    id: serde_json::Value,
+    // Photo on disk, or an https:// URL to download it from
+    path: Option<std::path::PathBuf>,
+    url: Option<String>,
+    #[serde(default)]
+    context: String,
+    // Overrides the backend and model from settings.toml for this job
+    backend: Option<String>,
+    model: Option<String>,
+}
+
+struct Worker {
+    settings: Settings,
+    keys: Vec<(Backend, String)>,
+}
+
+impl Worker {
+    fn api_key(&self, backend: Backend) -> String {
+        self.keys.iter().find(|(b, _)| *b == backend).map_or_else(String::new, |(_, key)| key.clone())
+    }
+
+    fn run(&self, job: &Job) -> serde_json::Value {
+        let backend = match job.backend.as_deref() {
+            None => self.settings.backend,
+            Some(id) => match Backend::ALL.into_iter().find(|b| b.id() == id) {
+                Some(backend) => backend,
+                None => return AnalysisError::UnknownBackend(id.to_string()).to_json(),
+            },
+        };
+        let model = job.model.clone().unwrap_or_else(|| self.settings.model(backend).to_string());
+        let provider = self.settings.provider(backend, model, self.api_key(backend));
+
+        let bytes = match (&job.path, &job.url) {
+            (Some(path), None) => std::fs::read(path).map_err(|err| format!("{}: {}", path.display(), err)),
+            (None, Some(url)) => crate::runtime::runtime()
+                .block_on(download_image(&http_client(), url, &Progress::default()))
+                .map_err(|err| format!("{}: {}", url, err)),
+            _ => return failure_json("invalid_request", "give exactly one of path and url".to_string(), None),
+        };
+        let bytes = match bytes {
+            Ok(bytes) => bytes,
+            Err(message) => return failure_json("unreadable_image", message, None),
+        };
+
+        let options = ClassifyOptions::current().with_context(job.context.as_str());
+        match crate::runtime::runtime().block_on(provider::classify(provider.as_ref(), &bytes, &options)) {
+            Ok(classification) => serde_json::json!({
+                "ok": true,
+                "analysis": classification.analysis,
+                "snapshot": classification.snapshot,
+            }),
+            Err(err) => err.to_json(),
+        }
+    }
+}
+
+// Serves jobs from stdin until it closes; the exit code is non-zero only when
+// stdin or stdout fail
+pub(crate) fn run() -> i32 {
+    let (settings, settings_error) = Settings::load();
+    if let Some(err) = settings_error {
+        eprintln!("{}", err);
+    }
+    settings.apply();
+    if let Err(err) = scoring::reload() {
+        eprintln!("Could not read scoring.toml, using defaults: {}", err);
+    }
+    let keys = [Backend::OpenAi, Backend::Anthropic]
+        .into_iter()
+        .filter_map(|backend| {
+            keychain::initial_key(backend, settings.remember_api_keys).map(|(key, _)| (backend, key))
+        })
+        .collect();
+    let worker = Worker { settings, keys };
+
+    let stdout = std::io::stdout();
+    for line in std::io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("Could not read stdin: {}", err);
+                return 1;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (id, mut reply) = match serde_json::from_str::<Job>(&line) {
+            Ok(job) => (job.id.clone(), worker.run(&job)),
+            Err(err) => (serde_json::Value::Null, failure_json("invalid_request", err.to_string(), None)),
+        };
+        reply["id"] = id;
+        let mut out = stdout.lock();
+        if writeln!(out, "{}", reply).and_then(|_| out.flush()).is_err() {
+            // The reader went away; nobody is left to serve
+            return 1;
+        }
+    }
+    0
+}
@@ -0,0 +1,229 @@
+use crate::palette::Palette;
+use crate::{monitor, preview, scores, AvalancheClassifier};
+use eframe::egui;
+
+// A display to present on, as xcap reports it
+struct Display {
+    name: String,
+    // Top-left corner in physical pixels
+    x: i32,
+    y: i32,
+    scale: f32,
+    primary: bool,
+}
+
+impl Display {
+    fn label(&self) -> String {
+        if self.primary {
+            format!("{} (primary)", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PresentationState {
+    pub(crate) open: bool,
+    // Listed on first use and on refresh; enumerating them is slow
+    displays: Option<Vec<Display>>,
+    // Name of the chosen display; None is the first non-primary one
+    chosen: Option<String>,
+}
+
+fn list_displays() -> Vec<Display> {
+    xcap::Monitor::all()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| Display {
+            name: m.name().to_string(),
+            x: m.x(),
+            y: m.y(),
+            scale: m.scale_factor().max(0.5),
+            primary: m.is_primary(),
+        })
+        .collect()
+}
+
+impl PresentationState {
+    fn target(&self) -> Option<&Display> {
+        let displays = self.displays.as_ref()?;
+        match &self.chosen {
+            Some(name) => displays.iter().find(|d| &d.name == name),
+            None => displays.iter().find(|d| !d.primary).or_else(|| displays.first()),
+        }
+    }
+}
+
+impl AvalancheClassifier {
+    // Display picker and the button that starts or stops presenting
+    pub(crate) fn presentation_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let state = &mut self.presentation;
+        let displays = state.displays.get_or_insert_with(list_displays);
+        ui.horizontal(|ui| {
+            let label = if state.open { "⏹ Stop Presenting" } else { "📽 Present" };
+            if ui
+                .button(label)
+                .on_hover_text("Shows the photo and key findings full-screen on another display, e.g. a projector")
+                .clicked()
+            {
+                state.open = !state.open;
+            }
+            let selected = match &state.chosen {
+                Some(name) => name.clone(),
+                None => "Auto".to_string(),
+            };
+            egui::ComboBox::from_id_source("presentation_display")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut state.chosen, None, "Auto (secondary display)");
+                    for display in displays.iter() {
+                        ui.selectable_value(&mut state.chosen, Some(display.name.clone()), display.label());
+                    }
+                });
+            if ui.small_button("⟳").on_hover_text("Look for displays again").clicked() {
+                *displays = list_displays();
+            }
+            if displays.is_empty() {
+                ui.label(egui::RichText::new("No displays found; presenting in a window").size(13.0).color(muted_color));
+            }
+        });
+    }
+
+    // The presentation itself, in its own full-screen window on the chosen
+    // display. Follows whatever the main window shows, so the operator
+    // drives it from there. Esc or closing the window ends it.
+    pub(crate) fn presentation_window(&mut self, ctx: &egui::Context) {
+        if !self.presentation.open {
+            return;
+        }
+        let target = self.presentation.target();
+        let mut builder = egui::ViewportBuilder::default()
+            .with_title("Avalanche Classifier — Presentation")
+            .with_inner_size([1280.0, 720.0]);
+        if let Some(display) = target {
+            // Placed on the display first, so full screen picks that one
+            builder = builder
+                .with_position([display.x as f32 / display.scale + 40.0, display.y as f32 / display.scale + 40.0])
+                .with_fullscreen(true)
+                .with_decorations(false);
+        }
+        // One window per display, so choosing another one moves it there
+        let id = egui::ViewportId::from_hash_of(("presentation", target.map(|d| d.name.clone())));
+
+        let closed = ctx.show_viewport_immediate(id, builder, |ctx, class| {
+            let close = ctx.input(|i| i.key_pressed(egui::Key::Escape) || i.viewport().close_requested());
+            if class == egui::ViewportClass::Embedded {
+                // No separate windows on this platform
+                let mut open = true;
+                egui::Window::new("Presentation")
+                    .open(&mut open)
+                    .default_size([960.0, 540.0])
+                    .show(ctx, |ui| self.presentation_contents(ui));
+                return close || !open;
+            }
+            egui::CentralPanel::default().show(ctx, |ui| self.presentation_contents(ui));
+            close
+        });
+        if closed {
+            self.presentation.open = false;
+        }
+    }
+
+    fn presentation_contents(&self, ui: &mut egui::Ui) {
+        let palette = Palette::of(ui.ctx());
+        let Some(image_data) = &self.image_data else {
+            ui.centered_and_justified(|ui| {
+                ui.label(egui::RichText::new("No photo loaded").size(32.0).color(palette.muted));
+            });
+            return;
+        };
+        let findings_width = (ui.available_width() * 0.32).max(320.0);
+        ui.horizontal_top(|ui| {
+            let photo_area = egui::vec2(ui.available_width() - findings_width - 24.0, ui.available_height());
+            let flat = image_data.view.flat_light;
+            let texture = if flat {
+                image_data.flat_full_texture.as_ref().or(image_data.flat_texture.as_ref())
+            } else {
+                None
+            }
+            .or(image_data.full_texture.as_ref())
+            .or(image_data.texture.as_ref());
+            if let Some(texture) = texture {
+                let size = texture.size_vec2();
+                let scale = (photo_area.x / size.x).min(photo_area.y / size.y);
+                let (rect, _) = ui.allocate_exact_size(size * scale, egui::Sense::hover());
+                let painter = ui.painter_at(rect);
+                let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                painter.image(texture.id(), rect, uv, egui::Color32::WHITE);
+                if let Some(overlay) = image_data.overlay.as_ref().filter(|_| image_data.view.show_overlay) {
+                    preview::draw_overlay(&painter, overlay, |x, y| rect.min + egui::vec2(x, y) * rect.size());
+                }
+            }
+            ui.add_space(24.0);
+            ui.vertical(|ui| {
+                ui.set_width(findings_width);
+                self.presentation_findings(ui, palette);
+            });
+        });
+    }
+
+    fn presentation_findings(&self, ui: &mut egui::Ui, palette: Palette) {
+        let Some(result) = &self.result else {
+            let text = if self.promise.is_some() { "Analyzing…" } else { "Not analyzed yet" };
+            ui.label(egui::RichText::new(text).size(32.0).color(palette.muted));
+            return;
+        };
+        let color = crate::type_color(&result.avalanche_type, palette, palette.muted);
+        ui.label(egui::RichText::new(crate::type_title(&result.avalanche_type)).size(44.0).strong().color(color));
+        ui.label(egui::RichText::new(format!("{:.0}% confidence", result.confidence_level)).size(28.0));
+        ui.add_space(16.0);
+
+        let entry = self
+            .snapshot
+            .as_ref()
+            .and_then(|snapshot| self.history.entries.iter().find(|e| e.snapshot.id == snapshot.id));
+        if let Some(entry) = entry {
+            let mut details = Vec::new();
+            if let Some(camera) = &entry.source {
+                details.push(camera.clone());
+            }
+            match entry.captured_at {
+                Some(taken) => details.push(format!("taken {}", monitor::format_date_time(taken.max(0) as u64))),
+                None => details.push(format!("analyzed {}", monitor::format_date_time(entry.snapshot.created_at))),
+            }
+            if let Some(location) = &entry.location {
+                details.push(format!("{:.4}, {:.4}", location.latitude, location.longitude));
+            }
+            ui.label(egui::RichText::new(details.join(" · ")).size(20.0).color(palette.muted));
+            ui.add_space(16.0);
+        }
+
+        let chars = &result.visual_characteristics;
+        let mut findings = Vec::new();
+        if chars.fracture_line {
+            findings.push(match &chars.fracture_depth {
+                Some(depth) => format!("Fracture line ({})", depth.label()),
+                None => "Fracture line".to_string(),
+            });
+        }
+        if chars.powder_cloud {
+            findings.push("Powder cloud".to_string());
+        }
+        if chars.point_release {
+            findings.push("Point release".to_string());
+        }
+        if !chars.debris_pattern.is_empty() && chars.debris_pattern != "none" {
+            findings.push(format!("Debris: {}", chars.debris_pattern));
+        }
+        if let Some(angle) = &chars.terrain.slope_angle {
+            findings.push(format!("Slope: {}", angle));
+        }
+        findings.extend(result.terrain_features.iter().take(4).cloned());
+        for finding in findings {
+            ui.label(egui::RichText::new(format!("• {}", finding)).size(24.0));
+        }
+        ui.add_space(16.0);
+        scores::type_scores_ui(ui, result, palette);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{photo_meta, ImageData};
+use avalanche_classifier_core::{camera::Roi, Annotations};
+use eframe::egui;
+
+// Longest side the preview is drawn at
+const VIEW_PX: f32 = 400.0;
+// Closest zoom, in screen pixels per photo pixel
+const MAX_PIXEL_SCALE: f32 = 4.0;
+pub(crate) const FRACTURE_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 59, 48);
+pub(crate) const START_ZONE_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 149, 0);
+pub(crate) const DEBRIS_COLOR: egui::Color32 = egui::Color32::from_rgb(175, 82, 222);
+
+// Zoom and pan of the photo preview
+pub(crate) struct PreviewView {
+    // 1.0 shows the whole photo
+    pub(crate) zoom: f32,
+    // Middle of the view, as fractions of the photo
+    center: egui::Pos2,
+    // Pointer was over the preview last frame; the page stops scrolling so
+    // the wheel zooms instead
+    pub(crate) hovered: bool,
+    pub(crate) show_overlay: bool,
+    // Draw the photo through the flat light filter
+    pub(crate) flat_light: bool,
+}
+
+impl Default for PreviewView {
+    fn default() -> Self {
+        Self { zoom: 1.0, center: egui::pos2(0.5, 0.5), hovered: false, show_overlay: true, flat_light: false }
+    }
+}
+
+impl PreviewView {
+    // Part of the photo in view, as fractions
+    fn visible(&self) -> egui::Rect {
+        egui::Rect::from_center_size(self.center, egui::Vec2::splat(1.0 / self.zoom))
+    }
+
+    // Photo position under a point given as fractions of the preview
+    fn to_photo(&self, in_view: egui::Pos2) -> egui::Pos2 {
+        let visible = self.visible();
+        visible.min + in_view.to_vec2() * visible.size()
+    }
+
+    // Keeps the view inside the photo
+    fn clamp(&mut self) {
+        let half = 0.5 / self.zoom;
+        self.center.x = self.center.x.clamp(half, 1.0 - half);
+        self.center.y = self.center.y.clamp(half, 1.0 - half);
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.zoom = 1.0;
+        self.center = egui::pos2(0.5, 0.5);
+    }
+}
+
+// Draws the photo with zoom (scroll or pinch) and pan (right or middle
+// drag) and the result's annotations on top, and lets a left drag mark the
+// region to analyze, kept as fractions of the upright photo. Everything
+// outside the region is dimmed.
+pub(crate) fn photo_preview(ui: &mut egui::Ui, image_data: &mut ImageData) {
+    let Some(preview) = image_data.texture.clone() else {
+        return;
+    };
+    let size = preview.size_vec2();
+    let scale = (VIEW_PX / size.x).min(VIEW_PX / size.y);
+    let (response, painter) = ui.allocate_painter(size * scale, egui::Sense::drag());
+    let rect = response.rect;
+    let in_view = |pos: egui::Pos2| ((pos - rect.min) / rect.size()).to_pos2();
+    let view = &mut image_data.view;
+    view.hovered = response.hovered();
+
+    if let Some(pointer) = response.hover_pos() {
+        let (scroll, pinch) = ui.input(|i| (i.scroll_delta.y, i.zoom_delta()));
+        let factor = pinch * (scroll / 200.0).exp();
+        if factor != 1.0 {
+            let max_zoom = (image_data.photo_size.x / rect.width()).max(1.0) * MAX_PIXEL_SCALE;
+            // Zoom around the pointer: the photo point under it stays put
+            let anchor = view.to_photo(in_view(pointer));
+            view.zoom = (view.zoom * factor).clamp(1.0, max_zoom);
+            view.center = anchor - (in_view(pointer) - egui::pos2(0.5, 0.5)) / view.zoom;
+            view.clamp();
+        }
+    }
+    if response.dragged_by(egui::PointerButton::Secondary) || response.dragged_by(egui::PointerButton::Middle) {
+        view.center -= response.drag_delta() / rect.size() / view.zoom;
+        view.clamp();
+    }
+
+    // Past 1:1 with the preview texture, the full-resolution photo is drawn
+    // instead so fine detail like a fracture line can be checked. Loaded on
+    // first use; a failed load falls back to the preview for good.
+    let magnified = view.zoom * rect.width() > size.x && image_data.photo_size.x > size.x;
+    let flat = view.flat_light;
+    let preview = if flat {
+        let preview_side = size.x.max(size.y) as u32;
+        image_data
+            .flat_texture
+            .get_or_insert_with(|| {
+                photo_texture(ui.ctx(), &image_data.bytes, "uploaded-image-flat", preview_side, true)
+                    .unwrap_or_else(|| preview.clone())
+            })
+            .clone()
+    } else {
+        preview
+    };
+    let (full, name) = if flat {
+        (&mut image_data.flat_full_texture, "uploaded-image-flat-full")
+    } else {
+        (&mut image_data.full_texture, "uploaded-image-full")
+    };
+    if magnified && full.is_none() {
+        *full = Some(photo_texture(ui.ctx(), &image_data.bytes, name, u32::MAX, flat).unwrap_or_else(|| preview.clone()));
+    }
+    let texture = full.as_ref().filter(|_| magnified).unwrap_or(&preview);
+    let visible = view.visible();
+    painter.image(texture.id(), rect, visible, egui::Color32::WHITE);
+
+    let to_photo = |pos: egui::Pos2| {
+        let point = view.to_photo(in_view(pos));
+        (point.x.clamp(0.0, 1.0), point.y.clamp(0.0, 1.0))
+    };
+    if let Some(pos) = response.interact_pointer_pos() {
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            image_data.crop_start = Some(to_photo(pos));
+        }
+        if let (true, Some((x0, y0))) = (response.dragged_by(egui::PointerButton::Primary), image_data.crop_start) {
+            let (x1, y1) = to_photo(pos);
+            // A click or a sliver keeps the previous region
+            if (x1 - x0).abs() > 0.02 / view.zoom && (y1 - y0).abs() > 0.02 / view.zoom {
+                image_data.crop =
+                    Some(Roi { x: x0.min(x1), y: y0.min(y1), width: (x1 - x0).abs(), height: (y1 - y0).abs() });
+            }
+        }
+    }
+    if response.drag_released() {
+        image_data.crop_start = None;
+    }
+
+    let to_screen = |x: f32, y: f32| rect.min + ((egui::pos2(x, y) - visible.min) / visible.size()) * rect.size();
+    if let Some(overlay) = image_data.overlay.as_ref().filter(|_| view.show_overlay) {
+        draw_overlay(&painter, overlay, to_screen);
+    }
+    if let Some(roi) = &image_data.crop {
+        let marked = egui::Rect::from_min_max(to_screen(roi.x, roi.y), to_screen(roi.x + roi.width, roi.y + roi.height));
+        let shade = egui::Color32::from_black_alpha(140);
+        let shown = marked.intersect(rect);
+        if shown.is_positive() {
+            let outside = [
+                egui::Rect::from_min_max(rect.min, egui::pos2(rect.right(), shown.top())),
+                egui::Rect::from_min_max(egui::pos2(rect.left(), shown.bottom()), rect.max),
+                egui::Rect::from_min_max(egui::pos2(rect.left(), shown.top()), egui::pos2(shown.left(), shown.bottom())),
+                egui::Rect::from_min_max(egui::pos2(shown.right(), shown.top()), egui::pos2(rect.right(), shown.bottom())),
+            ];
+            for part in outside {
+                painter.rect_filled(part, 0.0, shade);
+            }
+            painter.rect_stroke(marked, 0.0, egui::Stroke::new(2.0, egui::Color32::from_rgb(0, 122, 255)));
+        } else {
+            painter.rect_filled(rect, 0.0, shade);
+        }
+    }
+}
+
+pub(crate) fn draw_overlay(painter: &egui::Painter, overlay: &Annotations, to_screen: impl Fn(f32, f32) -> egui::Pos2) {
+    let label = |pos: egui::Pos2, text: &str, color: egui::Color32| {
+        painter.text(pos, egui::Align2::LEFT_BOTTOM, text, egui::FontId::proportional(12.0), color);
+    };
+    if let Some(zone) = &overlay.start_zone {
+        let min = to_screen(zone.x, zone.y);
+        painter.rect_stroke(
+            egui::Rect::from_min_max(min, to_screen(zone.x + zone.width, zone.y + zone.height)),
+            0.0,
+            egui::Stroke::new(2.0, START_ZONE_COLOR),
+        );
+        label(min, "Start zone", START_ZONE_COLOR);
+    }
+    if let Some(polygon) = &overlay.debris {
+        let points: Vec<egui::Pos2> = polygon.iter().map(|p| to_screen(p.x, p.y)).collect();
+        if let Some(&first) = points.first() {
+            label(first, "Debris", DEBRIS_COLOR);
+        }
+        painter.add(egui::Shape::closed_line(points, egui::Stroke::new(2.0, DEBRIS_COLOR)));
+    }
+    if let Some(line) = &overlay.fracture_line {
+        let points: Vec<egui::Pos2> = line.iter().map(|p| to_screen(p.x, p.y)).collect();
+        if let Some(&first) = points.first() {
+            label(first, "Fracture line", FRACTURE_COLOR);
+        }
+        painter.add(egui::Shape::line(points, egui::Stroke::new(3.0, FRACTURE_COLOR)));
+    }
+}
+
+// The upright photo at up to max_side px, or as close as the graphics
+// driver allows, optionally through the flat light filter
+fn photo_texture(
+    ctx: &egui::Context,
+    bytes: &[u8],
+    name: &str,
+    max_side: u32,
+    flat: bool,
+) -> Option<egui::TextureHandle> {
+    let image = photo_meta::load_upright(bytes).ok()?;
+    let max_side = u32::try_from(ctx.input(|i| i.max_texture_side)).unwrap_or(u32::MAX).min(max_side);
+    let image = if image.width().max(image.height()) > max_side {
+        image.thumbnail(max_side, max_side)
+    } else {
+        image
+    };
+    let rgba = if flat { flat_light(&image.to_rgba8()) } else { image.to_rgba8() };
+    let size = [rgba.width() as usize, rgba.height() as usize];
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
+    Some(ctx.load_texture(name, color_image, egui::TextureOptions::LINEAR))
+}
+
+// Share of the darkest and brightest pixels clipped by the stretch
+const CLIP: f32 = 0.01;
+// How much local detail is added back on top of the stretched brightness
+const DETAIL_GAIN: f32 = 1.5;
+
+// "Flat light" view for overcast shots, where snow sits in a narrow band of
+// near-white and surface texture disappears: stretches the brightness range
+// and then exaggerates local contrast, so wind texture, crowns and fracture
+// lines stand out. A human check on what the model could plausibly see;
+// the model always gets the photo as it is.
+pub(crate) fn flat_light(image: &image::RgbaImage) -> image::RgbaImage {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let luma: Vec<f32> = image
+        .pixels()
+        .map(|p| 0.299 * f32::from(p[0]) + 0.587 * f32::from(p[1]) + 0.114 * f32::from(p[2]))
+        .collect();
+
+    let mut histogram = [0usize; 256];
+    for &l in &luma {
+        histogram[l as usize] += 1;
+    }
+    let percentile = |share: f32| {
+        let target = (share * luma.len() as f32) as usize;
+        let mut seen = 0;
+        histogram.iter().position(|&count| {
+            seen += count;
+            seen > target
+        })
+    };
+    let low = percentile(CLIP).unwrap_or(0) as f32;
+    let high = (percentile(1.0 - CLIP).unwrap_or(255) as f32).max(low + 1.0);
+    let stretched: Vec<f32> = luma.iter().map(|&l| ((l - low) / (high - low)).clamp(0.0, 1.0)).collect();
+
+    // Detail is measured against a blur about 1/150 of the photo across,
+    // about the size of a slab's crown in a typical slope shot
+    let radius = (width.max(height) / 150).max(2);
+    let mut blurred = stretched.clone();
+    for _ in 0..3 {
+        box_blur(&mut blurred, width, height, radius);
+    }
+
+    let mut out = image.clone();
+    for (i, pixel) in out.pixels_mut().enumerate() {
+        let enhanced = (stretched[i] + DETAIL_GAIN * (stretched[i] - blurred[i])).clamp(0.0, 1.0);
+        // Shifted rather than scaled, so near-white snow keeps its tint
+        let shift = enhanced * 255.0 - luma[i];
+        for channel in &mut pixel.0[..3] {
+            *channel = (f32::from(*channel) + shift).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    out
+}
+
+// In-place box blur, rows then columns; three passes come close to a
+// Gaussian at a cost independent of the radius
+fn box_blur(values: &mut [f32], width: usize, height: usize, radius: usize) {
+    let mut line = Vec::new();
+    for y in 0..height {
+        line.clear();
+        line.extend((0..width).map(|x| values[y * width + x]));
+        for (x, value) in blur_line(&line, radius).into_iter().enumerate() {
+            values[y * width + x] = value;
+        }
+    }
+    for x in 0..width {
+        line.clear();
+        line.extend((0..height).map(|y| values[y * width + x]));
+        for (y, value) in blur_line(&line, radius).into_iter().enumerate() {
+            values[y * width + x] = value;
+        }
+    }
+}
+
+// Running mean over a window of 2 × radius + 1, repeating the edge values
+fn blur_line(line: &[f32], radius: usize) -> Vec<f32> {
+    let len = line.len();
+    let at = |i: isize| line[i.clamp(0, len as isize - 1) as usize];
+    let window = (2 * radius + 1) as f32;
+    let r = radius as isize;
+    let mut sum: f32 = (-r..=r).map(at).sum();
+    let mut out = Vec::with_capacity(len);
+    for i in 0..len as isize {
+        out.push(sum / window);
+        sum += at(i + r + 1) - at(i - r);
+    }
+    out
+}
@@ -0,0 +1,287 @@
+use crate::{runtime, AvalancheClassifier};
+use avalanche_classifier_core::{
+    canary::{self, CanaryCase, Outcome},
+    provider::ANALYSIS_PROMPT,
+    textdiff,
+};
+use eframe::egui;
+use poll_promise::Promise;
+
+#[derive(Default)]
+pub struct PromptState {
+    // Prompt being edited; analyses keep the saved one until it is made the
+    // default. Filled from the settings when the editor is first shown.
+    draft: Option<String>,
+    show_diff: bool,
+    // Running canary and the prompt it checks
+    canary: Option<(String, Promise<Vec<CanaryCase>>)>,
+    // Last canary run and the prompt it checked
+    canary_result: Option<(String, Vec<CanaryCase>)>,
+    // Make Default was pressed for a prompt the canary has not passed
+    confirming: bool,
+    // Make the draft the default once a pending canary passes
+    apply_on_pass: bool,
+    message: Option<String>,
+}
+
+impl AvalancheClassifier {
+    // Editor for the analysis prompt, with a diff against the built-in one so
+    // it is clear what was changed and each change can be reset on its own.
+    // An edited prompt is checked against the bundled reference photos before
+    // it replaces the one analyses use.
+    pub(crate) fn prompt_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        added_color: egui::Color32,
+        removed_color: egui::Color32,
+        muted_color: egui::Color32,
+    ) {
+        egui::CollapsingHeader::new("Prompt Template")
+            .default_open(false)
+            .show(ui, |ui| {
+                let active = self.settings.custom_prompt.as_deref().unwrap_or(ANALYSIS_PROMPT).to_string();
+                let state = &mut self.prompt;
+                let draft = state.draft.get_or_insert_with(|| active.clone());
+                ui.label(
+                    egui::RichText::new(if self.settings.custom_prompt.is_some() {
+                        "Analyses use a custom prompt"
+                    } else {
+                        "Analyses use the built-in prompt"
+                    })
+                    .size(13.0)
+                    .color(muted_color),
+                );
+
+                egui::ScrollArea::vertical()
+                    .id_source("prompt_editor")
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        ui.add(egui::TextEdit::multiline(draft).code_editor().desired_width(f32::INFINITY));
+                    });
+
+                let lines = textdiff::diff_lines(ANALYSIS_PROMPT, draft);
+                let changes = textdiff::hunk_count(&lines);
+                let mut reset_all = false;
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(match changes {
+                            0 => "Same as the built-in prompt".to_string(),
+                            1 => "1 change from the built-in prompt".to_string(),
+                            n => format!("{} changes from the built-in prompt", n),
+                        })
+                        .size(13.0)
+                        .color(muted_color),
+                    );
+                    if changes > 0 {
+                        ui.toggle_value(&mut state.show_diff, "Show Changes");
+                        reset_all = ui.button("Reset All").clicked();
+                    }
+                });
+                if draft.trim().is_empty() {
+                    ui.label(
+                        egui::RichText::new("An empty prompt falls back to the built-in one")
+                            .size(13.0)
+                            .color(muted_color),
+                    );
+                }
+
+                let mut reverted = None;
+                if state.show_diff && changes > 0 {
+                    let hunks = textdiff::hunk_ids(&lines);
+                    egui::ScrollArea::vertical()
+                        .id_source("prompt_diff")
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            for (index, (line, hunk)) in lines.iter().zip(&hunks).enumerate() {
+                                let (text, color) = match line {
+                                    textdiff::Line::Same(text) => (format!("  {}", text), muted_color),
+                                    textdiff::Line::Removed(text) => (format!("- {}", text), removed_color),
+                                    textdiff::Line::Added(text) => (format!("+ {}", text), added_color),
+                                };
+                                // The reset button sits on the first line of each change
+                                let starts_hunk = hunk.is_some() && (index == 0 || hunks[index - 1] != *hunk);
+                                ui.horizontal(|ui| {
+                                    ui.label(egui::RichText::new(text).monospace().size(12.0).color(color));
+                                    if let (true, Some(hunk)) = (starts_hunk, hunk) {
+                                        if ui.small_button("↺ Reset this change").clicked() {
+                                            reverted = Some(*hunk);
+                                        }
+                                    }
+                                });
+                            }
+                        });
+                }
+
+                if let Some(hunk) = reverted {
+                    *draft = textdiff::revert_hunk(&lines, hunk);
+                }
+                if reset_all {
+                    *draft = ANALYSIS_PROMPT.to_string();
+                }
+
+                let draft = draft.clone();
+                let builtin = draft.trim().is_empty() || draft == ANALYSIS_PROMPT;
+                let checked = state
+                    .canary_result
+                    .as_ref()
+                    .filter(|(prompt, _)| *prompt == draft)
+                    .map(|(_, cases)| cases.iter().filter(|c| c.is_regression()).count());
+                let mut run_canary = false;
+                let mut make_default = false;
+                let mut use_builtin = false;
+                ui.horizontal(|ui| {
+                    run_canary = ui
+                        .add_enabled(state.canary.is_none() && !builtin, egui::Button::new("🧪 Run Canary"))
+                        .on_hover_text(format!(
+                            "Classify the {} bundled reference photos with this prompt and the built-in one",
+                            canary::REFERENCE_SET.len()
+                        ))
+                        .clicked();
+                    make_default = ui
+                        .add_enabled(state.canary.is_none() && draft != active, egui::Button::new("✔ Make Default"))
+                        .clicked();
+                    use_builtin = ui
+                        .add_enabled(self.settings.custom_prompt.is_some(), egui::Button::new("Use Built-in"))
+                        .clicked();
+                });
+
+                if make_default {
+                    // The built-in prompt needs no check, nor does one that
+                    // already passed
+                    if builtin || checked == Some(0) {
+                        self.settings.custom_prompt = (!builtin).then(|| draft.clone());
+                        state.message = Some("Analyses now use this prompt".to_string());
+                    } else {
+                        state.confirming = true;
+                    }
+                }
+                if use_builtin {
+                    self.settings.custom_prompt = None;
+                    state.draft = Some(ANALYSIS_PROMPT.to_string());
+                    state.confirming = false;
+                    state.message = Some("Analyses now use the built-in prompt".to_string());
+                }
+
+                if state.confirming && state.canary.is_none() {
+                    let question = match checked {
+                        None => format!(
+                            "Check this prompt against the {} reference photos first? It sends {} requests with the current backend.",
+                            canary::REFERENCE_SET.len(),
+                            canary::REFERENCE_SET.len() * 2
+                        ),
+                        Some(regressions) => format!(
+                            "This prompt got {} reference photos wrong that the built-in prompt gets right. Use it anyway?",
+                            regressions
+                        ),
+                    };
+                    ui.label(egui::RichText::new(question).size(13.0).color(removed_color));
+                    ui.horizontal(|ui| {
+                        if checked.is_none() && ui.button("🧪 Run Canary").clicked() {
+                            run_canary = true;
+                            state.apply_on_pass = true;
+                        }
+                        if ui.button(if checked.is_none() { "Use Without Checking" } else { "Use Anyway" }).clicked() {
+                            self.settings.custom_prompt = Some(draft.clone());
+                            state.confirming = false;
+                            state.message = Some("Analyses now use this prompt".to_string());
+                        }
+                        if ui.button("Cancel").clicked() {
+                            state.confirming = false;
+                        }
+                    });
+                }
+
+                if run_canary {
+                    let provider = self.provider();
+                    let prompt = draft.clone();
+                    self.prompt.message = None;
+                    self.prompt.canary = Some((
+                        draft.clone(),
+                        runtime::spawn(async move {
+                            let _slot = runtime::analysis_slot(runtime::Priority::Interactive).await;
+                            canary::run(provider.as_ref(), &prompt).await
+                        }),
+                    ));
+                }
+
+                let state = &mut self.prompt;
+                if let Some((prompt, promise)) = state.canary.take() {
+                    match promise.try_take() {
+                        Ok(cases) => {
+                            let regressions = cases.iter().filter(|c| c.is_regression()).count();
+                            // Edits made while it ran are not what was checked
+                            if state.apply_on_pass && regressions == 0 && prompt == draft {
+                                self.settings.custom_prompt = Some(draft.clone());
+                                state.confirming = false;
+                                state.message = Some("No regressions; analyses now use this prompt".to_string());
+                            }
+                            state.apply_on_pass = false;
+                            state.canary_result = Some((prompt, cases));
+                        }
+                        Err(promise) => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Running the reference photos...");
+                            });
+                            state.canary = Some((prompt, promise));
+                        }
+                    }
+                }
+
+                if let Some((prompt, cases)) = &state.canary_result {
+                    let stale = *prompt != draft;
+                    canary_results_ui(ui, cases, stale, added_color, removed_color, muted_color);
+                }
+                if let Some(message) = &state.message {
+                    ui.label(egui::RichText::new(message).size(13.0));
+                }
+            });
+    }
+}
+
+fn canary_results_ui(
+    ui: &mut egui::Ui,
+    cases: &[CanaryCase],
+    stale: bool,
+    pass_color: egui::Color32,
+    fail_color: egui::Color32,
+    muted_color: egui::Color32,
+) {
+    let regressions = cases.iter().filter(|c| c.is_regression()).count();
+    let summary = match regressions {
+        0 => "Canary passed: no regressions against the built-in prompt".to_string(),
+        1 => "Canary found 1 regression against the built-in prompt".to_string(),
+        n => format!("Canary found {} regressions against the built-in prompt", n),
+    };
+    ui.label(
+        egui::RichText::new(summary)
+            .size(13.0)
+            .color(if regressions == 0 { pass_color } else { fail_color }),
+    );
+    if stale {
+        ui.label(
+            egui::RichText::new("The prompt was edited since; run the canary again")
+                .size(13.0)
+                .color(muted_color),
+        );
+    }
+    let outcome = |outcome: &Outcome| match outcome {
+        Outcome::Match => egui::RichText::new("✔").color(pass_color),
+        Outcome::Mismatch(said) => egui::RichText::new(format!("✖ {}", said)).color(fail_color),
+        Outcome::Failed(err) => egui::RichText::new(format!("✖ {}", err)).color(fail_color),
+    };
+    egui::Grid::new("canary_results").striped(true).show(ui, |ui| {
+        ui.label(egui::RichText::new("Reference").strong());
+        ui.label(egui::RichText::new("Expected").strong());
+        ui.label(egui::RichText::new("Built-in").strong());
+        ui.label(egui::RichText::new("Custom").strong());
+        ui.end_row();
+        for case in cases {
+            ui.label(case.name);
+            ui.label(case.expected);
+            ui.label(outcome(&case.builtin).size(13.0));
+            ui.label(outcome(&case.custom).size(13.0));
+            ui.end_row();
+        }
+    });
+}
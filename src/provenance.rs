@@ -0,0 +1,93 @@
+use avalanche_classifier_core::fallback::FailedAttempt;
+use avalanche_classifier_core::history::HistoryEntry;
+use avalanche_classifier_core::{models, snapshot::AnalysisSnapshot};
+use eframe::egui;
+
+fn attempts_text(attempts: &[FailedAttempt]) -> String {
+    attempts.iter().map(|attempt| format!("• {}", attempt.summary())).collect::<Vec<_>>().join("\n")
+}
+
+// How a result was produced: backend, model, prompt, what was done to the
+// photo, and any retries or fallbacks before this answer
+pub(crate) fn provenance_ui(
+    ui: &mut egui::Ui,
+    snapshot: &AnalysisSnapshot,
+    entry: Option<&HistoryEntry>,
+    warning_color: egui::Color32,
+    muted_color: egui::Color32,
+) {
+    let small = |text: String| egui::RichText::new(text).size(13.0);
+    let not_recorded = || small("not recorded".to_string()).color(muted_color);
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.label(small("Provenance".to_string()).strong());
+        egui::Grid::new("provenance").num_columns(2).spacing(egui::vec2(12.0, 2.0)).show(ui, |ui| {
+            ui.label(small("Backend".to_string()).color(muted_color));
+            ui.label(small(snapshot.backend.clone()));
+            ui.end_row();
+
+            ui.label(small("Model".to_string()).color(muted_color));
+            match models::lookup(&snapshot.model) {
+                Some(info) => ui.label(small(info.label.to_string())).on_hover_text(&snapshot.model),
+                None => ui.label(small(snapshot.model.clone())),
+            };
+            ui.end_row();
+
+            let provenance = snapshot.provenance.as_ref();
+            ui.label(small("Prompt".to_string()).color(muted_color));
+            match provenance {
+                Some(p) => {
+                    let mut text = p.prompt.clone();
+                    if p.site_context {
+                        text.push_str(" + site context");
+                    }
+                    if p.field_observations {
+                        text.push_str(" + field observations");
+                    }
+                    if p.weather {
+                        text.push_str(" + weather");
+                    }
+                    ui.label(small(text)).on_hover_text(format!("Prompt blob {}", &snapshot.prompt_hash[..12]))
+                }
+                None => ui.label(not_recorded()),
+            };
+            ui.end_row();
+
+            ui.label(small("Preprocessing".to_string()).color(muted_color));
+            match provenance {
+                Some(p) => ui.label(small(p.preprocessing.clone())),
+                None => ui.label(not_recorded()),
+            };
+            ui.end_row();
+
+            let retries = entry.map_or(&[][..], |e| &e.retries[..]);
+            ui.label(small("Retries".to_string()).color(muted_color));
+            if retries.is_empty() {
+                ui.label(small("none".to_string()));
+            } else {
+                ui.label(small(format!("{} unusable answer(s) before this one", retries.len())).color(warning_color))
+                    .on_hover_text(attempts_text(retries));
+            }
+            ui.end_row();
+
+            let conflicts = provenance.map_or(&[][..], |p| &p.conflicts[..]);
+            if !conflicts.is_empty() {
+                ui.label(small("Re-asked".to_string()).color(muted_color));
+                let text: Vec<String> = conflicts.iter().map(|conflict| format!("• {}", conflict)).collect();
+                ui.label(small(format!("{} time(s) after a type conflict", conflicts.len())).color(warning_color))
+                    .on_hover_text(text.join("\n"));
+                ui.end_row();
+            }
+
+            let fallbacks = entry.map_or(&[][..], |e| &e.fallbacks[..]);
+            ui.label(small("Fallback".to_string()).color(muted_color));
+            if fallbacks.is_empty() {
+                ui.label(small("none".to_string()));
+            } else {
+                let failed: Vec<&str> = fallbacks.iter().map(|a| a.backend.as_str()).collect();
+                ui.label(small(format!("↪ after {} failed", failed.join(", "))).color(warning_color))
+                    .on_hover_text(attempts_text(fallbacks));
+            }
+            ui.end_row();
+        });
+    });
+}
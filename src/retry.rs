@@ -0,0 +1,123 @@
+use crate::{runtime, AvalancheClassifier};
+use avalanche_classifier_core::fallback::FailedAttempt;
+use avalanche_classifier_core::{classify_image, models, AnalysisError, Backend};
+use eframe::egui;
+
+#[derive(Default)]
+pub struct RetryState {
+    // The image the last single analysis sent, after cropping, so a retry
+    // analyzes exactly the same picture
+    image_bytes: Option<Vec<u8>>,
+    // Backends and models already tried on it, first the one from settings
+    tried: Vec<(Backend, String)>,
+    // Why each of them failed, kept with the result of a successful retry
+    pub(crate) failures: Vec<FailedAttempt>,
+    // The last analysis failed in a way another model might not
+    pub(crate) offer: bool,
+}
+
+impl RetryState {
+    // A fresh analysis from the Analyze button
+    pub(crate) fn record(&mut self, backend: Backend, model: &str, image_bytes: &[u8]) {
+        self.image_bytes = Some(image_bytes.to_vec());
+        self.tried = vec![(backend, model.to_string())];
+        self.failures.clear();
+        self.offer = false;
+    }
+
+    // The model tried last failed with the given error
+    pub(crate) fn failed(&mut self, err: &AnalysisError) {
+        if let Some((backend, model)) = self.tried.last() {
+            self.failures.push(FailedAttempt {
+                backend: backend.id().to_string(),
+                model: model.clone(),
+                error: err.to_string(),
+            });
+        }
+    }
+}
+
+fn model_label(model: &str) -> String {
+    models::lookup(model).map_or_else(|| model.to_string(), |info| info.label.to_string())
+}
+
+impl AvalancheClassifier {
+    // Models not yet tried on this image: each configured backend's model
+    // from settings first, then the other models of the backend that failed
+    fn retry_candidates(&self) -> Vec<(Backend, String)> {
+        let Some((failed_backend, _)) = self.retry.tried.last() else {
+            return Vec::new();
+        };
+        let mut candidates: Vec<(Backend, String)> = self
+            .ensemble_backends()
+            .into_iter()
+            .map(|backend| (backend, self.provider_for(backend).model().to_string()))
+            .collect();
+        if self.is_configured(*failed_backend) {
+            candidates.extend(models::for_backend(*failed_backend).map(|info| (*failed_backend, info.id.to_string())));
+        }
+        let mut seen = self.retry.tried.clone();
+        candidates.retain(|candidate| {
+            let new = !seen.contains(candidate);
+            seen.push(candidate.clone());
+            new
+        });
+        candidates
+    }
+
+    // One-click fallback under a failed analysis
+    pub(crate) fn retry_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        if !self.retry.offer || self.error.is_none() || self.promise.is_some() {
+            return;
+        }
+        let candidates = self.retry_candidates();
+        let Some((first_backend, first_model)) = candidates.first().cloned() else {
+            ui.label(
+                egui::RichText::new("Configure another backend in ⚙ Settings to retry with a different model")
+                    .size(13.0)
+                    .color(muted_color),
+            );
+            return;
+        };
+        let mut chosen = None;
+        ui.horizontal(|ui| {
+            if ui
+                .button(format!("↻ Retry with {}", model_label(&first_model)))
+                .on_hover_text(format!("Sends the same image to {} without changing your settings", first_backend.label()))
+                .clicked()
+            {
+                chosen = Some((first_backend, first_model.clone()));
+            }
+            if candidates.len() > 1 {
+                ui.menu_button("Other models", |ui| {
+                    for (backend, model) in &candidates[1..] {
+                        if ui.button(format!("{} · {}", backend.label(), model_label(model))).clicked() {
+                            chosen = Some((*backend, model.clone()));
+                            ui.close_menu();
+                        }
+                    }
+                });
+            }
+        });
+        if let Some((backend, model)) = chosen {
+            self.start_retry(backend, model);
+        }
+    }
+
+    fn start_retry(&mut self, backend: Backend, model: String) {
+        let Some(image_bytes) = self.retry.image_bytes.clone() else {
+            return;
+        };
+        let provider = self.provider_with_model(backend, model.clone());
+        self.retry.tried.push((backend, model));
+        self.retry.offer = false;
+        self.error = None;
+
+        let (promise, abort) = runtime::spawn_abortable(async move {
+            let _slot = runtime::analysis_slot(runtime::Priority::Interactive).await;
+            classify_image(provider.as_ref(), &image_bytes).await
+        });
+        self.promise = Some(promise);
+        self.analysis_abort = Some(abort);
+    }
+}
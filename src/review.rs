@@ -0,0 +1,158 @@
+use crate::palette::Palette;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::history::{Review, Verdict};
+use avalanche_classifier_core::{photo_meta, snapshot, AVALANCHE_TYPES};
+use eframe::egui;
+
+// Correction targets, bound to number keys 1-9 in that order
+const CORRECTION_TYPES: [&str; 9] = AVALANCHE_TYPES;
+
+#[derive(Default)]
+pub struct ReviewState {
+    pub open: bool,
+    index: usize,
+    correcting: bool,
+    texture: Option<(String, egui::TextureHandle)>,
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn review_window(&mut self, ctx: &egui::Context) {
+        if !self.review.open {
+            return;
+        }
+
+        let queue: Vec<String> = self.history.review_queue().iter().map(|e| e.id().to_string()).collect();
+        if self.review.index >= queue.len() {
+            self.review.index = 0;
+        }
+        let current = queue.get(self.review.index).cloned();
+
+        // Single-key triage, ignored while a text field has focus
+        let mut verdict: Option<(Verdict, Option<String>)> = None;
+        if !ctx.wants_keyboard_input() {
+            ctx.input(|i| {
+                if self.review.correcting {
+                    for (n, key) in [
+                        egui::Key::Num1,
+                        egui::Key::Num2,
+                        egui::Key::Num3,
+                        egui::Key::Num4,
+                        egui::Key::Num5,
+                        egui::Key::Num6,
+                        egui::Key::Num7,
+                        egui::Key::Num8,
+                        egui::Key::Num9,
+                    ]
+                    .into_iter()
+                    .enumerate()
+                    {
+                        if i.key_pressed(key) {
+                            verdict = Some((Verdict::Corrected, Some(CORRECTION_TYPES[n].to_string())));
+                        }
+                    }
+                    if i.key_pressed(egui::Key::Escape) {
+                        self.review.correcting = false;
+                    }
+                    return;
+                }
+                if i.key_pressed(egui::Key::A) {
+                    verdict = Some((Verdict::Accepted, None));
+                }
+                if i.key_pressed(egui::Key::R) {
+                    verdict = Some((Verdict::Rejected, None));
+                }
+                if i.key_pressed(egui::Key::C) {
+                    self.review.correcting = true;
+                }
+                if i.key_pressed(egui::Key::J) || i.key_pressed(egui::Key::ArrowRight) {
+                    self.review.index += 1;
+                }
+                if (i.key_pressed(egui::Key::K) || i.key_pressed(egui::Key::ArrowLeft)) && self.review.index > 0 {
+                    self.review.index -= 1;
+                }
+                if i.key_pressed(egui::Key::Escape) {
+                    self.review.open = false;
+                }
+            });
+        }
+
+        let mut open = self.review.open;
+        egui::Window::new(format!("Review Queue ({})", queue.len()))
+            .open(&mut open)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                let Some(entry) = current
+                    .as_deref()
+                    .and_then(|id| self.history.entries.iter().find(|e| e.id() == id))
+                else {
+                    ui.label("Nothing left to review.");
+                    return;
+                };
+
+                if self.review.texture.as_ref().map(|(id, _)| id.as_str()) != Some(entry.id()) {
+                    self.review.texture = snapshot::BlobStore::open_default()
+                        .and_then(|store| entry.snapshot.image(&store))
+                        .ok()
+                        .and_then(|bytes| photo_meta::load_upright(&bytes).ok())
+                        .map(|image| {
+                            let rgba = image.thumbnail(640, 640).to_rgba8();
+                            let size = [rgba.width() as usize, rgba.height() as usize];
+                            let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &rgba.into_vec());
+                            let texture = ctx.load_texture("review-image", color_image, egui::TextureOptions::LINEAR);
+                            (entry.id().to_string(), texture)
+                        });
+                }
+                if let Some((_, texture)) = &self.review.texture {
+                    ui.add(
+                        egui::Image::new(texture)
+                            .fit_to_exact_size(egui::vec2(420.0, 280.0))
+                            .maintain_aspect_ratio(true)
+                    );
+                }
+
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} · {:.0}% · {}",
+                        entry.analysis.avalanche_type, entry.analysis.confidence_level, entry.snapshot.model
+                    ))
+                    .size(16.0)
+                    .strong()
+                );
+                for flag in &entry.flags {
+                    ui.label(
+                        egui::RichText::new(format!("⚑ {}", flag))
+                            .size(13.0)
+                            .color(Palette::of(ui.ctx()).warning)
+                    );
+                }
+
+                ui.add_space(8.0);
+                let hint = if self.review.correcting {
+                    let targets: Vec<String> =
+                        CORRECTION_TYPES.iter().enumerate().map(|(n, t)| format!("{} {}", n + 1, t)).collect();
+                    format!("Correct to: {} · Esc cancel", targets.join(" · "))
+                } else {
+                    "A accept · C correct · R reject · J/K next/previous · Esc close".to_string()
+                };
+                ui.label(
+                    egui::RichText::new(hint)
+                        .size(13.0)
+                        .color(Palette::of(ui.ctx()).muted)
+                );
+            });
+        self.review.open &= open;
+
+        if let (Some(id), Some((verdict, corrected_type))) = (current, verdict) {
+            let review = Review {
+                verdict,
+                corrected_type,
+                reviewed_at: avalanche_classifier_core::storage::unix_now(),
+            };
+            if let Err(err) = self.history.set_review(&id, review) {
+                self.error = Some(err.to_string());
+            }
+            // The reviewed entry leaves the queue, so the index already points at the next one
+            self.review.correcting = false;
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use poll_promise::Promise;
+use std::collections::BinaryHeap;
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+// Single long-lived runtime for all background work, so analyses don't pay
+// runtime startup each time and share the HTTP connection pool
+pub(crate) fn runtime() -> &'static tokio::runtime::Runtime {
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("avalanche-worker")
+            .build()
+            .expect("failed to start tokio runtime")
+    })
+}
+
+// Runs the future on the shared runtime, handing its output to a promise
+pub(crate) fn spawn<T: Send + 'static>(future: impl Future<Output = T> + Send + 'static) -> Promise<T> {
+    let (sender, promise) = Promise::new();
+    runtime().spawn(async move { sender.send(future.await) });
+    promise
+}
+
+// Like spawn, also returning a handle that cancels the task
+pub(crate) fn spawn_abortable<T: Send + 'static>(
+    future: impl Future<Output = T> + Send + 'static,
+) -> (Promise<T>, tokio::task::AbortHandle) {
+    let (sender, promise) = Promise::new();
+    let handle = runtime().spawn(async move { sender.send(future.await) });
+    (promise, handle.abort_handle())
+}
+
+// Who an analysis is for. Higher classes take free analysis slots first,
+// and one slot is held back for interactive work so a photo analyzed by hand
+// never waits behind webcam frames or a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Priority {
+    Background,   // webcam frames, scheduled jobs
+    Batch,        // the batch queue and re-runs
+    Interactive,  // a single photo the user is waiting on
+}
+
+// Analyses running at once across the whole app
+const ANALYSIS_SLOTS: usize = 8;
+const INTERACTIVE_RESERVE: usize = 1;
+
+fn slot_limit(priority: Priority) -> usize {
+    match priority {
+        Priority::Interactive => ANALYSIS_SLOTS,
+        _ => ANALYSIS_SLOTS - INTERACTIVE_RESERVE,
+    }
+}
+
+struct Waiter {
+    priority: Priority,
+    seq: u64,
+    sender: tokio::sync::oneshot::Sender<AnalysisSlot>,
+}
+
+impl Waiter {
+    // Highest priority first, then first come first served
+    fn key(&self) -> (Priority, std::cmp::Reverse<u64>) {
+        (self.priority, std::cmp::Reverse(self.seq))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+struct Slots {
+    running: usize,
+    next_seq: u64,
+    waiting: BinaryHeap<Waiter>,
+}
+
+impl Slots {
+    // Hands free slots to waiters in priority order
+    fn wake(&mut self) {
+        while self.waiting.peek().is_some_and(|w| self.running < slot_limit(w.priority)) {
+            let Some(waiter) = self.waiting.pop() else {
+                break;
+            };
+            self.running += 1;
+            // The waiter was cancelled; take the slot back without going
+            // through Drop, which would lock again
+            if let Err(slot) = waiter.sender.send(AnalysisSlot(())) {
+                std::mem::forget(slot);
+                self.running -= 1;
+            }
+        }
+    }
+}
+
+static SLOTS: Mutex<Slots> = Mutex::new(Slots { running: 0, next_seq: 0, waiting: BinaryHeap::new() });
+
+fn slots() -> MutexGuard<'static, Slots> {
+    SLOTS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Held for the length of an analysis; dropping it (including when the task
+// is aborted) lets the next waiter start
+pub(crate) struct AnalysisSlot(());
+
+impl Drop for AnalysisSlot {
+    fn drop(&mut self) {
+        let mut slots = slots();
+        slots.running -= 1;
+        slots.wake();
+    }
+}
+
+// Waits for a free analysis slot. Call inside the spawned task, right before
+// the provider requests, and keep the slot until they finish.
+pub(crate) async fn analysis_slot(priority: Priority) -> AnalysisSlot {
+    let receiver = {
+        let mut slots = slots();
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let seq = slots.next_seq;
+        slots.next_seq += 1;
+        slots.waiting.push(Waiter { priority, seq, sender });
+        slots.wake();
+        receiver
+    };
+    // Waiters only leave the heap by being sent a slot
+    receiver.await.expect("analysis slot waiter dropped")
+}
+
+// Analyses waiting for a slot, for status display
+pub(crate) fn queued_analyses() -> usize {
+    slots().waiting.len()
+}
@@ -0,0 +1,238 @@
+use crate::palette::Palette;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::history::HistoryEntry;
+use avalanche_classifier_core::schedule::{self, Cron, Job, JobKind, Schedule};
+use avalanche_classifier_core::{fallback, snapshot, Classification, ClassifyOptions};
+use avalanche_classifier_core::health::HealthTracker;
+use eframe::egui;
+use poll_promise::Promise;
+
+// A job still running after this long is treated as hung and abandoned
+const JOB_TIMEOUT_SECS: u64 = 30 * 60;
+
+struct RunningJob {
+    name: String,
+    started_at: u64,
+    promise: Promise<Vec<anyhow::Result<Classification>>>,
+    abort: tokio::task::AbortHandle,
+}
+
+pub struct SchedulerState {
+    schedule: Schedule,
+    running: Option<RunningJob>,
+    health: HealthTracker,
+    new_name: String,
+    new_cron: String,
+    new_folder: Option<std::path::PathBuf>,
+}
+
+impl Default for SchedulerState {
+    fn default() -> Self {
+        Self {
+            schedule: Schedule::load_default(),
+            running: None,
+            health: HealthTracker::default(),
+            new_name: String::new(),
+            new_cron: "0 7 * * *".to_string(),
+            new_folder: None,
+        }
+    }
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn run_due_jobs(&mut self, ctx: &egui::Context) {
+        if self.scheduler.schedule.jobs.is_empty() {
+            return;
+        }
+        // Wake up at least every 30s so jobs fire while the app sits idle
+        ctx.request_repaint_after(std::time::Duration::from_secs(30));
+
+        let now = avalanche_classifier_core::storage::unix_now();
+        if let Some(running) = &self.scheduler.running {
+            let Some(results) = running.promise.ready() else {
+                if now.saturating_sub(running.started_at) > JOB_TIMEOUT_SECS {
+                    running.abort.abort();
+                    let name = running.name.clone();
+                    self.scheduler.running = None;
+                    self.record_job_failure(&name, now, "Job timed out".to_string());
+                }
+                return;
+            };
+            let name = running.name.clone();
+            let mut batch_ids = Vec::new();
+            let mut last_error = None;
+            for result in results {
+                match result {
+                    Ok(Classification { analysis, snapshot: Some(snapshot), fallbacks }) => {
+                        batch_ids.push(snapshot.id.clone());
+                        let entry = HistoryEntry {
+                            fallbacks: fallbacks.clone(),
+                            ..HistoryEntry::new(snapshot.clone(), analysis.clone())
+                        };
+                        if let Err(err) = self.history.push(entry) {
+                            self.error = Some(err.to_string());
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => last_error = Some(err.to_string()),
+                }
+            }
+            if let Err(err) = self.history.flag_batch(&batch_ids) {
+                self.error = Some(err.to_string());
+            }
+
+            // Only a run where nothing got through counts as a failure
+            match last_error {
+                Some(err) if batch_ids.is_empty() => self.record_job_failure(&name, now, err),
+                _ => self.scheduler.health.record_success(&name, now),
+            }
+            self.scheduler.running = None;
+        }
+
+        if !self.has_api_key() {
+            return;
+        }
+        let health = &self.scheduler.health;
+        let Some(index) = self
+            .scheduler
+            .schedule
+            .jobs
+            .iter()
+            .position(|job| job.is_due(now) && health.can_run(&job.name, now))
+        else {
+            return;
+        };
+
+        let job = &mut self.scheduler.schedule.jobs[index];
+        job.last_run = Some(now);
+        let name = job.name.clone();
+        let kind = job.kind.clone();
+        if let Err(err) = self.scheduler.schedule.save() {
+            self.error = Some(err.to_string());
+        }
+
+        let chain = self.fallback_chain();
+        let known: std::collections::HashSet<String> =
+            self.history.entries.iter().map(|e| e.snapshot.original_hash().to_string()).collect();
+        let (promise, abort) = crate::runtime::spawn_abortable(async move {
+            let mut results = Vec::new();
+            match kind {
+                JobKind::FolderSweep { folder } => {
+                    let images = match schedule::folder_images(&folder) {
+                        Ok(images) => images,
+                        Err(err) => return vec![Err(err)],
+                    };
+                    for path in images {
+                        let bytes = match std::fs::read(&path) {
+                            Ok(bytes) => bytes,
+                            Err(err) => {
+                                results.push(Err(err.into()));
+                                continue;
+                            }
+                        };
+                        if known.contains(&snapshot::hash_bytes(&bytes)) {
+                            continue;
+                        }
+                        let _slot = crate::runtime::analysis_slot(crate::runtime::Priority::Background).await;
+                        results.push(fallback::classify_with_fallback(&chain, &bytes, &ClassifyOptions::current()).await.map_err(Into::into));
+                    }
+                }
+            }
+            results
+        });
+        self.scheduler.running = Some(RunningJob { name, started_at: now, promise, abort });
+    }
+
+    fn record_job_failure(&mut self, name: &str, now: u64, error: String) {
+        if self.scheduler.health.record_failure(name, now, error.clone()) {
+            self.error = Some(format!(
+                "Scheduled job \"{}\" failed {} times in a row: {}",
+                name, self.scheduler.health.escalate_after, error
+            ));
+        }
+    }
+
+    pub(crate) fn scheduler_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let state = &mut self.scheduler;
+        egui::CollapsingHeader::new(format!("Scheduled Jobs ({})", state.schedule.jobs.len()))
+            .default_open(false)
+            .show(ui, |ui| {
+                let mut changed = false;
+                let mut remove = None;
+                for (i, job) in state.schedule.jobs.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        changed |= ui.checkbox(&mut job.enabled, "").changed();
+                        ui.label(egui::RichText::new(&job.name).size(13.0).strong());
+                        ui.label(
+                            egui::RichText::new(format!("{} · {}", job.cron, job.kind.describe()))
+                                .size(13.0)
+                                .color(muted_color)
+                        );
+                        if state.running.as_ref().is_some_and(|r| r.name == job.name) {
+                            ui.spinner();
+                        }
+                        if let Some(health) = state.health.get(&job.name) {
+                            if health.consecutive_failures > 0 {
+                                ui.label(
+                                    egui::RichText::new(format!("⚠ {} failures", health.consecutive_failures))
+                                        .size(13.0)
+                                        .color(Palette::of(ui.ctx()).warning)
+                                )
+                                .on_hover_text(health.last_error.clone().unwrap_or_default());
+                            }
+                        }
+                        if ui.small_button("✕").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    state.schedule.jobs.remove(i);
+                    changed = true;
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut state.new_name)
+                            .hint_text("Job name")
+                            .desired_width(120.0)
+                    );
+                    ui.add(
+                        egui::TextEdit::singleline(&mut state.new_cron)
+                            .hint_text("min hour day month weekday")
+                            .desired_width(120.0)
+                    );
+                    let folder_label = state
+                        .new_folder
+                        .as_ref()
+                        .and_then(|f| f.file_name())
+                        .map_or("Choose Folder".to_string(), |n| n.to_string_lossy().into_owned());
+                    if ui.button(folder_label).clicked() {
+                        state.new_folder = rfd::FileDialog::new().pick_folder().or(state.new_folder.take());
+                    }
+                });
+                let cron_error = Cron::parse(&state.new_cron).err();
+                if let Some(err) = &cron_error {
+                    ui.label(egui::RichText::new(err.to_string()).size(13.0).color(muted_color));
+                }
+                let can_add = !state.new_name.is_empty() && cron_error.is_none() && state.new_folder.is_some();
+                if ui.add_enabled(can_add, egui::Button::new("Add Folder Sweep")).clicked() {
+                    state.schedule.jobs.push(Job {
+                        name: std::mem::take(&mut state.new_name),
+                        cron: state.new_cron.clone(),
+                        kind: JobKind::FolderSweep { folder: state.new_folder.take().unwrap() },
+                        enabled: true,
+                        last_run: None,
+                    });
+                    changed = true;
+                }
+
+                if changed {
+                    if let Err(err) = state.schedule.save() {
+                        self.error = Some(err.to_string());
+                    }
+                }
+            });
+    }
+}
@@ -0,0 +1,104 @@
+use crate::palette::Palette;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::{model, scoring, AvalancheAnalysis};
+use eframe::egui;
+
+// Warning banner for checks soft validation let through, then the
+// characteristic score of each type as a bar, so a close call is visible
+// instead of an error
+pub(crate) fn type_scores_ui(ui: &mut egui::Ui, analysis: &AvalancheAnalysis, palette: Palette) {
+    if !analysis.validation_warnings.is_empty() {
+        egui::Frame::none()
+            .fill(palette.warning.gamma_multiply(0.15))
+            .stroke(egui::Stroke::new(1.0, palette.warning))
+            .rounding(6.0)
+            .inner_margin(egui::vec2(10.0, 6.0))
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                for warning in &analysis.validation_warnings {
+                    ui.label(egui::RichText::new(format!("⚠ {}", warning)).size(13.0).color(palette.warning));
+                }
+                ui.label(
+                    egui::RichText::new("The model's characteristics don't clearly support its answer; weigh it accordingly")
+                        .size(13.0)
+                        .color(palette.muted),
+                );
+            });
+        ui.add_space(8.0);
+    }
+
+    let Some(scores) = analysis.type_scores else {
+        return;
+    };
+    let max_score = scoring::config().max_score().max(1);
+    let (_, highest) = scores.best();
+    ui.label(egui::RichText::new("Characteristic Scores").size(14.0).color(palette.muted));
+    egui::Grid::new("type_scores").num_columns(2).spacing(egui::vec2(8.0, 4.0)).show(ui, |ui| {
+        // Types without a single indicator would only be a column of empty
+        // bars, so only those that scored and the chosen one are listed
+        for (avalanche_type, score) in scores.iter() {
+            let chosen = analysis.avalanche_type == avalanche_type;
+            if score == 0 && !chosen {
+                continue;
+            }
+            let text = egui::RichText::new(model::type_label(avalanche_type)).size(13.0);
+            ui.label(if chosen { text.strong() } else { text });
+            let fill = if score == highest { palette.accent } else { palette.muted };
+            ui.add(
+                egui::ProgressBar::new(score.max(0) as f32 / max_score as f32)
+                    .desired_width(180.0)
+                    .fill(fill)
+                    .text(format!("{} / {}", score, max_score)),
+            );
+            ui.end_row();
+        }
+    });
+}
+
+impl AvalancheClassifier {
+    // Debug view of the weights and thresholds the rule engine is using, and
+    // where they came from
+    pub(crate) fn scoring_rules_ui(&mut self, ui: &mut egui::Ui, danger_color: egui::Color32, muted_color: egui::Color32) {
+        egui::CollapsingHeader::new("Scoring Rules").default_open(false).show(ui, |ui| {
+            let path = scoring::path();
+            let source = match &self.scoring_file {
+                Ok(true) => format!("From {}", path.display()),
+                Ok(false) => format!("Defaults; create {} to change them", path.display()),
+                Err(err) => format!("{} is invalid, still using the rules before it: {}", path.display(), err),
+            };
+            let color = if self.scoring_file.is_err() { danger_color } else { muted_color };
+            ui.label(egui::RichText::new(source).size(13.0).color(color));
+
+            let config = scoring::config();
+            let defaults = scoring::ScoringConfig::DEFAULT;
+            egui::Grid::new("scoring_rules").num_columns(3).spacing(egui::vec2(12.0, 2.0)).show(ui, |ui| {
+                for (label, value, default) in [
+                    ("Primary indicator", config.weights.primary, defaults.weights.primary),
+                    ("Secondary indicator", config.weights.secondary, defaults.weights.secondary),
+                    ("Minimum lead", config.thresholds.min_margin, defaults.thresholds.min_margin),
+                    ("Minimum score", config.thresholds.min_score, defaults.thresholds.min_score),
+                    ("Highest possible", config.max_score(), defaults.max_score()),
+                ] {
+                    ui.label(egui::RichText::new(label).size(13.0));
+                    ui.label(egui::RichText::new(value.to_string()).size(13.0).strong());
+                    if value != default {
+                        ui.label(egui::RichText::new(format!("default {}", default)).size(13.0).color(muted_color));
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                if ui.small_button("⟳ Reload").on_hover_text("Read scoring.toml again after editing it").clicked() {
+                    self.scoring_file = scoring::reload().map_err(|err| err.to_string());
+                }
+                if self.scoring_file == Ok(false) && ui.small_button("Create File").clicked() {
+                    match scoring::write_defaults() {
+                        Ok(()) => self.scoring_file = scoring::reload().map_err(|err| err.to_string()),
+                        Err(err) => self.error = Some(format!("Could not write scoring.toml: {}", err)),
+                    }
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,666 @@
+use avalanche_classifier_core::{
+    bulletin, decode, fallback, ffmpeg, models, provider, storage, superres, AnthropicProvider, Backend, OllamaProvider,
+    OpenAiProvider, VisionProvider,
+};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::keychain::{self, KeySource};
+use crate::palette::Palette;
+use crate::AvalancheClassifier;
+
+// Backend and model choices remembered between runs, in settings.toml under
+// the data directory so they can also be edited by hand. API keys are kept
+// out of it, in the system keychain when remembered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Settings {
+    pub backend: Backend,
+    pub openai_base_url: String,
+    pub openai_model: String,
+    pub anthropic_model: String,
+    pub ollama_url: String,
+    pub ollama_model: String,
+    // Classify with every configured backend and merge by majority vote
+    pub ensemble: bool,
+    // Fail results whose type scores are ambiguous, weak or contradict the
+    // model, rather than showing them with a warning
+    pub strict_validation: bool,
+    // Times a type that contradicts its own characteristics is sent back to
+    // the model before it counts as a conflict
+    pub conflict_rounds: u32,
+    // Recorded as who acknowledged an alert
+    pub operator: String,
+    pub mode: crate::verification::AnalysisMode,
+    pub recent_urls: Vec<String>,
+    // Images from the batch queue analyzed at the same time
+    pub batch_concurrency: usize,
+    // Shuffles the batch queue reproducibly; see manifest::seeded_order
+    pub batch_seed: u64,
+    pub upload: models::UploadEncoding,
+    // Photos decoded and resized at the same time, and the memory they may
+    // take together
+    pub decode_workers: usize,
+    pub memory_budget_mb: u64,
+    // Local upscaling of small frames before upload
+    pub super_resolution: superres::Config,
+    // Edited analysis prompt; None uses the built-in one
+    pub custom_prompt: Option<String>,
+    pub theme: Theme,
+    // Backends tried in order when the selected one fails or times out
+    pub fallback_chain: Vec<Backend>,
+    // Look up the weather before a photo with GPS and capture time and send
+    // it with the prompt
+    pub fetch_weather: bool,
+    // Where the regional bulletin shown beside located results comes from
+    pub bulletin_source: bulletin::BulletinSource,
+    // Save API keys to the system keychain and load them on startup
+    pub remember_api_keys: bool,
+    // Where save dialogs for exports start; None leaves it to the system
+    pub export_dir: Option<std::path::PathBuf>,
+    // How frames are picked from a video for analysis
+    pub video_sampling: ffmpeg::Sampling,
+    // Camera monitoring was running when the app closed, so it resumes on
+    // the next launch
+    pub monitoring: bool,
+    // Show monitoring status in the system tray, with a menu to pause it and
+    // bring back the minimized window
+    pub minimize_to_tray: bool,
+    // Folder whose new images are classified as they arrive; None when not
+    // watching
+    pub watch_folder: Option<std::path::PathBuf>,
+    // Global hotkey for screen capture, registered again at launch; None
+    // when not enabled
+    pub capture_hotkey: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub(crate) enum Theme {
+    Light,
+    Dark,
+    // Follows the operating system's light or dark mode as it changes
+    #[default]
+    System,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::System];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+            Theme::System => "Follow system",
+        }
+    }
+
+    // Whether to draw dark, given the system theme when it is known
+    pub fn is_dark(self, system: Option<eframe::Theme>) -> bool {
+        match self {
+            Theme::Light => false,
+            Theme::Dark => true,
+            Theme::System => system == Some(eframe::Theme::Dark),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            backend: Backend::OpenAi,
+            openai_base_url: OpenAiProvider::DEFAULT_BASE_URL.to_string(),
+            openai_model: OpenAiProvider::DEFAULT_MODEL.to_string(),
+            anthropic_model: AnthropicProvider::DEFAULT_MODEL.to_string(),
+            ollama_url: OllamaProvider::DEFAULT_URL.to_string(),
+            ollama_model: OllamaProvider::DEFAULT_MODEL.to_string(),
+            ensemble: false,
+            strict_validation: false,
+            conflict_rounds: provider::DEFAULT_CONFLICT_ROUNDS,
+            operator: String::new(),
+            mode: Default::default(),
+            recent_urls: Vec::new(),
+            batch_concurrency: 1,
+            batch_seed: 0,
+            upload: Default::default(),
+            decode_workers: decode::DEFAULT_WORKERS,
+            memory_budget_mb: decode::DEFAULT_BUDGET_MB,
+            super_resolution: Default::default(),
+            custom_prompt: None,
+            theme: Theme::default(),
+            fallback_chain: Vec::new(),
+            fetch_weather: true,
+            bulletin_source: Default::default(),
+            remember_api_keys: true,
+            export_dir: None,
+            video_sampling: Default::default(),
+            monitoring: false,
+            minimize_to_tray: false,
+            watch_folder: None,
+            capture_hotkey: None,
+        }
+    }
+}
+
+impl Settings {
+    fn path() -> std::path::PathBuf {
+        storage::data_dir().join("settings.toml")
+    }
+
+    // Settings were JSON before they moved to TOML
+    fn legacy_path() -> std::path::PathBuf {
+        storage::data_dir().join("settings.json")
+    }
+
+    // Saved settings, or the defaults with a message when settings.toml
+    // cannot be read. A broken file is moved aside rather than overwritten,
+    // so hand edits are not lost.
+    pub fn load() -> (Self, Option<String>) {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => match toml::from_str(&text) {
+                Ok(settings) => (settings, None),
+                Err(err) => {
+                    let backup = path.with_extension("toml.bak");
+                    let kept = std::fs::rename(&path, &backup).is_ok();
+                    let message = format!(
+                        "Could not read settings.toml, using defaults{}: {}",
+                        if kept { " (the file was kept as settings.toml.bak)" } else { "" },
+                        err.message()
+                    );
+                    (Self::default(), Some(message))
+                }
+            },
+            Err(_) => (Self::migrate_legacy().unwrap_or_default(), None),
+        }
+    }
+
+    // Converts settings.json from earlier versions to settings.toml
+    fn migrate_legacy() -> Option<Self> {
+        let legacy = Self::legacy_path();
+        let settings: Self = serde_json::from_str(&std::fs::read_to_string(&legacy).ok()?).ok()?;
+        if settings.save().is_ok() {
+            let _ = std::fs::remove_file(legacy);
+        }
+        Some(settings)
+    }
+
+    // Hands the decode limits and the analysis prompt to the core, which
+    // uses them from then on
+    pub fn apply(&self) {
+        decode::set_limits(self.decode_workers, self.memory_budget_mb);
+        provider::set_analysis_prompt(self.custom_prompt.clone());
+        superres::configure(self.super_resolution.clone());
+        provider::set_soft_validation(!self.strict_validation);
+        provider::set_conflict_rounds(self.conflict_rounds);
+    }
+
+    // The backend's model as set here
+    pub fn model(&self, backend: Backend) -> &str {
+        match backend {
+            Backend::OpenAi => &self.openai_model,
+            Backend::Anthropic => &self.anthropic_model,
+            Backend::Ollama => &self.ollama_model,
+            _ => "",
+        }
+    }
+
+    // A backend with these endpoints and upload encoding; the key is
+    // ignored for Ollama
+    pub fn provider(&self, backend: Backend, model: String, api_key: String) -> Box<dyn VisionProvider> {
+        match backend {
+            Backend::OpenAi => Box::new(
+                OpenAiProvider::new(api_key)
+                    .with_base_url(self.openai_base_url.clone())
+                    .with_model(model)
+                    .with_upload(self.upload),
+            ),
+            Backend::Anthropic => {
+                Box::new(AnthropicProvider::new(api_key).with_model(model).with_upload(self.upload))
+            }
+            Backend::Ollama => Box::new(OllamaProvider::new(self.ollama_url.clone(), model).with_upload(self.upload)),
+            // Never configured (see is_configured), so nothing is sent to it
+            other => unimplemented!("no provider for the {} backend", other.label()),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // Save dialog for an export, opening in the default export folder
+    pub fn export_dialog(&self) -> rfd::FileDialog {
+        match &self.export_dir {
+            Some(dir) => rfd::FileDialog::new().set_directory(dir),
+            None => rfd::FileDialog::new(),
+        }
+    }
+}
+
+fn super_resolution_ui(ui: &mut egui::Ui, config: &mut superres::Config, muted_color: egui::Color32) {
+    ui.add_enabled_ui(superres::AVAILABLE, |ui| {
+        ui.checkbox(&mut config.enabled, "Upscale small frames locally first");
+        ui.add_enabled_ui(config.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Model");
+                let shown = config
+                    .model
+                    .as_ref()
+                    .and_then(|path| path.file_name())
+                    .map_or("none".to_string(), |name| name.to_string_lossy().into_owned());
+                ui.label(egui::RichText::new(shown).size(13.0))
+                    .on_hover_text(config.model.as_ref().map_or(String::new(), |path| path.display().to_string()));
+                if ui.button("Choose…").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("ONNX model", &["onnx"]).pick_file() {
+                        config.model = Some(path);
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Below");
+                ui.add(egui::DragValue::new(&mut config.below_px).speed(16.0).clamp_range(64..=4096).suffix(" px"));
+            });
+        });
+    });
+    let note = if superres::AVAILABLE {
+        "An ESRGAN-style ONNX model enlarges photos whose longest side is below the limit, such as webcam frames"
+    } else {
+        "Local super-resolution needs a build with the `superres` feature"
+    };
+    ui.label(egui::RichText::new(note).size(13.0).color(muted_color));
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn model_picker_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let model = match self.settings.backend {
+            Backend::OpenAi => &mut self.settings.openai_model,
+            Backend::Anthropic => &mut self.settings.anthropic_model,
+            _ => return,
+        };
+        let selected = models::lookup(model).map_or(model.clone(), |m| m.label.to_string());
+        ui.horizontal(|ui| {
+            ui.label("Model");
+            egui::ComboBox::from_id_source("model")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for info in models::for_backend(self.settings.backend) {
+                        ui.selectable_value(model, info.id.to_string(), info.label);
+                    }
+                });
+        });
+        if let Some(info) = models::lookup(model) {
+            ui.label(
+                egui::RichText::new(format!(
+                    "Images up to {}px, detail {}{}",
+                    info.max_image_px,
+                    info.detail,
+                    if info.reasoning { ", reasoning" } else { "" }
+                ))
+                .size(13.0)
+                .color(muted_color),
+            );
+        }
+    }
+
+    pub(crate) fn upload_settings_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let settings = &mut self.settings;
+        let upload = &mut settings.upload;
+        egui::CollapsingHeader::new("Image Upload")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Longest side");
+                    ui.add(
+                        egui::DragValue::new(&mut upload.max_px)
+                            .speed(16.0)
+                            .clamp_range(models::UploadEncoding::MIN_PX..=8192)
+                            .suffix(" px"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Format");
+                    egui::ComboBox::from_id_source("upload_format")
+                        .selected_text(upload.format.label())
+                        .show_ui(ui, |ui| {
+                            for format in models::UploadFormat::ALL {
+                                ui.selectable_value(&mut upload.format, format, format.label());
+                            }
+                        });
+                    ui.label("Quality");
+                    ui.add(egui::DragValue::new(&mut upload.quality).clamp_range(1..=100));
+                });
+                let note = if settings.backend == Backend::Ollama {
+                    "Photos are resized and re-encoded before sending; Ollama always gets JPEG"
+                } else {
+                    "Photos are resized and re-encoded before sending; the model's own limit applies when lower"
+                };
+                ui.label(egui::RichText::new(note).size(13.0).color(muted_color));
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.label("Decode in parallel");
+                    ui.add(egui::DragValue::new(&mut settings.decode_workers).clamp_range(1..=16));
+                    ui.label("Memory budget");
+                    ui.add(
+                        egui::DragValue::new(&mut settings.memory_budget_mb)
+                            .speed(64.0)
+                            .clamp_range(256..=16384)
+                            .suffix(" MB"),
+                    );
+                });
+                ui.label(
+                    egui::RichText::new("Batches wait for room; lower both on laptops with 8 GB or less")
+                        .size(13.0)
+                        .color(muted_color),
+                );
+                ui.add_space(4.0);
+                super_resolution_ui(ui, &mut settings.super_resolution, muted_color);
+            });
+    }
+
+    // Backend, model, upload, appearance, export and prompt settings, opened
+    // from the gear button. Changes are saved as they are made.
+    pub(crate) fn settings_window(&mut self, ctx: &egui::Context) {
+        if !self.settings_open {
+            return;
+        }
+        let palette = Palette::of(ctx);
+        let (success_color, danger_color, muted_color) = (palette.success, palette.danger, palette.muted);
+
+        let mut open = true;
+        egui::Window::new("⚙ Settings")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Backend");
+                        egui::ComboBox::from_id_source("backend")
+                            .selected_text(self.settings.backend.label())
+                            .show_ui(ui, |ui| {
+                                for backend in Backend::ALL {
+                                    ui.selectable_value(&mut self.settings.backend, backend, backend.label());
+                                }
+                            });
+                    });
+                    self.ensemble_toggle_ui(ui, muted_color);
+                    ui.checkbox(&mut self.settings.strict_validation, "Strict validation").on_hover_text(
+                        "Reject results whose characteristic scores are too close to call, too weak or point to \
+                         another type, instead of showing them with a warning",
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Re-ask on conflict");
+                        ui.add(egui::DragValue::new(&mut self.settings.conflict_rounds).clamp_range(0..=5).suffix("×"))
+                            .on_hover_text(
+                                "When the model's type contradicts the characteristics it reported, ask it to \
+                                 re-examine them up to this many times before rejecting the result, or showing it \
+                                 with a warning without strict validation",
+                            );
+                    });
+                    ui.checkbox(&mut self.settings.fetch_weather, "Add recent weather to the prompt").on_hover_text(
+                        "For photos with GPS and a capture time, look up snowfall, temperature and wind over the \
+                         72 hours before from Open-Meteo and send a summary with the photo. The location is sent \
+                         to Open-Meteo",
+                    );
+                    self.bulletin_source_ui(ui, muted_color);
+                    ui.add_enabled(
+                        cfg!(not(target_os = "linux")),
+                        egui::Checkbox::new(&mut self.settings.minimize_to_tray, "Minimize to the system tray"),
+                    )
+                    .on_hover_text(
+                        "Show camera monitoring in the tray, colored by whether it is running, paused or has \
+                         open alerts, with a menu to pause or resume it. Monitoring and alerts keep running \
+                         while the window is minimized; click the icon to bring it back",
+                    )
+                    .on_disabled_hover_text("Not available on Linux");
+                    let label = self.settings.backend.label();
+                    let remember = self.settings.remember_api_keys;
+                    if let Some(api_key) = self.api_key_mut() {
+                        ui.label(format!("{} API Key", label));
+                        let response = ui.add(
+                            egui::TextEdit::singleline(api_key)
+                                .password(true)
+                                .hint_text(format!("Enter your {} API key", label))
+                        );
+                        let backend = self.settings.backend;
+                        let source = self.key_source(backend);
+                        if let (Some(source @ (KeySource::Environment | KeySource::DotEnv)), Some(var)) =
+                            (source, keychain::env_var(backend))
+                        {
+                            let key = if backend == Backend::OpenAi { &self.openai_api_key } else { &self.anthropic_api_key };
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "🔑 {} loaded from {}{}",
+                                    keychain::masked(key),
+                                    var,
+                                    if source == KeySource::DotEnv { " in .env" } else { "" }
+                                ))
+                                .size(13.0)
+                                .color(muted_color),
+                            );
+                        }
+                        // Saved once typing is done rather than on every
+                        // keystroke. Keys from the environment stay there.
+                        if remember && response.lost_focus() {
+                            self.store_api_key(self.settings.backend);
+                        }
+                        if ui
+                            .checkbox(&mut self.settings.remember_api_keys, "Remember API keys in the system keychain")
+                            .changed()
+                        {
+                            self.remember_api_keys_changed();
+                        }
+                        self.model_picker_ui(ui, muted_color);
+                        if self.settings.backend == Backend::OpenAi {
+                            egui::CollapsingHeader::new("Custom Endpoint")
+                                .default_open(false)
+                                .show(ui, |ui| {
+                                    ui.label("Base URL (any OpenAI-compatible server)");
+                                    ui.add(
+                                        egui::TextEdit::singleline(&mut self.settings.openai_base_url)
+                                            .hint_text(OpenAiProvider::DEFAULT_BASE_URL)
+                                    );
+                                    ui.label("Model id (overrides the picker above)");
+                                    ui.add(
+                                        egui::TextEdit::singleline(&mut self.settings.openai_model)
+                                            .hint_text(OpenAiProvider::DEFAULT_MODEL)
+                                    );
+                                });
+                        }
+                    } else {
+                        ui.label("Ollama Server URL");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.settings.ollama_url)
+                                .hint_text(OllamaProvider::DEFAULT_URL)
+                        );
+                        ui.label("Vision Model");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.settings.ollama_model)
+                                .hint_text("e.g. llava or qwen2-vl")
+                        );
+                    }
+                    self.fallback_chain_ui(ui, muted_color);
+                    self.upload_settings_ui(ui, muted_color);
+                    self.appearance_ui(ui, ctx);
+                    self.export_settings_ui(ui, muted_color);
+                    self.prompt_ui(ui, success_color, danger_color, muted_color);
+                    self.scoring_rules_ui(ui, danger_color, muted_color);
+                    ui.add_space(8.0);
+                    ui.label(
+                        egui::RichText::new(format!("Saved to {}", Settings::path().display()))
+                            .size(13.0)
+                            .color(muted_color),
+                    );
+                });
+            });
+        self.settings_open &= open;
+    }
+
+    // Ordered backends to fall back to, each with its own model and key
+    fn fallback_chain_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        egui::CollapsingHeader::new("Fallback Chain")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Tried in order when {} fails or takes over {} s",
+                        self.settings.backend.label(),
+                        fallback::ATTEMPT_TIMEOUT.as_secs()
+                    ))
+                    .size(13.0)
+                    .color(muted_color),
+                );
+                let mut move_up = None;
+                let mut remove = None;
+                for (i, backend) in self.settings.fallback_chain.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}. {}", i + 1, backend.label()));
+                        if *backend == self.settings.backend {
+                            ui.label(egui::RichText::new("selected, skipped").size(13.0).color(muted_color));
+                        } else if !self.is_configured(*backend) {
+                            ui.label(egui::RichText::new("not set up, skipped").size(13.0).color(muted_color));
+                        }
+                        if i > 0 && ui.small_button("⬆").clicked() {
+                            move_up = Some(i);
+                        }
+                        if ui.small_button("✖").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = move_up {
+                    self.settings.fallback_chain.swap(i - 1, i);
+                }
+                if let Some(i) = remove {
+                    self.settings.fallback_chain.remove(i);
+                }
+                let available: Vec<Backend> =
+                    Backend::ALL.into_iter().filter(|b| !self.settings.fallback_chain.contains(b)).collect();
+                if !available.is_empty() {
+                    ui.menu_button("➕ Add Backend", |ui| {
+                        for backend in available {
+                            if ui.button(backend.label()).clicked() {
+                                self.settings.fallback_chain.push(backend);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                }
+            });
+    }
+
+    fn store_api_key(&mut self, backend: Backend) {
+        if matches!(self.key_source(backend), Some(KeySource::Environment | KeySource::DotEnv)) {
+            return;
+        }
+        let key = match backend {
+            Backend::OpenAi => &self.openai_api_key,
+            Backend::Anthropic => &self.anthropic_api_key,
+            _ => return,
+        };
+        if keychain::load(backend).as_deref().unwrap_or("") != key.as_str() {
+            if let Err(err) = keychain::store(backend, key) {
+                self.error = Some(err);
+            }
+        }
+    }
+
+    // Turning it on saves the keys already entered; off removes them
+    fn remember_api_keys_changed(&mut self) {
+        for backend in [Backend::OpenAi, Backend::Anthropic] {
+            if self.settings.remember_api_keys {
+                self.store_api_key(backend);
+            } else if let Err(err) = keychain::forget(backend) {
+                self.error = Some(err);
+            }
+        }
+    }
+
+    fn bulletin_source_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let source = &mut self.settings.bulletin_source;
+        ui.horizontal(|ui| {
+            ui.label("Regional bulletin");
+            egui::ComboBox::from_id_source("bulletin_source")
+                .selected_text(source.label())
+                .show_ui(ui, |ui| {
+                    let caaml = match source {
+                        bulletin::BulletinSource::Caaml { .. } => source.clone(),
+                        _ => bulletin::BulletinSource::Caaml { url: String::new(), region_id: String::new() },
+                    };
+                    for option in [bulletin::BulletinSource::Off, bulletin::BulletinSource::AvalancheOrg, caaml] {
+                        let label = option.label();
+                        ui.selectable_value(source, option, label);
+                    }
+                });
+        });
+        if let bulletin::BulletinSource::Caaml { url, region_id } = source {
+            ui.add(egui::TextEdit::singleline(url).hint_text("https://… CAAMLv6 JSON bulletin"));
+            ui.add(egui::TextEdit::singleline(region_id).hint_text("Region ID, e.g. AT-07-14"));
+        }
+        ui.label(
+            egui::RichText::new("Shown beside results with a location, with whether the photo bears out the rating")
+                .size(13.0)
+                .color(muted_color),
+        );
+    }
+
+    fn appearance_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            ui.label("Theme");
+            let before = self.settings.theme;
+            egui::ComboBox::from_id_source("theme")
+                .selected_text(self.settings.theme.label())
+                .show_ui(ui, |ui| {
+                    for theme in Theme::ALL {
+                        ui.selectable_value(&mut self.settings.theme, theme, theme.label());
+                    }
+                });
+            if self.settings.theme != before {
+                // Applied by the next frame, which knows the system theme
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    fn export_settings_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        ui.horizontal(|ui| {
+            ui.label("Export folder");
+            let shown = self
+                .settings
+                .export_dir
+                .as_ref()
+                .map_or_else(|| "Ask each time".to_string(), |dir| dir.display().to_string());
+            ui.label(egui::RichText::new(shown).size(13.0).color(muted_color));
+            if ui.small_button("Choose...").clicked() {
+                if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                    self.settings.export_dir = Some(dir);
+                }
+            }
+            if self.settings.export_dir.is_some() && ui.small_button("Clear").clicked() {
+                self.settings.export_dir = None;
+            }
+        });
+    }
+
+    // Writes settings back to disk and applies them once they change, but
+    // not while a text field has focus so a half-typed prompt or URL isn't
+    // saved or put to use
+    pub(crate) fn persist_settings(&mut self, ctx: &egui::Context) {
+        if !ctx.wants_keyboard_input() {
+            self.flush_settings();
+        }
+    }
+
+    pub(crate) fn flush_settings(&mut self) {
+        if self.settings != self.saved_settings {
+            if let Err(e) = self.settings.save() {
+                self.error = Some(format!("Failed to save settings: {}", e));
+            }
+            self.settings.apply();
+            self.saved_settings = self.settings.clone();
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::palette::Palette;
+use avalanche_classifier_core::history::HistoryEntry;
+use avalanche_classifier_core::provider::http_client;
+use avalanche_classifier_core::slope_check::{self, SlopeCheck};
+use eframe::egui;
+use poll_promise::Promise;
+
+#[derive(Default)]
+pub struct SlopeCheckState {
+    // Entry the check below is for, and whether it had a confirmed slope then
+    key: Option<(String, bool)>,
+    promise: Option<Promise<Option<anyhow::Result<SlopeCheck>>>>,
+    check: Option<Result<SlopeCheck, String>>,
+}
+
+// The measured slope angle and aspect for a located result, with where the
+// model's estimates disagree. Returns the disagreements of a check that
+// finished this frame, for the entry's review flags.
+pub(crate) fn slope_check_ui(
+    ui: &mut egui::Ui,
+    state: &mut SlopeCheckState,
+    entry: Option<&HistoryEntry>,
+    palette: Palette,
+) -> Vec<String> {
+    let Some(entry) = entry.filter(|e| e.location.is_some() || e.slope.is_some()) else {
+        return Vec::new();
+    };
+    let key = (entry.id().to_string(), entry.slope.is_some());
+    if state.key.as_ref() != Some(&key) {
+        let entry = entry.clone();
+        state.key = Some(key);
+        state.check = None;
+        state.promise = Some(crate::runtime::spawn(async move { slope_check::check(&http_client(), &entry).await }));
+    }
+    let mut finished = Vec::new();
+    if let Some(promise) = state.promise.take() {
+        match promise.try_take() {
+            Ok(Some(result)) => {
+                if let Ok(check) = &result {
+                    finished = check.discrepancies.clone();
+                }
+                state.check = Some(result.map_err(|err| err.to_string()));
+            }
+            Ok(None) => {}
+            Err(promise) => {
+                state.promise = Some(promise);
+                ui.ctx().request_repaint();
+            }
+        }
+    }
+
+    ui.add_space(8.0);
+    match &state.check {
+        None if state.promise.is_some() => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(egui::RichText::new("Measuring the slope from elevation data…").size(13.0).color(palette.muted));
+            });
+        }
+        None => {}
+        Some(Err(err)) => {
+            ui.label(egui::RichText::new(format!("⛰ Slope not measured: {}", err)).size(13.0).color(palette.muted));
+        }
+        Some(Ok(check)) => {
+            ui.label(egui::RichText::new(format!("⛰ DEM: {}", check.describe())).size(13.0))
+                .on_hover_text("Measured from Open-Meteo's 90 m elevation model");
+            for discrepancy in &check.discrepancies {
+                ui.label(egui::RichText::new(format!("⚠ {}", discrepancy)).size(13.0).color(palette.warning));
+            }
+        }
+    }
+    finished
+}
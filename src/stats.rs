@@ -0,0 +1,171 @@
+use crate::palette::Palette;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::history::{HistoryEntry, Verdict};
+use avalanche_classifier_core::scoreboard;
+use avalanche_classifier_core::AVALANCHE_TYPES;
+use eframe::egui;
+
+pub const LABELS: [&str; AVALANCHE_TYPES.len()] = AVALANCHE_TYPES;
+
+// Lookback windows offered by the date filter, in days (None = all time)
+const DATE_RANGES: [(&str, Option<u64>); 5] = [
+    ("All time", None),
+    ("Last 7 days", Some(7)),
+    ("Last 30 days", Some(30)),
+    ("Last 90 days", Some(90)),
+    ("Last year", Some(365)),
+];
+
+#[derive(Default)]
+pub struct StatsState {
+    pub open: bool,
+    model: Option<String>,
+    range: usize,
+}
+
+// Rows are the human label, columns the AI label, both in LABELS order.
+// Only accepted or corrected entries count since they carry a human label.
+pub fn confusion_matrix<'a>(entries: impl Iterator<Item = &'a HistoryEntry>) -> [[usize; LABELS.len()]; LABELS.len()] {
+    let mut matrix = [[0; LABELS.len()]; LABELS.len()];
+    for entry in entries {
+        if !matches!(entry.review.as_ref().map(|r| r.verdict), Some(Verdict::Accepted | Verdict::Corrected)) {
+            continue;
+        }
+        let human = entry.effective_type().and_then(|t| LABELS.iter().position(|l| *l == t));
+        let ai = LABELS.iter().position(|l| *l == entry.analysis.avalanche_type);
+        if let (Some(human), Some(ai)) = (human, ai) {
+            matrix[human][ai] += 1;
+        }
+    }
+    matrix
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn stats_window(&mut self, ctx: &egui::Context) {
+        if !self.stats.open {
+            return;
+        }
+
+        let mut models: Vec<String> = self.history.entries.iter().map(|e| e.snapshot.model.clone()).collect();
+        models.sort();
+        models.dedup();
+
+        let mut open = true;
+        egui::Window::new("Stats")
+            .open(&mut open)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("Model")
+                        .selected_text(self.stats.model.as_deref().unwrap_or("All models"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.stats.model, None, "All models");
+                            for model in &models {
+                                ui.selectable_value(&mut self.stats.model, Some(model.clone()), model);
+                            }
+                        });
+                    egui::ComboBox::from_label("Period")
+                        .selected_text(DATE_RANGES[self.stats.range].0)
+                        .show_ui(ui, |ui| {
+                            for (i, (name, _)) in DATE_RANGES.iter().enumerate() {
+                                ui.selectable_value(&mut self.stats.range, i, *name);
+                            }
+                        });
+                });
+
+                let since = DATE_RANGES[self.stats.range]
+                    .1
+                    .map(|days| avalanche_classifier_core::storage::unix_now().saturating_sub(days * 86_400));
+                let matrix = confusion_matrix(self.history.entries.iter().filter(|e| {
+                    self.stats.model.as_ref().is_none_or(|m| *m == e.snapshot.model)
+                        && since.is_none_or(|since| e.snapshot.created_at >= since)
+                }));
+                let max = matrix.iter().flatten().copied().max().unwrap_or(0).max(1);
+                let total: usize = matrix.iter().flatten().sum();
+                let correct: usize = (0..LABELS.len()).map(|i| matrix[i][i]).sum();
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Confusion Matrix").size(16.0).strong());
+                ui.label(
+                    egui::RichText::new("Rows: human label · Columns: AI label")
+                        .size(13.0)
+                        .color(Palette::of(ui.ctx()).muted)
+                );
+                ui.add_space(4.0);
+                egui::Grid::new("confusion_matrix").spacing(egui::vec2(4.0, 4.0)).show(ui, |ui| {
+                    ui.label("");
+                    for label in LABELS {
+                        ui.label(egui::RichText::new(label).size(13.0).strong());
+                    }
+                    ui.end_row();
+                    for (row, label) in LABELS.iter().enumerate() {
+                        ui.label(egui::RichText::new(*label).size(13.0).strong());
+                        for (col, count) in matrix[row].iter().enumerate() {
+                            // Diagonal cells are agreements, everything else a confusion
+                            let base = if row == col {
+                                Palette::of(ui.ctx()).success
+                            } else {
+                                Palette::of(ui.ctx()).danger
+                            };
+                            let alpha = if *count == 0 { 0 } else { 40 + (*count * 215 / max) as u8 };
+                            egui::Frame::none()
+                                .fill(egui::Color32::from_rgba_unmultiplied(base.r(), base.g(), base.b(), alpha))
+                                .rounding(4.0)
+                                .inner_margin(egui::vec2(12.0, 6.0))
+                                .show(ui, |ui| {
+                                    ui.label(egui::RichText::new(count.to_string()).size(13.0));
+                                });
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                ui.add_space(8.0);
+                if total > 0 {
+                    ui.label(format!(
+                        "Agreement: {:.0}% of {} reviewed entries",
+                        correct as f32 / total as f32 * 100.0,
+                        total
+                    ));
+                } else {
+                    ui.label("No reviewed entries match the filters.");
+                }
+
+                ui.add_space(16.0);
+                ui.label(egui::RichText::new("Backend Scoreboard").size(16.0).strong());
+                ui.add_space(4.0);
+                let rows = scoreboard::scoreboard(&self.history);
+                if rows.is_empty() {
+                    ui.label("No analyses recorded yet.");
+                }
+                egui::Grid::new("backend_scoreboard").striped(true).show(ui, |ui| {
+                    for header in ["Backend", "Model", "Analyses", "Accuracy", "Avg Latency", "Cost"] {
+                        ui.label(egui::RichText::new(header).size(13.0).strong());
+                    }
+                    ui.end_row();
+                    for row in rows {
+                        ui.label(egui::RichText::new(&row.backend).size(13.0));
+                        ui.label(egui::RichText::new(&row.model).size(13.0));
+                        ui.label(egui::RichText::new(row.analyses.to_string()).size(13.0));
+                        ui.label(
+                            egui::RichText::new(row.accuracy.map_or_else(
+                                || "—".to_string(),
+                                |a| format!("{:.0}% ({})", a * 100.0, row.reviewed),
+                            ))
+                            .size(13.0)
+                        );
+                        ui.label(
+                            egui::RichText::new(
+                                row.avg_latency_ms
+                                    .map_or_else(|| "—".to_string(), |ms| format!("{:.1}s", ms / 1000.0)),
+                            )
+                            .size(13.0)
+                        );
+                        ui.label(egui::RichText::new(format!("${:.4}", row.total_cost_usd)).size(13.0));
+                        ui.end_row();
+                    }
+                });
+            });
+        self.stats.open &= open;
+    }
+}
@@ -0,0 +1,179 @@
+use crate::monitor::format_time;
+use crate::palette::Palette;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::zones::{self, Area, Areas, Zone};
+use eframe::egui;
+
+pub struct TerrainState {
+    areas: Areas,
+    selected: usize,
+    new_area: String,
+    zone_name: String,
+    // One "lat, lon" pair per line
+    zone_polygon: String,
+    // Comma-separated camera names
+    zone_sources: String,
+}
+
+impl Default for TerrainState {
+    fn default() -> Self {
+        Self {
+            areas: Areas::load_default(),
+            selected: 0,
+            new_area: String::new(),
+            zone_name: String::new(),
+            zone_polygon: String::new(),
+            zone_sources: String::new(),
+        }
+    }
+}
+
+// Local midnight as unix seconds
+fn start_of_today() -> u64 {
+    use chrono::{Local, TimeZone};
+    let midnight = Local::now().date_naive().and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map_or(0, |t| t.timestamp().max(0) as u64)
+}
+
+pub(crate) fn severity_color(severity: Option<u8>, palette: Palette) -> egui::Color32 {
+    match severity {
+        None => palette.muted,
+        Some(0) => palette.success,
+        Some(1..=2) => palette.warning,
+        Some(_) => palette.danger,
+    }
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn terrain_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let state = &mut self.terrain;
+        egui::CollapsingHeader::new("Terrain Status")
+            .default_open(false)
+            .show(ui, |ui| {
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    let current = state.areas.areas.get(state.selected).map_or("No areas", |a| a.name.as_str());
+                    egui::ComboBox::from_id_source("terrain-area")
+                        .selected_text(current)
+                        .show_ui(ui, |ui| {
+                            for (i, area) in state.areas.areas.iter().enumerate() {
+                                ui.selectable_value(&mut state.selected, i, &area.name);
+                            }
+                        });
+                    ui.add(egui::TextEdit::singleline(&mut state.new_area).hint_text("Area name").desired_width(120.0));
+                    if ui.add_enabled(!state.new_area.trim().is_empty(), egui::Button::new("Add Area")).clicked() {
+                        state.areas.areas.push(Area {
+                            name: std::mem::take(&mut state.new_area).trim().to_string(),
+                            zones: Vec::new(),
+                        });
+                        state.selected = state.areas.areas.len() - 1;
+                        changed = true;
+                    }
+                });
+
+                if let Some(area) = state.areas.areas.get_mut(state.selected) {
+                    let summary = zones::summarize(area, &self.history, start_of_today());
+                    ui.add_space(8.0);
+                    ui.label(egui::RichText::new(format!("Today's analyses · {}", area.name)).size(16.0).strong());
+                    let mut remove = None;
+                    egui::Grid::new("terrain-zones").striped(true).show(ui, |ui| {
+                        for (i, zone) in summary.zones.iter().enumerate() {
+                            let severity = zone.worst.as_ref().map(|w| zones::severity(&w.avalanche_type));
+                            ui.label(egui::RichText::new(&zone.zone).size(13.0).strong());
+                            ui.label(
+                                egui::RichText::new(zone.status())
+                                    .size(13.0)
+                                    .color(severity_color(severity, Palette::of(ui.ctx()))),
+                            );
+                            match &zone.worst {
+                                Some(worst) => ui.label(
+                                    egui::RichText::new(format!(
+                                        "worst {} {:.0}% at {} · {} analyses",
+                                        worst.avalanche_type,
+                                        worst.confidence,
+                                        format_time(worst.at),
+                                        zone.analyses
+                                    ))
+                                    .size(13.0)
+                                    .color(muted_color),
+                                ),
+                                None => ui.label(""),
+                            };
+                            if zone.unreviewed > 0 {
+                                ui.label(
+                                    egui::RichText::new(format!("⚠ {} unreviewed", zone.unreviewed))
+                                        .size(13.0)
+                                        .color(Palette::of(ui.ctx()).warning),
+                                )
+                                .on_hover_text("Confirm these in the review queue before relying on the summary");
+                            } else {
+                                ui.label("");
+                            }
+                            if ui.small_button("✕").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if summary.unassigned > 0 {
+                        ui.label(
+                            egui::RichText::new(format!("{} analyses today fall outside every zone", summary.unassigned))
+                                .size(13.0)
+                                .color(muted_color),
+                        );
+                    }
+                    if ui.button("📋 Copy Summary").on_hover_text("Plain text for meeting notes").clicked() {
+                        ui.output_mut(|o| o.copied_text = summary.text());
+                    }
+                    if let Some(i) = remove {
+                        area.zones.remove(i);
+                        changed = true;
+                    }
+
+                    ui.add_space(8.0);
+                    ui.label(egui::RichText::new("New Zone").size(13.0).strong());
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut state.zone_name).hint_text("Zone name").desired_width(120.0));
+                        ui.add(egui::TextEdit::singleline(&mut state.zone_sources).hint_text("Cameras, comma-separated"));
+                    });
+                    ui.add(
+                        egui::TextEdit::multiline(&mut state.zone_polygon)
+                            .desired_rows(3)
+                            .hint_text("Outline, one \"lat, lon\" per line"),
+                    );
+                    let polygon = zones::parse_polygon(&state.zone_polygon);
+                    if let Err(err) = &polygon {
+                        ui.label(egui::RichText::new(err.to_string()).size(13.0).color(muted_color));
+                    }
+                    let sources: Vec<String> = state
+                        .zone_sources
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                    let valid = !state.zone_name.trim().is_empty()
+                        && polygon.as_ref().is_ok_and(|p| p.len() >= 3 || (p.is_empty() && !sources.is_empty()));
+                    if ui.add_enabled(valid, egui::Button::new("Add Zone")).clicked() {
+                        area.zones.push(Zone {
+                            name: std::mem::take(&mut state.zone_name).trim().to_string(),
+                            polygon: polygon.unwrap_or_default(),
+                            sources,
+                        });
+                        state.zone_polygon.clear();
+                        state.zone_sources.clear();
+                        changed = true;
+                    }
+                }
+
+                if changed {
+                    if let Err(err) = state.areas.save() {
+                        self.error = Some(err.to_string());
+                    }
+                }
+            });
+    }
+}
@@ -0,0 +1,399 @@
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::dem::{self, Dem};
+use avalanche_classifier_core::georef::{self, ImageOutline};
+use avalanche_classifier_core::provider::http_client;
+use avalanche_classifier_core::viewshed::{self, SlopeCandidate};
+use avalanche_classifier_core::AnalysisError;
+use eframe::egui;
+use poll_promise::Promise;
+
+pub struct Terrain3dState {
+    // Analysis the grid was loaded for
+    entry_id: Option<String>,
+    loading: Option<Promise<anyhow::Result<Dem>>>,
+    dem: Option<Dem>,
+    visible: Vec<bool>,
+    // Bearing the candidates were computed for, and the candidates
+    candidates: Option<(Option<f32>, Vec<SlopeCandidate>)>,
+    // Candidate drawn instead of the general highlight
+    shown: Option<usize>,
+    outlining: Option<Promise<Result<Option<ImageOutline>, AnalysisError>>>,
+    // View angles in radians: yaw turns around the vertical, pitch 0 looks
+    // from the side and π/2 straight down
+    yaw: f32,
+    pitch: f32,
+    exaggeration: f32,
+}
+
+impl Default for Terrain3dState {
+    fn default() -> Self {
+        Self {
+            entry_id: None,
+            loading: None,
+            dem: None,
+            visible: Vec::new(),
+            candidates: None,
+            shown: None,
+            outlining: None,
+            yaw: 0.0,
+            pitch: 0.6,
+            exaggeration: 1.0,
+        }
+    }
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn terrain3d_ui(&mut self, ui: &mut egui::Ui, accent_color: egui::Color32, muted_color: egui::Color32) {
+        let index = self
+            .snapshot
+            .as_ref()
+            .and_then(|s| self.history.entries.iter().position(|e| e.id() == s.id));
+        let entry = index.map(|i| &self.history.entries[i]);
+        let attached = entry.and_then(|e| e.slope.clone());
+        let outline = entry.and_then(|e| e.outline.clone());
+        let hfov = entry.and_then(|e| e.camera_info.as_ref()).and_then(|c| c.horizontal_fov());
+        let location = entry.and_then(|e| e.location);
+        let bearing = entry.and_then(|e| e.bearing);
+        let label = entry.and_then(|e| e.effective_type()).unwrap_or("unknown").to_string();
+        let entry_id = entry.map(|e| e.id().to_string());
+        let can_outline = bearing.is_some() && self.has_api_key();
+
+        let state = &mut self.terrain3d;
+        if state.entry_id != entry_id {
+            state.entry_id = entry_id;
+            state.loading = None;
+            state.dem = None;
+            state.candidates = None;
+            state.shown = None;
+            state.outlining = None;
+        }
+        if let Some(promise) = &state.loading {
+            match promise.ready() {
+                Some(Ok(grid)) => {
+                    state.visible = viewshed::viewshed(grid);
+                    state.dem = Some(grid.clone());
+                    state.loading = None;
+                }
+                Some(Err(err)) => {
+                    self.error = Some(format!("Failed to load terrain: {}", err));
+                    state.loading = None;
+                }
+                None => ui.ctx().request_repaint(),
+            }
+        }
+
+        if let Some(grid) = &state.dem {
+            if state.candidates.as_ref().is_none_or(|(b, _)| *b != bearing) {
+                state.candidates = Some((bearing, viewshed::candidates(grid, bearing)));
+                state.shown = None;
+            }
+        }
+
+        // Some(None) clears the outline
+        let mut new_outline = None;
+        if let Some(promise) = &state.outlining {
+            match promise.ready() {
+                Some(Ok(Some(traced))) => {
+                    let projected = state.dem.as_ref().zip(bearing).and_then(|(grid, bearing)| {
+                        let pose = georef::estimate_pose(grid, bearing, attached.as_ref(), hfov);
+                        georef::project_outline(grid, &pose, traced)
+                    });
+                    match projected {
+                        Some(polygon) => new_outline = Some(Some(polygon)),
+                        None => self.error = Some("The traced outline does not land on the loaded terrain".to_string()),
+                    }
+                    state.outlining = None;
+                }
+                Some(Ok(None)) => {
+                    self.error = Some("The model found no avalanche to outline".to_string());
+                    state.outlining = None;
+                }
+                Some(Err(err)) => {
+                    self.error = Some(err.user_message());
+                    state.outlining = None;
+                }
+                None => ui.ctx().request_repaint(),
+            }
+        }
+
+        let mut start_outline = false;
+        let mut attach = None;
+        egui::CollapsingHeader::new("3D Terrain")
+            .default_open(false)
+            .show(ui, |ui| {
+                let Some(location) = location else {
+                    ui.label(
+                        egui::RichText::new("Shown for located analyses; add a location to the current result first")
+                            .size(13.0)
+                            .color(muted_color),
+                    );
+                    return;
+                };
+                let Some(grid) = &state.dem else {
+                    if state.loading.is_some() {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(egui::RichText::new("Loading elevation data…").size(13.0).color(muted_color));
+                        });
+                    } else if ui
+                        .button("⛰ Load Terrain")
+                        .on_hover_text("Fetches elevation around the photo location from Open-Meteo")
+                        .clicked()
+                    {
+                        state.loading = Some(crate::runtime::spawn(async move {
+                            Dem::fetch(&http_client(), location, dem::GRID_SIZE, dem::GRID_SPACING_M).await
+                        }));
+                    }
+                    return;
+                };
+
+                ui.horizontal(|ui| {
+                    ui.label("Vertical scale");
+                    ui.add(egui::Slider::new(&mut state.exaggeration, 1.0..=3.0).step_by(0.5).suffix("×"));
+                });
+                let width = ui.available_width().min(560.0);
+                let (rect, response) = ui.allocate_exact_size(egui::vec2(width, width * 0.6), egui::Sense::drag());
+                let drag = response.drag_delta();
+                state.yaw += drag.x * 0.01;
+                state.pitch = (state.pitch + drag.y * 0.01).clamp(0.1, std::f32::consts::FRAC_PI_2);
+
+                let highlight_color = crate::type_color(&label, crate::palette::Palette::of(ui.ctx()), accent_color);
+                let candidates = state.candidates.as_ref().map_or(&[][..], |(_, c)| c.as_slice());
+                let highlight = match state.shown.and_then(|i| candidates.get(i)) {
+                    Some(candidate) => {
+                        let mut marked = vec![false; grid.heights.len()];
+                        for &i in &candidate.cells {
+                            marked[i] = true;
+                        }
+                        marked
+                    }
+                    None => grid.highlight(bearing).iter().zip(&state.visible).map(|(h, v)| *h && *v).collect(),
+                };
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+                painter.add(egui::Shape::mesh(terrain_mesh(
+                    grid,
+                    &highlight,
+                    &state.visible,
+                    rect,
+                    state,
+                    highlight_color,
+                )));
+                if let Some(outline) = &outline {
+                    let points: Vec<egui::Pos2> = outline
+                        .iter()
+                        .map(|l| {
+                            let (east, north) = grid.offset_of(*l);
+                            let (col, row) = grid.grid_position(east, north);
+                            project(grid, state, rect, east, north, grid.height_at(col, row)).0
+                        })
+                        .collect();
+                    painter.add(egui::Shape::closed_line(points, egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 59, 48))));
+                }
+                let camera = project(grid, state, rect, 0.0, 0.0, center_height(grid)).0;
+                painter.circle_filled(camera, 4.0, accent_color);
+                painter.circle_stroke(camera, 4.0, egui::Stroke::new(1.0, egui::Color32::WHITE));
+
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Highlighted: visible {}–{}° slopes {} · shaded: hidden from the camera · drag to rotate · dot marks the photo location",
+                        dem::START_ZONE_SLOPE.start(),
+                        dem::START_ZONE_SLOPE.end(),
+                        if bearing.is_some() { "in the camera's view" } else { "around the photo location" }
+                    ))
+                    .size(13.0)
+                    .color(muted_color),
+                );
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Photographed Slope").size(13.0).strong());
+                match &attached {
+                    Some(slope) => {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(format!("✓ {}", slope.describe())).size(13.0));
+                            if ui.small_button("Detach").clicked() {
+                                attach = Some(None);
+                            }
+                        });
+                    }
+                    None if candidates.is_empty() => {
+                        ui.label(
+                            egui::RichText::new("No visible start-zone slopes found in view")
+                                .size(13.0)
+                                .color(muted_color),
+                        );
+                    }
+                    None => {
+                        ui.label(
+                            egui::RichText::new("Likely candidates, largest first; pick one to preview it")
+                                .size(13.0)
+                                .color(muted_color),
+                        );
+                        for (i, candidate) in candidates.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let shown = state.shown == Some(i);
+                                if ui.selectable_label(shown, candidate.slope.describe()).clicked() {
+                                    state.shown = if shown { None } else { Some(i) };
+                                }
+                                if ui.small_button("Confirm").on_hover_text("Attach this slope to the analysis").clicked() {
+                                    attach = Some(Some(candidate.slope.clone()));
+                                }
+                            });
+                        }
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.label(egui::RichText::new("Avalanche Outline").size(13.0).strong());
+                ui.horizontal(|ui| {
+                    if state.outlining.is_some() {
+                        ui.spinner();
+                        ui.label(egui::RichText::new("Tracing…").size(13.0).color(muted_color));
+                    } else {
+                        start_outline = ui
+                            .add_enabled(can_outline, egui::Button::new("✏ Outline Avalanche"))
+                            .on_hover_text("Ask the model to trace the avalanche and project it onto the terrain")
+                            .on_disabled_hover_text("Needs the camera bearing and a configured provider")
+                            .clicked();
+                    }
+                    if let Some(outline) = &outline {
+                        ui.label(egui::RichText::new(format!("✓ {} points, drawn in red", outline.len())).size(13.0));
+                        if ui.small_button("Clear").clicked() {
+                            new_outline = Some(None);
+                        }
+                    }
+                });
+                let lens = match hfov {
+                    Some(fov) => format!("a {:.0}° field of view from the photo's focal length", fov),
+                    None => "a 60° field of view".to_string(),
+                };
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Approximate: assumes {} aimed at the confirmed slope or the terrain ahead",
+                        lens
+                    ))
+                        .size(13.0)
+                        .color(muted_color),
+                );
+            });
+
+        if start_outline {
+            if let Some(image) = crate::analyzed_image(self.snapshot.as_ref(), self.image_data.as_ref()) {
+                let provider = self.provider();
+                self.terrain3d.outlining = Some(crate::runtime::spawn(async move {
+                    let _slot = crate::runtime::analysis_slot(crate::runtime::Priority::Interactive).await;
+                    georef::outline_avalanche(provider.as_ref(), &image).await
+                }));
+            }
+        }
+        let mut changed = false;
+        if let (Some(slope), Some(i)) = (attach, index) {
+            self.history.entries[i].slope = slope;
+            changed = true;
+        }
+        if let (Some(outline), Some(i)) = (new_outline, index) {
+            self.history.entries[i].outline = outline;
+            changed = true;
+        }
+        if let Some(i) = index.filter(|_| changed) {
+            if let Err(err) = self.history.update(&self.history.entries[i]) {
+                self.error = Some(err.to_string());
+            }
+        }
+    }
+}
+
+fn center_height(grid: &Dem) -> f32 {
+    grid.height(grid.size / 2, grid.size / 2)
+}
+
+// Orthographic projection of a point given in metres east, north and up;
+// returns the screen position and its depth (larger is further away)
+fn project(grid: &Dem, state: &Terrain3dState, rect: egui::Rect, east: f32, north: f32, up: f32) -> (egui::Pos2, f32) {
+    let extent = grid.size as f32 * grid.spacing_m;
+    let scale = rect.width().min(rect.height() * 1.4) / extent;
+    let (sin_yaw, cos_yaw) = state.yaw.sin_cos();
+    let (sin_pitch, cos_pitch) = state.pitch.sin_cos();
+    let x = east * cos_yaw - north * sin_yaw;
+    let y = east * sin_yaw + north * cos_yaw;
+    let z = (up - center_height(grid)) * state.exaggeration;
+    let screen = egui::pos2(x, -(z * cos_pitch + y * sin_pitch)) * scale;
+    (rect.center() + screen.to_vec2(), y * cos_pitch - z * sin_pitch)
+}
+
+// Flat-shaded triangles, painted back to front
+fn terrain_mesh(
+    grid: &Dem,
+    highlight: &[bool],
+    visible: &[bool],
+    rect: egui::Rect,
+    state: &Terrain3dState,
+    highlight_color: egui::Color32,
+) -> egui::Mesh {
+    // Light from the north-west and above, as on a relief map
+    let light = [-0.5f32, 0.5, 0.7];
+    let light_len = (light[0].powi(2) + light[1].powi(2) + light[2].powi(2)).sqrt();
+
+    let mut triangles = Vec::new();
+    for row in 0..grid.size - 1 {
+        for col in 0..grid.size - 1 {
+            let corners = [(col, row), (col + 1, row), (col + 1, row + 1), (col, row + 1)];
+            for tri in [[0, 1, 2], [0, 2, 3]] {
+                let points: Vec<[f32; 3]> = tri
+                    .iter()
+                    .map(|&i| {
+                        let (c, r) = corners[i];
+                        let (east, north) = grid.offset(c, r);
+                        [east, north, grid.height(c, r) * state.exaggeration]
+                    })
+                    .collect();
+                let u = [points[1][0] - points[0][0], points[1][1] - points[0][1], points[1][2] - points[0][2]];
+                let v = [points[2][0] - points[0][0], points[2][1] - points[0][1], points[2][2] - points[0][2]];
+                let normal = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+                let len = (normal[0].powi(2) + normal[1].powi(2) + normal[2].powi(2)).sqrt().max(f32::EPSILON);
+                // Rows run north to south, so the winding gives a downward normal
+                let lit = -(normal[0] * light[0] + normal[1] * light[1] + normal[2] * light[2]) / (len * light_len);
+                let mut shade = 0.35 + 0.65 * lit.max(0.0);
+                if !tri.iter().any(|&i| {
+                    let (c, r) = corners[i];
+                    visible.get(r * grid.size + c).copied().unwrap_or(true)
+                }) {
+                    shade *= 0.6;
+                }
+
+                let marked = tri.iter().any(|&i| {
+                    let (c, r) = corners[i];
+                    highlight[r * grid.size + c]
+                });
+                let base = if marked { highlight_color } else { egui::Color32::from_rgb(232, 234, 240) };
+                let color = egui::Color32::from_rgb(
+                    (base.r() as f32 * shade) as u8,
+                    (base.g() as f32 * shade) as u8,
+                    (base.b() as f32 * shade) as u8,
+                );
+
+                let projected: Vec<(egui::Pos2, f32)> = tri
+                    .iter()
+                    .map(|&i| {
+                        let (c, r) = corners[i];
+                        let (east, north) = grid.offset(c, r);
+                        project(grid, state, rect, east, north, grid.height(c, r))
+                    })
+                    .collect();
+                let depth = projected.iter().map(|(_, d)| d).sum::<f32>() / 3.0;
+                triangles.push((depth, [projected[0].0, projected[1].0, projected[2].0], color));
+            }
+        }
+    }
+    triangles.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut mesh = egui::Mesh::default();
+    for (_, corners, color) in triangles {
+        let start = mesh.vertices.len() as u32;
+        for pos in corners {
+            mesh.colored_vertex(pos, color);
+        }
+        mesh.add_triangle(start, start + 1, start + 2);
+    }
+    mesh
+}
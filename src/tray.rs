@@ -0,0 +1,184 @@
+// Not built on Linux, where the tray needs a GTK main loop that eframe does
+// not run
+use crate::AvalancheClassifier;
+use eframe::egui;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tray_icon::{ClickType, Icon, TrayIcon, TrayIconBuilder, TrayIconEvent};
+
+const ICON_SIZE: u32 = 32;
+
+// What the tray icon shows, by color
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Monitoring,
+    Paused,
+    // Alerts raised and not yet acknowledged
+    Alert(usize),
+}
+
+impl Status {
+    fn color(self) -> [u8; 3] {
+        match self {
+            Status::Monitoring => [52, 199, 89],
+            Status::Paused => [142, 142, 147],
+            Status::Alert(_) => [255, 59, 48],
+        }
+    }
+
+    fn tooltip(self) -> String {
+        match self {
+            Status::Monitoring => "Avalanche monitor: watching".to_string(),
+            Status::Paused => "Avalanche monitor: paused".to_string(),
+            Status::Alert(1) => "Avalanche monitor: 1 open alert".to_string(),
+            Status::Alert(count) => format!("Avalanche monitor: {} open alerts", count),
+        }
+    }
+
+    // A filled dot in the status color with a light rim, legible on light
+    // and dark trays
+    fn icon(self) -> anyhow::Result<Icon> {
+        let [r, g, b] = self.color();
+        let center = ICON_SIZE as f32 / 2.0 - 0.5;
+        let radius = ICON_SIZE as f32 / 2.0 - 2.0;
+        let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+        for y in 0..ICON_SIZE {
+            for x in 0..ICON_SIZE {
+                let distance = (x as f32 - center).hypot(y as f32 - center);
+                let pixel = if distance <= radius - 2.0 {
+                    [r, g, b, 255]
+                } else if distance <= radius {
+                    [255, 255, 255, 230]
+                } else {
+                    [0, 0, 0, 0]
+                };
+                rgba.extend_from_slice(&pixel);
+            }
+        }
+        Ok(Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE)?)
+    }
+}
+
+struct Tray {
+    icon: TrayIcon,
+    pause: MenuItem,
+    status: Status,
+    // Whether cameras are monitored, flipped straight from the menu
+    monitoring: Arc<AtomicBool>,
+    // The value last agreed with the settings, to tell which side changed
+    synced: bool,
+}
+
+impl Tray {
+    fn new(status: Status, monitoring: bool, ctx: &egui::Context) -> anyhow::Result<Self> {
+        let show = MenuItem::new("Show Window", true, None);
+        let pause = MenuItem::new(pause_label(status), true, None);
+        let quit = MenuItem::new("Quit", true, None);
+        let menu = Menu::new();
+        menu.append_items(&[&show, &pause, &PredefinedMenuItem::separator(), &quit])?;
+        let icon = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip(status.tooltip())
+            .with_icon(status.icon()?)
+            .build()?;
+
+        // Events are acted on as they arrive instead of waiting for the next
+        // frame to drain them
+        let shared = Arc::new(AtomicBool::new(monitoring));
+        let (show_id, pause_id, quit_id) = (show.id().clone(), pause.id().clone(), quit.id().clone());
+        let (menu_ctx, flag) = (ctx.clone(), shared.clone());
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if event.id == show_id {
+                show_window(&menu_ctx);
+            } else if event.id == pause_id {
+                flag.fetch_xor(true, Ordering::Relaxed);
+            } else if event.id == quit_id {
+                menu_ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            menu_ctx.request_repaint();
+        }));
+        let icon_ctx = ctx.clone();
+        TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+            if event.click_type == ClickType::Left {
+                show_window(&icon_ctx);
+                icon_ctx.request_repaint();
+            }
+        }));
+        Ok(Self { icon, pause, status, monitoring: shared, synced: monitoring })
+    }
+
+    // Takes up a pause or resume from the menu, or hands over one made in
+    // the window
+    fn sync_monitoring(&mut self, monitoring: &mut bool) {
+        let from_menu = self.monitoring.load(Ordering::Relaxed);
+        if from_menu != self.synced {
+            *monitoring = from_menu;
+        } else {
+            self.monitoring.store(*monitoring, Ordering::Relaxed);
+        }
+        self.synced = *monitoring;
+    }
+
+    fn set_status(&mut self, status: Status) -> anyhow::Result<()> {
+        if status != self.status {
+            self.icon.set_icon(Some(status.icon()?))?;
+            self.icon.set_tooltip(Some(status.tooltip()))?;
+            self.pause.set_text(pause_label(status));
+            self.status = status;
+        }
+        Ok(())
+    }
+}
+
+fn pause_label(status: Status) -> &'static str {
+    if status == Status::Paused {
+        "Resume Monitoring"
+    } else {
+        "Pause Monitoring"
+    }
+}
+
+#[derive(Default)]
+pub struct TrayState {
+    tray: Option<Tray>,
+}
+
+impl AvalancheClassifier {
+    // Keeps the tray icon in step with monitoring and open alerts. Runs
+    // before the cameras are polled so a pause from the menu holds for the
+    // same frame. A minimized window stays minimized rather than hidden:
+    // eframe runs no frames for a hidden window, and camera polling and
+    // alert escalation happen in them.
+    pub(crate) fn poll_tray(&mut self, ctx: &egui::Context) {
+        if !self.settings.minimize_to_tray {
+            self.tray.tray = None;
+            return;
+        }
+        if let Some(tray) = &mut self.tray.tray {
+            tray.sync_monitoring(&mut self.settings.monitoring);
+        }
+        let status = match self.open_alerts() {
+            0 if self.settings.monitoring => Status::Monitoring,
+            0 => Status::Paused,
+            count => Status::Alert(count),
+        };
+        let result = match self.tray.tray.take() {
+            Some(mut tray) => tray.set_status(status).map(|()| tray),
+            None => Tray::new(status, self.settings.monitoring, ctx),
+        };
+        match result {
+            Ok(tray) => self.tray.tray = Some(tray),
+            Err(err) => {
+                self.error = Some(format!("System tray unavailable: {}", err));
+                self.settings.minimize_to_tray = false;
+            }
+        }
+    }
+}
+
+fn show_window(ctx: &egui::Context) {
+    ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+}
@@ -0,0 +1,131 @@
+use crate::palette::Palette;
+use crate::terrain::severity_color;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::gpx::Gpx;
+use avalanche_classifier_core::trip::{self, TripPlan, TripPlans};
+use eframe::egui;
+
+pub struct TripsState {
+    plans: TripPlans,
+    selected: Option<u64>,
+}
+
+impl Default for TripsState {
+    fn default() -> Self {
+        let plans = TripPlans::load_default();
+        let selected = plans.plans.last().map(|p| p.id);
+        Self { plans, selected }
+    }
+}
+
+fn import_gpx(path: &std::path::Path) -> anyhow::Result<TripPlan> {
+    let gpx = Gpx::parse(&std::fs::read_to_string(path)?)?;
+    let name = gpx.name.clone().unwrap_or_else(|| {
+        path.file_stem().map_or("Route".to_string(), |s| s.to_string_lossy().into_owned())
+    });
+    TripPlan::from_gpx(name, &gpx)
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn trips_ui(&mut self, ui: &mut egui::Ui, danger_color: egui::Color32, muted_color: egui::Color32) {
+        egui::CollapsingHeader::new(format!("Trip Plans ({})", self.trips.plans.plans.len()))
+            .default_open(false)
+            .show(ui, |ui| {
+                let mut import = false;
+                let mut remove = false;
+                ui.horizontal(|ui| {
+                    let current = self
+                        .trips
+                        .plans
+                        .plans
+                        .iter()
+                        .find(|p| Some(p.id) == self.trips.selected)
+                        .map_or("No plan", |p| p.name.as_str());
+                    egui::ComboBox::from_id_source("trip-plan")
+                        .selected_text(current)
+                        .show_ui(ui, |ui| {
+                            for plan in &self.trips.plans.plans {
+                                ui.selectable_value(&mut self.trips.selected, Some(plan.id), &plan.name);
+                            }
+                        });
+                    import = ui.button("📁 Import GPX Route").clicked();
+                    if self.trips.selected.is_some() {
+                        remove = ui.small_button("✕").on_hover_text("Delete this plan").clicked();
+                    }
+                });
+                if import {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("GPX", &["gpx"]).pick_file() {
+                        match import_gpx(&path).and_then(|plan| self.trips.plans.add(plan)) {
+                            Ok(id) => self.trips.selected = Some(id),
+                            Err(err) => self.error = Some(format!("Failed to import route: {}", err)),
+                        }
+                    }
+                }
+                if remove {
+                    self.trips.plans.plans.retain(|p| Some(p.id) != self.trips.selected);
+                    self.trips.selected = self.trips.plans.plans.last().map(|p| p.id);
+                    if let Err(err) = self.trips.plans.save() {
+                        self.error = Some(err.to_string());
+                    }
+                }
+
+                let Some(plan) = self.trips.plans.plans.iter().find(|p| Some(p.id) == self.trips.selected) else {
+                    return;
+                };
+                let assessment = trip::assess(plan, &self.history);
+                let plan_id = plan.id;
+                let mut tag = None;
+                ui.add_space(8.0);
+                egui::Grid::new("trip-segments").striped(true).show(ui, |ui| {
+                    for (i, segment) in assessment.segments.iter().enumerate() {
+                        let riskiest = assessment.riskiest.contains(&i);
+                        let name = if riskiest { format!("⚠ {}", segment.name) } else { segment.name.clone() };
+                        ui.label(
+                            egui::RichText::new(name)
+                                .size(13.0)
+                                .strong()
+                                .color(if riskiest { danger_color } else { ui.visuals().text_color() }),
+                        );
+                        ui.label(
+                            egui::RichText::new(format!("{:.1} km", segment.length_m / 1000.0))
+                                .size(13.0)
+                                .color(muted_color),
+                        );
+                        match &segment.worst {
+                            Some(worst) => ui.label(
+                                egui::RichText::new(format!(
+                                    "{} {:.0}% · {} analyses",
+                                    worst.avalanche_type, worst.confidence, segment.analyses
+                                ))
+                                .size(13.0)
+                                .color(severity_color(segment.severity(), Palette::of(ui.ctx()))),
+                            ),
+                            None => ui.label(egui::RichText::new("no analyses").size(13.0).color(muted_color)),
+                        };
+                        if let Some(snapshot) = &self.snapshot {
+                            if ui.small_button("📎").on_hover_text("Tag the current result to this segment").clicked() {
+                                tag = Some((i, snapshot.id.clone()));
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Located analyses within {:.0} m of a segment count for it automatically",
+                        trip::NEARBY_M
+                    ))
+                    .size(13.0)
+                    .color(muted_color),
+                );
+                if ui.button("📋 Copy Route Summary").clicked() {
+                    ui.output_mut(|o| o.copied_text = assessment.text());
+                }
+                if let Some((segment, analysis_id)) = tag {
+                    if let Err(err) = self.trips.plans.tag(plan_id, segment, &analysis_id) {
+                        self.error = Some(err.to_string());
+                    }
+                }
+            });
+    }
+}
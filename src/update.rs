@@ -0,0 +1,148 @@
+use crate::AvalancheClassifier;
+use eframe::egui;
+use poll_promise::Promise;
+use serde::Deserialize;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/ronnakamoto/avalanche-classifier/releases/latest";
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Release {
+    pub tag_name: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    // Asset built for this OS and architecture, going by the usual naming
+    // (e.g. avalanche-classifier-linux-x86_64.tar.gz)
+    pub fn platform_asset(&self) -> Option<&Asset> {
+        let os = match std::env::consts::OS {
+            "macos" => ["macos", "darwin"],
+            "windows" => ["windows", "win"],
+            other => [other, other],
+        };
+        self.assets.iter().find(|asset| {
+            let name = asset.name.to_lowercase();
+            os.iter().any(|o| name.contains(o)) && name.contains(std::env::consts::ARCH)
+        })
+    }
+}
+
+// Latest release if it is newer than the running build
+pub async fn check_for_update() -> anyhow::Result<Option<Release>> {
+    let release: Release = avalanche_classifier_core::provider::http_client()
+        .get(RELEASES_URL)
+        .header("User-Agent", concat!("avalanche-classifier/", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if is_newer(&release.tag_name, env!("CARGO_PKG_VERSION")) {
+        Ok(Some(release))
+    } else {
+        Ok(None)
+    }
+}
+
+// Downloads the asset into the updates directory and returns where it was
+// staged; installing it is left to the user since it replaces the binary
+pub async fn download(asset: &Asset) -> anyhow::Result<std::path::PathBuf> {
+    let bytes = avalanche_classifier_core::provider::http_client()
+        .get(&asset.browser_download_url)
+        .header("User-Agent", concat!("avalanche-classifier/", env!("CARGO_PKG_VERSION")))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let dir = avalanche_classifier_core::storage::data_dir().join("updates");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(&asset.name);
+    std::fs::write(&path, &bytes)?;
+    Ok(path)
+}
+
+fn is_newer(tag: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split(['.', '-'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parse(tag) > parse(current)
+}
+
+#[derive(Default)]
+pub struct UpdateState {
+    check: Option<Promise<anyhow::Result<Option<Release>>>>,
+    download: Option<Promise<anyhow::Result<std::path::PathBuf>>>,
+    available: Option<Release>,
+    status: Option<String>,
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn update_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let state = &mut self.update;
+
+        if let Some(result) = state.check.as_ref().and_then(|p| p.ready()) {
+            match result {
+                Ok(Some(release)) => state.available = Some(release.clone()),
+                Ok(None) => state.status = Some("You're on the latest version".to_string()),
+                Err(err) => state.status = Some(format!("Update check failed: {}", err)),
+            }
+            state.check = None;
+        }
+        if let Some(result) = state.download.as_ref().and_then(|p| p.ready()) {
+            state.status = Some(match result {
+                Ok(path) => format!("Update downloaded to {}", path.display()),
+                Err(err) => format!("Download failed: {}", err),
+            });
+            state.download = None;
+        }
+
+        if let Some(release) = &state.available {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new(format!("Version {} is available", release.tag_name))
+                        .size(13.0)
+                        .strong()
+                );
+                ui.hyperlink_to("Release notes", &release.html_url);
+                if let Some(asset) = release.platform_asset() {
+                    if ui
+                        .add_enabled(state.download.is_none(), egui::Button::new("⬇ Download"))
+                        .clicked()
+                    {
+                        let asset = asset.clone();
+                        state.download = Some(crate::runtime::spawn(async move { download(&asset).await }));
+                    }
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            let busy = state.check.is_some() || state.download.is_some();
+            if ui.add_enabled(!busy, egui::Button::new("Check for Updates")).clicked() {
+                state.status = None;
+                state.check = Some(crate::runtime::spawn(check_for_update()));
+            }
+            if busy {
+                ui.spinner();
+            }
+            if let Some(status) = &state.status {
+                ui.label(egui::RichText::new(status).size(13.0).color(muted_color));
+            }
+        });
+    }
+}
@@ -0,0 +1,90 @@
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::fetch::{download_image, Progress};
+use avalanche_classifier_core::provider::http_client;
+use eframe::egui;
+use poll_promise::Promise;
+use std::sync::Arc;
+
+// URLs kept in settings for quick reloading
+const MAX_RECENT: usize = 10;
+
+#[derive(Default)]
+pub struct UrlLoaderState {
+    url: String,
+    promise: Option<Promise<anyhow::Result<Vec<u8>>>>,
+    progress: Arc<Progress>,
+}
+
+impl AvalancheClassifier {
+    fn start_url_download(&mut self, url: String) {
+        let progress = Arc::new(Progress::default());
+        self.url_loader.progress = progress.clone();
+        self.url_loader.promise = Some(crate::runtime::spawn(async move {
+            download_image(&http_client(), &url, &progress).await
+        }));
+    }
+
+    pub(crate) fn url_loader_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, muted_color: egui::Color32) {
+        let downloading = self.url_loader.promise.is_some();
+        ui.horizontal(|ui| {
+            let edit = ui.add(
+                egui::TextEdit::singleline(&mut self.url_loader.url)
+                    .hint_text("https://… image or webcam URL")
+                    .desired_width(280.0),
+            );
+            let submitted = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let can_load = !downloading && !self.url_loader.url.trim().is_empty();
+            if (ui.add_enabled(can_load, egui::Button::new("🌐 Load URL")).clicked() || submitted) && can_load {
+                let url = self.url_loader.url.trim().to_string();
+                self.start_url_download(url);
+            }
+            if !self.settings.recent_urls.is_empty() {
+                egui::ComboBox::from_id_source("recent-urls")
+                    .selected_text("Recent")
+                    .width(70.0)
+                    .show_ui(ui, |ui| {
+                        for url in self.settings.recent_urls.clone() {
+                            if ui.selectable_label(false, &url).clicked() {
+                                self.url_loader.url = url;
+                            }
+                        }
+                    });
+            }
+        });
+
+        let Some(promise) = self.url_loader.promise.take() else {
+            return;
+        };
+        match promise.try_take() {
+            Ok(Ok(bytes)) => {
+                let url = self.url_loader.url.trim().to_string();
+                self.settings.recent_urls.retain(|u| *u != url);
+                self.settings.recent_urls.insert(0, url);
+                self.settings.recent_urls.truncate(MAX_RECENT);
+                self.error = None;
+                self.load_image(ctx, bytes);
+            }
+            Ok(Err(err)) => self.error = Some(format!("Download failed: {}", err)),
+            Err(promise) => {
+                let progress = &self.url_loader.progress;
+                match progress.fraction() {
+                    Some(fraction) => {
+                        ui.add(egui::ProgressBar::new(fraction).show_percentage().desired_width(280.0));
+                    }
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(
+                                egui::RichText::new(format!("{} KB", progress.received() / 1024))
+                                    .size(13.0)
+                                    .color(muted_color),
+                            );
+                        });
+                    }
+                }
+                ctx.request_repaint();
+                self.url_loader.promise = Some(promise);
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::verification::{verify_control_result, ControlVerification};
+use avalanche_classifier_core::AnalysisError;
+use eframe::egui;
+use poll_promise::Promise;
+use serde::{Deserialize, Serialize};
+
+// Which prompt profile the Analyze button runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub(crate) enum AnalysisMode {
+    #[default]
+    Classification,
+    // Post-control photo: did the shot produce a result?
+    ControlVerification,
+}
+
+impl AnalysisMode {
+    pub const ALL: [AnalysisMode; 2] = [AnalysisMode::Classification, AnalysisMode::ControlVerification];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AnalysisMode::Classification => "Avalanche Analysis",
+            AnalysisMode::ControlVerification => "Control Verification",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct VerificationState {
+    promise: Option<Promise<Result<ControlVerification, AnalysisError>>>,
+    result: Option<ControlVerification>,
+}
+
+impl VerificationState {
+    pub fn cancel(&mut self) {
+        self.promise = None;
+    }
+}
+
+impl AvalancheClassifier {
+    pub(crate) fn start_verification(&mut self) {
+        let Some(image_data) = &mut self.image_data else {
+            return;
+        };
+        let image_bytes = match image_data.analysis_bytes() {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                self.error = Some(format!("Could not crop the image: {}", err));
+                return;
+            }
+        };
+        let provider = self.provider();
+        let (promise, abort) = crate::runtime::spawn_abortable(async move {
+            let _slot = crate::runtime::analysis_slot(crate::runtime::Priority::Interactive).await;
+            verify_control_result(provider.as_ref(), &image_bytes).await
+        });
+        self.verification.promise = Some(promise);
+        self.analysis_abort = Some(abort);
+    }
+
+    // Collects a finished verification and records it in the active mission
+    pub(crate) fn poll_verification(&mut self, ui: &mut egui::Ui, cancel: &mut bool) {
+        let Some(promise) = self.verification.promise.take() else {
+            return;
+        };
+        match promise.try_take() {
+            Ok(Ok(verification)) => {
+                self.analysis_abort = None;
+                self.error = None;
+                self.record_mission_verification(&verification);
+                self.verification.result = Some(verification);
+            }
+            Ok(Err(err)) => {
+                self.analysis_abort = None;
+                self.error = Some(err.user_message());
+                self.verification.result = None;
+            }
+            Err(promise) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Checking control result...");
+                    *cancel = ui.button("Cancel").clicked();
+                });
+                self.verification.promise = Some(promise);
+            }
+        }
+    }
+
+    pub(crate) fn verification_results_ui(
+        &self,
+        ui: &mut egui::Ui,
+        success_color: egui::Color32,
+        warning_color: egui::Color32,
+        muted_color: egui::Color32,
+    ) {
+        if self.settings.mode != crate::verification::AnalysisMode::ControlVerification {
+            return;
+        }
+        let Some(result) = &self.verification.result else {
+            return;
+        };
+        ui.add_space(16.0);
+        ui.vertical_centered(|ui| {
+            let (headline, color) = if result.result_produced {
+                (format!("Result: {}", result.result_size.as_deref().unwrap_or("size unknown")), warning_color)
+            } else {
+                ("No Result".to_string(), success_color)
+            };
+            ui.label(egui::RichText::new(headline).size(24.0).color(color).strong());
+            ui.label(
+                egui::RichText::new(format!("Confidence {:.0}%", result.confidence_level))
+                    .size(14.0)
+                    .color(muted_color),
+            );
+        });
+        ui.add_space(8.0);
+        ui.group(|ui| {
+            ui.label(egui::RichText::new("Control Verification").size(16.0).strong());
+            let rows = [
+                ("Propagation", result.propagation.clone()),
+                ("Crown visible", if result.crown_visible { "yes" } else { "no" }.to_string()),
+                ("Debris reached", result.debris_reached.clone().unwrap_or_else(|| "—".to_string())),
+            ];
+            for (label, value) in rows {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(label).size(13.0).color(muted_color));
+                    ui.label(egui::RichText::new(value).size(13.0));
+                });
+            }
+            for observation in &result.observations {
+                ui.label(egui::RichText::new(format!("• {}", observation)).size(13.0));
+            }
+        });
+    }
+}
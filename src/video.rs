@@ -0,0 +1,308 @@
+use crate::palette::Palette;
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::ffmpeg::{self, Sampling, VideoFrame};
+use avalanche_classifier_core::{fallback, Classification, ClassifyOptions};
+use eframe::egui;
+use poll_promise::Promise;
+use std::path::PathBuf;
+
+const MAX_CONCURRENCY: usize = 8;
+const TIMELINE_HEIGHT: f32 = 18.0;
+
+enum FrameStatus {
+    Queued,
+    Running(Promise<anyhow::Result<Classification>>, tokio::task::AbortHandle),
+    Done(Box<Classification>),
+    Failed(String),
+}
+
+struct Frame {
+    at_secs: f64,
+    jpeg: Vec<u8>,
+    status: FrameStatus,
+}
+
+#[derive(Default)]
+pub struct VideoState {
+    path: Option<PathBuf>,
+    extraction: Option<(Promise<anyhow::Result<Vec<VideoFrame>>>, tokio::task::AbortHandle)>,
+    frames: Vec<Frame>,
+    error: Option<String>,
+}
+
+impl VideoState {
+    fn pending(&self) -> bool {
+        self.extraction.is_some()
+            || self.frames.iter().any(|f| matches!(f.status, FrameStatus::Queued | FrameStatus::Running(..)))
+    }
+
+    // Aborting the extraction drops ffmpeg's process handle, which kills it
+    fn cancel(&mut self) {
+        if let Some((_, abort)) = self.extraction.take() {
+            abort.abort();
+        }
+        for frame in &mut self.frames {
+            match &frame.status {
+                FrameStatus::Running(_, abort) => abort.abort(),
+                FrameStatus::Queued => {}
+                _ => continue,
+            }
+            frame.status = FrameStatus::Failed("Cancelled".to_string());
+        }
+    }
+
+    // The first frame showing avalanche activity
+    fn first_activity(&self) -> Option<(usize, &Classification)> {
+        self.frames.iter().enumerate().find_map(|(i, frame)| match &frame.status {
+            FrameStatus::Done(classification) if classification.analysis.avalanche_present => {
+                Some((i, &**classification))
+            }
+            _ => None,
+        })
+    }
+}
+
+impl AvalancheClassifier {
+    // Collects extracted frames and classifications, and starts queued
+    // frames up to the batch concurrency. Frames run behind photos analyzed
+    // by hand, like the batch queue.
+    pub(crate) fn poll_video(&mut self, ctx: &egui::Context) {
+        if !self.video.pending() {
+            return;
+        }
+        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+
+        if let Some((promise, abort)) = self.video.extraction.take() {
+            match promise.try_take() {
+                Ok(Ok(frames)) => {
+                    self.video.frames = frames
+                        .into_iter()
+                        .map(|f| Frame { at_secs: f.at_secs, jpeg: f.jpeg, status: FrameStatus::Queued })
+                        .collect();
+                }
+                Ok(Err(err)) => self.video.error = Some(err.to_string()),
+                Err(promise) => self.video.extraction = Some((promise, abort)),
+            }
+        }
+
+        for frame in &mut self.video.frames {
+            frame.status = match std::mem::replace(&mut frame.status, FrameStatus::Queued) {
+                FrameStatus::Running(promise, abort) => match promise.try_take() {
+                    Ok(Ok(classification)) => FrameStatus::Done(Box::new(classification)),
+                    Ok(Err(err)) => FrameStatus::Failed(err.to_string()),
+                    Err(promise) => FrameStatus::Running(promise, abort),
+                },
+                status => status,
+            };
+        }
+
+        if !self.has_api_key() {
+            return;
+        }
+        let limit = self.settings.batch_concurrency.clamp(1, MAX_CONCURRENCY);
+        let running = self.video.frames.iter().filter(|f| matches!(f.status, FrameStatus::Running(..))).count();
+        let mut free = limit.saturating_sub(running);
+        for i in 0..self.video.frames.len() {
+            if free == 0 {
+                break;
+            }
+            if !matches!(self.video.frames[i].status, FrameStatus::Queued) {
+                continue;
+            }
+            let chain = self.fallback_chain();
+            let bytes = self.video.frames[i].jpeg.clone();
+            let (promise, abort) = crate::runtime::spawn_abortable(async move {
+                let _slot = crate::runtime::analysis_slot(crate::runtime::Priority::Batch).await;
+                fallback::classify_with_fallback(&chain, &bytes, &ClassifyOptions::current())
+                    .await
+                    .map_err(anyhow::Error::from)
+            });
+            self.video.frames[i].status = FrameStatus::Running(promise, abort);
+            free -= 1;
+        }
+    }
+
+    fn open_video(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("Video", &ffmpeg::VIDEO_EXTENSIONS).pick_file() else {
+            return;
+        };
+        self.video.cancel();
+        self.video.frames.clear();
+        self.video.error = None;
+        let sampling = self.settings.video_sampling;
+        let source = path.clone();
+        self.video.extraction =
+            Some(crate::runtime::spawn_abortable(async move { ffmpeg::extract_frames(&source, sampling).await }));
+        self.video.path = Some(path);
+    }
+
+    pub(crate) fn video_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, muted_color: egui::Color32) {
+        let palette = Palette::of(ctx);
+        let done = self.video.frames.iter().filter(|f| matches!(f.status, FrameStatus::Done(_))).count();
+        let title = match &self.video.path {
+            Some(path) => format!(
+                "Video: {} ({}/{})",
+                path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned()),
+                done,
+                self.video.frames.len()
+            ),
+            None => "Video".to_string(),
+        };
+        let mut open_video = false;
+        let mut open_frame = None;
+        egui::CollapsingHeader::new(title)
+            .id_source("video")
+            .default_open(self.video.path.is_some())
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let sampling = &mut self.settings.video_sampling;
+                    let interval = matches!(sampling, Sampling::Interval { .. });
+                    if ui.radio(interval, "Every").clicked() && !interval {
+                        *sampling = Sampling::default();
+                    }
+                    if let Sampling::Interval { secs } = sampling {
+                        ui.add(egui::DragValue::new(secs).speed(0.5).clamp_range(0.5..=600.0).suffix(" s"));
+                    }
+                    if ui.radio(!interval, "At scene changes").clicked() && interval {
+                        *sampling = Sampling::SceneChange { threshold: 0.3 };
+                    }
+                    if let Sampling::SceneChange { threshold } = sampling {
+                        ui.add(egui::DragValue::new(threshold).speed(0.01).clamp_range(0.05..=0.9))
+                            .on_hover_text("How much the picture has to change, from 0 to 1");
+                    }
+                });
+                ui.horizontal(|ui| {
+                    open_video = ui.button("🎞 Analyze Video…").clicked();
+                    if self.video.pending() {
+                        if ui.button("Cancel").clicked() {
+                            self.video.cancel();
+                        }
+                        ui.spinner();
+                    }
+                });
+                if self.video.extraction.is_some() {
+                    ui.label(egui::RichText::new("Extracting frames with ffmpeg…").size(13.0).color(muted_color));
+                }
+                if let Some(error) = &self.video.error {
+                    ui.label(egui::RichText::new(error).size(13.0).color(palette.danger));
+                }
+                if self.video.frames.is_empty() {
+                    return;
+                }
+
+                match self.video.first_activity() {
+                    Some((i, classification)) => {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "Activity first appears at {}: {}, {:.0}%",
+                                clip_time(self.video.frames[i].at_secs),
+                                crate::type_title(&classification.analysis.avalanche_type),
+                                classification.analysis.confidence_level
+                            ))
+                            .size(14.0)
+                            .strong(),
+                        );
+                    }
+                    None if done == self.video.frames.len() => {
+                        ui.label(egui::RichText::new("No avalanche activity in any frame").size(14.0));
+                    }
+                    None => {}
+                }
+                open_frame = timeline(ui, &self.video, palette, muted_color);
+                if self.video.frames.len() == ffmpeg::MAX_VIDEO_FRAMES {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Only the first {} frames were extracted",
+                            ffmpeg::MAX_VIDEO_FRAMES
+                        ))
+                        .size(13.0)
+                        .color(muted_color),
+                    );
+                }
+            });
+
+        if open_video {
+            self.open_video();
+        }
+        // Shows the frame and its result as if the frame had been analyzed
+        // on its own
+        if let Some(i) = open_frame {
+            let frame = &self.video.frames[i];
+            if let FrameStatus::Done(classification) = &frame.status {
+                let analysis = classification.analysis.clone();
+                let snapshot = classification.snapshot.clone();
+                self.load_image(ctx, frame.jpeg.clone());
+                if let Some(image_data) = &mut self.image_data {
+                    image_data.show_annotations(analysis.annotations.as_ref());
+                }
+                self.result = Some(analysis);
+                self.snapshot = snapshot;
+                self.ensemble = None;
+                self.error = None;
+            }
+        }
+    }
+}
+
+// One cell per frame colored by its result, the first with activity
+// outlined; returns the frame clicked
+fn timeline(ui: &mut egui::Ui, video: &VideoState, palette: Palette, muted_color: egui::Color32) -> Option<usize> {
+    let count = video.frames.len();
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), TIMELINE_HEIGHT), egui::Sense::click());
+    let cell_width = rect.width() / count as f32;
+    let cell = |i: usize| {
+        egui::Rect::from_min_size(
+            rect.min + egui::vec2(i as f32 * cell_width, 0.0),
+            egui::vec2((cell_width - 1.0).max(1.0), TIMELINE_HEIGHT),
+        )
+    };
+    let painter = ui.painter_at(rect);
+    for (i, frame) in video.frames.iter().enumerate() {
+        let color = match &frame.status {
+            FrameStatus::Done(c) if c.analysis.avalanche_present => {
+                crate::type_color(&c.analysis.avalanche_type, palette, muted_color)
+            }
+            FrameStatus::Done(_) => muted_color.gamma_multiply(0.4),
+            FrameStatus::Failed(_) => palette.danger.gamma_multiply(0.3),
+            FrameStatus::Queued | FrameStatus::Running(..) => muted_color.gamma_multiply(0.15),
+        };
+        painter.rect_filled(cell(i), 1.0, color);
+    }
+    if let Some((first, _)) = video.first_activity() {
+        painter.rect_stroke(cell(first).expand(1.0), 1.0, egui::Stroke::new(2.0, ui.visuals().strong_text_color()));
+    }
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(clip_time(video.frames[0].at_secs)).size(12.0).color(muted_color));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.label(egui::RichText::new(clip_time(video.frames[count - 1].at_secs)).size(12.0).color(muted_color));
+        });
+    });
+
+    let hovered = response
+        .hover_pos()
+        .map(|pos| (((pos.x - rect.min.x) / cell_width) as usize).min(count - 1))?;
+    let frame = &video.frames[hovered];
+    let text = match &frame.status {
+        FrameStatus::Done(c) => format!(
+            "{} · {} · {:.0}%\nClick to open",
+            clip_time(frame.at_secs),
+            crate::type_title(&c.analysis.avalanche_type),
+            c.analysis.confidence_level
+        ),
+        FrameStatus::Failed(message) => format!("{} · {}", clip_time(frame.at_secs), message),
+        FrameStatus::Queued | FrameStatus::Running(..) => format!("{} · waiting", clip_time(frame.at_secs)),
+    };
+    let clicked = response.clicked() && matches!(frame.status, FrameStatus::Done(_));
+    response.on_hover_text_at_pointer(text);
+    clicked.then_some(hovered)
+}
+
+// "1:05" or "1:02:05"
+fn clip_time(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    match secs / 3600 {
+        0 => format!("{}:{:02}", secs / 60, secs % 60),
+        hours => format!("{}:{:02}:{:02}", hours, secs / 60 % 60, secs % 60),
+    }
+}
@@ -0,0 +1,216 @@
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::history::HistoryEntry;
+use avalanche_classifier_core::{fallback, photo_meta, snapshot, storage, Classification, ClassifyOptions, VisionProvider};
+use eframe::egui;
+use notify::Watcher;
+use poll_promise::Promise;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+// A new file is classified once its size has held this long; cameras upload
+// frames over FTP a chunk at a time
+const SETTLE_SECS: u64 = 3;
+
+struct Watching {
+    folder: PathBuf,
+    // Dropping it stops the watch
+    _watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+}
+
+// None when the image was already in the history
+type Outcome = anyhow::Result<Option<Classification>>;
+
+#[derive(Default)]
+pub struct WatchState {
+    watching: Option<Watching>,
+    // Files still being written: last size seen and when it last changed
+    arriving: HashMap<PathBuf, (u64, u64)>,
+    queue: VecDeque<PathBuf>,
+    running: Option<(PathBuf, Promise<Outcome>)>,
+    classified: u32,
+    // File name and what came of it, for the latest file processed
+    last: Option<(String, String)>,
+}
+
+impl AvalancheClassifier {
+    // Classifies images as they appear in the watched folder and adds them
+    // to the history, one at a time in the background
+    pub(crate) fn poll_watch_folder(&mut self, ctx: &egui::Context) {
+        let folder = self.settings.watch_folder.clone();
+        if self.watch.watching.as_ref().map(|w| &w.folder) != folder.as_ref() {
+            self.watch.watching = None;
+            self.watch.arriving.clear();
+            self.watch.queue.clear();
+            if let Some(folder) = folder {
+                match watch(ctx, &folder) {
+                    Ok(watching) => self.watch.watching = Some(watching),
+                    Err(err) => {
+                        self.error = Some(format!("Could not watch {}: {}", folder.display(), err));
+                        self.settings.watch_folder = None;
+                    }
+                }
+            }
+        }
+        let Some(watching) = &self.watch.watching else {
+            return;
+        };
+
+        let now = storage::unix_now();
+        for event in watching.events.try_iter() {
+            let Ok(event) = event else {
+                continue;
+            };
+            if !matches!(event.kind, notify::EventKind::Create(_) | notify::EventKind::Modify(_)) {
+                continue;
+            }
+            for path in event.paths.into_iter().filter(|path| is_image(path)) {
+                if !self.watch.queue.contains(&path) {
+                    self.watch.arriving.entry(path).or_insert((0, now));
+                }
+            }
+        }
+        let mut settled = Vec::new();
+        self.watch.arriving.retain(|path, (size, changed_at)| {
+            let Ok(metadata) = std::fs::metadata(path) else {
+                return false;
+            };
+            if metadata.len() != *size {
+                *size = metadata.len();
+                *changed_at = now;
+            } else if now.saturating_sub(*changed_at) >= SETTLE_SECS && *size > 0 {
+                settled.push(path.clone());
+                return false;
+            }
+            true
+        });
+        settled.sort();
+        self.watch.queue.extend(settled);
+        if !self.watch.arriving.is_empty() {
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+
+        if let Some((path, promise)) = &self.watch.running {
+            let Some(outcome) = promise.ready() else {
+                ctx.request_repaint_after(std::time::Duration::from_secs(1));
+                return;
+            };
+            let name = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+            let status = match outcome {
+                Ok(Some(Classification { analysis, snapshot: Some(snapshot), fallbacks })) => {
+                    let entry = HistoryEntry {
+                        fallbacks: fallbacks.clone(),
+                        ..HistoryEntry::new(snapshot.clone(), analysis.clone())
+                    };
+                    if let Err(err) = self.history.push(entry) {
+                        self.error = Some(err.to_string());
+                    }
+                    self.watch.classified += 1;
+                    format!("{} · {:.0}%", crate::type_title(&analysis.avalanche_type), analysis.confidence_level)
+                }
+                Ok(Some(_)) => "Classified without a snapshot, not recorded".to_string(),
+                Ok(None) => "Already in history".to_string(),
+                Err(err) => err.to_string(),
+            };
+            self.watch.last = Some((name, status));
+            self.watch.running = None;
+        }
+
+        if !self.has_api_key() {
+            return;
+        }
+        let Some(path) = self.watch.queue.pop_front() else {
+            return;
+        };
+        let chain = self.fallback_chain();
+        let known: HashSet<String> = self.history.entries.iter().map(|e| e.snapshot.original_hash().to_string()).collect();
+        let promise = crate::runtime::spawn(classify_file(path.clone(), chain, known));
+        self.watch.running = Some((path, promise));
+        ctx.request_repaint_after(std::time::Duration::from_secs(1));
+    }
+
+    pub(crate) fn watch_ui(&mut self, ui: &mut egui::Ui, muted_color: egui::Color32) {
+        let has_api_key = self.has_api_key();
+        let state = &self.watch;
+        let title = if state.watching.is_some() { "Watch Folder (on)" } else { "Watch Folder" };
+        egui::CollapsingHeader::new(title)
+            .id_source("watch_folder")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new("New images in the folder are classified and added to the history")
+                        .size(13.0)
+                        .color(muted_color)
+                );
+                ui.horizontal(|ui| {
+                    let folder_label = self
+                        .settings
+                        .watch_folder
+                        .as_ref()
+                        .and_then(|f| f.file_name())
+                        .map_or("Choose Folder".to_string(), |n| n.to_string_lossy().into_owned());
+                    if ui.button(folder_label).clicked() {
+                        if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                            self.settings.watch_folder = Some(folder);
+                        }
+                    }
+                    if self.settings.watch_folder.is_some() && ui.button("Stop").clicked() {
+                        self.settings.watch_folder = None;
+                    }
+                    if state.running.is_some() {
+                        ui.spinner();
+                    }
+                });
+                if state.watching.is_none() {
+                    return;
+                }
+                if !has_api_key {
+                    ui.label(
+                        egui::RichText::new("Waiting for an API key before classifying")
+                            .size(13.0)
+                            .color(muted_color)
+                    );
+                }
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} classified · {} waiting",
+                        state.classified,
+                        state.arriving.len() + state.queue.len()
+                    ))
+                    .size(13.0)
+                    .color(muted_color)
+                );
+                if let Some((name, status)) = &state.last {
+                    ui.label(egui::RichText::new(format!("{}: {}", name, status)).size(13.0).color(muted_color));
+                }
+            });
+    }
+}
+
+async fn classify_file(path: PathBuf, chain: Vec<Box<dyn VisionProvider>>, known: HashSet<String>) -> Outcome {
+    let bytes = std::fs::read(&path)?;
+    if known.contains(&snapshot::hash_bytes(&bytes)) {
+        return Ok(None);
+    }
+    let _slot = crate::runtime::analysis_slot(crate::runtime::Priority::Background).await;
+    Ok(Some(fallback::classify_with_fallback(&chain, &bytes, &ClassifyOptions::current()).await?))
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()).is_some_and(photo_meta::decodable_extension)
+}
+
+// Watches the folder itself, not its subfolders; events wake the UI so
+// files are picked up while the window sits idle
+fn watch(ctx: &egui::Context, folder: &Path) -> notify::Result<Watching> {
+    let (sender, events) = mpsc::channel();
+    let ctx = ctx.clone();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        if sender.send(event).is_ok() {
+            ctx.request_repaint();
+        }
+    })?;
+    watcher.watch(folder, notify::RecursiveMode::NonRecursive)?;
+    Ok(Watching { folder: folder.to_path_buf(), _watcher: watcher, events })
+}
@@ -0,0 +1,39 @@
+use crate::AvalancheClassifier;
+use avalanche_classifier_core::camera::Location;
+use avalanche_classifier_core::photo_meta::PhotoMeta;
+use avalanche_classifier_core::weather::WeatherSummary;
+use eframe::egui;
+
+impl AvalancheClassifier {
+    // Where and when the photo on screen was taken, when the weather before
+    // it is to be looked up. Read from the original, as a crop drops EXIF.
+    pub(crate) fn weather_lookup(&self) -> Option<(Location, i64)> {
+        if !self.settings.fetch_weather {
+            return None;
+        }
+        let meta = PhotoMeta::read(&self.image_data.as_ref()?.bytes);
+        meta.location.zip(meta.captured_at)
+    }
+}
+
+// The weather summary a result was analyzed with
+pub(crate) fn weather_ui(ui: &mut egui::Ui, weather: &WeatherSummary, muted_color: egui::Color32) {
+    ui.group(|ui| {
+        ui.set_min_width(ui.available_width());
+        ui.label(egui::RichText::new("Recent Weather").size(16.0).strong());
+        ui.add_space(4.0);
+        for line in weather.lines() {
+            ui.label(egui::RichText::new(line).size(13.0));
+        }
+        if let Some(until) = chrono::DateTime::from_timestamp(weather.until, 0) {
+            ui.label(
+                egui::RichText::new(format!(
+                    "Open-Meteo model data for the 72 hours before {}",
+                    until.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                ))
+                .size(12.0)
+                .color(muted_color),
+            );
+        }
+    });
+}
@@ -0,0 +1,194 @@
+use crate::AvalancheClassifier;
+use eframe::egui;
+use nokhwa::pixel_format::RgbFormat;
+use nokhwa::utils::{ApiBackend, CameraIndex, RequestedFormat, RequestedFormatType};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+
+// A camera being read on its own thread, as some platforms' camera handles
+// cannot move between threads. Stops when dropped.
+struct Stream {
+    frames: mpsc::Receiver<anyhow::Result<image::RgbImage>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+pub struct WebcamState {
+    pub open: bool,
+    // Cameras found when the window opened, None until looked for
+    devices: Option<Vec<(CameraIndex, String)>>,
+    selected: usize,
+    stream: Option<Stream>,
+    // The latest frame and its preview
+    latest: Option<(image::RgbImage, egui::TextureHandle)>,
+    error: Option<String>,
+}
+
+fn find_devices() -> anyhow::Result<Vec<(CameraIndex, String)>> {
+    let devices = nokhwa::query(ApiBackend::Auto)?;
+    Ok(devices.into_iter().map(|info| (info.index().clone(), info.human_name())).collect())
+}
+
+fn start(index: CameraIndex, ctx: &egui::Context) -> Stream {
+    // Room for one frame: while the UI has not taken it, newer ones are
+    // dropped, so the preview never lags behind the camera
+    let (sender, frames) = mpsc::sync_channel(1);
+    let stop = Arc::new(AtomicBool::new(false));
+    let (flag, ctx) = (stop.clone(), ctx.clone());
+    std::thread::spawn(move || {
+        if let Err(err) = read_frames(index, &sender, &flag, &ctx) {
+            let _ = sender.send(Err(err));
+            ctx.request_repaint();
+        }
+    });
+    Stream { frames, stop }
+}
+
+fn read_frames(
+    index: CameraIndex,
+    sender: &mpsc::SyncSender<anyhow::Result<image::RgbImage>>,
+    stop: &AtomicBool,
+    ctx: &egui::Context,
+) -> anyhow::Result<()> {
+    let format = RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestResolution);
+    let mut camera = nokhwa::Camera::new(index, format)?;
+    camera.open_stream()?;
+    while !stop.load(Ordering::Relaxed) {
+        let decoded = camera.frame()?.decode_image::<RgbFormat>()?;
+        // nokhwa may build against another image crate, so hand the pixels
+        // over raw
+        let (width, height) = (decoded.width(), decoded.height());
+        let frame = image::RgbImage::from_raw(width, height, decoded.into_raw())
+            .ok_or_else(|| anyhow::anyhow!("The camera returned a malformed frame"))?;
+        match sender.try_send(Ok(frame)) {
+            Ok(()) => ctx.request_repaint(),
+            Err(mpsc::TrySendError::Full(_)) => {}
+            Err(mpsc::TrySendError::Disconnected(_)) => break,
+        }
+    }
+    camera.stop_stream()?;
+    Ok(())
+}
+
+impl AvalancheClassifier {
+    // Live preview of a local camera; the snapped frame is analyzed like an
+    // uploaded photo
+    pub(crate) fn webcam_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.webcam;
+        if !state.open {
+            state.stream = None;
+            state.latest = None;
+            state.devices = None;
+            return;
+        }
+        if state.devices.is_none() {
+            #[cfg(target_os = "macos")]
+            nokhwa::nokhwa_initialize(|_| {});
+            match find_devices() {
+                Ok(devices) => {
+                    state.error = devices.is_empty().then(|| "No camera found".to_string());
+                    state.selected = 0;
+                    state.devices = Some(devices);
+                }
+                Err(err) => {
+                    state.error = Some(format!("Could not list cameras: {}", err));
+                    state.devices = Some(Vec::new());
+                }
+            }
+        }
+        let devices = state.devices.clone().unwrap_or_default();
+        if state.stream.is_none() && state.error.is_none() {
+            if let Some((index, _)) = devices.get(state.selected) {
+                state.stream = Some(start(index.clone(), ctx));
+            }
+        }
+        if let Some(stream) = &state.stream {
+            for frame in stream.frames.try_iter() {
+                match frame {
+                    Ok(frame) => {
+                        let size = [frame.width() as usize, frame.height() as usize];
+                        let preview = egui::ColorImage::from_rgb(size, frame.as_raw());
+                        match &mut state.latest {
+                            Some((latest, texture)) => {
+                                texture.set(preview, egui::TextureOptions::LINEAR);
+                                *latest = frame;
+                            }
+                            None => {
+                                let texture = ctx.load_texture("webcam", preview, egui::TextureOptions::LINEAR);
+                                state.latest = Some((frame, texture));
+                            }
+                        }
+                    }
+                    Err(err) => state.error = Some(format!("Camera stopped: {}", err)),
+                }
+            }
+            if state.error.is_some() {
+                state.stream = None;
+            }
+        }
+
+        let muted_color = crate::palette::Palette::of(ctx).muted;
+        let mut snap = false;
+        egui::Window::new("Camera")
+            .open(&mut state.open)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                if devices.len() > 1 {
+                    let selected = state.selected;
+                    egui::ComboBox::from_label("Camera")
+                        .selected_text(devices.get(selected).map_or("", |(_, name)| name.as_str()))
+                        .show_ui(ui, |ui| {
+                            for (i, (_, name)) in devices.iter().enumerate() {
+                                ui.selectable_value(&mut state.selected, i, name);
+                            }
+                        });
+                    if state.selected != selected {
+                        state.stream = None;
+                        state.latest = None;
+                        state.error = None;
+                    }
+                }
+                if let Some(error) = &state.error {
+                    ui.label(egui::RichText::new(error).size(13.0).color(muted_color));
+                }
+                match &state.latest {
+                    Some((_, texture)) => {
+                        let size = texture.size_vec2();
+                        let width = ui.available_width().min(size.x);
+                        ui.add(egui::Image::new((texture.id(), size * (width / size.x))));
+                    }
+                    None if state.stream.is_some() => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(egui::RichText::new("Starting the camera…").size(13.0).color(muted_color));
+                        });
+                    }
+                    None => {}
+                }
+                ui.add_space(8.0);
+                snap = ui.add_enabled(state.latest.is_some(), egui::Button::new("📸 Snap and Analyze")).clicked();
+            });
+
+        if snap {
+            if let Some((frame, _)) = self.webcam.latest.take() {
+                self.webcam.open = false;
+                let mut bytes = Vec::new();
+                match frame.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageOutputFormat::Jpeg(92)) {
+                    Ok(()) => {
+                        self.load_image(ctx, bytes);
+                        if self.analysis_ready() {
+                            self.start_analysis();
+                        }
+                    }
+                    Err(err) => self.error = Some(err.to_string()),
+                }
+            }
+        }
+    }
+}